@@ -0,0 +1,25 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Usage shared by all sub-agents spawned under one root session, measured
+ * against the `[agents]` budgets.
+ */
+export type AgentTreeUsage = { 
+/**
+ * Tokens used by all sub-agents in the tree.
+ */
+total_tokens: bigint, 
+/**
+ * Seconds since the first sub-agent in the tree was spawned.
+ */
+elapsed_secs: bigint, 
+/**
+ * Configured token budget for the tree, if any.
+ */
+token_budget?: bigint, 
+/**
+ * Configured wall-clock budget for the tree in seconds, if any.
+ */
+wall_clock_budget_secs?: bigint, };
@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgentTreeUsage } from "./AgentTreeUsage";
+
+export type AgentTreeUsageEvent = { 
+/**
+ * Resources used so far by every sub-agent in the session's agent tree.
+ */
+usage: AgentTreeUsage, };
@@ -2,6 +2,7 @@
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AgentStatus } from "./AgentStatus";
+import type { AgentTreeUsage } from "./AgentTreeUsage";
 import type { ThreadId } from "./ThreadId";
 
 export type CollabWaitingEndEvent = { 
@@ -16,4 +17,8 @@ call_id: string,
 /**
  * Last known status of the receiver agents reported to the sender agent.
  */
-statuses: { [key in ThreadId]?: AgentStatus }, };
+statuses: { [key in ThreadId]?: AgentStatus }, 
+/**
+ * Resources used so far by every sub-agent in the sender's agent tree.
+ */
+tree_usage?: AgentTreeUsage, };
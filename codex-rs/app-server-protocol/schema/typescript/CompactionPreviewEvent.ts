@@ -0,0 +1,31 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CompactionPreviewItem } from "./CompactionPreviewItem";
+import type { CompactionStrategy } from "./CompactionStrategy";
+
+/**
+ * Response payload for `Op::PreviewCompact`.
+ */
+export type CompactionPreviewEvent = { 
+/**
+ * Strategy that would run. `drop_tool_outputs` reports `summary` when
+ * dropping outputs alone would not bring the history under the limit.
+ */
+strategy: CompactionStrategy, 
+/**
+ * Model that would write the summary, when the strategy calls one.
+ */
+summary_model: string | null, 
+/**
+ * History items, oldest first.
+ */
+items: Array<CompactionPreviewItem>, 
+/**
+ * Estimated size of the history before compacting.
+ */
+tokens_before: bigint, 
+/**
+ * Estimated size afterwards, when it can be known without calling the model.
+ */
+estimated_tokens_after: bigint | null, };
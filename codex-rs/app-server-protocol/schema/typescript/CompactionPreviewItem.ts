@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CompactionPreviewAction } from "./CompactionPreviewAction";
+
+export type CompactionPreviewItem = { 
+/**
+ * Short human-readable description, e.g. `user: fix the build`.
+ */
+description: string, 
+/**
+ * Estimated size of the item in tokens.
+ */
+tokens: bigint, action: CompactionPreviewAction, };
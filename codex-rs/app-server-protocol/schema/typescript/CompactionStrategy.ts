@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How conversation history is compacted once it nears the context window.
+ */
+export type CompactionStrategy = "summary" | "drop_tool_outputs" | "hybrid";
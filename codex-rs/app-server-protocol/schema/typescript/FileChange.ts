@@ -2,4 +2,12 @@
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type FileChange = { "type": "add", content: string, } | { "type": "delete", content: string, } | { "type": "update", unified_diff: string, move_path: string | null, };
+export type FileChange = { "type": "add", content: string, 
+/**
+ * Permission bits the file is created with, e.g. `0o755`.
+ */
+mode?: number, } | { "type": "add_binary", size: bigint, mode?: number, } | { "type": "delete", content: string, } | { "type": "update", unified_diff: string, move_path: string | null, 
+/**
+ * New permission bits, if the patch changes them.
+ */
+mode?: number, };
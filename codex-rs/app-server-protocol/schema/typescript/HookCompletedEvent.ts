@@ -0,0 +1,26 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HookEventName } from "./HookEventName";
+
+export type HookCompletedEvent = { hook: HookEventName, 
+/**
+ * The configured hook command.
+ */
+command: Array<string>, 
+/**
+ * Tool the hook ran for (`pre_tool_use` and `post_tool_use` only).
+ */
+tool_name?: string, 
+/**
+ * Exit code, or `None` if the hook could not be spawned or timed out.
+ */
+exit_code: number | null, stdout: string, stderr: string, duration: string, timed_out: boolean, 
+/**
+ * Whether the hook blocked the prompt or tool call.
+ */
+blocked: boolean, 
+/**
+ * Why the hook blocked, when it did.
+ */
+reason?: string, };
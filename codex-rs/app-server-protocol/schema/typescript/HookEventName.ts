@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Point in a session at which a lifecycle hook runs.
+ */
+export type HookEventName = "session_start" | "user_prompt_submit" | "pre_tool_use" | "post_tool_use" | "turn_complete";
@@ -0,0 +1,9 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionApprovalEntry } from "./SessionApprovalEntry";
+
+/**
+ * Response payload for `Op::ListSessionApprovals` and `Op::RevokeSessionApproval`.
+ */
+export type ListSessionApprovalsResponseEvent = { approvals: Array<SessionApprovalEntry>, };
@@ -2,6 +2,8 @@
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { FileChange } from "./FileChange";
+import type { PatchApplyOutcome } from "./PatchApplyOutcome";
+import type { PostEditCommandResult } from "./PostEditCommandResult";
 
 export type PatchApplyEndEvent = { 
 /**
@@ -28,4 +30,12 @@ success: boolean,
 /**
  * The changes that were applied (mirrors PatchApplyBeginEvent::changes).
  */
-changes: { [key in string]?: FileChange }, };
+changes: { [key in string]?: FileChange }, 
+/**
+ * Results of the `[post_edit]` commands run against the edited files.
+ */
+post_edit: Array<PostEditCommandResult>, 
+/**
+ * What the patch left on disk. Patches are applied all or nothing.
+ */
+outcome?: PatchApplyOutcome, };
@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What an `apply_patch` call left on disk.
+ */
+export type PatchApplyOutcome = "committed" | "not_applied" | "rolled_back" | "rollback_failed";
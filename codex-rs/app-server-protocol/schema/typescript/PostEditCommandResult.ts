@@ -0,0 +1,16 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Output of one `[post_edit]` formatter or linter run after a patch.
+ */
+export type PostEditCommandResult = { 
+/**
+ * The command that was run, after `{files}` expansion.
+ */
+command: Array<string>, exit_code: number, 
+/**
+ * Combined stdout and stderr.
+ */
+output: string, };
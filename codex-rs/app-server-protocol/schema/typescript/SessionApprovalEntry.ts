@@ -0,0 +1,19 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionApprovalKind } from "./SessionApprovalKind";
+
+export type SessionApprovalEntry = { 
+/**
+ * Stable id used to revoke the entry via `Op::RevokeSessionApproval`.
+ */
+id: string, kind: SessionApprovalKind, 
+/**
+ * Unix timestamp (in seconds) when the approval was granted.
+ */
+approved_at: bigint, 
+/**
+ * Unix timestamp (in seconds) after which the approval no longer applies,
+ * when `session_approval_ttl_secs` is configured.
+ */
+expires_at: bigint | null, };
@@ -0,0 +1,8 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a remembered "approve for session" decision covers.
+ */
+export type SessionApprovalKind = { "type": "command", command: Array<string>, cwd: string, } | { "type": "patch_path", path: string, } | { "type": "command_prefix", prefix: Array<string>, };
@@ -14,6 +14,8 @@ export type { AgentReasoningRawContentDeltaEvent } from "./AgentReasoningRawCont
 export type { AgentReasoningRawContentEvent } from "./AgentReasoningRawContentEvent";
 export type { AgentReasoningSectionBreakEvent } from "./AgentReasoningSectionBreakEvent";
 export type { AgentStatus } from "./AgentStatus";
+export type { AgentTreeUsage } from "./AgentTreeUsage";
+export type { AgentTreeUsageEvent } from "./AgentTreeUsageEvent";
 export type { ApplyPatchApprovalParams } from "./ApplyPatchApprovalParams";
 export type { ApplyPatchApprovalRequestEvent } from "./ApplyPatchApprovalRequestEvent";
 export type { ApplyPatchApprovalResponse } from "./ApplyPatchApprovalResponse";
@@ -41,6 +43,10 @@ export type { CollabWaitingBeginEvent } from "./CollabWaitingBeginEvent";
 export type { CollabWaitingEndEvent } from "./CollabWaitingEndEvent";
 export type { CollaborationMode } from "./CollaborationMode";
 export type { CollaborationModeMask } from "./CollaborationModeMask";
+export type { CompactionPreviewAction } from "./CompactionPreviewAction";
+export type { CompactionPreviewEvent } from "./CompactionPreviewEvent";
+export type { CompactionPreviewItem } from "./CompactionPreviewItem";
+export type { CompactionStrategy } from "./CompactionStrategy";
 export type { ContentItem } from "./ContentItem";
 export type { ContextCompactedEvent } from "./ContextCompactedEvent";
 export type { ContextCompactionItem } from "./ContextCompactionItem";
@@ -86,6 +92,8 @@ export type { GitDiffToRemoteParams } from "./GitDiffToRemoteParams";
 export type { GitDiffToRemoteResponse } from "./GitDiffToRemoteResponse";
 export type { GitSha } from "./GitSha";
 export type { HistoryEntry } from "./HistoryEntry";
+export type { HookCompletedEvent } from "./HookCompletedEvent";
+export type { HookEventName } from "./HookEventName";
 export type { InitializeCapabilities } from "./InitializeCapabilities";
 export type { InitializeParams } from "./InitializeParams";
 export type { InitializeResponse } from "./InitializeResponse";
@@ -99,6 +107,7 @@ export type { ListConversationsParams } from "./ListConversationsParams";
 export type { ListConversationsResponse } from "./ListConversationsResponse";
 export type { ListCustomPromptsResponseEvent } from "./ListCustomPromptsResponseEvent";
 export type { ListRemoteSkillsResponseEvent } from "./ListRemoteSkillsResponseEvent";
+export type { ListSessionApprovalsResponseEvent } from "./ListSessionApprovalsResponseEvent";
 export type { ListSkillsResponseEvent } from "./ListSkillsResponseEvent";
 export type { LocalShellAction } from "./LocalShellAction";
 export type { LocalShellExecAction } from "./LocalShellExecAction";
@@ -125,11 +134,13 @@ export type { NewConversationResponse } from "./NewConversationResponse";
 export type { ParsedCommand } from "./ParsedCommand";
 export type { PatchApplyBeginEvent } from "./PatchApplyBeginEvent";
 export type { PatchApplyEndEvent } from "./PatchApplyEndEvent";
+export type { PatchApplyOutcome } from "./PatchApplyOutcome";
 export type { Personality } from "./Personality";
 export type { PlanDeltaEvent } from "./PlanDeltaEvent";
 export type { PlanItem } from "./PlanItem";
 export type { PlanItemArg } from "./PlanItemArg";
 export type { PlanType } from "./PlanType";
+export type { PostEditCommandResult } from "./PostEditCommandResult";
 export type { Profile } from "./Profile";
 export type { RateLimitSnapshot } from "./RateLimitSnapshot";
 export type { RateLimitWindow } from "./RateLimitWindow";
@@ -170,6 +181,8 @@ export type { SendUserTurnParams } from "./SendUserTurnParams";
 export type { SendUserTurnResponse } from "./SendUserTurnResponse";
 export type { ServerNotification } from "./ServerNotification";
 export type { ServerRequest } from "./ServerRequest";
+export type { SessionApprovalEntry } from "./SessionApprovalEntry";
+export type { SessionApprovalKind } from "./SessionApprovalKind";
 export type { SessionConfiguredEvent } from "./SessionConfiguredEvent";
 export type { SessionConfiguredNotification } from "./SessionConfiguredNotification";
 export type { SessionSource } from "./SessionSource";
@@ -0,0 +1,14 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionApprovalKind } from "./SessionApprovalKind";
+
+export type SessionApproval = { id: string, kind: SessionApprovalKind, 
+/**
+ * Unix timestamp (in seconds) when the approval was granted.
+ */
+approvedAt: bigint, 
+/**
+ * Unix timestamp (in seconds) after which the approval stops applying.
+ */
+expiresAt: bigint | null, };
@@ -0,0 +1,10 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SessionApproval } from "./SessionApproval";
+
+export type ThreadApprovalsListResponse = { 
+/**
+ * Approvals remembered for the rest of the session, oldest first.
+ */
+data: Array<SessionApproval>, };
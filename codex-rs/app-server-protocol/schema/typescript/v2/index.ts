@@ -105,6 +105,8 @@ export type { ReviewTarget } from "./ReviewTarget";
 export type { SandboxMode } from "./SandboxMode";
 export type { SandboxPolicy } from "./SandboxPolicy";
 export type { SandboxWorkspaceWrite } from "./SandboxWorkspaceWrite";
+export type { SessionApproval } from "./SessionApproval";
+export type { SessionApprovalKind } from "./SessionApprovalKind";
 export type { SessionSource } from "./SessionSource";
 export type { SkillDependencies } from "./SkillDependencies";
 export type { SkillErrorInfo } from "./SkillErrorInfo";
@@ -126,6 +128,8 @@ export type { TextElement } from "./TextElement";
 export type { TextPosition } from "./TextPosition";
 export type { TextRange } from "./TextRange";
 export type { Thread } from "./Thread";
+export type { ThreadApprovalsListParams } from "./ThreadApprovalsListParams";
+export type { ThreadApprovalsListResponse } from "./ThreadApprovalsListResponse";
 export type { ThreadArchiveParams } from "./ThreadArchiveParams";
 export type { ThreadArchiveResponse } from "./ThreadArchiveResponse";
 export type { ThreadCompactStartParams } from "./ThreadCompactStartParams";
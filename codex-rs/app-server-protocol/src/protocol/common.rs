@@ -228,6 +228,10 @@ client_request_definitions! {
         params: v2::ThreadReadParams,
         response: v2::ThreadReadResponse,
     },
+    ThreadApprovalsList => "thread/approvals/list" {
+        params: v2::ThreadApprovalsListParams,
+        response: v2::ThreadApprovalsListResponse,
+    },
     SkillsList => "skills/list" {
         params: v2::SkillsListParams,
         response: v2::SkillsListResponse,
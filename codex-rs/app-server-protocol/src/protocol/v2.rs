@@ -32,6 +32,8 @@ use codex_protocol::protocol::CreditsSnapshot as CoreCreditsSnapshot;
 use codex_protocol::protocol::NetworkAccess as CoreNetworkAccess;
 use codex_protocol::protocol::RateLimitSnapshot as CoreRateLimitSnapshot;
 use codex_protocol::protocol::RateLimitWindow as CoreRateLimitWindow;
+use codex_protocol::protocol::SessionApprovalEntry as CoreSessionApprovalEntry;
+use codex_protocol::protocol::SessionApprovalKind as CoreSessionApprovalKind;
 use codex_protocol::protocol::SessionSource as CoreSessionSource;
 use codex_protocol::protocol::SkillDependencies as CoreSkillDependencies;
 use codex_protocol::protocol::SkillErrorInfo as CoreSkillErrorInfo;
@@ -1547,6 +1549,67 @@ pub struct ThreadReadResponse {
     pub thread: Thread,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ThreadApprovalsListParams {
+    pub thread_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct ThreadApprovalsListResponse {
+    /// Approvals remembered for the rest of the session, oldest first.
+    pub data: Vec<SessionApproval>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(tag = "type")]
+#[ts(export_to = "v2/")]
+pub enum SessionApprovalKind {
+    /// An exact command line run from `cwd`.
+    Command { command: Vec<String>, cwd: PathBuf },
+    /// Patches touching `path`.
+    PatchPath { path: PathBuf },
+    /// Commands starting with `prefix`, saved as an execpolicy rule.
+    CommandPrefix { prefix: Vec<String> },
+}
+
+impl From<CoreSessionApprovalKind> for SessionApprovalKind {
+    fn from(value: CoreSessionApprovalKind) -> Self {
+        match value {
+            CoreSessionApprovalKind::Command { command, cwd } => Self::Command { command, cwd },
+            CoreSessionApprovalKind::PatchPath { path } => Self::PatchPath { path },
+            CoreSessionApprovalKind::CommandPrefix { prefix } => Self::CommandPrefix { prefix },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "v2/")]
+pub struct SessionApproval {
+    pub id: String,
+    pub kind: SessionApprovalKind,
+    /// Unix timestamp (in seconds) when the approval was granted.
+    pub approved_at: i64,
+    /// Unix timestamp (in seconds) after which the approval stops applying.
+    pub expires_at: Option<i64>,
+}
+
+impl From<CoreSessionApprovalEntry> for SessionApproval {
+    fn from(value: CoreSessionApprovalEntry) -> Self {
+        Self {
+            id: value.id,
+            kind: value.kind.into(),
+            approved_at: value.approved_at,
+            expires_at: value.expires_at,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "camelCase")]
 #[ts(export_to = "v2/")]
@@ -2018,6 +2081,17 @@ pub enum ReviewTarget {
         title: Option<String>,
     },
 
+    /// Review the changes between two commits, as shown by `git diff from..to`.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    CommitRange { from: String, to: String },
+
+    /// Review the changes in a patch file, which need not be applied to the
+    /// working tree. Relative paths are resolved against the session cwd.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    PatchFile { path: PathBuf },
+
     /// Arbitrary instructions, equivalent to the old free-form prompt.
     #[serde(rename_all = "camelCase")]
     #[ts(rename_all = "camelCase")]
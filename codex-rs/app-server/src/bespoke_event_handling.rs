@@ -1577,7 +1577,7 @@ fn convert_patch_changes(changes: &HashMap<PathBuf, CoreFileChange>) -> Vec<File
 
 fn map_patch_change_kind(change: &CoreFileChange) -> V2PatchChangeKind {
     match change {
-        CoreFileChange::Add { .. } => V2PatchChangeKind::Add,
+        CoreFileChange::Add { .. } | CoreFileChange::AddBinary { .. } => V2PatchChangeKind::Add,
         CoreFileChange::Delete { .. } => V2PatchChangeKind::Delete,
         CoreFileChange::Update { move_path, .. } => V2PatchChangeKind::Update {
             move_path: move_path.clone(),
@@ -1586,12 +1586,14 @@ fn map_patch_change_kind(change: &CoreFileChange) -> V2PatchChangeKind {
 }
 
 fn format_file_change_diff(change: &CoreFileChange) -> String {
-    match change {
-        CoreFileChange::Add { content } => content.clone(),
+    let diff = match change {
+        CoreFileChange::Add { content, .. } => content.clone(),
+        CoreFileChange::AddBinary { size, .. } => format!("Binary file, {size} bytes"),
         CoreFileChange::Delete { content } => content.clone(),
         CoreFileChange::Update {
             unified_diff,
             move_path,
+            ..
         } => {
             if let Some(path) = move_path {
                 format!("{unified_diff}\n\nMoved to: {}", path.display())
@@ -1599,6 +1601,18 @@ fn format_file_change_diff(change: &CoreFileChange) -> String {
                 unified_diff.clone()
             }
         }
+    };
+    match change {
+        CoreFileChange::Add {
+            mode: Some(mode), ..
+        }
+        | CoreFileChange::AddBinary {
+            mode: Some(mode), ..
+        }
+        | CoreFileChange::Update {
+            mode: Some(mode), ..
+        } => format!("{diff}\n\nMode: {mode:o}"),
+        _ => diff,
     }
 }
 
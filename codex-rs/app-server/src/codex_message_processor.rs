@@ -102,6 +102,8 @@ use codex_app_server_protocol::SkillsRemoteReadResponse;
 use codex_app_server_protocol::SkillsRemoteWriteParams;
 use codex_app_server_protocol::SkillsRemoteWriteResponse;
 use codex_app_server_protocol::Thread;
+use codex_app_server_protocol::ThreadApprovalsListParams;
+use codex_app_server_protocol::ThreadApprovalsListResponse;
 use codex_app_server_protocol::ThreadArchiveParams;
 use codex_app_server_protocol::ThreadArchiveResponse;
 use codex_app_server_protocol::ThreadCompactStartParams;
@@ -404,6 +406,20 @@ impl CodexMessageProcessor {
                     .filter(|t| !t.is_empty());
                 ApiReviewTarget::Commit { sha, title }
             }
+            ApiReviewTarget::CommitRange { from, to } => {
+                let from = from.trim().to_string();
+                let to = to.trim().to_string();
+                if from.is_empty() || to.is_empty() {
+                    return Err(invalid_request("from and to must not be empty".to_string()));
+                }
+                ApiReviewTarget::CommitRange { from, to }
+            }
+            ApiReviewTarget::PatchFile { path } => {
+                if path.as_os_str().is_empty() {
+                    return Err(invalid_request("path must not be empty".to_string()));
+                }
+                ApiReviewTarget::PatchFile { path }
+            }
             ApiReviewTarget::Custom { instructions } => {
                 let trimmed = instructions.trim().to_string();
                 if trimmed.is_empty() {
@@ -421,6 +437,8 @@ impl CodexMessageProcessor {
             ApiReviewTarget::UncommittedChanges => CoreReviewTarget::UncommittedChanges,
             ApiReviewTarget::BaseBranch { branch } => CoreReviewTarget::BaseBranch { branch },
             ApiReviewTarget::Commit { sha, title } => CoreReviewTarget::Commit { sha, title },
+            ApiReviewTarget::CommitRange { from, to } => CoreReviewTarget::CommitRange { from, to },
+            ApiReviewTarget::PatchFile { path } => CoreReviewTarget::PatchFile { path },
             ApiReviewTarget::Custom { instructions } => CoreReviewTarget::Custom { instructions },
         };
 
@@ -472,6 +490,9 @@ impl CodexMessageProcessor {
             ClientRequest::ThreadRead { request_id, params } => {
                 self.thread_read(request_id, params).await;
             }
+            ClientRequest::ThreadApprovalsList { request_id, params } => {
+                self.thread_approvals_list(request_id, params).await;
+            }
             ClientRequest::SkillsList { request_id, params } => {
                 self.skills_list(request_id, params).await;
             }
@@ -2123,6 +2144,32 @@ impl CodexMessageProcessor {
         }
     }
 
+    async fn thread_approvals_list(
+        &self,
+        request_id: RequestId,
+        params: ThreadApprovalsListParams,
+    ) {
+        let ThreadApprovalsListParams { thread_id } = params;
+
+        let (_, thread) = match self.load_thread(&thread_id).await {
+            Ok(v) => v,
+            Err(error) => {
+                self.outgoing.send_error(request_id, error).await;
+                return;
+            }
+        };
+
+        let data = thread
+            .session_approvals()
+            .await
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        self.outgoing
+            .send_response(request_id, ThreadApprovalsListResponse { data })
+            .await;
+    }
+
     async fn thread_list(&self, request_id: RequestId, params: ThreadListParams) {
         let ThreadListParams {
             cursor,
@@ -4737,7 +4784,15 @@ impl CodexMessageProcessor {
 
         let results = match query.as_str() {
             "" => vec![],
-            _ => run_fuzzy_file_search(query, roots, cancel_flag.clone()).await,
+            _ => {
+                run_fuzzy_file_search(
+                    query,
+                    roots,
+                    self.config.file_search_index_dir(),
+                    cancel_flag.clone(),
+                )
+                .await
+            }
         };
 
         if let Some(token) = cancellation_token {
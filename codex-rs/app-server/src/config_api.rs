@@ -157,6 +157,11 @@ mod tests {
             mcp_servers: None,
             rules: None,
             enforce_residency: Some(CoreResidencyRequirement::Us),
+            allowed_models: None,
+            allowed_model_providers: None,
+            denied_mcp_tools: None,
+            shell_environment_policy: None,
+            max_additional_writable_roots: None,
         };
 
         let mapped = map_requirements_toml_to_api(requirements);
@@ -13,6 +13,7 @@ const MAX_THREADS: usize = 12;
 pub(crate) async fn run_fuzzy_file_search(
     query: String,
     roots: Vec<String>,
+    index_dir: Option<PathBuf>,
     cancellation_flag: Arc<AtomicBool>,
 ) -> Vec<FuzzyFileSearchResult> {
     if roots.is_empty() {
@@ -38,6 +39,7 @@ pub(crate) async fn run_fuzzy_file_search(
                 limit,
                 threads,
                 compute_indices: true,
+                index_dir,
                 ..Default::default()
             },
             Some(cancellation_flag),
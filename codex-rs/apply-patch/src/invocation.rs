@@ -15,6 +15,7 @@ use crate::ApplyPatchFileChange;
 use crate::ApplyPatchFileUpdate;
 use crate::IoError;
 use crate::MaybeApplyPatchVerified;
+use crate::mode_only_update;
 use crate::parser::Hunk;
 use crate::parser::ParseError;
 use crate::parser::parse_patch;
@@ -164,8 +165,17 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
             for hunk in hunks {
                 let path = hunk.resolve_path(&effective_cwd);
                 match hunk {
-                    Hunk::AddFile { contents, .. } => {
-                        changes.insert(path, ApplyPatchFileChange::Add { content: contents });
+                    Hunk::AddFile { contents, mode, .. } => {
+                        changes.insert(
+                            path,
+                            ApplyPatchFileChange::Add {
+                                content: contents,
+                                mode,
+                            },
+                        );
+                    }
+                    Hunk::AddBinaryFile { contents, mode, .. } => {
+                        changes.insert(path, ApplyPatchFileChange::AddBinary { contents, mode });
                     }
                     Hunk::DeleteFile { .. } => {
                         let content = match std::fs::read_to_string(&path) {
@@ -182,12 +192,20 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         changes.insert(path, ApplyPatchFileChange::Delete { content });
                     }
                     Hunk::UpdateFile {
-                        move_path, chunks, ..
+                        move_path,
+                        mode,
+                        chunks,
+                        ..
                     } => {
+                        let update = if chunks.is_empty() {
+                            mode_only_update(&path)
+                        } else {
+                            unified_diff_from_chunks(&path, &chunks)
+                        };
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match update {
                             Ok(diff) => diff,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
@@ -199,6 +217,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                                 unified_diff,
                                 move_path: move_path.map(|p| effective_cwd.join(p)),
                                 new_content: contents,
+                                mode,
                             },
                         );
                     }
@@ -424,6 +443,7 @@ mod tests {
         vec![Hunk::AddFile {
             path: PathBuf::from("foo"),
             contents: "hi\n".to_string(),
+            mode: None,
         }]
     }
 
@@ -489,7 +509,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        mode: None,
                     }]
                 );
             }
@@ -514,7 +535,8 @@ mod tests {
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        mode: None,
                     }]
                 );
             }
@@ -554,7 +576,8 @@ PATCH"#,
                     hunks,
                     vec![Hunk::AddFile {
                         path: PathBuf::from("foo"),
-                        contents: "hi\n".to_string()
+                        contents: "hi\n".to_string(),
+                        mode: None,
                     }]
                 );
             }
@@ -756,6 +779,7 @@ PATCH"#,
                         .to_string(),
                         move_path: None,
                         new_content: "updated session directory content\n".to_string(),
+                        mode: None,
                     },
                 )]),
                 patch: argv[1].clone(),
@@ -2,25 +2,31 @@ mod invocation;
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod transaction;
 
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 
-use anyhow::Context;
 use anyhow::Result;
 pub use parser::Hunk;
 pub use parser::ParseError;
 use parser::ParseError::*;
 use parser::UpdateFileChunk;
 pub use parser::parse_patch;
+use similar::ChangeTag;
 use similar::TextDiff;
 use thiserror::Error;
 
 pub use invocation::maybe_parse_apply_patch_verified;
 pub use standalone_executable::main;
+pub use transaction::ROLLBACK_FAILED_MESSAGE;
+pub use transaction::ROLLED_BACK_MESSAGE;
+pub use transaction::TransactionOutcome;
 
 use crate::invocation::ExtractHeredocError;
+use crate::transaction::CommitError;
+use crate::transaction::StagedChanges;
 
 /// Detailed instructions for gpt-4.1 on how to use the `apply_patch` tool.
 pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_tool_instructions.md");
@@ -31,9 +37,15 @@ pub enum ApplyPatchError {
     ParseError(#[from] ParseError),
     #[error(transparent)]
     IoError(#[from] IoError),
-    /// Error that occurs while computing replacements when applying patch chunks
+    /// The lines a patch chunk expects could not be found in the file.
     #[error("{0}")]
-    ComputeReplacements(String),
+    ContextNotFound(Box<ContextMismatch>),
+    /// Writing the changes failed; `outcome` says what was left on disk.
+    #[error("{message}")]
+    CommitFailed {
+        message: String,
+        outcome: TransactionOutcome,
+    },
     /// A raw patch body was provided without an explicit `apply_patch` invocation.
     #[error(
         "patch detected without explicit call to apply_patch. Rerun as [\"apply_patch\", \"<patch>\"]"
@@ -41,6 +53,16 @@ pub enum ApplyPatchError {
     ImplicitInvocation,
 }
 
+impl ApplyPatchError {
+    /// What the failed `apply_patch` call left on disk.
+    pub fn outcome(&self) -> TransactionOutcome {
+        match self {
+            Self::CommitFailed { outcome, .. } => *outcome,
+            _ => TransactionOutcome::NotApplied,
+        }
+    }
+}
+
 impl From<std::io::Error> for ApplyPatchError {
     fn from(err: std::io::Error) -> Self {
         ApplyPatchError::IoError(IoError {
@@ -73,6 +95,95 @@ impl PartialEq for IoError {
     }
 }
 
+/// Context from a patch chunk that did not match the file, with the most
+/// similar region of the file so the patch can be retried with the right lines.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContextMismatch {
+    pub path: PathBuf,
+    /// Whether `expected` is the chunk's `@@` context line rather than its old lines.
+    pub change_context: bool,
+    pub expected: Vec<String>,
+    pub closest: Option<ClosestContext>,
+}
+
+/// The region of a file most similar to the context a patch expected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosestContext {
+    /// 1-based number of the region's first line.
+    pub start_line: usize,
+    /// 1-based number of the region's last line.
+    pub end_line: usize,
+    /// Average similarity of the region's lines to the expected lines, from 0 to 1.
+    pub similarity: f32,
+    pub actual: Vec<String>,
+}
+
+/// Candidates less similar than this are not worth pointing the caller at.
+const MIN_CLOSEST_CONTEXT_SIMILARITY: f32 = 0.5;
+
+impl ContextMismatch {
+    fn new(path: &Path, change_context: bool, expected: &[String], lines: &[String]) -> Self {
+        let closest = seek_sequence::closest_sequence(lines, expected, 0, &[])
+            .filter(|closest| closest.similarity >= MIN_CLOSEST_CONTEXT_SIMILARITY)
+            .map(|closest| ClosestContext {
+                start_line: closest.start + 1,
+                end_line: closest.start + expected.len(),
+                similarity: closest.similarity,
+                actual: lines[closest.start..closest.start + expected.len()].to_vec(),
+            });
+        Self {
+            path: path.to_path_buf(),
+            change_context,
+            expected: expected.to_vec(),
+            closest,
+        }
+    }
+}
+
+impl std::fmt::Display for ContextMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.change_context {
+            write!(
+                f,
+                "Failed to find context '{}' in {}",
+                self.expected.join("\n"),
+                self.path.display()
+            )?;
+        } else {
+            write!(
+                f,
+                "Failed to find expected lines in {}:\n{}",
+                self.path.display(),
+                self.expected.join("\n")
+            )?;
+        }
+        let Some(closest) = &self.closest else {
+            return Ok(());
+        };
+        let lines = if closest.start_line == closest.end_line {
+            format!("line {}", closest.start_line)
+        } else {
+            format!("lines {}-{}", closest.start_line, closest.end_line)
+        };
+        write!(
+            f,
+            "\n\nClosest match is at {lines} ({:.0}% similar):\n--- expected\n+++ actual",
+            closest.similarity * 100.0
+        )?;
+        let expected: Vec<&str> = self.expected.iter().map(String::as_str).collect();
+        let actual: Vec<&str> = closest.actual.iter().map(String::as_str).collect();
+        for change in TextDiff::from_slices(&expected, &actual).iter_all_changes() {
+            let sign = match change.tag() {
+                ChangeTag::Equal => ' ',
+                ChangeTag::Delete => '-',
+                ChangeTag::Insert => '+',
+            };
+            write!(f, "\n{sign}{}", change.value())?;
+        }
+        Ok(())
+    }
+}
+
 /// Both the raw PATCH argument to `apply_patch` as well as the PATCH argument
 /// parsed into hunks.
 #[derive(Debug, PartialEq)]
@@ -86,6 +197,12 @@ pub struct ApplyPatchArgs {
 pub enum ApplyPatchFileChange {
     Add {
         content: String,
+        /// Permission bits requested with `*** Set Mode:`.
+        mode: Option<u32>,
+    },
+    AddBinary {
+        contents: Vec<u8>,
+        mode: Option<u32>,
     },
     Delete {
         content: String,
@@ -95,6 +212,7 @@ pub enum ApplyPatchFileChange {
         move_path: Option<PathBuf>,
         /// new_content that will result after the unified_diff is applied.
         new_content: String,
+        mode: Option<u32>,
     },
 }
 
@@ -157,7 +275,13 @@ impl ApplyPatchAction {
 + {content}
 *** End Patch"#,
         );
-        let changes = HashMap::from([(path.to_path_buf(), ApplyPatchFileChange::Add { content })]);
+        let changes = HashMap::from([(
+            path.to_path_buf(),
+            ApplyPatchFileChange::Add {
+                content,
+                mode: None,
+            },
+        )]);
         #[expect(clippy::expect_used)]
         Self {
             changes,
@@ -212,7 +336,7 @@ pub fn apply_hunks(
     let _existing_paths: Vec<&Path> = hunks
         .iter()
         .filter_map(|hunk| match hunk {
-            Hunk::AddFile { .. } => {
+            Hunk::AddFile { .. } | Hunk::AddBinaryFile { .. } => {
                 // The file is being added, so it doesn't exist yet.
                 None
             }
@@ -244,7 +368,12 @@ pub fn apply_hunks(
         Err(err) => {
             let msg = err.to_string();
             writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
-            if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            if let Some(commit) = err.downcast_ref::<CommitError>() {
+                Err(ApplyPatchError::CommitFailed {
+                    message: msg,
+                    outcome: commit.outcome(),
+                })
+            } else if let Some(io) = err.downcast_ref::<std::io::Error>() {
                 Err(ApplyPatchError::from(io))
             } else {
                 Err(ApplyPatchError::IoError(IoError {
@@ -263,75 +392,101 @@ pub struct AffectedPaths {
     pub added: Vec<PathBuf>,
     pub modified: Vec<PathBuf>,
     pub deleted: Vec<PathBuf>,
+    /// Updated files where some context matched the file only approximately.
+    pub approximate: Vec<PathBuf>,
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
+/// Every hunk is staged in memory first, so a patch that fails leaves the
+/// filesystem as it was.
 fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
 
+    let mut staged = StagedChanges::default();
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
+    let mut approximate: Vec<PathBuf> = Vec::new();
     for hunk in hunks {
         match hunk {
-            Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+            Hunk::AddFile {
+                path,
+                contents,
+                mode,
+            } => {
+                staged.write(path, contents.clone(), *mode);
+                added.push(path.clone());
+            }
+            Hunk::AddBinaryFile {
+                path,
+                contents,
+                mode,
+            } => {
+                staged.write(path, contents.clone(), *mode);
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                if !staged.is_file(path) {
+                    anyhow::bail!("Failed to delete file {}", path.display());
+                }
+                staged.delete(path);
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
                 path,
                 move_path,
+                mode,
                 chunks,
             } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
-                if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
+                let original_contents = staged
+                    .read(path)
+                    .unwrap_or_else(|| std::fs::read(path))
+                    .map_err(|err| update_read_error(path, err))?;
+                let new_contents = if chunks.is_empty() {
+                    // A hunk that only sets the mode keeps the contents, which
+                    // may not be text.
+                    original_contents
+                } else {
+                    let original_contents =
+                        String::from_utf8(original_contents).map_err(|err| {
+                            update_read_error(
+                                path,
+                                std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+                            )
                         })?;
+                    let applied = derive_new_contents_from_text(path, original_contents, chunks)?;
+                    if applied.approximate {
+                        approximate.push(move_path.as_ref().unwrap_or(path).clone());
                     }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    applied.new_contents.into_bytes()
+                };
+                if let Some(dest) = move_path {
+                    staged.write(dest, new_contents, *mode);
+                    staged.delete(path);
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    staged.write(path, new_contents, *mode);
                     modified.push(path.clone());
                 }
             }
         }
     }
+    staged.commit()?;
     Ok(AffectedPaths {
         added,
         modified,
         deleted,
+        approximate,
     })
 }
 
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
+    /// Whether some context was located only by similarity.
+    approximate: bool,
 }
 
 /// Return *only* the new file contents (joined into a single `String`) after
@@ -340,16 +495,27 @@ fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            return Err(ApplyPatchError::IoError(IoError {
-                context: format!("Failed to read file to update {}", path.display()),
-                source: err,
-            }));
-        }
-    };
+    let original_contents = read_file_to_update(path)?;
+    derive_new_contents_from_text(path, original_contents, chunks)
+}
+
+fn read_file_to_update(path: &Path) -> std::result::Result<String, ApplyPatchError> {
+    std::fs::read_to_string(path).map_err(|err| update_read_error(path, err))
+}
 
+fn update_read_error(path: &Path, err: std::io::Error) -> ApplyPatchError {
+    ApplyPatchError::IoError(IoError {
+        context: format!("Failed to read file to update {}", path.display()),
+        source: err,
+    })
+}
+
+/// Apply the chunks to `original_contents`, the current contents of `path`.
+fn derive_new_contents_from_text(
+    path: &Path,
+    original_contents: String,
+    chunks: &[UpdateFileChunk],
+) -> std::result::Result<AppliedPatch, ApplyPatchError> {
     let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
 
     // Drop the trailing empty element that results from the final newline so
@@ -358,7 +524,7 @@ fn derive_new_contents_from_chunks(
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let (replacements, approximate) = compute_replacements(&original_lines, path, chunks)?;
     let new_lines = apply_replacements(original_lines, &replacements);
     let mut new_lines = new_lines;
     if !new_lines.last().is_some_and(String::is_empty) {
@@ -368,19 +534,24 @@ fn derive_new_contents_from_chunks(
     Ok(AppliedPatch {
         original_contents,
         new_contents,
+        approximate,
     })
 }
 
+/// A `(start_index, old_len, new_lines)` edit of a file's lines.
+type Replacement = (usize, usize, Vec<String>);
+
 /// Compute a list of replacements needed to transform `original_lines` into the
-/// new lines, given the patch `chunks`. Each replacement is returned as
-/// `(start_index, old_len, new_lines)`.
+/// new lines, given the patch `chunks`. Also returns whether any chunk was
+/// located only by similarity.
 fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
-) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
-    let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
+) -> std::result::Result<(Vec<Replacement>, bool), ApplyPatchError> {
+    let mut replacements: Vec<Replacement> = Vec::new();
     let mut line_index: usize = 0;
+    let mut approximate = false;
 
     for chunk in chunks {
         // If a chunk has a `change_context`, we use seek_sequence to find it, then
@@ -394,10 +565,13 @@ fn compute_replacements(
             ) {
                 line_index = idx + 1;
             } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
+                return Err(ApplyPatchError::ContextNotFound(Box::new(
+                    ContextMismatch::new(
+                        path,
+                        true,
+                        std::slice::from_ref(ctx_line),
+                        original_lines,
+                    ),
                 )));
             }
         }
@@ -447,21 +621,74 @@ fn compute_replacements(
             );
         }
 
+        if found.is_none() {
+            found = seek_sequence::fuzzy_seek_sequence(
+                original_lines,
+                pattern,
+                line_index,
+                chunk.is_end_of_file,
+                &deleted_lines(pattern, new_slice),
+            );
+            approximate |= found.is_some();
+        }
+
         if let Some(start_idx) = found {
-            replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
+            let matched = &original_lines[start_idx..start_idx + pattern.len()];
+            let new_lines = if matched == pattern {
+                new_slice.to_vec()
+            } else {
+                keep_file_context_lines(pattern, new_slice, matched)
+            };
+            replacements.push((start_idx, pattern.len(), new_lines));
             line_index = start_idx + pattern.len();
         } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
-                path.display(),
-                chunk.old_lines.join("\n"),
+            return Err(ApplyPatchError::ContextNotFound(Box::new(
+                ContextMismatch::new(path, false, pattern, original_lines),
             )));
         }
     }
 
     replacements.sort_by(|(lhs_idx, _, _), (rhs_idx, _, _)| lhs_idx.cmp(rhs_idx));
 
-    Ok(replacements)
+    Ok((replacements, approximate))
+}
+
+/// Flags the lines of `old_lines` that the chunk deletes rather than keeps.
+fn deleted_lines(old_lines: &[String], new_lines: &[String]) -> Vec<bool> {
+    let old: Vec<&str> = old_lines.iter().map(String::as_str).collect();
+    let new: Vec<&str> = new_lines.iter().map(String::as_str).collect();
+    let mut deleted = vec![false; old_lines.len()];
+    for change in TextDiff::from_slices(&old, &new).iter_all_changes() {
+        if change.tag() == ChangeTag::Delete
+            && let Some(index) = change.old_index()
+        {
+            deleted[index] = true;
+        }
+    }
+    deleted
+}
+
+/// When `old_lines` matched the file only loosely, carry the file's own text
+/// over for the context lines the chunk leaves unchanged, so that typos or
+/// whitespace differences in the patch's context are not written to the file.
+fn keep_file_context_lines(
+    old_lines: &[String],
+    new_lines: &[String],
+    matched: &[String],
+) -> Vec<String> {
+    let old: Vec<&str> = old_lines.iter().map(String::as_str).collect();
+    let new: Vec<&str> = new_lines.iter().map(String::as_str).collect();
+    TextDiff::from_slices(&old, &new)
+        .iter_all_changes()
+        .filter_map(|change| match change.tag() {
+            ChangeTag::Equal => change
+                .old_index()
+                .and_then(|index| matched.get(index))
+                .cloned(),
+            ChangeTag::Insert => Some(change.value().to_string()),
+            ChangeTag::Delete => None,
+        })
+        .collect()
 }
 
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
@@ -514,6 +741,7 @@ pub fn unified_diff_from_chunks_with_context(
     let AppliedPatch {
         original_contents,
         new_contents,
+        ..
     } = derive_new_contents_from_chunks(path, chunks)?;
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
@@ -523,6 +751,18 @@ pub fn unified_diff_from_chunks_with_context(
     })
 }
 
+/// The change for an update hunk that only sets the mode: an empty diff and
+/// the file's current contents.
+pub(crate) fn mode_only_update(
+    path: &Path,
+) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
+    let contents = std::fs::read(path).map_err(|err| update_read_error(path, err))?;
+    Ok(ApplyPatchFileUpdate {
+        unified_diff: String::new(),
+        content: String::from_utf8_lossy(&contents).into_owned(),
+    })
+}
+
 /// Print the summary of changes in git-style format.
 /// Write a summary of changes to the given writer.
 pub fn print_summary(
@@ -539,6 +779,13 @@ pub fn print_summary(
     for path in &affected.deleted {
         writeln!(out, "D {}", path.display())?;
     }
+    for path in &affected.approximate {
+        writeln!(
+            out,
+            "Note: some context lines in {} did not match the file exactly and were located by similarity; check that the right lines changed.",
+            path.display()
+        )?;
+    }
     Ok(())
 }
 
@@ -581,6 +828,35 @@ mod tests {
         assert_eq!(contents, "ab\ncd\n");
     }
 
+    #[test]
+    fn test_add_binary_file_and_set_mode() {
+        let dir = tempdir().unwrap();
+        let binary = dir.path().join("fixture.bin");
+        let script = dir.path().join("run.sh");
+        fs::write(&script, "echo hi\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Add Binary File: {}
++AAEC
++/w==
+*** Update File: {}
+*** Set Mode: 755"#,
+            binary.display(),
+            script.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read(&binary).unwrap(), vec![0, 1, 2, 255]);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "echo hi\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&script).unwrap().permissions().mode();
+            assert_eq!(mode & 0o7777, 0o755);
+        }
+    }
+
     #[test]
     fn test_delete_file_hunk_removes_file() {
         let dir = tempdir().unwrap();
@@ -601,6 +877,24 @@ mod tests {
         assert!(!path.exists());
     }
 
+    #[test]
+    fn test_add_then_delete_same_file_leaves_nothing_behind() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("scratch.txt");
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {}
++temporary
+*** Delete File: {}"#,
+            path.display(),
+            path.display()
+        ));
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+        assert_eq!(String::from_utf8(stderr).unwrap(), "");
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_update_file_hunk_modifies_content() {
         let dir = tempdir().unwrap();
@@ -824,6 +1118,117 @@ mod tests {
         assert_eq!(String::from_utf8(stderr).unwrap(), "");
     }
 
+    #[test]
+    fn test_update_tolerates_typo_in_context() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("total.rs");
+        std::fs::write(
+            &path,
+            "fn main() {\n    let total = compute_total(&items);\n    println!(\"{total}\");\n}\n",
+        )
+        .unwrap();
+
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ fn main() {{
+     let total = compute_totl(&items);
+-    println!("{{total}}");
++    println!("total: {{total}}");"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        // The file's own context line is kept, and the model is told.
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn main() {\n    let total = compute_total(&items);\n    println!(\"total: {total}\");\n}\n"
+        );
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Success. Updated the following files:\nM {path}\nNote: some context lines in {path} did not match the file exactly and were located by similarity; check that the right lines changed.\n",
+                path = path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_update_requires_deleted_lines_to_match() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("total.rs");
+        let original =
+            "fn main() {\n    let total = compute_total(&items);\n    println!(\"{total}\");\n}\n";
+        std::fs::write(&path, original).unwrap();
+
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ fn main() {{
+-    let total = compute_totl(&items);
++    let total = sum(&items);
+     println!("{{total}}");"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        assert!(apply_patch(&patch, &mut stdout, &mut stderr).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_missing_context_reports_closest_region() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("add.rs");
+        fs::write(
+            &path,
+            "// math\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        )
+        .unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-fn sum(x: u64, y: u64) -> u64 {{
+-    a + b
++fn sum(x: u64, y: u64) -> u64 {{
++    x + y"#,
+            path.display()
+        ));
+        let patch = parse_patch(&patch).unwrap();
+        let chunks = match patch.hunks.as_slice() {
+            [Hunk::UpdateFile { chunks, .. }] => chunks,
+            _ => panic!("Expected a single UpdateFile hunk"),
+        };
+
+        let Err(ApplyPatchError::ContextNotFound(mismatch)) =
+            unified_diff_from_chunks(&path, chunks)
+        else {
+            panic!("expected the context to be missing");
+        };
+
+        let closest = mismatch.closest.as_ref().unwrap();
+        assert_eq!((closest.start_line, closest.end_line), (2, 3));
+        assert_eq!(
+            mismatch.to_string(),
+            format!(
+                "Failed to find expected lines in {}:\n\
+                 fn sum(x: u64, y: u64) -> u64 {{\n    a + b\n\n\
+                 Closest match is at lines 2-3 ({:.0}% similar):\n--- expected\n+++ actual\n\
+                 -fn sum(x: u64, y: u64) -> u64 {{\n+fn add(a: i32, b: i32) -> i32 {{\n     a + b",
+                path.display(),
+                closest.similarity * 100.0
+            )
+        );
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "// math\nfn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n"
+        );
+    }
+
     #[test]
     fn test_unified_diff() {
         // Start with a file containing four lines.
@@ -7,14 +7,17 @@
 //! begin_patch: "*** Begin Patch" LF
 //! end_patch: "*** End Patch" LF?
 //!
-//! hunk: add_hunk | delete_hunk | update_hunk
-//! add_hunk: "*** Add File: " filename LF add_line+
+//! hunk: add_hunk | add_binary_hunk | delete_hunk | update_hunk
+//! add_hunk: "*** Add File: " filename LF set_mode? add_line+
+//! add_binary_hunk: "*** Add Binary File: " filename LF set_mode? binary_line*
 //! delete_hunk: "*** Delete File: " filename LF
-//! update_hunk: "*** Update File: " filename LF change_move? change?
+//! update_hunk: "*** Update File: " filename LF change_move? set_mode? change?
 //! filename: /(.+)/
 //! add_line: "+" /(.+)/ LF -> line
+//! binary_line: "+" /([A-Za-z0-9+\/=]*)/ LF
 //!
 //! change_move: "*** Move to: " filename LF
+//! set_mode: "*** Set Mode: " /(0?[0-7]{3})/ LF
 //! change: (change_context | change_line)+ eof_line?
 //! change_context: ("@@" | "@@ " /(.+)/) LF
 //! change_line: ("+" | "-" | " ") /(.+)/ LF
@@ -23,6 +26,7 @@
 //! The parser below is a little more lenient than the explicit spec and allows for
 //! leading/trailing whitespace around patch markers.
 use crate::ApplyPatchArgs;
+use base64::Engine;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -31,9 +35,11 @@ use thiserror::Error;
 const BEGIN_PATCH_MARKER: &str = "*** Begin Patch";
 const END_PATCH_MARKER: &str = "*** End Patch";
 const ADD_FILE_MARKER: &str = "*** Add File: ";
+const ADD_BINARY_FILE_MARKER: &str = "*** Add Binary File: ";
 const DELETE_FILE_MARKER: &str = "*** Delete File: ";
 const UPDATE_FILE_MARKER: &str = "*** Update File: ";
 const MOVE_TO_MARKER: &str = "*** Move to: ";
+const SET_MODE_MARKER: &str = "*** Set Mode: ";
 const EOF_MARKER: &str = "*** End of File";
 const CHANGE_CONTEXT_MARKER: &str = "@@ ";
 const EMPTY_CHANGE_CONTEXT_MARKER: &str = "@@";
@@ -61,6 +67,14 @@ pub enum Hunk {
     AddFile {
         path: PathBuf,
         contents: String,
+        /// Permission bits from `*** Set Mode:`, e.g. `0o755`.
+        mode: Option<u32>,
+    },
+    /// A file whose contents are given as base64 lines.
+    AddBinaryFile {
+        path: PathBuf,
+        contents: Vec<u8>,
+        mode: Option<u32>,
     },
     DeleteFile {
         path: PathBuf,
@@ -68,6 +82,7 @@ pub enum Hunk {
     UpdateFile {
         path: PathBuf,
         move_path: Option<PathBuf>,
+        mode: Option<u32>,
 
         /// Chunks should be in order, i.e. the `change_context` of one chunk
         /// should occur later in the file than the previous chunk.
@@ -79,6 +94,7 @@ impl Hunk {
     pub fn resolve_path(&self, cwd: &Path) -> PathBuf {
         match self {
             Hunk::AddFile { path, .. } => cwd.join(path),
+            Hunk::AddBinaryFile { path, .. } => cwd.join(path),
             Hunk::DeleteFile { path } => cwd.join(path),
             Hunk::UpdateFile { path, .. } => cwd.join(path),
         }
@@ -250,9 +266,10 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
     let first_line = lines[0].trim();
     if let Some(path) = first_line.strip_prefix(ADD_FILE_MARKER) {
         // Add File
+        let (mode, mut parsed_lines) = parse_set_mode(&lines[1..], line_number + 1)?;
+        parsed_lines += 1;
         let mut contents = String::new();
-        let mut parsed_lines = 1;
-        for add_line in &lines[1..] {
+        for add_line in &lines[parsed_lines..] {
             if let Some(line_to_add) = add_line.strip_prefix('+') {
                 contents.push_str(line_to_add);
                 contents.push('\n');
@@ -265,6 +282,34 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             AddFile {
                 path: PathBuf::from(path),
                 contents,
+                mode,
+            },
+            parsed_lines,
+        ));
+    } else if let Some(path) = first_line.strip_prefix(ADD_BINARY_FILE_MARKER) {
+        // Add Binary File
+        let (mode, mut parsed_lines) = parse_set_mode(&lines[1..], line_number + 1)?;
+        parsed_lines += 1;
+        let mut encoded = String::new();
+        for add_line in &lines[parsed_lines..] {
+            if let Some(line_to_add) = add_line.strip_prefix('+') {
+                encoded.push_str(line_to_add.trim());
+                parsed_lines += 1;
+            } else {
+                break;
+            }
+        }
+        let contents = base64::engine::general_purpose::STANDARD
+            .decode(&encoded)
+            .map_err(|err| InvalidHunkError {
+                message: format!("Invalid base64 contents for binary file '{path}': {err}"),
+                line_number,
+            })?;
+        return Ok((
+            AddBinaryFile {
+                path: PathBuf::from(path),
+                contents,
+                mode,
             },
             parsed_lines,
         ));
@@ -291,6 +336,11 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             parsed_lines += 1;
         }
 
+        // Optional: set mode line
+        let (mode, mode_lines) = parse_set_mode(remaining_lines, line_number + parsed_lines)?;
+        remaining_lines = &remaining_lines[mode_lines..];
+        parsed_lines += mode_lines;
+
         let mut chunks = Vec::new();
         // NOTE: we need to know to stop once we reach the next special marker header.
         while !remaining_lines.is_empty() {
@@ -315,7 +365,7 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             remaining_lines = &remaining_lines[chunk_lines..]
         }
 
-        if chunks.is_empty() {
+        if chunks.is_empty() && mode.is_none() {
             return Err(InvalidHunkError {
                 message: format!("Update file hunk for path '{path}' is empty"),
                 line_number,
@@ -326,6 +376,7 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
             UpdateFile {
                 path: PathBuf::from(path),
                 move_path: move_path.map(PathBuf::from),
+                mode,
                 chunks,
             },
             parsed_lines,
@@ -334,12 +385,42 @@ fn parse_one_hunk(lines: &[&str], line_number: usize) -> Result<(Hunk, usize), P
 
     Err(InvalidHunkError {
         message: format!(
-            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}'"
+            "'{first_line}' is not a valid hunk header. Valid hunk headers: '*** Add File: {{path}}', '*** Add Binary File: {{path}}', '*** Delete File: {{path}}', '*** Update File: {{path}}'"
         ),
         line_number,
     })
 }
 
+/// Parses an optional `*** Set Mode: <octal>` line at the start of `lines`.
+/// Returns the permission bits, if any, and the number of lines consumed.
+fn parse_set_mode(lines: &[&str], line_number: usize) -> Result<(Option<u32>, usize), ParseError> {
+    let Some(value) = lines
+        .first()
+        .and_then(|line| line.trim().strip_prefix(SET_MODE_MARKER))
+    else {
+        return Ok((None, 0));
+    };
+    let value = value.trim();
+    // Only permission bits: setuid, setgid and sticky bits are refused.
+    let mode = if (3..=4).contains(&value.len()) && value.chars().all(|c| ('0'..='7').contains(&c))
+    {
+        u32::from_str_radix(value, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+    } else {
+        None
+    };
+    match mode {
+        Some(mode) => Ok((Some(mode), 1)),
+        None => Err(InvalidHunkError {
+            message: format!(
+                "Invalid mode '{value}'. Expected octal permission bits no higher than 777, such as 755 or 0644"
+            ),
+            line_number,
+        }),
+    }
+}
+
 fn parse_update_file_chunk(
     lines: &[&str],
     line_number: usize,
@@ -463,7 +544,8 @@ fn test_parse_patch() {
         .hunks,
         vec![AddFile {
             path: PathBuf::from("foo"),
-            contents: "hi\n".to_string()
+            contents: "hi\n".to_string(),
+            mode: None
         }]
     );
     assert_eq!(
@@ -508,7 +590,8 @@ fn test_parse_patch() {
         vec![
             AddFile {
                 path: PathBuf::from("path/add.py"),
-                contents: "abc\ndef\n".to_string()
+                contents: "abc\ndef\n".to_string(),
+                mode: None
             },
             DeleteFile {
                 path: PathBuf::from("path/delete.py")
@@ -516,6 +599,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("path/update.py"),
                 move_path: Some(PathBuf::from("path/update2.py")),
+                mode: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: Some("def f():".to_string()),
                     old_lines: vec!["    pass".to_string()],
@@ -543,6 +627,7 @@ fn test_parse_patch() {
             UpdateFile {
                 path: PathBuf::from("file.py"),
                 move_path: None,
+                mode: None,
                 chunks: vec![UpdateFileChunk {
                     change_context: None,
                     old_lines: vec![],
@@ -552,7 +637,8 @@ fn test_parse_patch() {
             },
             AddFile {
                 path: PathBuf::from("other.py"),
-                contents: "content\n".to_string()
+                contents: "content\n".to_string(),
+                mode: None
             }
         ]
     );
@@ -573,6 +659,7 @@ fn test_parse_patch() {
         vec![UpdateFile {
             path: PathBuf::from("file2.py"),
             move_path: None,
+            mode: None,
             chunks: vec![UpdateFileChunk {
                 change_context: None,
                 old_lines: vec!["import foo".to_string()],
@@ -593,6 +680,7 @@ fn test_parse_patch_lenient() {
     let expected_patch = vec![UpdateFile {
         path: PathBuf::from("file2.py"),
         move_path: None,
+        mode: None,
         chunks: vec![UpdateFileChunk {
             change_context: None,
             old_lines: vec!["import foo".to_string()],
@@ -669,13 +757,112 @@ fn test_parse_patch_lenient() {
     );
 }
 
+#[test]
+fn test_parse_mode_and_binary_hunks() {
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Add File: run.sh\n\
+             *** Set Mode: 755\n\
+             +#!/bin/sh\n\
+             *** Add Binary File: fixture.bin\n\
+             +AAEC\n\
+             +/w==\n\
+             *** Update File: tool.py\n\
+             *** Set Mode: 0644\n\
+             *** Update File: lib.py\n\
+             *** Move to: lib2.py\n\
+             *** Set Mode: 600\n\
+             @@\n\
+             +x = 1\n\
+             *** End Patch",
+            ParseMode::Strict
+        )
+        .unwrap()
+        .hunks,
+        vec![
+            AddFile {
+                path: PathBuf::from("run.sh"),
+                contents: "#!/bin/sh\n".to_string(),
+                mode: Some(0o755),
+            },
+            AddBinaryFile {
+                path: PathBuf::from("fixture.bin"),
+                contents: vec![0, 1, 2, 255],
+                mode: None,
+            },
+            UpdateFile {
+                path: PathBuf::from("tool.py"),
+                move_path: None,
+                mode: Some(0o644),
+                chunks: Vec::new(),
+            },
+            UpdateFile {
+                path: PathBuf::from("lib.py"),
+                move_path: Some(PathBuf::from("lib2.py")),
+                mode: Some(0o600),
+                chunks: vec![UpdateFileChunk {
+                    change_context: None,
+                    old_lines: vec![],
+                    new_lines: vec!["x = 1".to_string()],
+                    is_end_of_file: false,
+                }],
+            },
+        ]
+    );
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Add File: run.sh\n\
+             *** Set Mode: rwx\n\
+             +echo\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError {
+            message: "Invalid mode 'rwx'. Expected octal permission bits no higher than 777, such as 755 or 0644"
+                .to_string(),
+            line_number: 3,
+        })
+    );
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Update File: run.sh\n\
+             *** Set Mode: 4755\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError {
+            message: "Invalid mode '4755'. Expected octal permission bits no higher than 777, such as 755 or 0644"
+                .to_string(),
+            line_number: 3,
+        })
+    );
+    assert_eq!(
+        parse_patch_text(
+            "*** Begin Patch\n\
+             *** Add Binary File: data.bin\n\
+             +not base64!\n\
+             *** End Patch",
+            ParseMode::Strict
+        ),
+        Err(InvalidHunkError {
+            message:
+                "Invalid base64 contents for binary file 'data.bin': Invalid symbol 32, offset 3."
+                    .to_string(),
+            line_number: 2,
+        })
+    );
+}
+
 #[test]
 fn test_parse_one_hunk() {
     assert_eq!(
         parse_one_hunk(&["bad"], 234),
         Err(InvalidHunkError {
             message: "'bad' is not a valid hunk header. \
-            Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}'".to_string(),
+            Valid hunk headers: '*** Add File: {path}', '*** Add Binary File: {path}', '*** Delete File: {path}', '*** Update File: {path}'".to_string(),
             line_number: 234
         })
     );
@@ -1,7 +1,10 @@
+use similar::TextDiff;
+
 /// Attempt to find the sequence of `pattern` lines within `lines` beginning at or after `start`.
 /// Returns the starting index of the match or `None` if not found. Matches are attempted with
 /// decreasing strictness: exact match, then ignoring trailing whitespace, then ignoring leading
-/// and trailing whitespace. When `eof` is true, we first try starting at the end-of-file (so that
+/// and trailing whitespace, and finally normalising punctuation. See [`fuzzy_seek_sequence`] for
+/// the last-resort similarity match. When `eof` is true, we first try starting at the end-of-file (so that
 /// patterns intended to match file endings are applied at the end), and fall back to searching
 /// from `start` if needed.
 ///
@@ -65,7 +68,7 @@ pub(crate) fn seek_sequence(
     }
 
     // ------------------------------------------------------------------
-    // Final, most permissive pass – attempt to match after *normalising*
+    // Punctuation-normalising pass – attempt to match after *normalising*
     // common Unicode punctuation to their ASCII equivalents so that diffs
     // authored with plain ASCII characters can still be applied to source
     // files that contain typographic dashes / quotes, etc.  This mirrors the
@@ -73,26 +76,6 @@ pub(crate) fn seek_sequence(
     // differences when locating context lines.
     // ------------------------------------------------------------------
 
-    fn normalise(s: &str) -> String {
-        s.trim()
-            .chars()
-            .map(|c| match c {
-                // Various dash / hyphen code-points → ASCII '-'
-                '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
-                | '\u{2212}' => '-',
-                // Fancy single quotes → '\''
-                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
-                // Fancy double quotes → '"'
-                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
-                // Non-breaking space and other odd spaces → normal space
-                '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
-                | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
-                | '\u{3000}' => ' ',
-                other => other,
-            })
-            .collect::<String>()
-    }
-
     for i in search_start..=lines.len().saturating_sub(pattern.len()) {
         let mut ok = true;
         for (p_idx, pat) in pattern.iter().enumerate() {
@@ -109,8 +92,140 @@ pub(crate) fn seek_sequence(
     None
 }
 
+/// Minimum average line similarity for [`fuzzy_seek_sequence`].
+const FUZZY_MATCH_THRESHOLD: f32 = 0.9;
+
+/// Patterns shorter than this are too short to locate by similarity alone.
+pub(crate) const MIN_FUZZY_MATCH_LINES: usize = 3;
+
+/// Lines are compared on at most this many characters when scoring
+/// similarity, to bound the cost of the character-level diff.
+const MAX_SIMILARITY_CHARS: usize = 160;
+
+/// Upper bound on line diffs computed by one [`closest_sequence`] search. The
+/// best region found so far is returned once it is exhausted.
+const MAX_LINE_DIFFS: usize = 20_000;
+
+/// Last resort after [`seek_sequence`]: accept the most similar region if its
+/// lines are on average at least 90% similar, so that small typos in context
+/// lines do not fail the whole patch. Only patterns of at least
+/// [`MIN_FUZZY_MATCH_LINES`] lines are matched this way, and lines flagged in
+/// `required` (typically the lines the patch deletes) must still match after
+/// trimming and normalising punctuation.
+pub(crate) fn fuzzy_seek_sequence(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    eof: bool,
+    required: &[bool],
+) -> Option<usize> {
+    if pattern.len() < MIN_FUZZY_MATCH_LINES || pattern.len() > lines.len() {
+        return None;
+    }
+    let search_start = if eof {
+        lines.len() - pattern.len()
+    } else {
+        start
+    };
+    closest_sequence(lines, pattern, search_start, required)
+        .filter(|closest| closest.similarity >= FUZZY_MATCH_THRESHOLD)
+        .map(|closest| closest.start)
+}
+
+/// The region of a file most similar to a pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ClosestMatch {
+    /// Index of the first line of the region.
+    pub start: usize,
+    /// Average similarity of the region's lines to the pattern, from 0 to 1.
+    pub similarity: f32,
+}
+
+/// Find the `pattern.len()` lines at or after `start` that are most similar to
+/// `pattern`. Lines are compared after trimming and normalising punctuation,
+/// and scored by the character-level similarity ratio of their diff. Regions
+/// where a line flagged in `required` differs are skipped.
+pub(crate) fn closest_sequence(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    required: &[bool],
+) -> Option<ClosestMatch> {
+    if pattern.is_empty() || pattern.len() > lines.len() {
+        return None;
+    }
+    let lines: Vec<String> = lines.iter().map(|line| normalise(line)).collect();
+    let pattern: Vec<String> = pattern.iter().map(|line| normalise(line)).collect();
+    let len = pattern.len() as f32;
+
+    let mut diffs = 0;
+    let mut best: Option<(usize, f32)> = None;
+    'windows: for i in start..=lines.len() - pattern.len() {
+        let window = &lines[i..i + pattern.len()];
+        let is_required = |p_idx: usize| required.get(p_idx).copied().unwrap_or(false);
+        if (0..pattern.len()).any(|p_idx| is_required(p_idx) && window[p_idx] != pattern[p_idx]) {
+            continue;
+        }
+        let best_total = best.map_or(0.0, |(_, total)| total);
+        let mut total = 0.0;
+        for (p_idx, pat) in pattern.iter().enumerate() {
+            // Stop early once this window can no longer beat the best one.
+            let remaining = (pattern.len() - p_idx) as f32;
+            if total + remaining <= best_total {
+                break;
+            }
+            if window[p_idx] == *pat {
+                total += 1.0;
+                continue;
+            }
+            if diffs == MAX_LINE_DIFFS {
+                break 'windows;
+            }
+            diffs += 1;
+            total += line_similarity(&window[p_idx], pat);
+        }
+        if total > best_total {
+            best = Some((i, total));
+        }
+    }
+    best.map(|(start, total)| ClosestMatch {
+        start,
+        similarity: total / len,
+    })
+}
+
+fn line_similarity(line: &str, pattern: &str) -> f32 {
+    let prefix = |text: &str| match text.char_indices().nth(MAX_SIMILARITY_CHARS) {
+        Some((end, _)) => text[..end].to_string(),
+        None => text.to_string(),
+    };
+    TextDiff::from_chars(&prefix(line), &prefix(pattern)).ratio()
+}
+
+fn normalise(s: &str) -> String {
+    s.trim()
+        .chars()
+        .map(|c| match c {
+            // Various dash / hyphen code-points → ASCII '-'
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+            | '\u{2212}' => '-',
+            // Fancy single quotes → '\''
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            // Fancy double quotes → '"'
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            // Non-breaking space and other odd spaces → normal space
+            '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
+            | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
+            | '\u{3000}' => ' ',
+            other => other,
+        })
+        .collect::<String>()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::closest_sequence;
+    use super::fuzzy_seek_sequence;
     use super::seek_sequence;
     use std::string::ToString;
 
@@ -148,4 +263,51 @@ mod tests {
         // Should not panic – must return None when pattern cannot possibly fit.
         assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
     }
+
+    #[test]
+    fn test_fuzzy_match_tolerates_small_typos() {
+        let lines = to_vec(&[
+            "fn main() {",
+            "    let total = compute_total(&items);",
+            "    println!(\"{total}\");",
+            "}",
+        ]);
+        // Pattern misspells the function name.
+        let pattern = to_vec(&[
+            "fn main() {",
+            "    let total = compute_totl(&items);",
+            "    println!(\"{total}\");",
+        ]);
+        assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
+        assert_eq!(
+            fuzzy_seek_sequence(&lines, &pattern, 0, false, &[]),
+            Some(0)
+        );
+        // A line the patch deletes has to match.
+        assert_eq!(
+            fuzzy_seek_sequence(&lines, &pattern, 0, false, &[false, true, false]),
+            None
+        );
+        // Too few lines to match by similarity.
+        assert_eq!(
+            fuzzy_seek_sequence(&lines, &pattern[1..], 0, false, &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_fuzzy_match_rejects_dissimilar_lines() {
+        let lines = to_vec(&["alpha", "beta", "gamma", "delta"]);
+        let pattern = to_vec(&["alpha", "beta", "epsilon"]);
+        assert_eq!(fuzzy_seek_sequence(&lines, &pattern, 0, false, &[]), None);
+    }
+
+    #[test]
+    fn test_closest_sequence_reports_most_similar_region() {
+        let lines = to_vec(&["one", "two", "three", "four"]);
+        let pattern = to_vec(&["three", "fiver"]);
+        let closest = closest_sequence(&lines, &pattern, 0, &[]).expect("a candidate region");
+        assert_eq!(closest.start, 2);
+        assert!(closest.similarity > 0.5 && closest.similarity < 0.9);
+    }
 }
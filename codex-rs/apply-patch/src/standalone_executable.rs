@@ -54,6 +54,6 @@ pub fn run_main() -> i32 {
             let _ = stdout.flush();
             0
         }
-        Err(_) => 1,
+        Err(err) => err.outcome().exit_code(),
     }
 }
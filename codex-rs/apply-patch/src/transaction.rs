@@ -0,0 +1,509 @@
+//! All-or-nothing application of the file changes of a patch.
+//!
+//! Every hunk is first applied to an in-memory [`StagedChanges`]. Committing
+//! writes each new file to a temporary sibling, then renames the temporaries
+//! into place and moves deleted files aside. If any step fails, the files
+//! already replaced are restored from in-memory backups and the deleted ones
+//! are moved back.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Printed on stderr after the error when a failed commit restored every file.
+pub const ROLLED_BACK_MESSAGE: &str = "Rolled back; no files were changed.";
+
+/// Printed on stderr after the error, followed by one path per line, when a
+/// failed commit could not restore every file.
+pub const ROLLBACK_FAILED_MESSAGE: &str = "Rollback failed; these files may be left modified:";
+
+/// What an `apply_patch` run left on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    /// Every change was written.
+    Committed,
+    /// The patch failed before anything was written.
+    NotApplied,
+    /// Writing failed part way and every file was restored.
+    RolledBack,
+    /// Writing failed part way and some files could not be restored.
+    RollbackFailed,
+}
+
+impl TransactionOutcome {
+    /// Exit status of an `apply_patch` process that ended with this outcome.
+    /// `2` is left for usage errors.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Self::Committed => 0,
+            Self::NotApplied => 1,
+            Self::RolledBack => 3,
+            Self::RollbackFailed => 4,
+        }
+    }
+
+    /// Recovers the outcome of an `apply_patch` process from its exit status.
+    pub fn from_exit_code(exit_code: i32) -> Self {
+        match exit_code {
+            0 => Self::Committed,
+            3 => Self::RolledBack,
+            4 => Self::RollbackFailed,
+            _ => Self::NotApplied,
+        }
+    }
+}
+
+/// The final contents of every path a patch touches, `None` for deleted
+/// files, in the order the paths were first touched.
+#[derive(Debug, Default)]
+pub(crate) struct StagedChanges {
+    order: Vec<PathBuf>,
+    contents: HashMap<PathBuf, Option<StagedFile>>,
+}
+
+#[derive(Debug, Clone)]
+struct StagedFile {
+    contents: Vec<u8>,
+    /// Permission bits to set; `None` keeps those of the file being replaced.
+    mode: Option<u32>,
+}
+
+impl StagedChanges {
+    /// The staged contents of `path`, or `None` if the patch has not touched it.
+    pub(crate) fn read(&self, path: &Path) -> Option<io::Result<Vec<u8>>> {
+        self.contents.get(path).map(|file| {
+            file.as_ref()
+                .map(|file| file.contents.clone())
+                .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+        })
+    }
+
+    /// Whether `path` is a file once the changes staged so far are applied.
+    pub(crate) fn is_file(&self, path: &Path) -> bool {
+        match self.contents.get(path) {
+            Some(contents) => contents.is_some(),
+            None => fs::symlink_metadata(path)
+                .is_ok_and(|metadata| metadata.is_file() || metadata.is_symlink()),
+        }
+    }
+
+    /// Stages new contents for `path`. Without a `mode`, a mode staged
+    /// earlier for the same path is kept.
+    pub(crate) fn write(&mut self, path: &Path, contents: impl Into<Vec<u8>>, mode: Option<u32>) {
+        let mode = mode.or_else(|| {
+            self.contents
+                .get(path)
+                .and_then(|file| file.as_ref().and_then(|file| file.mode))
+        });
+        self.stage(
+            path,
+            Some(StagedFile {
+                contents: contents.into(),
+                mode,
+            }),
+        );
+    }
+
+    pub(crate) fn delete(&mut self, path: &Path) {
+        self.stage(path, None);
+    }
+
+    fn stage(&mut self, path: &Path, file: Option<StagedFile>) {
+        if self.contents.insert(path.to_path_buf(), file).is_none() {
+            self.order.push(path.to_path_buf());
+        }
+    }
+
+    /// Writes every staged change, or none of them.
+    pub(crate) fn commit(self) -> Result<(), CommitError> {
+        let mut prepared = Prepared::default();
+        for path in &self.order {
+            let file = self.contents.get(path).cloned().flatten();
+            if let Err(error) = prepared.prepare(path, file) {
+                return Err(prepared.rollback(error));
+            }
+        }
+        prepared.apply()
+    }
+}
+
+/// A failed commit and the files it could not restore.
+#[derive(Debug)]
+pub(crate) struct CommitError {
+    pub(crate) error: anyhow::Error,
+    /// Whether any file had already been replaced or deleted when the error occurred.
+    pub(crate) rolled_back: bool,
+    pub(crate) unrestored: Vec<PathBuf>,
+}
+
+impl CommitError {
+    pub(crate) fn outcome(&self) -> TransactionOutcome {
+        if !self.unrestored.is_empty() {
+            TransactionOutcome::RollbackFailed
+        } else if self.rolled_back {
+            TransactionOutcome::RolledBack
+        } else {
+            TransactionOutcome::NotApplied
+        }
+    }
+}
+
+impl std::error::Error for CommitError {}
+
+impl std::fmt::Display for CommitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.error)?;
+        if !self.unrestored.is_empty() {
+            write!(f, "\n{ROLLBACK_FAILED_MESSAGE}")?;
+            for path in &self.unrestored {
+                write!(f, "\n  {}", path.display())?;
+            }
+        } else if self.rolled_back {
+            write!(f, "\n{ROLLED_BACK_MESSAGE}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct PreparedChange {
+    /// The path as written in the patch, used in messages.
+    path: PathBuf,
+    /// The file actually changed: `path`, or the file it links to.
+    target: PathBuf,
+    action: PreparedAction,
+}
+
+#[derive(Debug)]
+enum PreparedAction {
+    /// Rename `temp` over the target. `backup` holds the original contents
+    /// and permissions, if the target existed.
+    Replace {
+        temp: PathBuf,
+        backup: Option<(Vec<u8>, fs::Permissions)>,
+    },
+    /// Rename the target to `backup`, which is removed once the commit succeeds.
+    Delete { backup: PathBuf },
+}
+
+#[derive(Debug, Default)]
+struct Prepared {
+    changes: Vec<PreparedChange>,
+    created_dirs: Vec<PathBuf>,
+    /// Number of `changes` already applied to their targets.
+    applied: usize,
+}
+
+impl Prepared {
+    /// Writes the new contents next to the target without touching the
+    /// target itself.
+    fn prepare(&mut self, path: &Path, file: Option<StagedFile>) -> anyhow::Result<()> {
+        let Some(StagedFile { contents, mode }) = file else {
+            // A file the same patch added and then deleted never reached the disk.
+            if fs::symlink_metadata(path).is_err() {
+                return Ok(());
+            }
+            self.changes.push(PreparedChange {
+                path: path.to_path_buf(),
+                target: path.to_path_buf(),
+                action: PreparedAction::Delete {
+                    backup: sibling_path(path, "bak"),
+                },
+            });
+            return Ok(());
+        };
+
+        let write_error =
+            |err: io::Error| anyhow::anyhow!("Failed to write file {}: {err}", path.display());
+        let target = match fs::symlink_metadata(path) {
+            Ok(metadata) if metadata.is_symlink() => fs::canonicalize(path).map_err(write_error)?,
+            _ => path.to_path_buf(),
+        };
+        let backup = match fs::metadata(&target) {
+            Ok(metadata) => Some((
+                fs::read(&target).map_err(write_error)?,
+                metadata.permissions(),
+            )),
+            Err(_) => None,
+        };
+        if let Some(parent) = target.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            self.create_dir_all(parent).map_err(|err| {
+                anyhow::anyhow!(
+                    "Failed to create parent directories for {}: {err}",
+                    path.display()
+                )
+            })?;
+        }
+        let temp = sibling_path(&target, "tmp");
+        let permissions =
+            permissions_for(mode, backup.as_ref().map(|(_, permissions)| permissions));
+        let write = fs::write(&temp, contents).and_then(|()| match permissions {
+            Some(permissions) => fs::set_permissions(&temp, permissions),
+            None => Ok(()),
+        });
+        // Record the temporary file even if the write failed so it is cleaned up.
+        self.changes.push(PreparedChange {
+            path: path.to_path_buf(),
+            target,
+            action: PreparedAction::Replace { temp, backup },
+        });
+        write.map_err(write_error)
+    }
+
+    fn create_dir_all(&mut self, dir: &Path) -> io::Result<()> {
+        let mut missing = Vec::new();
+        let mut current = Some(dir);
+        while let Some(dir) = current
+            && !dir.as_os_str().is_empty()
+            && fs::symlink_metadata(dir).is_err()
+        {
+            missing.push(dir.to_path_buf());
+            current = dir.parent();
+        }
+        for dir in missing.into_iter().rev() {
+            fs::create_dir(&dir)?;
+            self.created_dirs.push(dir);
+        }
+        Ok(())
+    }
+
+    /// Moves every prepared change into place, rolling back on the first failure.
+    fn apply(mut self) -> Result<(), CommitError> {
+        while let Some(change) = self.changes.get(self.applied) {
+            let result = match &change.action {
+                PreparedAction::Replace { temp, .. } => {
+                    fs::rename(temp, &change.target).map_err(|err| {
+                        anyhow::anyhow!("Failed to write file {}: {err}", change.path.display())
+                    })
+                }
+                PreparedAction::Delete { backup } => {
+                    fs::rename(&change.target, backup).map_err(|err| {
+                        anyhow::anyhow!("Failed to delete file {}: {err}", change.path.display())
+                    })
+                }
+            };
+            if let Err(error) = result {
+                return Err(self.rollback(error));
+            }
+            self.applied += 1;
+        }
+        for change in &self.changes {
+            if let PreparedAction::Delete { backup } = &change.action {
+                let _ = fs::remove_file(backup);
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores every applied change and removes temporary files and created
+    /// directories.
+    fn rollback(self, error: anyhow::Error) -> CommitError {
+        let mut unrestored = Vec::new();
+        for change in self.changes[..self.applied].iter().rev() {
+            let restored = match &change.action {
+                PreparedAction::Replace {
+                    backup: Some((contents, permissions)),
+                    ..
+                } => fs::write(&change.target, contents)
+                    .and_then(|()| fs::set_permissions(&change.target, permissions.clone())),
+                PreparedAction::Replace { backup: None, .. } => fs::remove_file(&change.target),
+                PreparedAction::Delete { backup } => fs::rename(backup, &change.target),
+            };
+            if restored.is_err() {
+                unrestored.push(change.path.clone());
+            }
+        }
+        unrestored.reverse();
+        for change in &self.changes[self.applied..] {
+            if let PreparedAction::Replace { temp, .. } = &change.action {
+                let _ = fs::remove_file(temp);
+            }
+        }
+        for dir in self.created_dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+        CommitError {
+            error,
+            rolled_back: self.applied > 0,
+            unrestored,
+        }
+    }
+}
+
+/// The permissions for a new file: `mode` where the platform supports it,
+/// otherwise those of the file being replaced.
+#[cfg(unix)]
+fn permissions_for(
+    mode: Option<u32>,
+    existing: Option<&fs::Permissions>,
+) -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+
+    match mode {
+        Some(mode) => Some(fs::Permissions::from_mode(mode & 0o777)),
+        None => existing.cloned(),
+    }
+}
+
+#[cfg(not(unix))]
+fn permissions_for(
+    _mode: Option<u32>,
+    existing: Option<&fs::Permissions>,
+) -> Option<fs::Permissions> {
+    existing.cloned()
+}
+
+/// A hidden file next to `path` for temporary contents or backups.
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(
+        ".{name}.apply_patch.{}.{extension}",
+        std::process::id()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn commit_writes_and_deletes_staged_files() {
+        let dir = tempdir().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let removed = dir.path().join("removed.txt");
+        fs::write(&kept, "old\n").unwrap();
+        fs::write(&removed, "bye\n").unwrap();
+
+        let mut staged = StagedChanges::default();
+        staged.write(&kept, "new\n", None);
+        staged.write(&dir.path().join("nested/added.txt"), "hi\n", None);
+        staged.delete(&removed);
+        staged.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&kept).unwrap(), "new\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("nested/added.txt")).unwrap(),
+            "hi\n"
+        );
+        assert!(!removed.exists());
+    }
+
+    fn staged_file(contents: &str) -> Option<StagedFile> {
+        Some(StagedFile {
+            contents: contents.as_bytes().to_vec(),
+            mode: None,
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commit_sets_staged_mode_and_keeps_existing_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        let kept = dir.path().join("kept.sh");
+        fs::write(&kept, "old\n").unwrap();
+        fs::set_permissions(&kept, fs::Permissions::from_mode(0o750)).unwrap();
+
+        let mut staged = StagedChanges::default();
+        staged.write(&script, "#!/bin/sh\n", Some(0o755));
+        // A later write without a mode keeps the one staged earlier.
+        staged.write(&script, "#!/bin/sh\necho hi\n", None);
+        staged.write(&kept, "new\n", None);
+        staged.commit().unwrap();
+
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&script), 0o755);
+        assert_eq!(mode(&kept), 0o750);
+    }
+
+    #[test]
+    fn failed_apply_restores_files_already_replaced() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        let deleted = dir.path().join("deleted.txt");
+        fs::write(&first, "first\n").unwrap();
+        fs::write(&second, "second\n").unwrap();
+        fs::write(&deleted, "deleted\n").unwrap();
+
+        let mut prepared = Prepared::default();
+        prepared.prepare(&first, staged_file("changed\n")).unwrap();
+        prepared
+            .prepare(&dir.path().join("new/added.txt"), staged_file("added\n"))
+            .unwrap();
+        prepared.prepare(&deleted, None).unwrap();
+        prepared.prepare(&second, staged_file("changed\n")).unwrap();
+        // Make the last rename fail after the others succeeded.
+        let PreparedAction::Replace { temp, .. } = &prepared.changes[3].action else {
+            panic!("expected a replacement");
+        };
+        fs::remove_file(temp).unwrap();
+
+        let err = prepared.apply().unwrap_err();
+
+        assert_eq!(err.outcome(), TransactionOutcome::RolledBack);
+        assert_eq!(err.unrestored, Vec::<PathBuf>::new());
+        assert!(err.to_string().ends_with(ROLLED_BACK_MESSAGE));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second\n");
+        assert_eq!(fs::read_to_string(&deleted).unwrap(), "deleted\n");
+        assert!(!dir.path().join("new").exists());
+        let mut leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        leftovers.sort();
+        assert_eq!(leftovers, vec!["deleted.txt", "first.txt", "second.txt"]);
+    }
+
+    #[test]
+    fn commit_skips_files_added_and_deleted_in_the_same_patch() {
+        let dir = tempdir().unwrap();
+        let scratch = dir.path().join("scratch.txt");
+        let kept = dir.path().join("kept.txt");
+
+        let mut staged = StagedChanges::default();
+        staged.write(&scratch, "temporary\n", None);
+        staged.write(&kept, "kept\n", None);
+        staged.delete(&scratch);
+        staged.commit().unwrap();
+
+        assert!(!scratch.exists());
+        assert_eq!(fs::read_to_string(&kept).unwrap(), "kept\n");
+    }
+
+    #[test]
+    fn outcome_round_trips_through_the_exit_code() {
+        for outcome in [
+            TransactionOutcome::Committed,
+            TransactionOutcome::NotApplied,
+            TransactionOutcome::RolledBack,
+            TransactionOutcome::RollbackFailed,
+        ] {
+            assert_eq!(
+                TransactionOutcome::from_exit_code(outcome.exit_code()),
+                outcome
+            );
+        }
+        // Usage errors and signals mean nothing was written.
+        assert_eq!(
+            TransactionOutcome::from_exit_code(2),
+            TransactionOutcome::NotApplied
+        );
+        assert_eq!(
+            TransactionOutcome::from_exit_code(-1),
+            TransactionOutcome::NotApplied
+        );
+    }
+}
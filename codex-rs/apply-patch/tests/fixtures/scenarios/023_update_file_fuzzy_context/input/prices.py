@@ -0,0 +1,3 @@
+def total(items):
+    result = sum(item.price for item in items)
+    return result
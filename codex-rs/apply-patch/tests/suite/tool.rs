@@ -214,7 +214,7 @@ fn test_apply_patch_cli_rejects_invalid_hunk_header() -> anyhow::Result<()> {
         .arg("*** Begin Patch\n*** Frobnicate File: foo\n*** End Patch")
         .assert()
         .failure()
-        .stderr("Invalid patch hunk on line 2: '*** Frobnicate File: foo' is not a valid hunk header. Valid hunk headers: '*** Add File: {path}', '*** Delete File: {path}', '*** Update File: {path}'\n");
+        .stderr("Invalid patch hunk on line 2: '*** Frobnicate File: foo' is not a valid hunk header. Valid hunk headers: '*** Add File: {path}', '*** Add Binary File: {path}', '*** Delete File: {path}', '*** Update File: {path}'\n");
 
     Ok(())
 }
@@ -240,7 +240,7 @@ fn test_apply_patch_cli_updates_file_appends_trailing_newline() -> anyhow::Resul
 }
 
 #[test]
-fn test_apply_patch_cli_failure_after_partial_success_leaves_changes() -> anyhow::Result<()> {
+fn test_apply_patch_cli_failure_after_earlier_hunks_changes_nothing() -> anyhow::Result<()> {
     let tmp = tempdir()?;
     let new_file = tmp.path().join("created.txt");
 
@@ -251,7 +251,40 @@ fn test_apply_patch_cli_failure_after_partial_success_leaves_changes() -> anyhow
         .stdout("")
         .stderr("Failed to read file to update missing.txt: No such file or directory (os error 2)\n");
 
-    assert_eq!(fs::read_to_string(&new_file)?, "hello\n");
+    assert!(!new_file.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_apply_patch_cli_write_failure_restores_earlier_files() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let modify_path = tmp.path().join("modify.txt");
+    let delete_path = tmp.path().join("delete.txt");
+    fs::write(&modify_path, "line1\nline2\n")?;
+    fs::write(&delete_path, "obsolete\n")?;
+    // A file where the patch expects a directory makes the last write fail.
+    fs::write(tmp.path().join("blocked"), "not a directory\n")?;
+
+    let assert = apply_patch_command(tmp.path())?
+        .arg("*** Begin Patch\n*** Update File: modify.txt\n@@\n-line2\n+changed\n*** Delete File: delete.txt\n*** Add File: nested/new.txt\n+created\n*** Add File: blocked/new.txt\n+created\n*** End Patch")
+        .assert()
+        .failure()
+        .stdout("");
+    let stderr = String::from_utf8(assert.get_output().stderr.clone())?;
+    assert!(
+        stderr.starts_with("Failed to write file blocked/new.txt"),
+        "unexpected stderr: {stderr}"
+    );
+
+    assert_eq!(fs::read_to_string(&modify_path)?, "line1\nline2\n");
+    assert_eq!(fs::read_to_string(&delete_path)?, "obsolete\n");
+    assert!(!tmp.path().join("nested").exists());
+    let mut entries = fs::read_dir(tmp.path())?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    entries.sort();
+    assert_eq!(entries, vec!["blocked", "delete.txt", "modify.txt"]);
 
     Ok(())
 }
@@ -53,7 +53,7 @@ pub fn arg0_dispatch() -> Option<Arg0PathEntryGuard> {
                 let mut stderr = std::io::stderr();
                 match codex_apply_patch::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
                     Ok(()) => 0,
-                    Err(_) => 1,
+                    Err(err) => err.outcome().exit_code(),
                 }
             }
             None => {
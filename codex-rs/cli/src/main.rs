@@ -372,17 +372,28 @@ fn format_exit_messages(exit_info: AppExitInfo, color_enabled: bool) -> Vec<Stri
         token_usage,
         thread_id: conversation_id,
         thread_name,
+        kept_worktrees,
         ..
     } = exit_info;
 
+    let mut lines = Vec::new();
+    if !kept_worktrees.is_empty() {
+        lines.push("Kept agent worktrees with unmerged changes:".to_string());
+        lines.extend(
+            kept_worktrees
+                .iter()
+                .map(|path| format!("  {}", path.display())),
+        );
+    }
+
     if token_usage.is_zero() {
-        return Vec::new();
+        return lines;
     }
 
-    let mut lines = vec![format!(
+    lines.push(format!(
         "{}",
         codex_core::protocol::FinalOutput::from(token_usage)
-    )];
+    ));
 
     if let Some(resume_cmd) =
         codex_core::util::resume_command(thread_name.as_deref(), conversation_id)
@@ -1117,6 +1128,7 @@ mod tests {
                 .map(Result::unwrap),
             thread_name: thread_name.map(str::to_string),
             update_action: None,
+            kept_worktrees: Vec::new(),
             exit_reason: ExitReason::UserRequested,
         }
     }
@@ -1128,6 +1140,7 @@ mod tests {
             thread_id: None,
             thread_name: None,
             update_action: None,
+            kept_worktrees: Vec::new(),
             exit_reason: ExitReason::UserRequested,
         };
         let lines = format_exit_messages(exit_info, false);
@@ -1148,6 +1161,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_exit_messages_lists_kept_worktrees() {
+        let exit_info = AppExitInfo {
+            kept_worktrees: vec![PathBuf::from("/tmp/worktrees/agent-1")],
+            ..sample_exit_info(None, None)
+        };
+        let lines = format_exit_messages(exit_info, false);
+        assert_eq!(
+            lines,
+            vec![
+                "Kept agent worktrees with unmerged changes:".to_string(),
+                "  /tmp/worktrees/agent-1".to_string(),
+                "Token usage: total=2 input=0 output=2".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn format_exit_messages_applies_color_when_enabled() {
         let exit_info = sample_exit_info(Some("123e4567-e89b-12d3-a456-426614174000"), None);
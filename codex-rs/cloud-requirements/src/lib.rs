@@ -302,6 +302,11 @@ mod tests {
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
+                allowed_models: None,
+                allowed_model_providers: None,
+                denied_mcp_tools: None,
+                shell_environment_policy: None,
+                max_additional_writable_roots: None,
             })
         );
     }
@@ -342,6 +347,11 @@ mod tests {
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
+                allowed_models: None,
+                allowed_model_providers: None,
+                denied_mcp_tools: None,
+                shell_environment_policy: None,
+                max_additional_writable_roots: None,
             })
         );
     }
@@ -1,12 +1,19 @@
 use crate::agent::AgentStatus;
+use crate::agent::guards::AgentTreeBudget;
 use crate::agent::guards::Guards;
+use crate::agent::worktree::AgentWorktreeAction;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::thread_manager::ThreadManagerState;
+use codex_git::AgentWorktree;
 use codex_protocol::ThreadId;
+use codex_protocol::protocol::AgentTreeUsage;
 use codex_protocol::protocol::Op;
 use codex_protocol::user_input::UserInput;
+use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::Weak;
 use tokio::sync::watch;
 
@@ -23,6 +30,9 @@ pub(crate) struct AgentControl {
     /// `ThreadManagerState -> CodexThread -> Session -> SessionServices -> ThreadManagerState`.
     manager: Weak<ThreadManagerState>,
     state: Arc<Guards>,
+    /// Final-answer schemas of agents spawned with an `output_schema`, re-sent with every
+    /// prompt so each of their turns ends in a structured result.
+    output_schemas: Arc<Mutex<HashMap<ThreadId, Value>>>,
 }
 
 impl AgentControl {
@@ -40,9 +50,13 @@ impl AgentControl {
         config: crate::config::Config,
         prompt: String,
         session_source: Option<codex_protocol::protocol::SessionSource>,
+        output_schema: Option<Value>,
     ) -> CodexResult<ThreadId> {
         let state = self.upgrade()?;
-        let reservation = self.state.reserve_spawn_slot(config.agent_max_threads)?;
+        let reservation = self.state.reserve_spawn_slot(
+            config.agent_max_threads,
+            &AgentTreeBudget::from_config(&config),
+        )?;
 
         // The same `AgentControl` is sent to spawn the thread.
         let new_thread = match session_source {
@@ -54,6 +68,12 @@ impl AgentControl {
             None => state.spawn_new_thread(config, self.clone()).await?,
         };
         reservation.commit(new_thread.thread_id);
+        if let Some(output_schema) = output_schema {
+            self.output_schemas
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .insert(new_thread.thread_id, output_schema);
+        }
 
         // Notify a new thread has been created. This notification will be processed by clients
         // to subscribe or drain this newly created thread.
@@ -81,17 +101,25 @@ impl AgentControl {
                         // Agent control prompts are plain text with no UI text elements.
                         text_elements: Vec::new(),
                     }],
-                    final_output_json_schema: None,
+                    final_output_json_schema: self.output_schema(agent_id),
                 },
             )
             .await;
         if matches!(result, Err(CodexErr::InternalAgentDied)) {
-            let _ = state.remove_thread(&agent_id).await;
-            self.state.release_spawned_thread(agent_id);
+            self.release_agent(&state, agent_id).await;
         }
         result
     }
 
+    /// Schema the final answers of `agent_id` must follow, if it was spawned with one.
+    pub(crate) fn output_schema(&self, agent_id: ThreadId) -> Option<Value> {
+        self.output_schemas
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .get(&agent_id)
+            .cloned()
+    }
+
     /// Interrupt the current task for an existing agent thread.
     pub(crate) async fn interrupt_agent(&self, agent_id: ThreadId) -> CodexResult<String> {
         let state = self.upgrade()?;
@@ -102,9 +130,19 @@ impl AgentControl {
     pub(crate) async fn shutdown_agent(&self, agent_id: ThreadId) -> CodexResult<String> {
         let state = self.upgrade()?;
         let result = state.send_op(agent_id, Op::Shutdown {}).await;
+        self.release_agent(&state, agent_id).await;
+        result
+    }
+
+    /// Forget a finished agent: drop its thread, free its spawn slot and its
+    /// output schema.
+    async fn release_agent(&self, state: &ThreadManagerState, agent_id: ThreadId) {
         let _ = state.remove_thread(&agent_id).await;
         self.state.release_spawned_thread(agent_id);
-        result
+        self.output_schemas
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .remove(&agent_id);
     }
 
     /// Fetch the last known status for `agent_id`, returning `NotFound` when unavailable.
@@ -129,6 +167,50 @@ impl AgentControl {
         Ok(thread.subscribe_status())
     }
 
+    /// Charge `agent_id`'s cumulative token usage to its agent tree.
+    pub(crate) fn record_token_usage(&self, agent_id: ThreadId, total_tokens: i64) {
+        self.state.record_token_usage(agent_id, total_tokens);
+    }
+
+    /// Tokens and time spent by the agent tree so far, against `budget`.
+    pub(crate) fn tree_usage(&self, budget: &AgentTreeBudget) -> AgentTreeUsage {
+        self.state.usage(budget)
+    }
+
+    /// Changes whenever a sub-agent in the tree reports token usage.
+    pub(crate) fn subscribe_tree_usage(&self) -> watch::Receiver<i64> {
+        self.state.subscribe_token_usage()
+    }
+
+    /// Track the isolated worktree `agent_id` was spawned into.
+    pub(crate) async fn register_worktree(&self, agent_id: ThreadId, worktree: AgentWorktree) {
+        if let Ok(state) = self.upgrade() {
+            state.register_agent_worktree(agent_id, worktree).await;
+        }
+    }
+
+    /// Diff, merge, cherry-pick or discard the worktree of `agent_id`.
+    pub(crate) async fn resolve_worktree(
+        &self,
+        agent_id: ThreadId,
+        action: AgentWorktreeAction,
+    ) -> CodexResult<String> {
+        let state = self.upgrade()?;
+        state.resolve_agent_worktree(agent_id, action).await
+    }
+
+    /// The worktree of `agent_id`, once the agent has finished.
+    pub(crate) async fn finished_worktree(&self, agent_id: ThreadId) -> CodexResult<AgentWorktree> {
+        let state = self.upgrade()?;
+        state.finished_agent_worktree(agent_id).await
+    }
+
+    /// Remove the worktree of `agent_id` and its branch.
+    pub(crate) async fn remove_worktree(&self, agent_id: ThreadId) -> CodexResult<()> {
+        let state = self.upgrade()?;
+        state.remove_agent_worktree(agent_id).await
+    }
+
     fn upgrade(&self) -> CodexResult<Arc<ThreadManagerState>> {
         self.manager
             .upgrade()
@@ -278,7 +360,7 @@ mod tests {
         let control = AgentControl::default();
         let (_home, config) = test_config().await;
         let err = control
-            .spawn_agent(config, "hello".to_string(), None)
+            .spawn_agent(config, "hello".to_string(), None, None)
             .await
             .expect_err("spawn_agent should fail without a manager");
         assert_eq!(
@@ -380,7 +462,7 @@ mod tests {
         let harness = AgentControlHarness::new().await;
         let thread_id = harness
             .control
-            .spawn_agent(harness.config.clone(), "spawned".to_string(), None)
+            .spawn_agent(harness.config.clone(), "spawned".to_string(), None, None)
             .await
             .expect("spawn_agent should succeed");
         let _thread = harness
@@ -406,6 +488,49 @@ mod tests {
         assert_eq!(captured, Some(expected));
     }
 
+    #[tokio::test]
+    async fn output_schema_is_sent_with_every_prompt() {
+        let harness = AgentControlHarness::new().await;
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {"summary": {"type": "string"}},
+            "required": ["summary"],
+            "additionalProperties": false
+        });
+        let thread_id = harness
+            .control
+            .spawn_agent(
+                harness.config.clone(),
+                "spawned".to_string(),
+                None,
+                Some(schema.clone()),
+            )
+            .await
+            .expect("spawn_agent should succeed");
+        harness
+            .control
+            .send_prompt(thread_id, "follow up".to_string())
+            .await
+            .expect("send_prompt should succeed");
+
+        let schemas: Vec<_> = harness
+            .manager
+            .captured_ops()
+            .into_iter()
+            .filter_map(|(id, op)| match op {
+                Op::UserInput {
+                    final_output_json_schema,
+                    ..
+                } if id == thread_id => Some(final_output_json_schema),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(schemas, vec![Some(schema.clone()), Some(schema)]);
+
+        let _ = harness.control.shutdown_agent(thread_id).await;
+        assert_eq!(harness.control.output_schema(thread_id), None);
+    }
+
     #[tokio::test]
     async fn spawn_agent_respects_max_threads_limit() {
         let max_threads = 1usize;
@@ -427,12 +552,12 @@ mod tests {
             .expect("start thread");
 
         let first_agent_id = control
-            .spawn_agent(config.clone(), "hello".to_string(), None)
+            .spawn_agent(config.clone(), "hello".to_string(), None, None)
             .await
             .expect("spawn_agent should succeed");
 
         let err = control
-            .spawn_agent(config, "hello again".to_string(), None)
+            .spawn_agent(config, "hello again".to_string(), None, None)
             .await
             .expect_err("spawn_agent should respect max threads");
         let CodexErr::AgentLimitReached {
@@ -465,7 +590,7 @@ mod tests {
         let control = manager.agent_control();
 
         let first_agent_id = control
-            .spawn_agent(config.clone(), "hello".to_string(), None)
+            .spawn_agent(config.clone(), "hello".to_string(), None, None)
             .await
             .expect("spawn_agent should succeed");
         let _ = control
@@ -474,7 +599,7 @@ mod tests {
             .expect("shutdown agent");
 
         let second_agent_id = control
-            .spawn_agent(config.clone(), "hello again".to_string(), None)
+            .spawn_agent(config.clone(), "hello again".to_string(), None, None)
             .await
             .expect("spawn_agent should succeed after shutdown");
         let _ = control
@@ -500,12 +625,12 @@ mod tests {
         let cloned = control.clone();
 
         let first_agent_id = cloned
-            .spawn_agent(config.clone(), "hello".to_string(), None)
+            .spawn_agent(config.clone(), "hello".to_string(), None, None)
             .await
             .expect("spawn_agent should succeed");
 
         let err = control
-            .spawn_agent(config, "hello again".to_string(), None)
+            .spawn_agent(config, "hello again".to_string(), None, None)
             .await
             .expect_err("spawn_agent should respect shared guard");
         let CodexErr::AgentLimitReached { max_threads } = err else {
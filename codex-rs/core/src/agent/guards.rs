@@ -1,17 +1,24 @@
 use crate::error::CodexErr;
 use crate::error::Result;
 use codex_protocol::ThreadId;
+use codex_protocol::protocol::AgentTreeUsage;
 use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::SubAgentSource;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::watch;
 
 /// This structure is used to add some limits on the multi-agent capabilities for Codex. In
 /// the current implementation, it limits:
 /// * Total number of sub-agents (i.e. threads) per user session
+/// * Tokens spent and wall-clock time elapsed across the whole agent tree
 ///
 /// This structure is shared by all agents in the same user session (because the `AgentControl`
 /// is).
@@ -19,10 +26,30 @@ use std::sync::atomic::Ordering;
 pub(crate) struct Guards {
     threads_set: Mutex<HashSet<ThreadId>>,
     total_count: AtomicUsize,
+    /// Set on the first spawn; the wall-clock budget is measured from here.
+    tree_started: OnceLock<Instant>,
+    /// Latest total token usage reported by every sub-agent ever spawned in the tree. Entries
+    /// outlive the thread so that finished agents keep counting against the budget.
+    token_usage: Mutex<HashMap<ThreadId, i64>>,
+    /// Total tokens used by the tree, updated whenever a sub-agent reports usage.
+    tokens_tx: watch::Sender<i64>,
 }
 
-/// Initial agent is depth 0.
-pub(crate) const MAX_THREAD_SPAWN_DEPTH: i32 = 1;
+/// Limits shared by every sub-agent spawned under the same root session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct AgentTreeBudget {
+    pub(crate) max_tokens: Option<i64>,
+    pub(crate) max_wall_clock: Option<Duration>,
+}
+
+impl AgentTreeBudget {
+    pub(crate) fn from_config(config: &crate::config::Config) -> Self {
+        Self {
+            max_tokens: config.agent_token_budget,
+            max_wall_clock: config.agent_wall_clock_budget,
+        }
+    }
+}
 
 fn session_depth(session_source: &SessionSource) -> i32 {
     match session_source {
@@ -36,15 +63,18 @@ pub(crate) fn next_thread_spawn_depth(session_source: &SessionSource) -> i32 {
     session_depth(session_source).saturating_add(1)
 }
 
-pub(crate) fn exceeds_thread_spawn_depth_limit(depth: i32) -> bool {
-    depth > MAX_THREAD_SPAWN_DEPTH
+/// Initial agent is depth 0, so `max_depth` is the deepest level a sub-agent may run at.
+pub(crate) fn exceeds_thread_spawn_depth_limit(depth: i32, max_depth: i32) -> bool {
+    depth > max_depth
 }
 
 impl Guards {
     pub(crate) fn reserve_spawn_slot(
         self: &Arc<Self>,
         max_threads: Option<usize>,
+        budget: &AgentTreeBudget,
     ) -> Result<SpawnReservation> {
+        self.check_budget(budget)?;
         if let Some(max_threads) = max_threads {
             if !self.try_increment_spawned(max_threads) {
                 return Err(CodexErr::AgentLimitReached { max_threads });
@@ -52,12 +82,88 @@ impl Guards {
         } else {
             self.total_count.fetch_add(1, Ordering::AcqRel);
         }
+        self.tree_started.get_or_init(Instant::now);
         Ok(SpawnReservation {
             state: Arc::clone(self),
             active: true,
         })
     }
 
+    /// Records the cumulative token usage reported by `thread_id`. Threads that were not
+    /// spawned through this tree (e.g. the root session) are ignored.
+    pub(crate) fn record_token_usage(&self, thread_id: ThreadId, total_tokens: i64) {
+        let recorded = {
+            let mut usage = self
+                .token_usage
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            match usage.get_mut(&thread_id) {
+                Some(tokens) => {
+                    *tokens = total_tokens;
+                    true
+                }
+                None => false,
+            }
+        };
+        if recorded {
+            self.tokens_tx.send_replace(self.total_tokens());
+        }
+    }
+
+    /// Notifies the receiver every time a sub-agent in the tree reports token usage.
+    pub(crate) fn subscribe_token_usage(&self) -> watch::Receiver<i64> {
+        self.tokens_tx.subscribe()
+    }
+
+    /// Usage of the agent tree so far, alongside the configured budgets.
+    pub(crate) fn usage(&self, budget: &AgentTreeBudget) -> AgentTreeUsage {
+        AgentTreeUsage {
+            total_tokens: self.total_tokens(),
+            elapsed_secs: self.elapsed().as_secs(),
+            token_budget: budget.max_tokens,
+            wall_clock_budget_secs: budget.max_wall_clock.map(|limit| limit.as_secs()),
+        }
+    }
+
+    fn check_budget(&self, budget: &AgentTreeBudget) -> Result<()> {
+        if let Some(max_tokens) = budget.max_tokens {
+            let used = self.total_tokens();
+            if used >= max_tokens {
+                return Err(CodexErr::AgentBudgetExhausted(format!(
+                    "{used} of {max_tokens} tokens used"
+                )));
+            }
+        }
+        if let Some(max_wall_clock) = budget.max_wall_clock
+            && let Some(started) = self.tree_started.get()
+        {
+            let elapsed = started.elapsed();
+            if elapsed >= max_wall_clock {
+                return Err(CodexErr::AgentBudgetExhausted(format!(
+                    "{}s of {}s elapsed",
+                    elapsed.as_secs(),
+                    max_wall_clock.as_secs()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn total_tokens(&self) -> i64 {
+        self.token_usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .values()
+            .sum()
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.tree_started
+            .get()
+            .map(Instant::elapsed)
+            .unwrap_or_default()
+    }
+
     pub(crate) fn release_spawned_thread(&self, thread_id: ThreadId) {
         let removed = {
             let mut threads = self
@@ -77,6 +183,11 @@ impl Guards {
             .lock()
             .unwrap_or_else(std::sync::PoisonError::into_inner);
         threads.insert(thread_id);
+        self.token_usage
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry(thread_id)
+            .or_default();
     }
 
     fn try_increment_spawned(&self, max_threads: usize) -> bool {
@@ -136,7 +247,8 @@ mod tests {
         });
         let child_depth = next_thread_spawn_depth(&session_source);
         assert_eq!(child_depth, 2);
-        assert!(exceeds_thread_spawn_depth_limit(child_depth));
+        assert!(exceeds_thread_spawn_depth_limit(child_depth, 1));
+        assert!(!exceeds_thread_spawn_depth_limit(child_depth, 3));
     }
 
     #[test]
@@ -144,27 +256,33 @@ mod tests {
         let session_source = SessionSource::SubAgent(SubAgentSource::Review);
         assert_eq!(session_depth(&session_source), 0);
         assert_eq!(next_thread_spawn_depth(&session_source), 1);
-        assert!(!exceeds_thread_spawn_depth_limit(1));
+        assert!(!exceeds_thread_spawn_depth_limit(1, 1));
     }
 
     #[test]
     fn reservation_drop_releases_slot() {
         let guards = Arc::new(Guards::default());
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("reserve slot");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("reserve slot");
         drop(reservation);
 
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("slot released");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("slot released");
         drop(reservation);
     }
 
     #[test]
     fn commit_holds_slot_until_release() {
         let guards = Arc::new(Guards::default());
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("reserve slot");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("reserve slot");
         let thread_id = ThreadId::new();
         reservation.commit(thread_id);
 
-        let err = match guards.reserve_spawn_slot(Some(1)) {
+        let err = match guards.reserve_spawn_slot(Some(1), &AgentTreeBudget::default()) {
             Ok(_) => panic!("limit should be enforced"),
             Err(err) => err,
         };
@@ -175,7 +293,7 @@ mod tests {
 
         guards.release_spawned_thread(thread_id);
         let reservation = guards
-            .reserve_spawn_slot(Some(1))
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
             .expect("slot released after thread removal");
         drop(reservation);
     }
@@ -183,13 +301,15 @@ mod tests {
     #[test]
     fn release_ignores_unknown_thread_id() {
         let guards = Arc::new(Guards::default());
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("reserve slot");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("reserve slot");
         let thread_id = ThreadId::new();
         reservation.commit(thread_id);
 
         guards.release_spawned_thread(ThreadId::new());
 
-        let err = match guards.reserve_spawn_slot(Some(1)) {
+        let err = match guards.reserve_spawn_slot(Some(1), &AgentTreeBudget::default()) {
             Ok(_) => panic!("limit should still be enforced"),
             Err(err) => err,
         };
@@ -200,7 +320,7 @@ mod tests {
 
         guards.release_spawned_thread(thread_id);
         let reservation = guards
-            .reserve_spawn_slot(Some(1))
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
             .expect("slot released after real thread removal");
         drop(reservation);
     }
@@ -208,19 +328,23 @@ mod tests {
     #[test]
     fn release_is_idempotent_for_registered_threads() {
         let guards = Arc::new(Guards::default());
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("reserve slot");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("reserve slot");
         let first_id = ThreadId::new();
         reservation.commit(first_id);
 
         guards.release_spawned_thread(first_id);
 
-        let reservation = guards.reserve_spawn_slot(Some(1)).expect("slot reused");
+        let reservation = guards
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
+            .expect("slot reused");
         let second_id = ThreadId::new();
         reservation.commit(second_id);
 
         guards.release_spawned_thread(first_id);
 
-        let err = match guards.reserve_spawn_slot(Some(1)) {
+        let err = match guards.reserve_spawn_slot(Some(1), &AgentTreeBudget::default()) {
             Ok(_) => panic!("limit should still be enforced"),
             Err(err) => err,
         };
@@ -231,8 +355,86 @@ mod tests {
 
         guards.release_spawned_thread(second_id);
         let reservation = guards
-            .reserve_spawn_slot(Some(1))
+            .reserve_spawn_slot(Some(1), &AgentTreeBudget::default())
             .expect("slot released after second thread removal");
         drop(reservation);
     }
+
+    #[test]
+    fn token_usage_notifies_subscribers_for_tree_members() {
+        let guards = Arc::new(Guards::default());
+        let mut usage_rx = guards.subscribe_token_usage();
+        let child_id = ThreadId::new();
+        guards
+            .reserve_spawn_slot(None, &AgentTreeBudget::default())
+            .expect("reserve slot")
+            .commit(child_id);
+
+        guards.record_token_usage(ThreadId::new(), 10_000);
+        assert!(!usage_rx.has_changed().expect("sender alive"));
+
+        guards.record_token_usage(child_id, 250);
+        assert!(usage_rx.has_changed().expect("sender alive"));
+        assert_eq!(*usage_rx.borrow_and_update(), 250);
+    }
+
+    #[test]
+    fn token_budget_counts_finished_children() {
+        let guards = Arc::new(Guards::default());
+        let budget = AgentTreeBudget {
+            max_tokens: Some(1_000),
+            max_wall_clock: None,
+        };
+        let first_id = ThreadId::new();
+        guards
+            .reserve_spawn_slot(None, &budget)
+            .expect("reserve slot")
+            .commit(first_id);
+        guards.record_token_usage(first_id, 400);
+        guards.record_token_usage(ThreadId::new(), 10_000);
+        let second_id = ThreadId::new();
+        guards
+            .reserve_spawn_slot(None, &budget)
+            .expect("budget left")
+            .commit(second_id);
+        guards.record_token_usage(second_id, 600);
+        guards.release_spawned_thread(first_id);
+
+        let err = match guards.reserve_spawn_slot(None, &budget) {
+            Ok(_) => panic!("token budget should be enforced"),
+            Err(err) => err,
+        };
+        let CodexErr::AgentBudgetExhausted(reason) = err else {
+            panic!("expected CodexErr::AgentBudgetExhausted");
+        };
+        assert_eq!(reason, "1000 of 1000 tokens used");
+        assert_eq!(
+            guards.usage(&budget),
+            AgentTreeUsage {
+                total_tokens: 1_000,
+                elapsed_secs: 0,
+                token_budget: Some(1_000),
+                wall_clock_budget_secs: None,
+            }
+        );
+    }
+
+    #[test]
+    fn wall_clock_budget_starts_with_first_spawn() {
+        let guards = Arc::new(Guards::default());
+        let budget = AgentTreeBudget {
+            max_tokens: None,
+            max_wall_clock: Some(Duration::ZERO),
+        };
+        let reservation = guards
+            .reserve_spawn_slot(None, &budget)
+            .expect("clock starts at first spawn");
+        drop(reservation);
+
+        let err = match guards.reserve_spawn_slot(None, &budget) {
+            Ok(_) => panic!("wall-clock budget should be enforced"),
+            Err(err) => err,
+        };
+        assert!(matches!(err, CodexErr::AgentBudgetExhausted(_)));
+    }
 }
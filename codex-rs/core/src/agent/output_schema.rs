@@ -0,0 +1,276 @@
+//! Structured results for sub-agents spawned with an `output_schema`.
+//!
+//! The schema is sent to the model as the final-output format of every turn the
+//! sub-agent runs (the same mechanism as `codex exec --output-schema`), and the
+//! agent's final message is checked against it before `wait` hands it back to
+//! the parent. Schemas must follow the structured outputs strict mode: every
+//! object lists all of its properties in `required` and sets
+//! `"additionalProperties": false`. Only `type`, `properties`, `required`,
+//! `additionalProperties`, `items` and `enum` are understood; composition
+//! keywords and references are rejected when the agent is spawned.
+
+use serde_json::Value;
+
+/// Keywords strict mode accepts but that this module does not validate.
+const UNSUPPORTED_KEYWORDS: &[&str] = &[
+    "anyOf",
+    "allOf",
+    "oneOf",
+    "not",
+    "$ref",
+    "$defs",
+    "definitions",
+];
+
+/// Rejects schemas that cannot describe an agent's final answer in strict mode.
+pub(crate) fn check_output_schema(schema: &Value) -> Result<(), String> {
+    let Some(object) = schema.as_object() else {
+        return Err("output_schema must be a JSON object".to_string());
+    };
+    match object.get("type") {
+        Some(Value::String(kind)) if kind == "object" => {}
+        _ => return Err("output_schema must have \"type\": \"object\"".to_string()),
+    }
+    check_strict(schema, "$").map_err(|err| format!("output_schema: {err}"))
+}
+
+fn check_strict(schema: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Err(format!("{path}: schema must be a JSON object"));
+    };
+    if let Some(keyword) = UNSUPPORTED_KEYWORDS
+        .iter()
+        .find(|keyword| schema.contains_key(**keyword))
+    {
+        return Err(format!("{path}: `{keyword}` is not supported"));
+    }
+    let is_object = match schema.get("type") {
+        Some(Value::String(kind)) => kind == "object",
+        Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind == "object"),
+        _ => schema.contains_key("properties"),
+    };
+    if is_object {
+        if schema.get("additionalProperties") != Some(&Value::Bool(false)) {
+            return Err(format!(
+                "{path}: objects must set \"additionalProperties\": false"
+            ));
+        }
+        let required: Vec<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|required| required.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+            for (name, property) in properties {
+                if !required.contains(&name.as_str()) {
+                    return Err(format!(
+                        "{path}: property `{name}` must be listed in `required`; use a \"null\" type for optional values"
+                    ));
+                }
+                check_strict(property, &format!("{path}.{name}"))?;
+            }
+        }
+    }
+    if let Some(items) = schema.get("items") {
+        check_strict(items, &format!("{path}[]"))?;
+    }
+    Ok(())
+}
+
+/// Parses `message` as JSON and validates it against `schema`.
+pub(crate) fn parse_structured_result(schema: &Value, message: &str) -> Result<Value, String> {
+    let value: Value = serde_json::from_str(message.trim())
+        .map_err(|err| format!("final message is not valid JSON: {err}"))?;
+    validate(schema, &value, "$")?;
+    Ok(value)
+}
+
+fn validate(schema: &Value, value: &Value, path: &str) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        return Ok(());
+    };
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        return Err(format!("{path}: {value} is not one of the allowed values"));
+    }
+    if let Some(kind) = schema.get("type") {
+        let matches = match kind {
+            Value::String(kind) => type_matches(kind, value),
+            Value::Array(kinds) => kinds
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|kind| type_matches(kind, value)),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!("{path}: expected {kind}, got {value}"));
+        }
+    }
+    match value {
+        Value::Object(fields) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !fields.contains_key(name) {
+                        return Err(format!("{path}: missing required property `{name}`"));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (name, field) in fields {
+                let field_path = format!("{path}.{name}");
+                match properties.and_then(|properties| properties.get(name)) {
+                    Some(field_schema) => validate(field_schema, field, &field_path)?,
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            return Err(format!("{path}: unexpected property `{name}`"));
+                        }
+                        Some(extra @ Value::Object(_)) => validate(extra, field, &field_path)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(item_schema, item, &format!("{path}[{index}]"))?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn type_matches(kind: &str, value: &Value) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn findings_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "files": {"type": "array", "items": {"type": "string"}},
+                "severity": {"type": "string", "enum": ["low", "high"]},
+                "count": {"type": ["integer", "null"]}
+            },
+            "required": ["files", "severity", "count"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_matching_result() {
+        let value = parse_structured_result(
+            &findings_schema(),
+            r#" {"files": ["a.rs"], "severity": "high", "count": null} "#,
+        )
+        .expect("valid result");
+        assert_eq!(
+            value,
+            json!({"files": ["a.rs"], "severity": "high", "count": null})
+        );
+    }
+
+    #[test]
+    fn reports_first_mismatch_with_its_path() {
+        let schema = findings_schema();
+        let cases = [
+            ("done", "final message is not valid JSON"),
+            (
+                r#"{"files": []}"#,
+                "$: missing required property `severity`",
+            ),
+            (
+                r#"{"files": [1], "severity": "low", "count": null}"#,
+                "$.files[0]: expected \"string\", got 1",
+            ),
+            (
+                r#"{"files": [], "severity": "medium", "count": 2}"#,
+                "$.severity: \"medium\" is not one of the allowed values",
+            ),
+            (
+                r#"{"files": [], "severity": "low", "count": 2, "extra": true}"#,
+                "$: unexpected property `extra`",
+            ),
+        ];
+        for (message, expected) in cases {
+            let err = parse_structured_result(&schema, message).expect_err(message);
+            assert!(err.starts_with(expected), "{message}: {err}");
+        }
+    }
+
+    #[test]
+    fn output_schema_must_describe_an_object() {
+        assert_eq!(check_output_schema(&findings_schema()), Ok(()));
+        assert_eq!(
+            check_output_schema(&json!({"type": "string"})),
+            Err("output_schema must have \"type\": \"object\"".to_string())
+        );
+    }
+
+    #[test]
+    fn output_schema_must_follow_strict_mode() {
+        let cases = [
+            (
+                json!({"type": "object", "properties": {"a": {"type": "string"}}, "required": ["a"]}),
+                "output_schema: $: objects must set \"additionalProperties\": false",
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "properties": {"a": {"type": "string"}, "b": {"type": "string"}},
+                    "required": ["a"],
+                    "additionalProperties": false
+                }),
+                "output_schema: $: property `b` must be listed in `required`; use a \"null\" type for optional values",
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "properties": {"items": {"type": "array", "items": {"type": "object", "properties": {}}}},
+                    "required": ["items"],
+                    "additionalProperties": false
+                }),
+                "output_schema: $.items[]: objects must set \"additionalProperties\": false",
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "properties": {"a": {"anyOf": [{"type": "string"}, {"type": "null"}]}},
+                    "required": ["a"],
+                    "additionalProperties": false
+                }),
+                "output_schema: $.a: `anyOf` is not supported",
+            ),
+            (
+                json!({
+                    "type": "object",
+                    "properties": {"a": {"$ref": "#/$defs/a"}},
+                    "required": ["a"],
+                    "additionalProperties": false
+                }),
+                "output_schema: $.a: `$ref` is not supported",
+            ),
+        ];
+        for (schema, expected) in cases {
+            assert_eq!(check_output_schema(&schema), Err(expected.to_string()));
+        }
+    }
+}
@@ -1,99 +1,38 @@
+use crate::config::AgentRoleToml;
 use crate::config::Config;
+use crate::config::types::AgentRoleConfig;
+use crate::config_loader::ConfigLayerStack;
+use crate::config_loader::ConfigLayerStackOrdering;
 use crate::protocol::SandboxPolicy;
+use codex_protocol::config_types::SandboxMode;
 use codex_protocol::openai_models::ReasoningEffort;
 use serde::Deserialize;
-use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+use toml::Value as TomlValue;
 
-/// Base instructions for the orchestrator role.
-const ORCHESTRATOR_PROMPT: &str = include_str!("../../templates/agents/orchestrator.md");
+/// Role used when `spawn_agent` is called without an `agent_type`. It inherits
+/// the parent agent's configuration unchanged.
+pub(crate) const DEFAULT_AGENT_ROLE: &str = "default";
+/// Directory, inside `$CODEX_HOME` or a project's `.codex`, holding one
+/// `<role>.md` file per role.
+const AGENT_ROLES_DIR_NAME: &str = "agents";
 /// Default model override used.
 // TODO(jif) update when we have something smarter.
 const EXPLORER_MODEL: &str = "gpt-5.2-codex";
 
-/// Enumerated list of all supported agent roles.
-const ALL_ROLES: [AgentRole; 3] = [
-    AgentRole::Default,
-    AgentRole::Explorer,
-    AgentRole::Worker,
-    // TODO(jif) add when we have stable prompts + models
-    // AgentRole::Orchestrator,
-];
-
-/// Hard-coded agent role selection used when spawning sub-agents.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum AgentRole {
-    /// Inherit the parent agent's configuration unchanged.
-    Default,
-    /// Coordination-only agent that delegates to workers.
-    Orchestrator,
-    /// Task-executing agent with a fixed model override.
-    Worker,
-    /// Task-executing agent with a fixed model override.
-    Explorer,
-}
-
-/// Immutable profile data that drives per-agent configuration overrides.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub struct AgentProfile {
-    /// Optional base instructions override.
-    pub base_instructions: Option<&'static str>,
-    /// Optional model override.
-    pub model: Option<&'static str>,
-    /// Optional reasoning effort override.
-    pub reasoning_effort: Option<ReasoningEffort>,
-    /// Whether to force a read-only sandbox policy.
-    pub read_only: bool,
-    /// Description to include in the tool specs.
-    pub description: &'static str,
-}
-
-impl AgentRole {
-    /// Returns the string values used by JSON schema enums.
-    pub fn enum_values() -> Vec<String> {
-        ALL_ROLES
-            .iter()
-            .filter_map(|role| {
-                let description = role.profile().description;
-                serde_json::to_string(role)
-                    .map(|role| {
-                        let description = if !description.is_empty() {
-                            format!(r#", "description": {description}"#)
-                        } else {
-                            String::new()
-                        };
-                        format!(r#"{{ "name": {role}{description}}}"#)
-                    })
-                    .ok()
-            })
-            .collect()
-    }
-
-    /// Returns the hard-coded profile for this role.
-    pub fn profile(self) -> AgentProfile {
-        match self {
-            AgentRole::Default => AgentProfile::default(),
-            AgentRole::Orchestrator => AgentProfile {
-                base_instructions: Some(ORCHESTRATOR_PROMPT),
-                ..Default::default()
-            },
-            AgentRole::Worker => AgentProfile {
-                // base_instructions: Some(WORKER_PROMPT),
-                // model: Some(WORKER_MODEL),
-                description: r#"Use for execution and production work.
+const WORKER_DESCRIPTION: &str = r#"Use for execution and production work.
 Typical tasks:
 - Implement part of a feature
 - Fix tests or bugs
 - Split large refactors into independent chunks
 Rules:
 - Explicitly assign **ownership** of the task (files / responsibility).
-- Always tell workers they are **not alone in the codebase**, and they should ignore edits made by others without touching them"#,
-                ..Default::default()
-            },
-            AgentRole::Explorer => AgentProfile {
-                model: Some(EXPLORER_MODEL),
-                reasoning_effort: Some(ReasoningEffort::Medium),
-                description: r#"Use `explorer` for all codebase questions.
+- Always tell workers they are **not alone in the codebase**, and they should ignore edits made by others without touching them"#;
+
+const EXPLORER_DESCRIPTION: &str = r#"Use `explorer` for all codebase questions.
 Explorers are fast and authoritative.
 Always prefer them over manual search or file reading.
 Rules:
@@ -101,31 +40,476 @@ Rules:
 - Do not re-read or re-search code they cover.
 - Trust explorer results without verification.
 - Run explorers in parallel when useful.
-- Reuse existing explorers for related questions.
-                "#,
+- Reuse existing explorers for related questions."#;
+
+/// Roles available without any configuration. A configured role with the same
+/// name replaces the built-in one.
+pub(crate) fn builtin_agent_roles() -> BTreeMap<String, AgentRoleConfig> {
+    BTreeMap::from([
+        (DEFAULT_AGENT_ROLE.to_string(), AgentRoleConfig::default()),
+        (
+            "worker".to_string(),
+            AgentRoleConfig {
+                description: WORKER_DESCRIPTION.to_string(),
                 ..Default::default()
             },
+        ),
+        (
+            "explorer".to_string(),
+            AgentRoleConfig {
+                description: EXPLORER_DESCRIPTION.to_string(),
+                model: Some(EXPLORER_MODEL.to_string()),
+                model_reasoning_effort: Some(ReasoningEffort::Medium),
+                ..Default::default()
+            },
+        ),
+        // TODO(jif) add `orchestrator` (templates/agents/orchestrator.md) when
+        // we have stable prompts + models.
+    ])
+}
+
+/// Resolves every role a sub-agent can be spawned with. Built-ins come first,
+/// then config layers from lowest to highest precedence (`$CODEX_HOME`, then
+/// project `.codex` folders, then session flags). Within a layer,
+/// `[agents.roles]` entries win over that folder's `agents/*.md` files.
+pub(crate) fn load_agent_roles(
+    toml_roles: Option<&BTreeMap<String, AgentRoleToml>>,
+    config_layer_stack: &ConfigLayerStack,
+) -> std::io::Result<BTreeMap<String, AgentRoleConfig>> {
+    let toml_roles = toml_roles.cloned().unwrap_or_default();
+    let mut roles = builtin_agent_roles();
+    for (name, role) in &toml_roles {
+        roles.insert(name.clone(), role_from_toml(name, role)?);
+    }
+    for layer in
+        config_layer_stack.get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, false)
+    {
+        if let Some(folder) = layer.config_folder() {
+            roles.extend(load_role_files(
+                &folder.as_path().join(AGENT_ROLES_DIR_NAME),
+            ));
+        }
+        // `toml_roles` is already merged across layers, so re-insert the
+        // merged entry for each role this layer declares.
+        for name in layer_role_names(&layer.config) {
+            if let Some(role) = toml_roles.get(name) {
+                roles.insert(name.to_string(), role_from_toml(name, role)?);
+            }
         }
     }
+    Ok(roles)
+}
+
+fn layer_role_names(config: &TomlValue) -> impl Iterator<Item = &str> {
+    config
+        .get("agents")
+        .and_then(|agents| agents.get("roles"))
+        .and_then(TomlValue::as_table)
+        .into_iter()
+        .flat_map(|roles| roles.keys().map(String::as_str))
+}
 
-    /// Applies this role's profile onto the provided config.
-    pub fn apply_to_config(self, config: &mut Config) -> Result<(), String> {
-        let profile = self.profile();
-        if let Some(base_instructions) = profile.base_instructions {
-            config.base_instructions = Some(base_instructions.to_string());
+/// Returns the role entries advertised in the `spawn_agent` tool schema.
+pub(crate) fn agent_type_values(roles: &BTreeMap<String, AgentRoleConfig>) -> Vec<String> {
+    roles
+        .iter()
+        .map(|(name, role)| {
+            let name = serde_json::Value::from(name.as_str());
+            if role.description.is_empty() {
+                format!(r#"{{"name":{name}}}"#)
+            } else {
+                let description = serde_json::Value::from(role.description.as_str());
+                format!(r#"{{"name":{name},"description":{description}}}"#)
+            }
+        })
+        .collect()
+}
+
+/// Applies the named role's overrides onto `config`. A role may narrow the
+/// sandbox and tool set but never widen them.
+pub fn apply_agent_role(config: &mut Config, name: &str) -> Result<(), String> {
+    let Some(role) = config.agent_roles.get(name).cloned() else {
+        let available = config
+            .agent_roles
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(format!(
+            "unknown agent_type `{name}`; available types: {available}"
+        ));
+    };
+    if let Some(model) = &role.model {
+        config
+            .config_layer_stack
+            .requirements()
+            .model
+            .can_set(&Some(model.clone()))
+            .map_err(|err| {
+                format!("agent role `{name}` asks for a model that is not allowed: {err}")
+            })?;
+    }
+    if let Some(instructions) = role.instructions {
+        config.base_instructions = Some(instructions);
+    }
+    if let Some(model) = role.model {
+        config.model = Some(model);
+    }
+    if let Some(reasoning_effort) = role.model_reasoning_effort {
+        config.model_reasoning_effort = Some(reasoning_effort);
+    }
+    if let Some(sandbox_mode) = role.sandbox_mode {
+        let current = config.sandbox_policy.get();
+        match sandbox_mode_rank(sandbox_mode).cmp(&sandbox_policy_rank(current)) {
+            Ordering::Greater => {
+                return Err(format!(
+                    "agent role `{name}` asks for sandbox_mode {sandbox_mode}, which is more permissive than the current sandbox"
+                ));
+            }
+            Ordering::Equal => {}
+            Ordering::Less => {
+                let policy = match sandbox_mode {
+                    SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+                    SandboxMode::WorkspaceWrite => SandboxPolicy::new_workspace_write_policy(),
+                    SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
+                };
+                config
+                    .sandbox_policy
+                    .set(policy)
+                    .map_err(|err| format!("sandbox_policy is invalid: {err}"))?;
+            }
         }
-        if let Some(model) = profile.model {
-            config.model = Some(model.to_string());
+    }
+    if let Some(mut tools) = role.tools {
+        // Like the sandbox, a role cannot re-enable tools hidden from its parent.
+        if let Some(allowed) = &config.tool_allowlist {
+            tools.retain(|tool| allowed.contains(tool));
         }
-        if let Some(reasoning_effort) = profile.reasoning_effort {
-            config.model_reasoning_effort = Some(reasoning_effort)
+        config.tool_allowlist = Some(tools);
+    }
+    Ok(())
+}
+
+fn sandbox_mode_rank(mode: SandboxMode) -> u8 {
+    match mode {
+        SandboxMode::ReadOnly => 0,
+        SandboxMode::WorkspaceWrite => 1,
+        SandboxMode::DangerFullAccess => 2,
+    }
+}
+
+fn sandbox_policy_rank(policy: &SandboxPolicy) -> u8 {
+    match policy {
+        SandboxPolicy::ReadOnly => 0,
+        SandboxPolicy::WorkspaceWrite { .. } => 1,
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. } => 2,
+    }
+}
+
+fn role_from_toml(name: &str, role: &AgentRoleToml) -> std::io::Result<AgentRoleConfig> {
+    let instructions = match &role.instructions_file {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path).map_err(|err| {
+                std::io::Error::new(
+                    err.kind(),
+                    format!(
+                        "failed to read instructions_file for agent role `{name}` {}: {err}",
+                        path.display()
+                    ),
+                )
+            })?;
+            let contents = contents.trim();
+            if contents.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!(
+                        "instructions_file for agent role `{name}` is empty: {}",
+                        path.display()
+                    ),
+                ));
+            }
+            Some(contents.to_string())
         }
-        if profile.read_only {
-            config
-                .sandbox_policy
-                .set(SandboxPolicy::new_read_only_policy())
-                .map_err(|err| format!("sandbox_policy is invalid: {err}"))?;
+        None => None,
+    };
+    Ok(AgentRoleConfig {
+        description: role.description.clone().unwrap_or_default(),
+        instructions,
+        model: role.model.clone(),
+        model_reasoning_effort: role.model_reasoning_effort,
+        sandbox_mode: role.sandbox_mode,
+        tools: role.tools.clone(),
+    })
+}
+
+/// YAML frontmatter of an `agents/<role>.md` file. The Markdown body becomes
+/// the role's instructions.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AgentRoleFrontmatter {
+    name: Option<String>,
+    description: Option<String>,
+    model: Option<String>,
+    model_reasoning_effort: Option<ReasoningEffort>,
+    sandbox_mode: Option<SandboxMode>,
+    tools: Option<Vec<String>>,
+}
+
+/// Loads every `*.md` role file in `dir`, skipping (and logging) files that
+/// fail to parse so one bad file does not prevent startup.
+fn load_role_files(dir: &Path) -> Vec<(String, AgentRoleConfig)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "md"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| match load_role_file(&path) {
+            Ok(role) => Some(role),
+            Err(err) => {
+                tracing::warn!("ignoring agent role file {}: {err}", path.display());
+                None
+            }
+        })
+        .collect()
+}
+
+fn load_role_file(path: &Path) -> Result<(String, AgentRoleConfig), String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let (frontmatter, body) = parse_role_markdown(&contents)?;
+    let name = match frontmatter.name {
+        Some(name) => name,
+        None => path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| "file name is not valid UTF-8".to_string())?
+            .to_string(),
+    };
+    let body = body.trim();
+    Ok((
+        name,
+        AgentRoleConfig {
+            description: frontmatter.description.unwrap_or_default(),
+            instructions: (!body.is_empty()).then(|| body.to_string()),
+            model: frontmatter.model,
+            model_reasoning_effort: frontmatter.model_reasoning_effort,
+            sandbox_mode: frontmatter.sandbox_mode,
+            tools: frontmatter.tools,
+        },
+    ))
+}
+
+/// Splits an optional `---`-delimited YAML frontmatter block from the body.
+fn parse_role_markdown(contents: &str) -> Result<(AgentRoleFrontmatter, &str), String> {
+    let Some(rest) = contents
+        .strip_prefix("---\n")
+        .or_else(|| contents.strip_prefix("---\r\n"))
+    else {
+        return Ok((AgentRoleFrontmatter::default(), contents));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let frontmatter = serde_yaml::from_str(&rest[..offset])
+                .map_err(|err| format!("invalid frontmatter: {err}"))?;
+            return Ok((frontmatter, &rest[offset + line.len()..]));
         }
-        Ok(())
+        offset += line.len();
+    }
+    Err("missing closing --- for frontmatter".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigBuilder;
+    use crate::config::test_config;
+    use crate::config_loader::CloudRequirementsLoader;
+    use crate::config_loader::ConfigLayerEntry;
+    use crate::config_loader::ConfigRequirements;
+    use crate::config_loader::ConfigRequirementsToml;
+    use codex_app_server_protocol::ConfigLayerSource;
+    use codex_utils_absolute_path::AbsolutePathBuf;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn role_file_frontmatter_and_body_become_role() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            dir.path().join("test-writer.md"),
+            "---\ndescription: Writes focused unit tests.\nmodel: gpt-5.1-codex\nmodel_reasoning_effort: high\nsandbox_mode: workspace-write\ntools: [shell_command, apply_patch]\n---\n\nOnly add tests; never change production code.\n",
+        )
+        .expect("write role");
+        std::fs::write(dir.path().join("notes.txt"), "not a role").expect("write notes");
+        std::fs::write(dir.path().join("broken.md"), "---\nbogus: true\n---\n").expect("write");
+
+        let roles = load_role_files(dir.path());
+
+        assert_eq!(
+            roles,
+            vec![(
+                "test-writer".to_string(),
+                AgentRoleConfig {
+                    description: "Writes focused unit tests.".to_string(),
+                    instructions: Some("Only add tests; never change production code.".to_string()),
+                    model: Some("gpt-5.1-codex".to_string()),
+                    model_reasoning_effort: Some(ReasoningEffort::High),
+                    sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+                    tools: Some(vec!["shell_command".to_string(), "apply_patch".to_string()]),
+                },
+            )]
+        );
+    }
+
+    #[test]
+    fn role_file_without_frontmatter_is_all_instructions() {
+        let (frontmatter, body) =
+            parse_role_markdown("Review diffs for security issues.\n").expect("parse");
+
+        assert_eq!(frontmatter.name, None);
+        assert_eq!(body, "Review diffs for security issues.\n");
+    }
+
+    #[test]
+    fn project_role_table_overrides_codex_home_role_file() {
+        let tmp = tempfile::tempdir().expect("tempdir");
+        let home = tmp.path().join("home");
+        let dot_codex = tmp.path().join("repo").join(".codex");
+        std::fs::create_dir_all(home.join(AGENT_ROLES_DIR_NAME)).expect("create home");
+        std::fs::create_dir_all(&dot_codex).expect("create project");
+        std::fs::write(
+            home.join(AGENT_ROLES_DIR_NAME).join("reviewer.md"),
+            "---
+description: From CODEX_HOME.
+---
+",
+        )
+        .expect("write role");
+        let project_config: TomlValue =
+            toml::from_str("[agents.roles.reviewer]\ndescription = \"From the project.\"\n")
+                .expect("parse project config");
+        let stack = ConfigLayerStack::new(
+            vec![
+                ConfigLayerEntry::new(
+                    ConfigLayerSource::User {
+                        file: AbsolutePathBuf::from_absolute_path(home.join("config.toml"))
+                            .expect("absolute"),
+                    },
+                    TomlValue::Table(Default::default()),
+                ),
+                ConfigLayerEntry::new(
+                    ConfigLayerSource::Project {
+                        dot_codex_folder: AbsolutePathBuf::from_absolute_path(&dot_codex)
+                            .expect("absolute"),
+                    },
+                    project_config,
+                ),
+            ],
+            ConfigRequirements::default(),
+            ConfigRequirementsToml::default(),
+        )
+        .expect("layer stack");
+        let toml_roles = BTreeMap::from([(
+            "reviewer".to_string(),
+            AgentRoleToml {
+                description: Some("From the project.".to_string()),
+                ..Default::default()
+            },
+        )]);
+
+        let roles = load_agent_roles(Some(&toml_roles), &stack).expect("load roles");
+
+        assert_eq!(roles["reviewer"].description, "From the project.");
+    }
+
+    #[test]
+    fn apply_agent_role_overrides_config() {
+        let mut config = test_config();
+        config
+            .sandbox_policy
+            .set(SandboxPolicy::new_workspace_write_policy())
+            .expect("set");
+        config.agent_roles.insert(
+            "reviewer".to_string(),
+            AgentRoleConfig {
+                instructions: Some("Review only.".to_string()),
+                model: Some("gpt-5.1".to_string()),
+                sandbox_mode: Some(SandboxMode::ReadOnly),
+                tools: Some(vec!["read_file".to_string()]),
+                ..Default::default()
+            },
+        );
+
+        apply_agent_role(&mut config, "reviewer").expect("apply role");
+
+        assert_eq!(config.base_instructions.as_deref(), Some("Review only."));
+        assert_eq!(config.model.as_deref(), Some("gpt-5.1"));
+        assert_eq!(config.sandbox_policy.get(), &SandboxPolicy::ReadOnly);
+        assert_eq!(config.tool_allowlist, Some(vec!["read_file".to_string()]));
+    }
+
+    #[tokio::test]
+    async fn apply_agent_role_rejects_models_outside_requirements() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let mut config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .cloud_requirements(CloudRequirementsLoader::new(async {
+                Some(ConfigRequirementsToml {
+                    allowed_models: Some(vec!["gpt-5.1-codex".to_string()]),
+                    ..Default::default()
+                })
+            }))
+            .build()
+            .await
+            .expect("config");
+        config.agent_roles.insert(
+            "fast".to_string(),
+            AgentRoleConfig {
+                instructions: Some("Be quick.".to_string()),
+                model: Some("o3".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let err = apply_agent_role(&mut config, "fast").expect_err("o3 is not allowed");
+
+        assert!(err.contains("`o3` is not in the allowed set"), "{err}");
+        assert!(err.contains("set by cloud requirements"), "{err}");
+        assert_eq!(config.model.as_deref(), Some("gpt-5.1-codex"));
+        assert_ne!(config.base_instructions.as_deref(), Some("Be quick."));
+    }
+
+    #[test]
+    fn apply_agent_role_rejects_looser_sandbox_and_unknown_roles() {
+        let mut config = test_config();
+        config
+            .sandbox_policy
+            .set(SandboxPolicy::ReadOnly)
+            .expect("set");
+        config.agent_roles.insert(
+            "yolo".to_string(),
+            AgentRoleConfig {
+                sandbox_mode: Some(SandboxMode::DangerFullAccess),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            apply_agent_role(&mut config, "yolo"),
+            Err("agent role `yolo` asks for sandbox_mode danger-full-access, which is more permissive than the current sandbox".to_string())
+        );
+        assert_eq!(
+            apply_agent_role(&mut config, "missing"),
+            Err(
+                "unknown agent_type `missing`; available types: default, explorer, worker, yolo"
+                    .to_string()
+            )
+        );
     }
 }
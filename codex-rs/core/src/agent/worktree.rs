@@ -0,0 +1,66 @@
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use codex_git::AgentWorktree;
+use codex_git::GitToolingError;
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Directory under `$CODEX_HOME` holding one checkout per isolated sub-agent.
+const AGENT_WORKTREES_DIR: &str = "worktrees";
+
+/// What to do with an isolated sub-agent's worktree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentWorktreeAction {
+    /// Show the agent's changes without touching either checkout.
+    Diff,
+    /// Merge the agent's branch with a merge commit, then remove the worktree.
+    Merge,
+    /// Cherry-pick the agent's commits, then remove the worktree.
+    CherryPick,
+    /// Remove the worktree and its branch without applying anything.
+    Discard,
+}
+
+/// Creates a worktree for a sub-agent spawned from `config.cwd` on a fresh
+/// `codex/agent-*` branch. The worktree starts from `HEAD`, so the sub-agent
+/// does not see the parent's uncommitted edits.
+pub(crate) async fn create_agent_worktree(config: &Config) -> CodexResult<AgentWorktree> {
+    let id = uuid::Uuid::new_v4().simple().to_string();
+    let name = format!("agent-{}", &id[..12]);
+    let path = config.codex_home.join(AGENT_WORKTREES_DIR).join(&name);
+    let branch = format!("codex/{name}");
+    let cwd = config.cwd.clone();
+    tokio::task::spawn_blocking(move || codex_git::create_agent_worktree(&cwd, &path, &branch))
+        .await?
+        .map_err(git_error)
+}
+
+/// Best-effort cleanup for a worktree whose agent never started.
+pub(crate) async fn discard_agent_worktree(worktree: AgentWorktree) {
+    let result =
+        tokio::task::spawn_blocking(move || codex_git::remove_agent_worktree(&worktree)).await;
+    if let Ok(Err(err)) = result {
+        tracing::warn!("failed to remove agent worktree: {err}");
+    }
+}
+
+/// Commit message for changes an agent left uncommitted in its worktree.
+pub(crate) fn agent_merge_message(thread_id: ThreadId) -> String {
+    format!("Changes from agent {thread_id}")
+}
+
+/// Short result of applying `commits` commits from `branch`.
+pub(crate) fn merge_summary(branch: &str, commits: usize) -> String {
+    match commits {
+        0 => format!("{branch} has no changes to apply"),
+        1 => format!("applied 1 commit from {branch}"),
+        count => format!("applied {count} commits from {branch}"),
+    }
+}
+
+pub(crate) fn git_error(err: GitToolingError) -> CodexErr {
+    CodexErr::Io(std::io::Error::other(err.to_string()))
+}
@@ -1,16 +1,35 @@
+use crate::codex::Session;
 use crate::codex::TurnContext;
+use crate::error::CodexErr;
+use crate::error::SandboxErr;
+use crate::exec::ExecExpiration;
+use crate::exec::ExecParams;
+use crate::exec::process_exec_tool_call;
+use crate::exec_env::create_env;
 use crate::function_tool::FunctionCallError;
 use crate::protocol::FileChange;
+use crate::protocol::PostEditCommandResult;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_patch_safety;
+use crate::sandboxing::SandboxPermissions;
 use crate::tools::sandboxing::ExecApprovalRequirement;
 use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use wildmatch::WildMatchPattern;
 
 pub const CODEX_APPLY_PATCH_ARG1: &str = "--codex-run-as-apply-patch";
 
+/// Placeholder in a `[post_edit]` command that expands to the matching files.
+const POST_EDIT_FILES_PLACEHOLDER: &str = "{files}";
+
+/// Formatters and linters get longer than the default exec timeout.
+const POST_EDIT_TIMEOUT: Duration = Duration::from_secs(60);
+
 pub(crate) enum InternalApplyPatchInvocation {
     /// The `apply_patch` call was handled programmatically, without any sort
     /// of sandbox, because the user explicitly approved it. This is the
@@ -81,8 +100,13 @@ pub(crate) fn convert_apply_patch_to_protocol(
     let mut result = HashMap::with_capacity(changes.len());
     for (path, change) in changes {
         let protocol_change = match change {
-            ApplyPatchFileChange::Add { content } => FileChange::Add {
+            ApplyPatchFileChange::Add { content, mode } => FileChange::Add {
                 content: content.clone(),
+                mode: *mode,
+            },
+            ApplyPatchFileChange::AddBinary { contents, mode } => FileChange::AddBinary {
+                size: contents.len() as u64,
+                mode: *mode,
             },
             ApplyPatchFileChange::Delete { content } => FileChange::Delete {
                 content: content.clone(),
@@ -91,9 +115,11 @@ pub(crate) fn convert_apply_patch_to_protocol(
                 unified_diff,
                 move_path,
                 new_content: _new_content,
+                mode,
             } => FileChange::Update {
                 unified_diff: unified_diff.clone(),
                 move_path: move_path.clone(),
+                mode: *mode,
             },
         };
         result.insert(path.clone(), protocol_change);
@@ -101,6 +127,169 @@ pub(crate) fn convert_apply_patch_to_protocol(
     result
 }
 
+/// Runs the configured `[post_edit]` commands against the files a patch added
+/// or updated. Each glob with at least one matching file runs its command once,
+/// in the turn's sandbox, in glob order.
+pub(crate) async fn run_post_edit_commands(
+    session: &Session,
+    turn_context: &TurnContext,
+    changes: &HashMap<PathBuf, FileChange>,
+) -> Vec<PostEditCommandResult> {
+    let config = turn_context.client.config();
+    let mut results = Vec::new();
+    for command in post_edit_commands(&config.post_edit, changes, &turn_context.cwd) {
+        let result = match command {
+            Ok(command) => run_post_edit_command(session, turn_context, command).await,
+            Err(command) => PostEditCommandResult {
+                command: vec![command],
+                exit_code: -1,
+                output: "failed to parse post_edit command".to_string(),
+            },
+        };
+        results.push(result);
+    }
+    results
+}
+
+/// Renders post-edit results so they can be appended to the `apply_patch`
+/// output the model sees.
+pub(crate) fn format_post_edit_results(results: &[PostEditCommandResult]) -> Option<String> {
+    if results.is_empty() {
+        return None;
+    }
+    let mut report =
+        String::from("\n\nPost-edit commands ran on the edited files and may have changed them:");
+    for result in results {
+        let command = shlex::try_join(result.command.iter().map(String::as_str))
+            .unwrap_or_else(|_| result.command.join(" "));
+        report.push_str(&format!("\n$ {command}\nexit code: {}", result.exit_code));
+        let output = result.output.trim_end();
+        if !output.is_empty() {
+            report.push('\n');
+            report.push_str(output);
+        }
+    }
+    Some(report)
+}
+
+/// Expands each configured glob into the command to run, or the raw command
+/// string when it cannot be split into arguments.
+fn post_edit_commands(
+    post_edit: &BTreeMap<String, String>,
+    changes: &HashMap<PathBuf, FileChange>,
+    cwd: &Path,
+) -> Vec<Result<Vec<String>, String>> {
+    if post_edit.is_empty() {
+        return Vec::new();
+    }
+    let edited = edited_files(changes, cwd);
+    post_edit
+        .iter()
+        .filter_map(|(glob, command)| {
+            let files: Vec<&str> = edited
+                .iter()
+                .map(String::as_str)
+                .filter(|file| glob_matches(glob, file))
+                .collect();
+            if files.is_empty() {
+                return None;
+            }
+            Some(expand_post_edit_command(command, &files).ok_or_else(|| command.clone()))
+        })
+        .collect()
+}
+
+/// Files left on disk by the patch, relative to `cwd` where possible.
+fn edited_files(changes: &HashMap<PathBuf, FileChange>, cwd: &Path) -> Vec<String> {
+    let mut files: Vec<String> = changes
+        .iter()
+        .filter_map(|(path, change)| match change {
+            FileChange::Add { .. } | FileChange::AddBinary { .. } => Some(path),
+            FileChange::Delete { .. } => None,
+            FileChange::Update { move_path, .. } => Some(move_path.as_ref().unwrap_or(path)),
+        })
+        .map(|path| {
+            path.strip_prefix(cwd)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Globs without a `/` match the file name; others match the whole relative path.
+fn glob_matches(glob: &str, file: &str) -> bool {
+    let target = if glob.contains('/') {
+        file
+    } else {
+        Path::new(file)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(file)
+    };
+    WildMatchPattern::<'*', '?'>::new(glob).matches(target)
+}
+
+fn expand_post_edit_command(command: &str, files: &[&str]) -> Option<Vec<String>> {
+    let mut argv = Vec::new();
+    for arg in shlex::split(command)? {
+        if arg == POST_EDIT_FILES_PLACEHOLDER {
+            argv.extend(files.iter().map(|file| (*file).to_string()));
+        } else {
+            argv.push(arg);
+        }
+    }
+    (!argv.is_empty()).then_some(argv)
+}
+
+async fn run_post_edit_command(
+    session: &Session,
+    turn_context: &TurnContext,
+    command: Vec<String>,
+) -> PostEditCommandResult {
+    let params = ExecParams {
+        command: command.clone(),
+        cwd: turn_context.cwd.clone(),
+        expiration: ExecExpiration::Timeout(POST_EDIT_TIMEOUT),
+        env: create_env(
+            &turn_context.shell_environment_policy,
+            Some(session.conversation_id),
+        ),
+        sandbox_permissions: SandboxPermissions::UseDefault,
+        windows_sandbox_level: turn_context.windows_sandbox_level,
+        justification: None,
+        arg0: None,
+    };
+    let result = process_exec_tool_call(
+        params,
+        &turn_context.sandbox_policy,
+        &turn_context.cwd,
+        &turn_context.codex_linux_sandbox_exe,
+        None,
+    )
+    .await;
+    let output = match result {
+        Ok(output) => output,
+        Err(CodexErr::Sandbox(SandboxErr::Denied { output } | SandboxErr::Timeout { output })) => {
+            *output
+        }
+        Err(err) => {
+            return PostEditCommandResult {
+                command,
+                exit_code: -1,
+                output: format!("failed to run post_edit command: {err}"),
+            };
+        }
+    };
+    PostEditCommandResult {
+        command,
+        exit_code: output.exit_code,
+        output: output.aggregated_output.text,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,8 +309,86 @@ mod tests {
         assert_eq!(
             got.get(&p),
             Some(&FileChange::Add {
-                content: "hello".to_string()
+                content: "hello".to_string(),
+                mode: None,
             })
         );
     }
+
+    #[test]
+    fn post_edit_commands_expand_matching_files() {
+        let cwd = PathBuf::from("/repo");
+        let changes = HashMap::from([
+            (
+                cwd.join("src/lib.rs"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: None,
+                    mode: None,
+                },
+            ),
+            (
+                cwd.join("src/old.rs"),
+                FileChange::Update {
+                    unified_diff: String::new(),
+                    move_path: Some(cwd.join("src/new.rs")),
+                    mode: None,
+                },
+            ),
+            (
+                cwd.join("gone.rs"),
+                FileChange::Delete {
+                    content: String::new(),
+                },
+            ),
+            (
+                cwd.join("scripts/run.py"),
+                FileChange::Add {
+                    content: String::new(),
+                    mode: None,
+                },
+            ),
+        ]);
+        let post_edit = BTreeMap::from([
+            ("*.rs".to_string(), "rustfmt {files}".to_string()),
+            ("docs/*.md".to_string(), "mdlint {files}".to_string()),
+            ("scripts/*.py".to_string(), "ruff check --quiet".to_string()),
+        ]);
+
+        let commands = post_edit_commands(&post_edit, &changes, &cwd);
+
+        assert_eq!(
+            commands,
+            vec![
+                Ok(vec![
+                    "rustfmt".to_string(),
+                    "src/lib.rs".to_string(),
+                    "src/new.rs".to_string(),
+                ]),
+                Ok(vec![
+                    "ruff".to_string(),
+                    "check".to_string(),
+                    "--quiet".to_string(),
+                ]),
+            ]
+        );
+    }
+
+    #[test]
+    fn post_edit_commands_report_unparsable_commands() {
+        let cwd = PathBuf::from("/repo");
+        let changes = HashMap::from([(
+            cwd.join("a.rs"),
+            FileChange::Add {
+                content: String::new(),
+                mode: None,
+            },
+        )]);
+        let post_edit = BTreeMap::from([("*.rs".to_string(), "rustfmt 'unterminated".to_string())]);
+
+        assert_eq!(
+            post_edit_commands(&post_edit, &changes, &cwd),
+            vec![Err("rustfmt 'unterminated".to_string())]
+        );
+    }
 }
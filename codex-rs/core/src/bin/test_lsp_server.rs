@@ -0,0 +1,112 @@
+//! A minimal language server for tests.
+//!
+//! It answers `initialize` and `shutdown`, and whenever a document is opened
+//! or changed it publishes one error for every line that contains `ERROR:`,
+//! with the rest of the line as the message.
+
+use std::io::BufRead;
+use std::io::Write;
+
+use anyhow::Result;
+use serde_json::Value;
+use serde_json::json;
+
+const ERROR_MARKER: &str = "ERROR:";
+
+fn main() -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = std::io::stdout();
+    while let Some(message) = read_message(&mut reader)? {
+        let method = message["method"].as_str().unwrap_or_default();
+        match method {
+            "initialize" => write_message(
+                &mut stdout,
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": message["id"],
+                    "result": {"capabilities": {"textDocumentSync": 1}},
+                }),
+            )?,
+            "shutdown" => write_message(
+                &mut stdout,
+                &json!({"jsonrpc": "2.0", "id": message["id"], "result": null}),
+            )?,
+            "exit" => break,
+            "textDocument/didOpen" => {
+                let document = &message["params"]["textDocument"];
+                publish(&mut stdout, document, &document["text"])?;
+            }
+            "textDocument/didChange" => {
+                let params = &message["params"];
+                publish(
+                    &mut stdout,
+                    &params["textDocument"],
+                    &params["contentChanges"][0]["text"],
+                )?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn publish(stdout: &mut impl Write, document: &Value, text: &Value) -> Result<()> {
+    let diagnostics: Vec<Value> = text
+        .as_str()
+        .unwrap_or_default()
+        .lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            let column = content.find(ERROR_MARKER)?;
+            Some(json!({
+                "range": {
+                    "start": {"line": line, "character": column},
+                    "end": {"line": line, "character": content.len()},
+                },
+                "severity": 1,
+                "message": content[column + ERROR_MARKER.len()..].trim(),
+            }))
+        })
+        .collect();
+    write_message(
+        stdout,
+        &json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": {
+                "uri": document["uri"],
+                "version": document["version"],
+                "diagnostics": diagnostics,
+            },
+        }),
+    )
+}
+
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Value>> {
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            content_length = value.trim().parse()?;
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn write_message(stdout: &mut impl Write, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    write!(stdout, "Content-Length: {}\r\n\r\n", body.len())?;
+    stdout.write_all(&body)?;
+    stdout.flush()?;
+    Ok(())
+}
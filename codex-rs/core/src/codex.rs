@@ -3,6 +3,7 @@ use std::collections::HashSet;
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::Weak;
 use std::sync::atomic::AtomicU64;
 
 use crate::AuthManager;
@@ -10,7 +11,7 @@ use crate::CodexAuth;
 use crate::SandboxState;
 use crate::agent::AgentControl;
 use crate::agent::AgentStatus;
-use crate::agent::MAX_THREAD_SPAWN_DEPTH;
+use crate::agent::AgentTreeBudget;
 use crate::agent::agent_status_from_event;
 use crate::analytics_client::AnalyticsEventsClient;
 use crate::analytics_client::build_track_events_context;
@@ -32,6 +33,7 @@ use crate::stream_events_utils::handle_non_tool_response_item;
 use crate::stream_events_utils::handle_output_item_done;
 use crate::stream_events_utils::last_assistant_message_from_item;
 use crate::terminal;
+use crate::tokenizer::Tokenizer;
 use crate::transport_manager::TransportManager;
 use crate::truncate::TruncationPolicy;
 use crate::user_notification::UserNotifier;
@@ -52,6 +54,8 @@ use codex_protocol::mcp::CallToolResult;
 use codex_protocol::models::BaseInstructions;
 use codex_protocol::models::format_allow_prefixes;
 use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::protocol::AgentTreeUsageEvent;
+use codex_protocol::protocol::CheckpointItem;
 use codex_protocol::protocol::FileChange;
 use codex_protocol::protocol::HasLegacyEvent;
 use codex_protocol::protocol::ItemCompletedEvent;
@@ -59,8 +63,10 @@ use codex_protocol::protocol::ItemStartedEvent;
 use codex_protocol::protocol::RawResponseItemEvent;
 use codex_protocol::protocol::ReviewRequest;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::SessionApprovalEntry;
 use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::SubAgentSource;
+use codex_protocol::protocol::ToolOutputsPrunedItem;
 use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnContextItem;
 use codex_protocol::protocol::TurnStartedEvent;
@@ -116,7 +122,10 @@ use crate::exec::StreamOutput;
 use crate::exec_policy::ExecPolicyUpdateError;
 use crate::feedback_tags;
 use crate::git_info::get_git_repo_root;
+use crate::hooks::HookEvent;
+use crate::hooks::run_hooks;
 use crate::instructions::UserInstructions;
+use crate::lsp::LspManager;
 use crate::mcp::CODEX_APPS_MCP_SERVER_NAME;
 use crate::mcp::auth::compute_auth_statuses;
 use crate::mcp::effective_mcp_servers;
@@ -266,8 +275,9 @@ impl Codex {
             );
         }
 
+        let is_root_session = !matches!(session_source, SessionSource::SubAgent(_));
         if let SessionSource::SubAgent(SubAgentSource::ThreadSpawn { depth, .. }) = session_source
-            && depth >= MAX_THREAD_SPAWN_DEPTH
+            && depth >= config.agent_max_depth
         {
             config.features.disable(Feature::Collab);
         }
@@ -399,6 +409,9 @@ impl Codex {
         tokio::spawn(
             submission_loop(Arc::clone(&session), config, rx_sub).instrument(session_loop_span),
         );
+        if is_root_session {
+            tokio::spawn(publish_agent_tree_usage(Arc::downgrade(&session)));
+        }
         let codex = Codex {
             next_id: AtomicU64::new(0),
             tx_sub,
@@ -457,6 +470,10 @@ impl Codex {
     pub(crate) fn state_db(&self) -> Option<state_db::StateDbHandle> {
         self.session.state_db()
     }
+
+    pub(crate) async fn session_approvals(&self) -> Vec<SessionApprovalEntry> {
+        self.session.services.tool_approvals.lock().await.list()
+    }
 }
 
 /// Context for an initialized model agent
@@ -500,6 +517,8 @@ pub(crate) struct TurnContext {
     pub(crate) codex_linux_sandbox_exe: Option<PathBuf>,
     pub(crate) tool_call_gate: Arc<ReadinessFlag>,
     pub(crate) truncation_policy: TruncationPolicy,
+    /// Counts tokens for this turn's model; see [`crate::tokenizer`].
+    pub(crate) tokenizer: Tokenizer,
     pub(crate) dynamic_tools: Vec<DynamicToolSpec>,
 }
 impl TurnContext {
@@ -586,6 +605,11 @@ impl SessionConfiguration {
     pub(crate) fn apply(&self, updates: &SessionSettingsUpdate) -> ConstraintResult<Self> {
         let mut next_configuration = self.clone();
         if let Some(collaboration_mode) = updates.collaboration_mode.clone() {
+            self.original_config_do_not_use
+                .config_layer_stack
+                .requirements()
+                .model
+                .can_set(&Some(collaboration_mode.model().to_string()))?;
             next_configuration.collaboration_mode = collaboration_mode;
         }
         if let Some(summary) = updates.reasoning_summary {
@@ -680,7 +704,9 @@ impl Session {
             model_info: &model_info,
             features: &per_turn_config.features,
             web_search_mode: per_turn_config.web_search_mode,
-        });
+        })
+        .with_agent_roles(&per_turn_config.agent_roles)
+        .with_tool_allowlist(per_turn_config.tool_allowlist.clone());
 
         TurnContext {
             sub_id,
@@ -701,6 +727,7 @@ impl Session {
             codex_linux_sandbox_exe: per_turn_config.codex_linux_sandbox_exe.clone(),
             tool_call_gate: Arc::new(ReadinessFlag::new()),
             truncation_policy: model_info.truncation_policy.into(),
+            tokenizer: Tokenizer::for_model(&model_info, &per_turn_config),
             dynamic_tools: session_configuration.dynamic_tools.clone(),
         }
     }
@@ -914,11 +941,12 @@ impl Session {
             auth_manager: Arc::clone(&auth_manager),
             otel_manager,
             models_manager: Arc::clone(&models_manager),
-            tool_approvals: Mutex::new(ApprovalStore::default()),
+            tool_approvals: Mutex::new(ApprovalStore::new(config.session_approval_ttl)),
             skills_manager,
             agent_control,
             state_db: state_db_ctx.clone(),
             transport_manager: TransportManager::new(),
+            lsp_manager: LspManager::new(config.lsp.clone()),
         };
 
         let sess = Arc::new(Session {
@@ -982,12 +1010,27 @@ impl Session {
             )
             .await;
 
+        let session_start_source = match initial_history {
+            InitialHistory::New => "startup",
+            InitialHistory::Resumed(_) | InitialHistory::Forked(_) => "resume",
+        };
         // record_initial_history can emit events. We record only after the SessionConfiguredEvent is emitted.
         sess.record_initial_history(initial_history).await;
+        sess.run_session_start_hooks(session_start_source).await;
 
         Ok(sess)
     }
 
+    async fn run_session_start_hooks(&self, source: &'static str) {
+        let turn_context = self.new_default_turn().await;
+        let outcome = run_hooks(self, &turn_context, HookEvent::SessionStart { source }).await;
+        if let Some(context) = outcome.context_text() {
+            let message: ResponseItem = DeveloperInstructions::new(context).into();
+            self.record_conversation_items(&turn_context, std::slice::from_ref(&message))
+                .await;
+        }
+    }
+
     pub(crate) fn get_tx_event(&self) -> Sender<Event> {
         self.tx_event.clone()
     }
@@ -1016,9 +1059,9 @@ impl Session {
         format!("auto-compact-{id}")
     }
 
-    async fn get_total_token_usage(&self) -> i64 {
+    async fn get_total_token_usage(&self, turn_context: &TurnContext) -> i64 {
         let state = self.state.lock().await;
-        state.get_total_token_usage(state.server_reasoning_included())
+        state.get_total_token_usage(state.server_reasoning_included(), &turn_context.tokenizer)
     }
 
     async fn get_estimated_token_count(&self, turn_context: &TurnContext) -> Option<i64> {
@@ -1423,6 +1466,15 @@ impl Session {
         if let Some(status) = agent_status_from_event(&event.msg) {
             self.agent_status.send_replace(status);
         }
+        // Charge this thread's usage against its agent tree's budget.
+        if let EventMsg::TokenCount(TokenCountEvent {
+            info: Some(info), ..
+        }) = &event.msg
+        {
+            self.services
+                .agent_control
+                .record_token_usage(self.conversation_id, info.total_token_usage.total_tokens);
+        }
         // Persist the event into rollout (recorder filters as needed)
         let rollout_items = vec![RolloutItem::EventMsg(event.msg.clone())];
         self.persist_rollout_items(&rollout_items).await;
@@ -1441,6 +1493,15 @@ impl Session {
         if let Some(status) = agent_status_from_event(&event.msg) {
             self.agent_status.send_replace(status);
         }
+        // Charge this thread's usage against its agent tree's budget.
+        if let EventMsg::TokenCount(TokenCountEvent {
+            info: Some(info), ..
+        }) = &event.msg
+        {
+            self.services
+                .agent_control
+                .record_token_usage(self.conversation_id, info.total_token_usage.total_tokens);
+        }
         self.persist_rollout_items(&[RolloutItem::EventMsg(event.msg.clone())])
             .await;
         self.flush_rollout().await;
@@ -1497,14 +1558,45 @@ impl Session {
             return Err(ExecPolicyUpdateError::FeatureDisabled);
         }
 
-        self.services
+        let added = self
+            .services
             .exec_policy
             .append_amendment_and_update(&codex_home, amendment)
             .await?;
+        if !added {
+            // The rules file already allowed this prefix; it isn't ours to revoke.
+            return Ok(());
+        }
+        // List the prefix with the session's other approvals so it can be revoked.
+        self.services.tool_approvals.lock().await.put(
+            amendment.clone(),
+            ReviewDecision::ApprovedExecpolicyAmendment {
+                proposed_execpolicy_amendment: amendment.clone(),
+            },
+        );
 
         Ok(())
     }
 
+    /// Removes an execpolicy amendment approved during this session from both
+    /// the in-memory and on-disk policies.
+    pub(crate) async fn revoke_execpolicy_amendment(
+        &self,
+        amendment: &ExecPolicyAmendment,
+    ) -> Result<(), ExecPolicyUpdateError> {
+        let codex_home = self
+            .state
+            .lock()
+            .await
+            .session_configuration
+            .codex_home()
+            .clone();
+        self.services
+            .exec_policy
+            .remove_amendment_and_update(&codex_home, amendment)
+            .await
+    }
+
     async fn turn_context_for_sub_id(&self, sub_id: &str) -> Option<Arc<TurnContext>> {
         let active = self.active_turn.lock().await;
         active
@@ -1764,6 +1856,7 @@ impl Session {
                     history.record_items(
                         std::iter::once(response_item),
                         turn_context.truncation_policy,
+                        &turn_context.tokenizer,
                     );
                 }
                 RolloutItem::Compacted(compacted) => {
@@ -1782,6 +1875,9 @@ impl Session {
                 RolloutItem::EventMsg(EventMsg::ThreadRolledBack(rollback)) => {
                     history.drop_last_n_user_turns(rollback.num_turns);
                 }
+                RolloutItem::ToolOutputsPruned(pruned) => {
+                    history.apply_pruned_tool_outputs(&pruned.outputs);
+                }
                 _ => {}
             }
         }
@@ -1795,7 +1891,11 @@ impl Session {
         turn_context: &TurnContext,
     ) {
         let mut state = self.state.lock().await;
-        state.record_items(items.iter(), turn_context.truncation_policy);
+        state.record_items(
+            items.iter(),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
     }
 
     pub(crate) async fn record_model_warning(&self, message: impl Into<String>, ctx: &TurnContext) {
@@ -1930,6 +2030,44 @@ impl Session {
         items
     }
 
+    /// Replaces old or large tool outputs with stubs as configured under
+    /// `[tool_output_pruning]`, and records the replacement so a resumed
+    /// session rebuilds the same prompt. Token usage is re-estimated so
+    /// auto-compaction sees the smaller history.
+    async fn prune_tool_outputs(&self, turn_context: &TurnContext) {
+        let config = turn_context.client.config();
+        let outputs = {
+            let mut state = self.state.lock().await;
+            state.prune_tool_outputs(&config.tool_output_pruning)
+        };
+        if outputs.is_empty() {
+            return;
+        }
+        self.persist_rollout_items(&[RolloutItem::ToolOutputsPruned(ToolOutputsPrunedItem {
+            outputs,
+        })])
+        .await;
+        self.recompute_token_usage(turn_context).await;
+    }
+
+    /// Marks the history recorded so far as safe to replay with
+    /// `codex exec resume --continue`. Only `codex exec` sessions write checkpoints.
+    pub(crate) async fn record_checkpoint(
+        &self,
+        turn_context: &TurnContext,
+        call_id: Option<String>,
+    ) {
+        if turn_context.client.get_session_source() != SessionSource::Exec {
+            return;
+        }
+        self.persist_rollout_items(&[RolloutItem::Checkpoint(CheckpointItem {
+            turn_id: turn_context.sub_id.clone(),
+            call_id,
+        })])
+        .await;
+        self.flush_rollout().await;
+    }
+
     pub(crate) async fn persist_rollout_items(&self, items: &[RolloutItem]) {
         let recorder = {
             let guard = self.services.rollout.lock().await;
@@ -2386,6 +2524,30 @@ impl Session {
     }
 }
 
+/// Forwards the usage of a root session's sub-agent tree to its client every
+/// time a sub-agent reports tokens, so `/status` stays current between waits.
+async fn publish_agent_tree_usage(session: Weak<Session>) {
+    let Some(mut usage_rx) = session
+        .upgrade()
+        .map(|session| session.services.agent_control.subscribe_tree_usage())
+    else {
+        return;
+    };
+    while usage_rx.changed().await.is_ok() {
+        let Some(session) = session.upgrade() else {
+            break;
+        };
+        let budget = AgentTreeBudget::from_config(session.get_config().await.as_ref());
+        let usage = session.services.agent_control.tree_usage(&budget);
+        session
+            .send_event_raw(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::AgentTreeUsage(AgentTreeUsageEvent { usage }),
+            })
+            .await;
+    }
+}
+
 async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiver<Submission>) {
     // Seed with context in case there is an OverrideTurnContext first.
     let mut previous_context: Option<Arc<TurnContext>> = Some(sess.new_default_turn().await);
@@ -2485,12 +2647,21 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                 )
                 .await;
             }
+            Op::ListSessionApprovals => {
+                handlers::list_session_approvals(&sess, sub.id.clone()).await;
+            }
+            Op::RevokeSessionApproval { id } => {
+                handlers::revoke_session_approval(&sess, sub.id.clone(), id).await;
+            }
             Op::Undo => {
                 handlers::undo(&sess, sub.id.clone()).await;
             }
             Op::Compact => {
                 handlers::compact(&sess, sub.id.clone()).await;
             }
+            Op::PreviewCompact => {
+                handlers::preview_compact(&sess, sub.id.clone()).await;
+            }
             Op::ThreadRollback { num_turns } => {
                 handlers::thread_rollback(&sess, sub.id.clone(), num_turns).await;
             }
@@ -2552,6 +2723,7 @@ mod handlers {
     use codex_protocol::protocol::EventMsg;
     use codex_protocol::protocol::ListCustomPromptsResponseEvent;
     use codex_protocol::protocol::ListRemoteSkillsResponseEvent;
+    use codex_protocol::protocol::ListSessionApprovalsResponseEvent;
     use codex_protocol::protocol::ListSkillsResponseEvent;
     use codex_protocol::protocol::McpServerRefreshConfig;
     use codex_protocol::protocol::Op;
@@ -2559,6 +2731,7 @@ mod handlers {
     use codex_protocol::protocol::RemoteSkillSummary;
     use codex_protocol::protocol::ReviewDecision;
     use codex_protocol::protocol::ReviewRequest;
+    use codex_protocol::protocol::SessionApprovalKind;
     use codex_protocol::protocol::SkillsListEntry;
     use codex_protocol::protocol::ThreadNameUpdatedEvent;
     use codex_protocol::protocol::ThreadRolledBackEvent;
@@ -2567,6 +2740,7 @@ mod handlers {
     use codex_protocol::request_user_input::RequestUserInputResponse;
 
     use crate::context_manager::is_user_turn_boundary;
+    use codex_protocol::approvals::ExecPolicyAmendment;
     use codex_protocol::config_types::CollaborationMode;
     use codex_protocol::config_types::ModeKind;
     use codex_protocol::config_types::Settings;
@@ -2881,6 +3055,49 @@ mod handlers {
         sess.send_event_raw(event).await;
     }
 
+    pub async fn list_session_approvals(sess: &Session, sub_id: String) {
+        let approvals = sess.services.tool_approvals.lock().await.list();
+        let event = Event {
+            id: sub_id,
+            msg: EventMsg::ListSessionApprovalsResponse(ListSessionApprovalsResponseEvent {
+                approvals,
+            }),
+        };
+        sess.send_event_raw(event).await;
+    }
+
+    pub async fn revoke_session_approval(sess: &Session, sub_id: String, id: String) {
+        let revoked = sess.services.tool_approvals.lock().await.revoke(&id);
+        match revoked {
+            None => {
+                sess.send_event_raw(Event {
+                    id: sub_id.clone(),
+                    msg: EventMsg::Error(ErrorEvent {
+                        message: format!("No session approval with id `{id}`."),
+                        codex_error_info: Some(CodexErrorInfo::Other),
+                    }),
+                })
+                .await;
+            }
+            Some(SessionApprovalKind::CommandPrefix { prefix }) => {
+                if let Err(err) = sess
+                    .revoke_execpolicy_amendment(&ExecPolicyAmendment::from(prefix))
+                    .await
+                {
+                    let message = format!("Failed to revoke execpolicy amendment: {err}");
+                    tracing::warn!("{message}");
+                    sess.send_event_raw(Event {
+                        id: sub_id.clone(),
+                        msg: EventMsg::Warning(WarningEvent { message }),
+                    })
+                    .await;
+                }
+            }
+            Some(_) => {}
+        }
+        list_session_approvals(sess, sub_id).await;
+    }
+
     pub async fn list_skills(
         sess: &Session,
         sub_id: String,
@@ -3006,6 +3223,16 @@ mod handlers {
         .await;
     }
 
+    pub async fn preview_compact(sess: &Arc<Session>, sub_id: String) {
+        let turn_context = sess.new_default_turn_with_sub_id(sub_id.clone()).await;
+        let preview = crate::compact::preview_compaction(sess, turn_context.as_ref()).await;
+        sess.send_event_raw(Event {
+            id: sub_id,
+            msg: EventMsg::CompactionPreview(preview),
+        })
+        .await;
+    }
+
     pub async fn thread_rollback(sess: &Arc<Session>, sub_id: String, num_turns: u32) {
         if num_turns == 0 {
             sess.send_event_raw(Event {
@@ -3274,6 +3501,7 @@ async fn spawn_review_thread(
         tool_call_gate: Arc::new(ReadinessFlag::new()),
         dynamic_tools: parent_turn_context.dynamic_tools.clone(),
         truncation_policy: model_info.truncation_policy.into(),
+        tokenizer: Tokenizer::for_model(&model_info, &per_turn_config),
     };
 
     // Seed the child task with the review prompt as the initial user message.
@@ -3374,7 +3602,7 @@ pub(crate) async fn run_turn(
 
     let model_info = turn_context.client.get_model_info();
     let auto_compact_limit = model_info.auto_compact_token_limit().unwrap_or(i64::MAX);
-    let total_usage_tokens = sess.get_total_token_usage().await;
+    let total_usage_tokens = sess.get_total_token_usage(&turn_context).await;
 
     let event = EventMsg::TurnStarted(TurnStartedEvent {
         model_context_window: turn_context.client.get_model_context_window(),
@@ -3385,6 +3613,27 @@ pub(crate) async fn run_turn(
         run_auto_compact(&sess, &turn_context).await;
     }
 
+    let prompt_hooks = run_hooks(
+        &sess,
+        &turn_context,
+        HookEvent::UserPromptSubmit {
+            turn_id: turn_context.sub_id.clone(),
+            prompt: input
+                .iter()
+                .filter_map(|item| match item {
+                    UserInput::Text { text, .. } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        },
+    )
+    .await;
+    if prompt_hooks.blocked.is_some() {
+        // The hook's reason reaches clients through its `HookCompleted` event.
+        return None;
+    }
+
     let skills_outcome = Some(
         sess.services
             .skills_manager
@@ -3465,12 +3714,19 @@ pub(crate) async fn run_turn(
     let response_item: ResponseItem = initial_input_for_turn.clone().into();
     sess.record_user_prompt_and_emit_turn_item(turn_context.as_ref(), &input, response_item)
         .await;
+    sess.prune_tool_outputs(turn_context.as_ref()).await;
 
     if !skill_items.is_empty() {
         sess.record_conversation_items(&turn_context, &skill_items)
             .await;
     }
 
+    if let Some(context) = prompt_hooks.context_text() {
+        let message: ResponseItem = DeveloperInstructions::new(context).into();
+        sess.record_conversation_items(&turn_context, std::slice::from_ref(&message))
+            .await;
+    }
+
     sess.maybe_start_ghost_snapshot(Arc::clone(&turn_context), cancellation_token.child_token())
         .await;
     let mut last_agent_message: Option<String> = None;
@@ -3528,7 +3784,7 @@ pub(crate) async fn run_turn(
                     needs_follow_up,
                     last_agent_message: sampling_request_last_agent_message,
                 } = sampling_request_output;
-                let total_usage_tokens = sess.get_total_token_usage().await;
+                let total_usage_tokens = sess.get_total_token_usage(&turn_context).await;
                 let token_limit_reached = total_usage_tokens >= auto_compact_limit;
 
                 let estimated_token_count =
@@ -3560,6 +3816,15 @@ pub(crate) async fn run_turn(
                             input_messages: sampling_request_input_messages,
                             last_assistant_message: last_agent_message.clone(),
                         });
+                    run_hooks(
+                        &sess,
+                        &turn_context,
+                        HookEvent::TurnComplete {
+                            turn_id: turn_context.sub_id.clone(),
+                            last_assistant_message: last_agent_message.clone(),
+                        },
+                    )
+                    .await;
                     break;
                 }
                 continue;
@@ -3598,7 +3863,7 @@ pub(crate) async fn run_turn(
 }
 
 async fn run_auto_compact(sess: &Arc<Session>, turn_context: &Arc<TurnContext>) {
-    if should_use_remote_compact_task(sess.as_ref(), &turn_context.client.get_provider()) {
+    if should_use_remote_compact_task(sess.as_ref(), turn_context.as_ref()) {
         run_inline_remote_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await;
     } else {
         run_inline_auto_compact_task(Arc::clone(sess), Arc::clone(turn_context)).await;
@@ -4234,8 +4499,19 @@ async fn drain_in_flight(
     while let Some(res) = in_flight.next().await {
         match res {
             Ok(response_input) => {
+                let call_id = match &response_input {
+                    ResponseInputItem::FunctionCallOutput { call_id, .. }
+                    | ResponseInputItem::McpToolCallOutput { call_id, .. }
+                    | ResponseInputItem::CustomToolCallOutput { call_id, .. } => {
+                        Some(call_id.clone())
+                    }
+                    ResponseInputItem::Message { .. } => None,
+                };
                 sess.record_conversation_items(&turn_context, &[response_input.into()])
                     .await;
+                if call_id.is_some() {
+                    sess.record_checkpoint(&turn_context, call_id).await;
+                }
             }
             Err(err) => {
                 error_or_panic(format!("in-flight tool future failed during drain: {err}"));
@@ -5228,7 +5504,11 @@ mod tests {
         };
         let (_, turn_context) = make_session_and_context().await;
 
-        let out = format_exec_output_str(&exec, turn_context.truncation_policy);
+        let out = format_exec_output_str(
+            &exec,
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
 
         assert_eq!(
             out,
@@ -5451,6 +5731,7 @@ mod tests {
             agent_control,
             state_db: None,
             transport_manager: TransportManager::new(),
+            lsp_manager: LspManager::new(config.lsp.clone()),
         };
 
         let turn_context = Session::make_turn_context(
@@ -5571,6 +5852,7 @@ mod tests {
             agent_control,
             state_db: None,
             transport_manager: TransportManager::new(),
+            lsp_manager: LspManager::new(config.lsp.clone()),
         };
 
         let turn_context = Arc::new(Session::make_turn_context(
@@ -5909,7 +6191,11 @@ mod tests {
         for item in &initial_context {
             rollout_items.push(RolloutItem::ResponseItem(item.clone()));
         }
-        live_history.record_items(initial_context.iter(), turn_context.truncation_policy);
+        live_history.record_items(
+            initial_context.iter(),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
 
         let user1 = ResponseItem::Message {
             id: None,
@@ -5920,7 +6206,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&user1), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&user1),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(user1.clone()));
 
         let assistant1 = ResponseItem::Message {
@@ -5932,7 +6222,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&assistant1), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&assistant1),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(assistant1.clone()));
 
         let summary1 = "summary one";
@@ -5958,7 +6252,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&user2), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&user2),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(user2.clone()));
 
         let assistant2 = ResponseItem::Message {
@@ -5970,7 +6268,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&assistant2), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&assistant2),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(assistant2.clone()));
 
         let summary2 = "summary two";
@@ -5996,7 +6298,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&user3), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&user3),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(user3));
 
         let assistant3 = ResponseItem::Message {
@@ -6008,7 +6314,11 @@ mod tests {
             end_turn: None,
             phase: None,
         };
-        live_history.record_items(std::iter::once(&assistant3), turn_context.truncation_policy);
+        live_history.record_items(
+            std::iter::once(&assistant3),
+            turn_context.truncation_policy,
+            &turn_context.tokenizer,
+        );
         rollout_items.push(RolloutItem::ResponseItem(assistant3));
 
         (rollout_items, live_history.for_prompt())
@@ -8,6 +8,7 @@ use codex_protocol::config_types::Personality;
 use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::SessionApprovalEntry;
 use codex_protocol::protocol::SessionSource;
 use std::path::PathBuf;
 use tokio::sync::watch;
@@ -73,4 +74,9 @@ impl CodexThread {
     pub async fn config_snapshot(&self) -> ThreadConfigSnapshot {
         self.codex.thread_config_snapshot().await
     }
+
+    /// Approvals remembered for the remainder of this thread's session.
+    pub async fn session_approvals(&self) -> Vec<SessionApprovalEntry> {
+        self.codex.session_approvals().await
+    }
 }
@@ -1,20 +1,33 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::ModelProviderInfo;
+use crate::ModelClient;
 use crate::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::codex::get_last_assistant_message_from_turn;
+use crate::config::types::CompactionConfig;
+use crate::config::types::CompactionStrategy;
+use crate::context_manager::estimate_item_token_count;
+use crate::context_manager::is_user_turn_boundary;
+use crate::context_manager::replace_tool_output;
+use crate::context_manager::stubbable_tool_output;
+use crate::context_manager::tool_names_by_call_id;
+use crate::context_manager::tool_output_stub;
 use crate::error::CodexErr;
 use crate::error::Result as CodexResult;
 use crate::features::Feature;
 use crate::protocol::CompactedItem;
+use crate::protocol::CompactionPreviewAction;
+use crate::protocol::CompactionPreviewEvent;
+use crate::protocol::CompactionPreviewItem;
 use crate::protocol::EventMsg;
 use crate::protocol::TurnContextItem;
 use crate::protocol::TurnStartedEvent;
 use crate::protocol::WarningEvent;
 use crate::session_prefix::TURN_ABORTED_OPEN_TAG;
+use crate::tokenizer::Tokenizer;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::approx_token_count;
 use crate::truncate::truncate_text;
@@ -35,9 +48,15 @@ const COMPACT_USER_MESSAGE_MAX_TOKENS: usize = 20_000;
 
 pub(crate) fn should_use_remote_compact_task(
     session: &Session,
-    provider: &ModelProviderInfo,
+    turn_context: &TurnContext,
 ) -> bool {
-    provider.is_openai() && session.enabled(Feature::RemoteCompaction)
+    turn_context.client.get_provider().is_openai()
+        && session.enabled(Feature::RemoteCompaction)
+        && turn_context
+            .client
+            .config()
+            .compaction
+            .uses_default_summarizer()
 }
 
 pub(crate) async fn run_inline_auto_compact_task(
@@ -67,6 +86,276 @@ pub(crate) async fn run_compact_task(
     run_compact_task_inner(sess.clone(), turn_context, input).await;
 }
 
+/// What a compaction run does to the history. Decided before anything runs so
+/// that `Op::PreviewCompact` reports exactly what `Op::Compact` would do.
+#[derive(Debug, PartialEq)]
+enum CompactionPlan {
+    /// Summarize `items[..keep_from]` and keep the rest verbatim. Plain
+    /// summaries keep nothing, i.e. `keep_from == items.len()`.
+    Summarize { keep_from: usize },
+    /// Replace the tool outputs at `dropped` with stubs; no model call.
+    DropToolOutputs {
+        history: Vec<ResponseItem>,
+        dropped: Vec<usize>,
+        tokens_after: i64,
+    },
+}
+
+fn plan_compaction(
+    compaction: &CompactionConfig,
+    items: &[ResponseItem],
+    tokenizer: &Tokenizer,
+    tokens_before: i64,
+    auto_compact_limit: Option<i64>,
+) -> CompactionPlan {
+    let summarize_all = CompactionPlan::Summarize {
+        keep_from: items.len(),
+    };
+    // Whatever survives compaction must leave room for the conversation to
+    // continue, or the next turn would compact again straight away.
+    let target = auto_compact_limit.map_or(0, |limit| limit / 2);
+    match compaction.strategy {
+        CompactionStrategy::Summary => summarize_all,
+        CompactionStrategy::Hybrid => {
+            let Some(keep_from) = hybrid_split(items, compaction.keep_recent_turns) else {
+                return summarize_all;
+            };
+            let recent_tokens = items[keep_from..].iter().fold(0i64, |acc, item| {
+                acc.saturating_add(estimate_item_token_count(item, tokenizer))
+            });
+            if auto_compact_limit.is_some() && recent_tokens > target {
+                summarize_all
+            } else {
+                CompactionPlan::Summarize { keep_from }
+            }
+        }
+        CompactionStrategy::DropToolOutputs => {
+            let (history, dropped, tokens_after) =
+                drop_tool_outputs(items, tokenizer, tokens_before, target);
+            if auto_compact_limit.is_some_and(|limit| tokens_after >= limit) {
+                summarize_all
+            } else {
+                CompactionPlan::DropToolOutputs {
+                    history,
+                    dropped,
+                    tokens_after,
+                }
+            }
+        }
+    }
+}
+
+fn plan_for_turn(
+    items: &[ResponseItem],
+    tokens_before: i64,
+    turn_context: &TurnContext,
+) -> CompactionPlan {
+    plan_compaction(
+        &turn_context.client.config().compaction,
+        items,
+        &turn_context.tokenizer,
+        tokens_before,
+        turn_context
+            .client
+            .get_model_info()
+            .auto_compact_token_limit(),
+    )
+}
+
+/// Index of the first item of the `keep_recent_turns` most recent user turns,
+/// or `None` when there is nothing older to summarize.
+fn hybrid_split(items: &[ResponseItem], keep_recent_turns: usize) -> Option<usize> {
+    if keep_recent_turns == 0 {
+        return None;
+    }
+    let turn_starts: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| is_user_turn_boundary(item))
+        .map(|(index, _)| index)
+        .collect();
+    if turn_starts.len() <= keep_recent_turns {
+        return None;
+    }
+    Some(turn_starts[turn_starts.len() - keep_recent_turns])
+}
+
+/// Replaces tool outputs with stubs, oldest first, until the estimate drops to
+/// `target` tokens. Returns the new history, the indices that were stubbed and
+/// the resulting estimate.
+fn drop_tool_outputs(
+    items: &[ResponseItem],
+    tokenizer: &Tokenizer,
+    mut tokens: i64,
+    target: i64,
+) -> (Vec<ResponseItem>, Vec<usize>, i64) {
+    let tool_names = tool_names_by_call_id(items);
+    let mut history = items.to_vec();
+    let mut dropped = Vec::new();
+    for (index, item) in history.iter_mut().enumerate() {
+        if tokens <= target {
+            break;
+        }
+        let Some((call_id, bytes)) = stubbable_tool_output(item) else {
+            continue;
+        };
+        let tool = tool_names.get(call_id).copied().unwrap_or("tool");
+        let mut stub = item.clone();
+        replace_tool_output(&mut stub, &tool_output_stub(tool, bytes));
+        let item_tokens = estimate_item_token_count(item, tokenizer);
+        let stub_tokens = estimate_item_token_count(&stub, tokenizer);
+        if stub_tokens >= item_tokens {
+            continue;
+        }
+        tokens = tokens.saturating_sub(item_tokens - stub_tokens);
+        *item = stub;
+        dropped.push(index);
+    }
+    (history, dropped, tokens)
+}
+
+pub(crate) async fn preview_compaction(
+    sess: &Session,
+    turn_context: &TurnContext,
+) -> CompactionPreviewEvent {
+    let history = sess.clone_history().await;
+    let items = history.raw_items();
+    let tokens_before = history.estimate_token_count(turn_context).unwrap_or(0);
+    let plan = plan_for_turn(items, tokens_before, turn_context);
+    let compaction = &turn_context.client.config().compaction;
+    let summary_model = Some(
+        compaction
+            .model
+            .clone()
+            .unwrap_or_else(|| turn_context.client.get_model()),
+    );
+    let (strategy, summary_model, estimated_tokens_after) = match &plan {
+        CompactionPlan::Summarize { keep_from } if *keep_from == items.len() => {
+            (CompactionStrategy::Summary, summary_model, None)
+        }
+        CompactionPlan::Summarize { .. } => (CompactionStrategy::Hybrid, summary_model, None),
+        CompactionPlan::DropToolOutputs { tokens_after, .. } => (
+            CompactionStrategy::DropToolOutputs,
+            None,
+            Some(*tokens_after),
+        ),
+    };
+    CompactionPreviewEvent {
+        strategy,
+        summary_model,
+        items: preview_items(items, &plan, &turn_context.tokenizer),
+        tokens_before,
+        estimated_tokens_after,
+    }
+}
+
+fn preview_items(
+    items: &[ResponseItem],
+    plan: &CompactionPlan,
+    tokenizer: &Tokenizer,
+) -> Vec<CompactionPreviewItem> {
+    let mut tool_names: HashMap<&str, &str> = HashMap::new();
+    let mut preview = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        match item {
+            ResponseItem::FunctionCall { name, call_id, .. }
+            | ResponseItem::CustomToolCall { name, call_id, .. } => {
+                tool_names.insert(call_id.as_str(), name.as_str());
+            }
+            ResponseItem::GhostSnapshot { .. } => continue,
+            _ => {}
+        }
+        let action = match plan {
+            CompactionPlan::DropToolOutputs { dropped, .. } if dropped.contains(&index) => {
+                CompactionPreviewAction::Drop
+            }
+            CompactionPlan::DropToolOutputs { .. } => CompactionPreviewAction::Keep,
+            CompactionPlan::Summarize { keep_from } if index >= *keep_from => {
+                CompactionPreviewAction::Keep
+            }
+            // User messages are carried over next to the summary.
+            CompactionPlan::Summarize { .. }
+                if !collect_user_messages(std::slice::from_ref(item)).is_empty() =>
+            {
+                CompactionPreviewAction::Keep
+            }
+            CompactionPlan::Summarize { .. } => CompactionPreviewAction::Summarize,
+        };
+        preview.push(CompactionPreviewItem {
+            description: describe_item(item, &tool_names),
+            tokens: estimate_item_token_count(item, tokenizer),
+            action,
+        });
+    }
+    preview
+}
+
+fn describe_item(item: &ResponseItem, tool_names: &HashMap<&str, &str>) -> String {
+    const MAX_CHARS: usize = 60;
+    match item {
+        ResponseItem::Message { role, content, .. } => {
+            let text = content_items_to_text(content).unwrap_or_default();
+            let first_line = text.trim().lines().next().unwrap_or_default();
+            let mut excerpt: String = first_line.chars().take(MAX_CHARS).collect();
+            if excerpt.len() < text.trim().len() {
+                excerpt.push('…');
+            }
+            format!("{role}: {excerpt}")
+        }
+        ResponseItem::Reasoning { .. } => "reasoning".to_string(),
+        ResponseItem::FunctionCall { name, .. } | ResponseItem::CustomToolCall { name, .. } => {
+            format!("call {name}")
+        }
+        ResponseItem::FunctionCallOutput { call_id, .. }
+        | ResponseItem::CustomToolCallOutput { call_id, .. } => {
+            match tool_names.get(call_id.as_str()) {
+                Some(name) => format!("output of {name}"),
+                None => "tool output".to_string(),
+            }
+        }
+        ResponseItem::LocalShellCall { .. } => "shell call".to_string(),
+        ResponseItem::WebSearchCall { .. } => "web search".to_string(),
+        ResponseItem::Compaction { .. } => "compacted history".to_string(),
+        ResponseItem::GhostSnapshot { .. } => "snapshot".to_string(),
+        ResponseItem::Other => "other".to_string(),
+    }
+}
+
+/// Client for the summarization request: the session's own, or one for
+/// `[compaction].model` when set. The session's reasoning effort is tuned for
+/// the session model, so the summarizer uses `[compaction].reasoning_effort`
+/// or else its own model's default.
+async fn summarizer_client(sess: &Session, turn_context: &TurnContext) -> ModelClient {
+    let config = turn_context.client.config();
+    let Some(model) = config.compaction.model.clone() else {
+        return turn_context.client.clone();
+    };
+    let model_info = sess
+        .services
+        .models_manager
+        .get_model_info(&model, &config)
+        .await;
+    let otel_manager = turn_context
+        .client
+        .get_otel_manager()
+        .with_model(model.as_str(), model_info.slug.as_str());
+    let mut summarizer_config = (*config).clone();
+    summarizer_config.model = Some(model);
+    let summarizer_config = Arc::new(summarizer_config);
+    ModelClient::new(
+        summarizer_config.clone(),
+        turn_context.client.get_auth_manager(),
+        model_info,
+        otel_manager,
+        turn_context.client.get_provider(),
+        summarizer_config.compaction.reasoning_effort,
+        turn_context.client.get_reasoning_summary(),
+        sess.conversation_id,
+        turn_context.client.get_session_source(),
+        turn_context.client.transport_manager(),
+    )
+}
+
 async fn run_compact_task_inner(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
@@ -75,17 +364,53 @@ async fn run_compact_task_inner(
     let compaction_item = TurnItem::ContextCompaction(ContextCompactionItem::new());
     sess.emit_turn_item_started(&turn_context, &compaction_item)
         .await;
-    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
 
     let mut history = sess.clone_history().await;
+    let tokens_before = history.estimate_token_count(&turn_context).unwrap_or(0);
+    let plan = plan_for_turn(history.raw_items(), tokens_before, &turn_context);
+    let keep_from = match plan {
+        CompactionPlan::Summarize { keep_from } => keep_from,
+        CompactionPlan::DropToolOutputs {
+            history: new_history,
+            dropped,
+            ..
+        } => {
+            sess.replace_history(new_history.clone()).await;
+            sess.recompute_token_usage(&turn_context).await;
+            let rollout_item = RolloutItem::Compacted(CompactedItem {
+                message: String::new(),
+                replacement_history: Some(new_history),
+            });
+            sess.persist_rollout_items(&[rollout_item]).await;
+            sess.emit_turn_item_completed(&turn_context, compaction_item)
+                .await;
+            sess.notify_background_event(
+                turn_context.as_ref(),
+                format!(
+                    "Dropped {} older tool output(s) to free up context.",
+                    dropped.len()
+                ),
+            )
+            .await;
+            return;
+        }
+    };
+    // Items from `keep_from` on are the turns a hybrid compaction keeps verbatim.
+    let older_items = history.raw_items()[..keep_from].to_vec();
+    let recent_items = history.raw_items()[keep_from..].to_vec();
+    history.replace(older_items.clone());
+
+    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
     history.record_items(
         &[initial_input_for_turn.into()],
         turn_context.truncation_policy,
+        &turn_context.tokenizer,
     );
 
     let mut truncated_count = 0usize;
 
-    let max_retries = turn_context.client.get_provider().stream_max_retries();
+    let client = summarizer_client(&sess, &turn_context).await;
+    let max_retries = client.get_provider().stream_max_retries();
     let mut retries = 0;
 
     // TODO: If we need to guarantee the persisted mode always matches the prompt used for this
@@ -119,7 +444,8 @@ async fn run_compact_task_inner(
             personality: turn_context.personality,
             ..Default::default()
         };
-        let attempt_result = drain_to_completed(&sess, turn_context.as_ref(), &prompt).await;
+        let attempt_result =
+            drain_to_completed(&sess, turn_context.as_ref(), &client, &prompt).await;
 
         match attempt_result {
             Ok(()) => {
@@ -178,22 +504,35 @@ async fn run_compact_task_inner(
     let history_items = history_snapshot.raw_items();
     let summary_suffix = get_last_assistant_message_from_turn(history_items).unwrap_or_default();
     let summary_text = format!("{SUMMARY_PREFIX}\n{summary_suffix}");
-    let user_messages = collect_user_messages(history_items);
+    let summarized_items = if recent_items.is_empty() {
+        history_items
+    } else {
+        older_items.as_slice()
+    };
+    let user_messages = collect_user_messages(summarized_items);
 
     let initial_context = sess.build_initial_context(turn_context.as_ref()).await;
     let mut new_history = build_compacted_history(initial_context, &user_messages, &summary_text);
-    let ghost_snapshots: Vec<ResponseItem> = history_items
+    let ghost_snapshots: Vec<ResponseItem> = summarized_items
         .iter()
         .filter(|item| matches!(item, ResponseItem::GhostSnapshot { .. }))
         .cloned()
         .collect();
     new_history.extend(ghost_snapshots);
+    // Resume can only rebuild plain summaries from the message, so hybrid
+    // compactions record the full replacement.
+    let replacement_history = if recent_items.is_empty() {
+        None
+    } else {
+        new_history.extend(recent_items);
+        Some(new_history.clone())
+    };
     sess.replace_history(new_history).await;
     sess.recompute_token_usage(&turn_context).await;
 
     let rollout_item = RolloutItem::Compacted(CompactedItem {
         message: summary_text.clone(),
-        replacement_history: None,
+        replacement_history,
     });
     sess.persist_rollout_items(&[rollout_item]).await;
 
@@ -295,7 +634,11 @@ fn build_compacted_history_with_limit(
                 selected_messages.push(message.clone());
                 remaining = remaining.saturating_sub(tokens);
             } else {
-                let truncated = truncate_text(message, TruncationPolicy::Tokens(remaining));
+                let truncated = truncate_text(
+                    message,
+                    TruncationPolicy::Tokens(remaining),
+                    &Tokenizer::default(),
+                );
                 selected_messages.push(truncated);
                 break;
             }
@@ -335,11 +678,10 @@ fn build_compacted_history_with_limit(
 async fn drain_to_completed(
     sess: &Session,
     turn_context: &TurnContext,
+    client: &ModelClient,
     prompt: &Prompt,
 ) -> CodexResult<()> {
-    let mut client_session = turn_context
-        .client
-        .new_session(Some(turn_context.cwd.clone()));
+    let mut client_session = client.new_session(Some(turn_context.cwd.clone()));
     let mut stream = client_session.stream(prompt).await?;
     loop {
         let maybe_event = stream.next().await;
@@ -376,6 +718,7 @@ mod tests {
 
     use super::*;
     use crate::session_prefix::TURN_ABORTED_OPEN_TAG;
+    use codex_protocol::models::FunctionCallOutputPayload;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -579,4 +922,158 @@ mod tests {
             "expected compacted history to retain <turn_aborted> marker"
         );
     }
+
+    fn user_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+            end_turn: None,
+            phase: None,
+        }
+    }
+
+    fn tool_call(call_id: &str, output: &str) -> [ResponseItem; 2] {
+        [
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: "{}".to_string(),
+                call_id: call_id.to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: call_id.to_string(),
+                output: FunctionCallOutputPayload {
+                    content: output.to_string(),
+                    content_items: None,
+                    success: Some(true),
+                },
+            },
+        ]
+    }
+
+    fn history_with_two_turns() -> Vec<ResponseItem> {
+        let big_output = "output line\n".repeat(500);
+        let mut items = vec![user_message("first task")];
+        items.extend(tool_call("call-1", &big_output));
+        items.push(user_message("second task"));
+        items.extend(tool_call("call-2", &big_output));
+        items
+    }
+
+    fn total_tokens(items: &[ResponseItem], tokenizer: &Tokenizer) -> i64 {
+        items
+            .iter()
+            .map(|item| estimate_item_token_count(item, tokenizer))
+            .sum()
+    }
+
+    #[test]
+    fn drop_tool_outputs_stubs_oldest_outputs_first() {
+        let tokenizer = Tokenizer::default();
+        let items = history_with_two_turns();
+        let tokens_before = total_tokens(&items, &tokenizer);
+        let output_tokens = estimate_item_token_count(&items[2], &tokenizer);
+
+        let (history, dropped, tokens_after) = drop_tool_outputs(
+            &items,
+            &tokenizer,
+            tokens_before,
+            tokens_before - output_tokens / 2,
+        );
+
+        assert_eq!(dropped, vec![2]);
+        assert_eq!(tokens_after, total_tokens(&history, &tokenizer));
+        let ResponseItem::FunctionCallOutput { output, .. } = &history[2] else {
+            panic!("expected a tool output, got {:?}", history[2]);
+        };
+        assert_eq!(output.content, "[shell output elided, 6 KB, re-run to see]");
+        assert_eq!(history[5], items[5]);
+
+        // Stubs are never stubbed again.
+        let (_, dropped_again, _) = drop_tool_outputs(&history, &tokenizer, tokens_after, 0);
+        assert_eq!(dropped_again, vec![5]);
+    }
+
+    #[test]
+    fn plan_follows_configured_strategy() {
+        let tokenizer = Tokenizer::default();
+        let items = history_with_two_turns();
+        let tokens_before = total_tokens(&items, &tokenizer);
+        let plan = |strategy, keep_recent_turns, limit| {
+            let compaction = CompactionConfig {
+                strategy,
+                keep_recent_turns,
+                ..Default::default()
+            };
+            plan_compaction(&compaction, &items, &tokenizer, tokens_before, limit)
+        };
+
+        assert_eq!(
+            plan(CompactionStrategy::Summary, 1, None),
+            CompactionPlan::Summarize { keep_from: 6 }
+        );
+        assert_eq!(
+            plan(CompactionStrategy::Hybrid, 1, None),
+            CompactionPlan::Summarize { keep_from: 3 }
+        );
+        // Not enough older turns, or the kept turns alone are too large.
+        assert_eq!(
+            plan(CompactionStrategy::Hybrid, 2, None),
+            CompactionPlan::Summarize { keep_from: 6 }
+        );
+        assert_eq!(
+            plan(CompactionStrategy::Hybrid, 1, Some(tokens_before / 2)),
+            CompactionPlan::Summarize { keep_from: 6 }
+        );
+
+        let CompactionPlan::DropToolOutputs {
+            dropped,
+            tokens_after,
+            ..
+        } = plan(CompactionStrategy::DropToolOutputs, 1, Some(tokens_before))
+        else {
+            panic!("expected tool outputs to be dropped");
+        };
+        assert_eq!(dropped, vec![2, 5]);
+        assert!(tokens_after < tokens_before / 2, "{tokens_after}");
+        // Falls back to a summary when stubs alone cannot get under the limit.
+        assert_eq!(
+            plan(CompactionStrategy::DropToolOutputs, 1, Some(10)),
+            CompactionPlan::Summarize { keep_from: 6 }
+        );
+    }
+
+    #[test]
+    fn preview_items_report_actions() {
+        let tokenizer = Tokenizer::default();
+        let items = history_with_two_turns();
+        let preview = preview_items(
+            &items,
+            &CompactionPlan::Summarize { keep_from: 3 },
+            &tokenizer,
+        );
+
+        let described: Vec<(&str, CompactionPreviewAction)> = preview
+            .iter()
+            .map(|item| (item.description.as_str(), item.action))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("user: first task", CompactionPreviewAction::Keep),
+                ("call shell", CompactionPreviewAction::Summarize),
+                ("output of shell", CompactionPreviewAction::Summarize),
+                ("user: second task", CompactionPreviewAction::Keep),
+                ("call shell", CompactionPreviewAction::Keep),
+                ("output of shell", CompactionPreviewAction::Keep),
+            ]
+        );
+        assert_eq!(
+            preview[2].tokens,
+            estimate_item_token_count(&items[2], &tokenizer)
+        );
+    }
 }
@@ -798,6 +798,15 @@ impl ConfigEditsBuilder {
         self
     }
 
+    /// Set the TUI color theme under the `[tui]` table.
+    pub fn set_tui_theme(mut self, name: &str) -> Self {
+        self.edits.push(ConfigEdit::SetPath {
+            segments: vec!["tui".to_string(), "theme".to_string()],
+            value: value(name),
+        });
+        self
+    }
+
     pub fn with_edits<I>(mut self, edits: I) -> Self
     where
         I: IntoIterator<Item = ConfigEdit>,
@@ -873,6 +882,26 @@ model_reasoning_effort = "high"
         assert_eq!(contents, "enabled = true\n");
     }
 
+    #[test]
+    fn set_tui_theme_preserves_existing_tui_settings() {
+        let tmp = tempdir().expect("tmpdir");
+        let codex_home = tmp.path();
+        std::fs::write(
+            codex_home.join(CONFIG_TOML_FILE),
+            "[tui]\nanimations = false\n",
+        )
+        .expect("seed config");
+
+        ConfigEditsBuilder::new(codex_home)
+            .set_tui_theme("light")
+            .apply_blocking()
+            .expect("persist");
+
+        let contents =
+            std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)).expect("read config");
+        assert_eq!(contents, "[tui]\nanimations = false\ntheme = \"light\"\n");
+    }
+
     #[test]
     fn set_skill_config_writes_disabled_entry() {
         let tmp = tempdir().expect("tmpdir");
@@ -1,8 +1,16 @@
+use crate::agent::role::load_agent_roles;
 use crate::auth::AuthCredentialsStoreMode;
 use crate::config::edit::ConfigEdit;
 use crate::config::edit::ConfigEditsBuilder;
+use crate::config::types::AgentRoleConfig;
+use crate::config::types::CompactionConfig;
+use crate::config::types::CompactionToml;
+use crate::config::types::DEFAULT_COMPACTION_KEEP_RECENT_TURNS;
 use crate::config::types::DEFAULT_OTEL_ENVIRONMENT;
+use crate::config::types::EditingMode;
 use crate::config::types::History;
+use crate::config::types::Hooks;
+use crate::config::types::LspServerConfig;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerDisabledReason;
 use crate::config::types::McpServerTransportConfig;
@@ -16,6 +24,8 @@ use crate::config::types::SandboxWorkspaceWrite;
 use crate::config::types::ShellEnvironmentPolicy;
 use crate::config::types::ShellEnvironmentPolicyToml;
 use crate::config::types::SkillsConfig;
+use crate::config::types::ThemeToml;
+use crate::config::types::ToolOutputPruning;
 use crate::config::types::Tui;
 use crate::config::types::UriBasedFileOpener;
 use crate::config_loader::CloudRequirementsLoader;
@@ -77,6 +87,7 @@ use toml_edit::DocumentMut;
 
 mod constraint;
 pub mod edit;
+pub mod network_proxy;
 pub mod profile;
 pub mod schema;
 pub mod service;
@@ -95,6 +106,8 @@ pub use codex_git::GhostSnapshotConfig;
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 pub(crate) const DEFAULT_AGENT_MAX_THREADS: Option<usize> = Some(6);
+/// Sub-agents may spawn no further agents by default; the root session is depth 0.
+pub(crate) const DEFAULT_AGENT_MAX_DEPTH: i32 = 1;
 
 pub const CONFIG_TOML_FILE: &str = "config.toml";
 
@@ -140,6 +153,10 @@ pub struct Config {
     /// Approval policy for executing commands.
     pub approval_policy: Constrained<AskForApproval>,
 
+    /// How long an "approve for this session" decision stays in effect.
+    /// `None` keeps it until the session ends.
+    pub session_approval_ttl: Option<std::time::Duration>,
+
     pub sandbox_policy: Constrained<SandboxPolicy>,
 
     /// enforce_residency means web traffic cannot be routed outside of a
@@ -178,6 +195,12 @@ pub struct Config {
     /// Compact prompt override.
     pub compact_prompt: Option<String>,
 
+    /// How history is compacted; see `[compaction]`.
+    pub compaction: CompactionConfig,
+
+    /// Which tool outputs are replaced with stubs before each turn.
+    pub tool_output_pruning: ToolOutputPruning,
+
     /// Optional external notifier command. When set, Codex will spawn this
     /// program after each completed *turn* (i.e. when the agent finishes
     /// processing a user submission). The value must be the full command
@@ -200,6 +223,18 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Lifecycle hooks run at fixed points in a session.
+    pub hooks: Hooks,
+
+    /// Formatter/linter commands run after every successful `apply_patch`,
+    /// keyed by the file glob they apply to. `{files}` in a command expands
+    /// to the matching edited files.
+    pub post_edit: BTreeMap<String, String>,
+
+    /// Language servers whose errors for edited files are appended to
+    /// `apply_patch` results, keyed by language id.
+    pub lsp: BTreeMap<String, LspServerConfig>,
+
     /// TUI notifications preference. When set, the TUI will send terminal notifications on
     /// approvals and turn completions when not focused.
     pub tui_notifications: Notifications,
@@ -224,6 +259,15 @@ pub struct Config {
     /// - `never`: Never use alternate screen (inline mode, preserves scrollback).
     pub tui_alternate_screen: AltScreenMode,
 
+    /// Key bindings used by the TUI composer (`tui.editing_mode`).
+    pub tui_editing_mode: EditingMode,
+
+    /// Name of the TUI color theme (`tui.theme`); `None` selects the default dark theme.
+    pub tui_theme: Option<String>,
+
+    /// User-defined TUI color themes from `[tui.themes]`.
+    pub tui_themes: BTreeMap<String, ThemeToml>,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -267,6 +311,25 @@ pub struct Config {
     /// Maximum number of agent threads that can be open concurrently.
     pub agent_max_threads: Option<usize>,
 
+    /// Deepest level of the agent tree a sub-agent may run at.
+    pub agent_max_depth: i32,
+
+    /// Tokens all sub-agents of one root session may spend before further
+    /// spawns are refused.
+    pub agent_token_budget: Option<i64>,
+
+    /// Wall-clock time, measured from the first spawn, after which further
+    /// sub-agent spawns are refused.
+    pub agent_wall_clock_budget: Option<std::time::Duration>,
+
+    /// Roles a sub-agent can be spawned with, keyed by name: the built-in
+    /// roles plus those from `[agents.roles]` and `agents/*.md` files.
+    pub agent_roles: BTreeMap<String, AgentRoleConfig>,
+
+    /// When set, only these tools are offered to the model. Agent roles use
+    /// this to narrow what a sub-agent can do.
+    pub tool_allowlist: Option<Vec<String>>,
+
     /// Directory containing all Codex state (defaults to `~/.codex` but can be
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
@@ -580,17 +643,39 @@ fn filter_mcp_servers_by_requirements(
     }
 }
 
+/// Adds every denied tool to each server's `disabled_tools`.
+pub(crate) fn disable_denied_mcp_tools(
+    mcp_servers: &mut HashMap<String, McpServerConfig>,
+    denied_tools: Option<&Sourced<Vec<String>>>,
+) {
+    let Some(denied_tools) = denied_tools else {
+        return;
+    };
+
+    for server in mcp_servers.values_mut() {
+        let disabled_tools = server.disabled_tools.get_or_insert_with(Vec::new);
+        for tool in denied_tools.iter() {
+            if !disabled_tools.contains(tool) {
+                disabled_tools.push(tool.clone());
+            }
+        }
+    }
+}
+
 fn constrain_mcp_servers(
     mcp_servers: HashMap<String, McpServerConfig>,
     mcp_requirements: Option<&Sourced<BTreeMap<String, McpServerRequirement>>>,
+    denied_tools: Option<&Sourced<Vec<String>>>,
 ) -> ConstraintResult<Constrained<HashMap<String, McpServerConfig>>> {
-    if mcp_requirements.is_none() {
+    if mcp_requirements.is_none() && denied_tools.is_none() {
         return Ok(Constrained::allow_any(mcp_servers));
     }
 
     let mcp_requirements = mcp_requirements.cloned();
+    let denied_tools = denied_tools.cloned();
     Constrained::normalized(mcp_servers, move |mut servers| {
         filter_mcp_servers_by_requirements(&mut servers, mcp_requirements.as_ref());
+        disable_denied_mcp_tools(&mut servers, denied_tools.as_ref());
         servers
     })
 }
@@ -810,6 +895,11 @@ pub struct ConfigToml {
     /// Default approval policy for executing commands.
     pub approval_policy: Option<AskForApproval>,
 
+    /// Expire "approve for this session" decisions after this many seconds.
+    /// When unset, they last until the session ends. Command prefixes approved
+    /// with "don't ask again" are saved as rules and never expire.
+    pub session_approval_ttl_secs: Option<u64>,
+
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
@@ -823,6 +913,20 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Lifecycle hooks run at fixed points in a session.
+    #[serde(default)]
+    pub hooks: Option<Hooks>,
+
+    /// Commands run after every successful `apply_patch`, keyed by file glob,
+    /// e.g. `"*.rs" = "rustfmt {files}"`.
+    #[serde(default)]
+    pub post_edit: Option<BTreeMap<String, String>>,
+
+    /// Language servers that report diagnostics after `apply_patch`, e.g.
+    /// `[lsp.rust] command = "rust-analyzer"`.
+    #[serde(default)]
+    pub lsp: Option<BTreeMap<String, LspServerConfig>>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -839,6 +943,14 @@ pub struct ConfigToml {
     /// Compact prompt used for history compaction.
     pub compact_prompt: Option<String>,
 
+    /// Compaction strategy, summarizer prompt file and model.
+    #[serde(default)]
+    pub compaction: Option<CompactionToml>,
+
+    /// Replace old or large tool outputs in the history with short stubs.
+    #[serde(default)]
+    pub tool_output_pruning: Option<ToolOutputPruning>,
+
     /// When set, restricts ChatGPT login to a specific workspace identifier.
     #[serde(default)]
     pub forced_chatgpt_workspace_id: Option<String>,
@@ -1048,13 +1160,52 @@ pub struct ToolsToml {
     pub view_image: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
 #[schemars(deny_unknown_fields)]
 pub struct AgentsToml {
     /// Maximum number of agent threads that can be open concurrently.
     /// When unset, no limit is enforced.
     #[schemars(range(min = 1))]
     pub max_threads: Option<usize>,
+
+    /// How many levels of sub-agents may be nested below the root session.
+    /// Defaults to 1, meaning sub-agents cannot spawn agents of their own.
+    #[schemars(range(min = 1))]
+    pub max_depth: Option<i32>,
+
+    /// Total tokens the whole agent tree may spend. Once reached, further
+    /// `spawn_agent` calls fail; running agents are not interrupted.
+    #[schemars(range(min = 1))]
+    pub token_budget: Option<i64>,
+
+    /// Seconds, counted from the first sub-agent spawn, after which further
+    /// `spawn_agent` calls fail.
+    #[schemars(range(min = 1))]
+    pub wall_clock_budget_secs: Option<u64>,
+
+    /// User-defined sub-agent roles, keyed by the name passed to
+    /// `spawn_agent`. A role with a built-in name replaces the built-in.
+    #[serde(default)]
+    pub roles: BTreeMap<String, AgentRoleToml>,
+}
+
+/// A sub-agent role declared under `[agents.roles.<name>]`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct AgentRoleToml {
+    /// Tells the parent agent when to pick this role.
+    pub description: Option<String>,
+    /// File whose contents replace the base instructions of agents with this role.
+    pub instructions_file: Option<AbsolutePathBuf>,
+    /// Model used by agents with this role.
+    pub model: Option<String>,
+    /// Reasoning effort used by agents with this role.
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    /// Sandbox mode for agents with this role. It may only be as permissive
+    /// as the parent's sandbox.
+    pub sandbox_mode: Option<SandboxMode>,
+    /// When set, only these tools are offered to agents with this role.
+    pub tools: Option<Vec<String>>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -1401,9 +1552,9 @@ impl Config {
             Some(&requirements.sandbox_policy),
         );
         if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut sandbox_policy {
-            for path in additional_writable_roots {
-                if !writable_roots.iter().any(|existing| existing == &path) {
-                    writable_roots.push(path);
+            for path in &additional_writable_roots {
+                if !writable_roots.iter().any(|existing| existing == path) {
+                    writable_roots.push(path.clone());
                 }
             }
         }
@@ -1446,7 +1597,7 @@ impl Config {
         let model_provider_id = model_provider
             .or(config_profile.model_provider)
             .or(cfg.model_provider)
-            .unwrap_or_else(|| "openai".to_string());
+            .unwrap_or_else(|| requirements.model_provider.get().clone());
         let model_provider = model_providers
             .get(&model_provider_id)
             .ok_or_else(|| {
@@ -1459,7 +1610,7 @@ impl Config {
             })?
             .clone();
 
-        let shell_environment_policy = cfg.shell_environment_policy.into();
+        let shell_environment_policy: ShellEnvironmentPolicy = cfg.shell_environment_policy.into();
 
         let history = cfg.history.unwrap_or_default();
 
@@ -1474,6 +1625,39 @@ impl Config {
                 "agents.max_threads must be at least 1",
             ));
         }
+        let agent_max_depth = cfg
+            .agents
+            .as_ref()
+            .and_then(|agents| agents.max_depth)
+            .unwrap_or(DEFAULT_AGENT_MAX_DEPTH);
+        if agent_max_depth < 1 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "agents.max_depth must be at least 1",
+            ));
+        }
+        let agent_token_budget = cfg.agents.as_ref().and_then(|agents| agents.token_budget);
+        if agent_token_budget.is_some_and(|budget| budget < 1) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "agents.token_budget must be at least 1",
+            ));
+        }
+        let agent_wall_clock_budget = cfg
+            .agents
+            .as_ref()
+            .and_then(|agents| agents.wall_clock_budget_secs)
+            .map(std::time::Duration::from_secs);
+        if agent_wall_clock_budget == Some(std::time::Duration::ZERO) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "agents.wall_clock_budget_secs must be at least 1",
+            ));
+        }
+        let agent_roles = load_agent_roles(
+            cfg.agents.as_ref().map(|agents| &agents.roles),
+            &config_layer_stack,
+        )?;
 
         let ghost_snapshot = {
             let mut config = GhostSnapshotConfig::default();
@@ -1515,7 +1699,10 @@ impl Config {
 
         let forced_login_method = cfg.forced_login_method;
 
-        let model = model.or(config_profile.model).or(cfg.model);
+        let model = model
+            .or(config_profile.model)
+            .or(cfg.model)
+            .or_else(|| requirements.model.get().clone());
 
         let compact_prompt = compact_prompt.or(cfg.compact_prompt).and_then(|value| {
             let trimmed = value.trim();
@@ -1554,7 +1741,21 @@ impl Config {
             experimental_compact_prompt_path,
             "experimental compact prompt file",
         )?;
-        let compact_prompt = compact_prompt.or(file_compact_prompt);
+        let compaction_toml = cfg.compaction.clone().unwrap_or_default();
+        let compaction_prompt = Self::try_read_non_empty_file(
+            compaction_toml.prompt_file.as_ref(),
+            "compaction prompt file",
+        )?;
+        let compaction = CompactionConfig {
+            strategy: compaction_toml.strategy.unwrap_or_default(),
+            model: compaction_toml.model,
+            reasoning_effort: compaction_toml.reasoning_effort,
+            keep_recent_turns: compaction_toml
+                .keep_recent_turns
+                .unwrap_or(DEFAULT_COMPACTION_KEEP_RECENT_TURNS),
+            custom_prompt: compaction_prompt.is_some(),
+        };
+        let compact_prompt = compact_prompt.or(compaction_prompt).or(file_compact_prompt);
 
         let review_model = override_review_model.or(cfg.review_model);
 
@@ -1568,6 +1769,11 @@ impl Config {
             mcp_servers,
             exec_policy: _,
             enforce_residency,
+            model: mut constrained_model,
+            model_provider: mut constrained_model_provider,
+            denied_mcp_tools,
+            shell_environment_policy: mut constrained_shell_environment_policy,
+            additional_writable_roots: mut constrained_additional_writable_roots,
         } = requirements;
 
         constrained_approval_policy
@@ -1577,8 +1783,42 @@ impl Config {
             .set(sandbox_policy)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
 
-        let mcp_servers = constrain_mcp_servers(cfg.mcp_servers.clone(), mcp_servers.as_ref())
+        constrained_model
+            .set(model.clone())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
+        // Other settings that pick a model must stay within the same set.
+        for (setting, other_model) in [
+            ("review_model", &review_model),
+            ("compaction.model", &compaction.model),
+        ] {
+            if let Some(other_model) = other_model {
+                constrained_model
+                    .can_set(&Some(other_model.clone()))
+                    .map_err(|e| {
+                        std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("{setting}: {e}"),
+                        )
+                    })?;
+            }
+        }
+        constrained_model_provider
+            .set(model_provider_id.clone())
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
+        constrained_additional_writable_roots
+            .set(additional_writable_roots)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
+        constrained_shell_environment_policy
+            .set(shell_environment_policy)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
+        let shell_environment_policy = constrained_shell_environment_policy.get().clone();
+
+        let mcp_servers = constrain_mcp_servers(
+            cfg.mcp_servers.clone(),
+            mcp_servers.as_ref(),
+            denied_mcp_tools.as_ref(),
+        )
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{e}")))?;
 
         let config = Self {
             model,
@@ -1589,17 +1829,25 @@ impl Config {
             model_provider,
             cwd: resolved_cwd,
             approval_policy: constrained_approval_policy,
+            session_approval_ttl: cfg
+                .session_approval_ttl_secs
+                .map(std::time::Duration::from_secs),
             sandbox_policy: constrained_sandbox_policy,
             enforce_residency,
             did_user_set_custom_approval_policy_or_sandbox_mode,
             forced_auto_mode_downgraded_on_windows,
             shell_environment_policy,
             notify: cfg.notify,
+            hooks: cfg.hooks.unwrap_or_default(),
+            post_edit: cfg.post_edit.unwrap_or_default(),
+            lsp: cfg.lsp.unwrap_or_default(),
             user_instructions,
             base_instructions,
             personality,
             developer_instructions,
             compact_prompt,
+            compaction,
+            tool_output_pruning: cfg.tool_output_pruning.unwrap_or_default(),
             // The config.toml omits "_mode" because it's a config file. However, "_mode"
             // is important in code to differentiate the mode from the store implementation.
             cli_auth_credentials_store_mode: cfg.cli_auth_credentials_store.unwrap_or_default(),
@@ -1625,6 +1873,11 @@ impl Config {
                 .collect(),
             tool_output_token_limit: cfg.tool_output_token_limit,
             agent_max_threads,
+            agent_max_depth,
+            agent_token_budget,
+            agent_wall_clock_budget,
+            agent_roles,
+            tool_allowlist: None,
             codex_home,
             config_layer_stack,
             history,
@@ -1694,6 +1947,13 @@ impl Config {
                 .as_ref()
                 .map(|t| t.alternate_screen)
                 .unwrap_or_default(),
+            tui_editing_mode: cfg.tui.as_ref().map(|t| t.editing_mode).unwrap_or_default(),
+            tui_theme: cfg.tui.as_ref().and_then(|t| t.theme.clone()),
+            tui_themes: cfg
+                .tui
+                .as_ref()
+                .map(|t| t.themes.clone())
+                .unwrap_or_default(),
             otel: {
                 let t: OtelConfigToml = cfg.otel.unwrap_or_default();
                 let log_user_prompt = t.log_user_prompt.unwrap_or(false);
@@ -1775,6 +2035,14 @@ impl Config {
             self.features.disable(Feature::WindowsSandboxElevated);
         }
     }
+
+    /// Directory for persistent file search indexes, when the
+    /// `file_search_index` feature is enabled.
+    pub fn file_search_index_dir(&self) -> Option<PathBuf> {
+        self.features
+            .enabled(Feature::FileSearchIndex)
+            .then(|| self.codex_home.join(codex_file_search::INDEX_DIR_NAME))
+    }
 }
 
 pub(crate) fn uses_deprecated_instructions_file(config_layer_stack: &ConfigLayerStack) -> bool {
@@ -1826,15 +2094,19 @@ mod tests {
     use crate::config::edit::ConfigEdit;
     use crate::config::edit::ConfigEditsBuilder;
     use crate::config::edit::apply_blocking;
+    use crate::config::types::CompactionStrategy;
+    use crate::config::types::EnvironmentVariablePattern;
     use crate::config::types::FeedbackConfigToml;
     use crate::config::types::HistoryPersistence;
     use crate::config::types::McpServerTransportConfig;
     use crate::config::types::NotificationMethod;
     use crate::config::types::Notifications;
+    use crate::config::types::ToolOutputPruningRule;
     use crate::config_loader::RequirementSource;
     use crate::features::Feature;
 
     use super::*;
+    use crate::agent::role::builtin_agent_roles;
     use core_test_support::test_absolute_path;
     use pretty_assertions::assert_eq;
 
@@ -1931,10 +2203,27 @@ persistence = "none"
                 show_tooltips: true,
                 experimental_mode: None,
                 alternate_screen: AltScreenMode::Auto,
+                editing_mode: EditingMode::Emacs,
+                theme: None,
+                themes: BTreeMap::new(),
             }
         );
     }
 
+    #[test]
+    fn tui_config_parses_vi_editing_mode() {
+        let cfg = r#"
+[tui]
+editing_mode = "vi"
+"#;
+
+        let parsed =
+            toml::from_str::<ConfigToml>(cfg).expect("TUI config with editing_mode should succeed");
+        let tui = parsed.tui.expect("config should include tui section");
+
+        assert_eq!(tui.editing_mode, EditingMode::Vi);
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -3691,6 +3980,92 @@ model = "gpt-5.1-codex"
         Ok(())
     }
 
+    #[test]
+    fn loads_compaction_section() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let prompt_path = codex_home.path().join("summarize.md");
+        std::fs::write(&prompt_path, "Summarize tersely.\n")?;
+        let cfg = toml::from_str::<ConfigToml>(&format!(
+            r#"
+compact_prompt = ""
+experimental_compact_prompt_file = "{path}"
+
+[compaction]
+strategy = "hybrid"
+prompt_file = "{path}"
+model = "gpt-5.1-codex-mini"
+reasoning_effort = "low"
+keep_recent_turns = 3
+"#,
+            path = prompt_path.display()
+        ))
+        .expect("valid [compaction] section");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(config.compact_prompt.as_deref(), Some("Summarize tersely."));
+        assert_eq!(
+            config.compaction,
+            CompactionConfig {
+                strategy: CompactionStrategy::Hybrid,
+                model: Some("gpt-5.1-codex-mini".to_string()),
+                reasoning_effort: Some(ReasoningEffort::Low),
+                keep_recent_turns: 3,
+                custom_prompt: true,
+            }
+        );
+        assert!(!config.compaction.uses_default_summarizer());
+        assert!(CompactionConfig::default().uses_default_summarizer());
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_tool_output_pruning_section() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[tool_output_pruning]
+after_turns = 4
+max_bytes = 20000
+
+[tool_output_pruning.tools.read_file]
+after_turns = 1
+"#,
+        )
+        .expect("valid [tool_output_pruning] section");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        let pruning = &config.tool_output_pruning;
+        assert!(pruning.is_enabled());
+        assert_eq!(
+            pruning.rule_for("read_file"),
+            ToolOutputPruningRule {
+                after_turns: Some(1),
+                max_bytes: Some(20000),
+            }
+        );
+        assert_eq!(
+            pruning.rule_for("exec_command"),
+            ToolOutputPruningRule {
+                after_turns: Some(4),
+                max_bytes: Some(20000),
+            }
+        );
+        assert!(!ToolOutputPruning::default().is_enabled());
+
+        Ok(())
+    }
+
     fn create_test_fixture() -> std::io::Result<PrecedenceTestFixture> {
         let toml = r#"
 model = "o3"
@@ -3824,6 +4199,7 @@ model_verbosity = "high"
                 model_provider_id: "openai".to_string(),
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: Constrained::allow_any(AskForApproval::Never),
+                session_approval_ttl: None,
                 sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
                 enforce_residency: Constrained::allow_any(None),
                 did_user_set_custom_approval_policy_or_sandbox_mode: true,
@@ -3831,6 +4207,9 @@ model_verbosity = "high"
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
+                hooks: Hooks::default(),
+                post_edit: BTreeMap::new(),
+                lsp: BTreeMap::new(),
                 cwd: fixture.cwd(),
                 cli_auth_credentials_store_mode: Default::default(),
                 mcp_servers: Constrained::allow_any(HashMap::new()),
@@ -3841,6 +4220,11 @@ model_verbosity = "high"
                 project_doc_fallback_filenames: Vec::new(),
                 tool_output_token_limit: None,
                 agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+                agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
+                agent_token_budget: None,
+                agent_wall_clock_budget: None,
+                agent_roles: builtin_agent_roles(),
+                tool_allowlist: None,
                 codex_home: fixture.codex_home(),
                 config_layer_stack: Default::default(),
                 history: History::default(),
@@ -3858,6 +4242,8 @@ model_verbosity = "high"
                 base_instructions: None,
                 developer_instructions: None,
                 compact_prompt: None,
+                compaction: CompactionConfig::default(),
+                tool_output_pruning: ToolOutputPruning::default(),
                 forced_chatgpt_workspace_id: None,
                 forced_login_method: None,
                 include_apply_patch_tool: false,
@@ -3880,6 +4266,9 @@ model_verbosity = "high"
                 analytics_enabled: Some(true),
                 feedback_enabled: true,
                 tui_alternate_screen: AltScreenMode::Auto,
+                tui_editing_mode: EditingMode::Emacs,
+                tui_theme: None,
+                tui_themes: BTreeMap::new(),
                 otel: OtelConfig::default(),
             },
             o3_profile_config
@@ -3909,6 +4298,7 @@ model_verbosity = "high"
             model_provider_id: "openai-custom".to_string(),
             model_provider: fixture.openai_custom_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::UnlessTrusted),
+            session_approval_ttl: None,
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             enforce_residency: Constrained::allow_any(None),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
@@ -3916,6 +4306,9 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
+            post_edit: BTreeMap::new(),
+            lsp: BTreeMap::new(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
@@ -3926,6 +4319,11 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
+            agent_token_budget: None,
+            agent_wall_clock_budget: None,
+            agent_roles: builtin_agent_roles(),
+            tool_allowlist: None,
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
@@ -3943,6 +4341,8 @@ model_verbosity = "high"
             base_instructions: None,
             developer_instructions: None,
             compact_prompt: None,
+            compaction: CompactionConfig::default(),
+            tool_output_pruning: ToolOutputPruning::default(),
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
             include_apply_patch_tool: false,
@@ -3965,6 +4365,9 @@ model_verbosity = "high"
             analytics_enabled: Some(true),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_editing_mode: EditingMode::Emacs,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
             otel: OtelConfig::default(),
         };
 
@@ -4009,6 +4412,7 @@ model_verbosity = "high"
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::OnFailure),
+            session_approval_ttl: None,
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             enforce_residency: Constrained::allow_any(None),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
@@ -4016,6 +4420,9 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
+            post_edit: BTreeMap::new(),
+            lsp: BTreeMap::new(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
@@ -4026,6 +4433,11 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
+            agent_token_budget: None,
+            agent_wall_clock_budget: None,
+            agent_roles: builtin_agent_roles(),
+            tool_allowlist: None,
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
@@ -4043,6 +4455,8 @@ model_verbosity = "high"
             base_instructions: None,
             developer_instructions: None,
             compact_prompt: None,
+            compaction: CompactionConfig::default(),
+            tool_output_pruning: ToolOutputPruning::default(),
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
             include_apply_patch_tool: false,
@@ -4065,6 +4479,9 @@ model_verbosity = "high"
             analytics_enabled: Some(false),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_editing_mode: EditingMode::Emacs,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
             otel: OtelConfig::default(),
         };
 
@@ -4095,6 +4512,7 @@ model_verbosity = "high"
             model_provider_id: "openai".to_string(),
             model_provider: fixture.openai_provider.clone(),
             approval_policy: Constrained::allow_any(AskForApproval::OnFailure),
+            session_approval_ttl: None,
             sandbox_policy: Constrained::allow_any(SandboxPolicy::new_read_only_policy()),
             enforce_residency: Constrained::allow_any(None),
             did_user_set_custom_approval_policy_or_sandbox_mode: true,
@@ -4102,6 +4520,9 @@ model_verbosity = "high"
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            hooks: Hooks::default(),
+            post_edit: BTreeMap::new(),
+            lsp: BTreeMap::new(),
             cwd: fixture.cwd(),
             cli_auth_credentials_store_mode: Default::default(),
             mcp_servers: Constrained::allow_any(HashMap::new()),
@@ -4112,6 +4533,11 @@ model_verbosity = "high"
             project_doc_fallback_filenames: Vec::new(),
             tool_output_token_limit: None,
             agent_max_threads: DEFAULT_AGENT_MAX_THREADS,
+            agent_max_depth: DEFAULT_AGENT_MAX_DEPTH,
+            agent_token_budget: None,
+            agent_wall_clock_budget: None,
+            agent_roles: builtin_agent_roles(),
+            tool_allowlist: None,
             codex_home: fixture.codex_home(),
             config_layer_stack: Default::default(),
             history: History::default(),
@@ -4129,6 +4555,8 @@ model_verbosity = "high"
             base_instructions: None,
             developer_instructions: None,
             compact_prompt: None,
+            compaction: CompactionConfig::default(),
+            tool_output_pruning: ToolOutputPruning::default(),
             forced_chatgpt_workspace_id: None,
             forced_login_method: None,
             include_apply_patch_tool: false,
@@ -4151,6 +4579,9 @@ model_verbosity = "high"
             analytics_enabled: Some(true),
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
+            tui_editing_mode: EditingMode::Emacs,
+            tui_theme: None,
+            tui_themes: BTreeMap::new(),
             otel: OtelConfig::default(),
         };
 
@@ -4681,6 +5112,11 @@ mcp_oauth_callback_port = 5678
             mcp_servers: None,
             rules: None,
             enforce_residency: None,
+            allowed_models: None,
+            allowed_model_providers: None,
+            denied_mcp_tools: None,
+            shell_environment_policy: None,
+            max_additional_writable_roots: None,
         };
 
         let err = ConfigBuilder::default()
@@ -4699,6 +5135,168 @@ mcp_oauth_callback_port = 5678
         Ok(())
     }
 
+    #[tokio::test]
+    async fn requirements_restrict_models() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let requirements = || {
+            CloudRequirementsLoader::new(async {
+                Some(crate::config_loader::ConfigRequirementsToml {
+                    allowed_models: Some(vec!["gpt-5.1-codex".to_string()]),
+                    ..Default::default()
+                })
+            })
+        };
+
+        let config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .cloud_requirements(requirements())
+            .build()
+            .await?;
+        assert_eq!(config.model.as_deref(), Some("gpt-5.1-codex"));
+
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), "model = \"o3\"\n")?;
+        let err = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .cloud_requirements(requirements())
+            .build()
+            .await
+            .expect_err("explicit disallowed model should fail");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        let message = err.to_string();
+        assert!(message.contains("invalid value for `model`: `o3` is not in the allowed set"));
+        assert!(message.contains("set by cloud requirements"));
+
+        for (setting, config_toml) in [
+            ("review_model", "review_model = \"o3\"\n"),
+            ("compaction.model", "[compaction]\nmodel = \"o3\"\n"),
+        ] {
+            std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), config_toml)?;
+            let err = ConfigBuilder::default()
+                .codex_home(codex_home.path().to_path_buf())
+                .fallback_cwd(Some(codex_home.path().to_path_buf()))
+                .cloud_requirements(requirements())
+                .build()
+                .await
+                .expect_err("disallowed secondary model should fail");
+            let message = err.to_string();
+            assert!(
+                message.starts_with(&format!("{setting}: invalid value for `model`: `o3`")),
+                "{message}"
+            );
+            assert!(message.contains("set by cloud requirements"), "{message}");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requirements_reject_disallowed_model_provider() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let err = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .harness_overrides(ConfigOverrides {
+                model_provider: Some("openai".to_string()),
+                ..Default::default()
+            })
+            .cloud_requirements(CloudRequirementsLoader::new(async {
+                Some(crate::config_loader::ConfigRequirementsToml {
+                    allowed_model_providers: Some(vec!["azure".to_string()]),
+                    ..Default::default()
+                })
+            }))
+            .build()
+            .await
+            .expect_err("disallowed provider should fail");
+        let message = err.to_string();
+        assert!(message.contains("invalid value for `model_provider`"));
+        assert!(message.contains("set by cloud requirements"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requirements_deny_mcp_tools_and_force_env_excludes() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        std::fs::write(
+            codex_home.path().join(CONFIG_TOML_FILE),
+            r#"
+[shell_environment_policy]
+set = { AWS_PROFILE = "prod", EDITOR = "vim" }
+
+[mcp_servers.github]
+command = "github-mcp"
+disabled_tools = ["create_issue"]
+"#,
+        )?;
+
+        let config = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .cloud_requirements(CloudRequirementsLoader::new(async {
+                Some(crate::config_loader::ConfigRequirementsToml {
+                    denied_mcp_tools: Some(vec!["delete_repo".to_string()]),
+                    shell_environment_policy: Some(
+                        crate::config_loader::ShellEnvironmentPolicyRequirement {
+                            exclude: vec!["AWS_*".to_string()],
+                        },
+                    ),
+                    ..Default::default()
+                })
+            }))
+            .build()
+            .await?;
+
+        assert_eq!(
+            config.mcp_servers.get()["github"].disabled_tools,
+            Some(vec!["create_issue".to_string(), "delete_repo".to_string()])
+        );
+        // The built-in apps server is added later and gets the same denials.
+        let mut config = config;
+        config.features.enable(Feature::Apps);
+        let servers = crate::mcp::effective_mcp_servers(&config, None);
+        assert_eq!(
+            servers[crate::mcp::CODEX_APPS_MCP_SERVER_NAME].disabled_tools,
+            Some(vec!["delete_repo".to_string()])
+        );
+        assert_eq!(
+            config.shell_environment_policy.exclude,
+            vec![EnvironmentVariablePattern::new_case_insensitive("AWS_*")]
+        );
+        assert_eq!(
+            config.shell_environment_policy.r#set,
+            HashMap::from([("EDITOR".to_string(), "vim".to_string())])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requirements_cap_additional_writable_roots() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let err = ConfigBuilder::default()
+            .codex_home(codex_home.path().to_path_buf())
+            .fallback_cwd(Some(codex_home.path().to_path_buf()))
+            .harness_overrides(ConfigOverrides {
+                additional_writable_roots: vec![PathBuf::from("a"), PathBuf::from("b")],
+                ..Default::default()
+            })
+            .cloud_requirements(CloudRequirementsLoader::new(async {
+                Some(crate::config_loader::ConfigRequirementsToml {
+                    max_additional_writable_roots: Some(1),
+                    ..Default::default()
+                })
+            }))
+            .build()
+            .await
+            .expect_err("too many writable roots should fail");
+        let message = err.to_string();
+        assert!(message.contains("invalid value for `additional_writable_roots`"));
+        assert!(message.contains("at most 1"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn requirements_disallowing_default_approval_falls_back_to_required_default()
     -> std::io::Result<()> {
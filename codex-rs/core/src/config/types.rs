@@ -5,9 +5,12 @@
 
 use crate::config_loader::RequirementSource;
 pub use codex_protocol::config_types::AltScreenMode;
+pub use codex_protocol::config_types::CompactionStrategy;
 pub use codex_protocol::config_types::ModeKind;
 pub use codex_protocol::config_types::Personality;
+use codex_protocol::config_types::SandboxMode;
 pub use codex_protocol::config_types::WebSearchMode;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_utils_absolute_path::AbsolutePathBuf;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
@@ -315,6 +318,186 @@ pub enum HistoryPersistence {
     None,
 }
 
+// ===== Language server configuration =====
+
+/// A language server from `[lsp.<language>]`. The table key is also the
+/// language id sent to the server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct LspServerConfig {
+    /// Program that speaks LSP over stdio, e.g. `rust-analyzer`.
+    pub command: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// File extensions the server handles, without the dot. Defaults to the
+    /// usual extensions of well-known languages such as `rust` or `python`.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+
+    /// How long to wait for diagnostics after an edit. Defaults to 3000.
+    pub diagnostics_timeout_ms: Option<u64>,
+}
+
+// ===== Compaction configuration =====
+
+/// Number of recent user turns the `hybrid` strategy keeps verbatim by default.
+pub const DEFAULT_COMPACTION_KEEP_RECENT_TURNS: usize = 2;
+
+/// `[compaction]` settings from config.toml.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct CompactionToml {
+    /// How history is compacted. Defaults to `summary`.
+    pub strategy: Option<CompactionStrategy>,
+
+    /// File with the prompt used to summarize history. Takes precedence over
+    /// `experimental_compact_prompt_file`.
+    pub prompt_file: Option<AbsolutePathBuf>,
+
+    /// Model that writes summaries, e.g. a cheaper one than the session model.
+    pub model: Option<String>,
+
+    /// Reasoning effort for `model`. Defaults to that model's own default
+    /// rather than the session's `model_reasoning_effort`.
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Number of most recent user turns the `hybrid` strategy keeps verbatim.
+    pub keep_recent_turns: Option<usize>,
+}
+
+/// Resolved `[compaction]` settings. The prompt itself lives in
+/// [`crate::config::Config::compact_prompt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactionConfig {
+    pub strategy: CompactionStrategy,
+    pub model: Option<String>,
+    pub reasoning_effort: Option<ReasoningEffort>,
+    pub keep_recent_turns: usize,
+    /// Whether `prompt_file` supplied the summarizer prompt.
+    pub custom_prompt: bool,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            strategy: CompactionStrategy::default(),
+            model: None,
+            reasoning_effort: None,
+            keep_recent_turns: DEFAULT_COMPACTION_KEEP_RECENT_TURNS,
+            custom_prompt: false,
+        }
+    }
+}
+
+impl CompactionConfig {
+    /// Whether compaction runs the built-in summarizer with the session model,
+    /// which is the only setup the remote compaction endpoint can serve.
+    pub fn uses_default_summarizer(&self) -> bool {
+        self.strategy == CompactionStrategy::Summary && self.model.is_none() && !self.custom_prompt
+    }
+}
+
+// ===== Tool output pruning =====
+
+/// `[tool_output_pruning]` settings: replace old or large tool outputs in the
+/// history with short stubs before each turn. Unset fields never prune.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolOutputPruning {
+    /// Prune outputs once this many user turns have passed since the call.
+    pub after_turns: Option<u32>,
+    /// Prune outputs from earlier turns that are larger than this many bytes.
+    pub max_bytes: Option<usize>,
+    /// Per-tool overrides keyed by tool name, e.g. `[tool_output_pruning.tools.read_file]`.
+    #[serde(default)]
+    pub tools: HashMap<String, ToolOutputPruningRule>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ToolOutputPruningRule {
+    pub after_turns: Option<u32>,
+    pub max_bytes: Option<usize>,
+}
+
+impl ToolOutputPruning {
+    pub fn is_enabled(&self) -> bool {
+        self.after_turns.is_some()
+            || self.max_bytes.is_some()
+            || self
+                .tools
+                .values()
+                .any(|rule| rule.after_turns.is_some() || rule.max_bytes.is_some())
+    }
+
+    /// The rule for `tool`: its own fields where set, the defaults otherwise.
+    pub fn rule_for(&self, tool: &str) -> ToolOutputPruningRule {
+        let own = self.tools.get(tool).copied().unwrap_or_default();
+        ToolOutputPruningRule {
+            after_turns: own.after_turns.or(self.after_turns),
+            max_bytes: own.max_bytes.or(self.max_bytes),
+        }
+    }
+}
+
+// ===== Agent roles =====
+
+/// A sub-agent role resolved from the built-ins, `[agents.roles.<name>]`
+/// tables and `agents/<name>.md` files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentRoleConfig {
+    /// Tells the parent agent when to pick this role.
+    pub description: String,
+    /// Replaces the base instructions of agents with this role.
+    pub instructions: Option<String>,
+    pub model: Option<String>,
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    pub sandbox_mode: Option<SandboxMode>,
+    /// When set, only these tools are offered to agents with this role.
+    pub tools: Option<Vec<String>>,
+}
+
+// ===== Hooks configuration =====
+
+/// Commands run at fixed points in a session, declared under `[hooks]`.
+///
+/// Each hook receives a JSON description of the event on stdin. See
+/// `docs/config.md` for the payload and the responses a hook may print.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct Hooks {
+    /// Run once when a session starts or resumes.
+    #[serde(default)]
+    pub session_start: Vec<HookCommand>,
+    /// Run before a user prompt is sent to the model; may block it or add context.
+    #[serde(default)]
+    pub user_prompt_submit: Vec<HookCommand>,
+    /// Run before a tool call; may block it, rewrite its arguments or add context.
+    #[serde(default)]
+    pub pre_tool_use: Vec<HookCommand>,
+    /// Run after a tool call; may add context to its output.
+    #[serde(default)]
+    pub post_tool_use: Vec<HookCommand>,
+    /// Run when the agent finishes a turn.
+    #[serde(default)]
+    pub turn_complete: Vec<HookCommand>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct HookCommand {
+    /// Program and arguments to run, e.g. `["./scripts/lint.sh", "--fix"]`.
+    pub command: Vec<String>,
+    /// Tool names this hook applies to. Only used by `pre_tool_use` and
+    /// `post_tool_use`; empty means every tool.
+    #[serde(default)]
+    pub tools: Vec<String>,
+    /// Kill the hook after this many milliseconds. Defaults to 60 seconds.
+    pub timeout_ms: Option<u64>,
+}
+
 // ===== Analytics configuration =====
 
 /// Analytics settings loaded from config.toml. Fields are optional so we can apply defaults.
@@ -447,6 +630,51 @@ impl fmt::Display for NotificationMethod {
     }
 }
 
+/// Key bindings used by the TUI composer.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EditingMode {
+    /// Emacs-style line editing (Ctrl-A, Ctrl-E, Ctrl-K, ...).
+    #[default]
+    Emacs,
+    /// Modal vi editing with normal, insert and visual modes.
+    Vi,
+}
+
+/// A user-defined TUI color theme declared under `[tui.themes.<name>]`.
+///
+/// Colors accept ANSI names (`"green"`, `"light-magenta"`). Unset colors come from `base`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ThemeToml {
+    /// Built-in theme to start from: `dark` (default), `light` or `high-contrast`.
+    pub base: Option<String>,
+    /// Added lines in diffs.
+    pub diff_add: Option<String>,
+    /// Removed lines in diffs.
+    pub diff_delete: Option<String>,
+    /// Inline code in rendered markdown.
+    pub markdown_code: Option<String>,
+    /// Links in rendered markdown.
+    pub markdown_link: Option<String>,
+    /// Block quotes in rendered markdown.
+    pub markdown_blockquote: Option<String>,
+    /// Ordered list markers in rendered markdown.
+    pub markdown_list_marker: Option<String>,
+    /// Accent text in the `/status` card.
+    pub status_accent: Option<String>,
+    /// Mode labels in the footer (collaboration mode, vi mode).
+    pub footer_mode: Option<String>,
+    /// Highlighted row in selection popups and approval prompts.
+    pub selection: Option<String>,
+    /// Question at the top of approval prompts.
+    pub approval_title: Option<String>,
+    /// Check mark recorded in the transcript when a request is approved.
+    pub decision_approved: Option<String>,
+    /// Cross recorded in the transcript when a request is denied or canceled.
+    pub decision_denied: Option<String>,
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default, JsonSchema)]
 #[schemars(deny_unknown_fields)]
@@ -486,6 +714,19 @@ pub struct Tui {
     /// scrollback in terminal multiplexers like Zellij that follow the xterm spec.
     #[serde(default)]
     pub alternate_screen: AltScreenMode,
+
+    /// Key bindings for the composer: `emacs` (default) or `vi`.
+    #[serde(default)]
+    pub editing_mode: EditingMode,
+
+    /// Color theme to use: `dark` (default), `light`, `high-contrast`, or the name of a theme
+    /// defined under `[tui.themes]`.
+    #[serde(default)]
+    pub theme: Option<String>,
+
+    /// User-defined color themes, keyed by name.
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeToml>,
 }
 
 const fn default_true() -> bool {
@@ -10,6 +10,8 @@ use super::requirements_exec_policy::RequirementsExecPolicy;
 use super::requirements_exec_policy::RequirementsExecPolicyToml;
 use crate::config::Constrained;
 use crate::config::ConstraintError;
+use crate::config::types::EnvironmentVariablePattern;
+use crate::config::types::ShellEnvironmentPolicy;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum RequirementSource {
@@ -53,6 +55,12 @@ pub struct ConfigRequirements {
     pub mcp_servers: Option<Sourced<BTreeMap<String, McpServerRequirement>>>,
     pub(crate) exec_policy: Option<Sourced<RequirementsExecPolicy>>,
     pub enforce_residency: Constrained<Option<ResidencyRequirement>>,
+    pub model: Constrained<Option<String>>,
+    pub model_provider: Constrained<String>,
+    /// MCP tool names that are disabled on every server.
+    pub denied_mcp_tools: Option<Sourced<Vec<String>>>,
+    pub shell_environment_policy: Constrained<ShellEnvironmentPolicy>,
+    pub additional_writable_roots: Constrained<Vec<AbsolutePathBuf>>,
 }
 
 impl Default for ConfigRequirements {
@@ -63,10 +71,18 @@ impl Default for ConfigRequirements {
             mcp_servers: None,
             exec_policy: None,
             enforce_residency: Constrained::allow_any(None),
+            model: Constrained::allow_any(None),
+            model_provider: Constrained::allow_any(DEFAULT_MODEL_PROVIDER_ID.to_string()),
+            denied_mcp_tools: None,
+            shell_environment_policy: Constrained::allow_any_from_default(),
+            additional_writable_roots: Constrained::allow_any_from_default(),
         }
     }
 }
 
+/// Provider used when neither the config nor the requirements pick one.
+const DEFAULT_MODEL_PROVIDER_ID: &str = "openai";
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(untagged)]
 pub enum McpServerIdentity {
@@ -79,6 +95,15 @@ pub struct McpServerRequirement {
     pub identity: McpServerIdentity,
 }
 
+/// Settings forced onto `shell_environment_policy`.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellEnvironmentPolicyRequirement {
+    /// Patterns appended to `shell_environment_policy.exclude`. Matching
+    /// entries are also dropped from `shell_environment_policy.set`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
 /// Base config deserialized from /etc/codex/requirements.toml or MDM.
 #[derive(Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct ConfigRequirementsToml {
@@ -87,6 +112,12 @@ pub struct ConfigRequirementsToml {
     pub mcp_servers: Option<BTreeMap<String, McpServerRequirement>>,
     pub rules: Option<RequirementsExecPolicyToml>,
     pub enforce_residency: Option<ResidencyRequirement>,
+    pub allowed_models: Option<Vec<String>>,
+    pub allowed_model_providers: Option<Vec<String>>,
+    pub denied_mcp_tools: Option<Vec<String>>,
+    pub shell_environment_policy: Option<ShellEnvironmentPolicyRequirement>,
+    /// Maximum number of extra writable roots (`--add-dir`) a session may use.
+    pub max_additional_writable_roots: Option<usize>,
 }
 
 /// Value paired with the requirement source it came from, for better error
@@ -118,6 +149,11 @@ pub struct ConfigRequirementsWithSources {
     pub mcp_servers: Option<Sourced<BTreeMap<String, McpServerRequirement>>>,
     pub rules: Option<Sourced<RequirementsExecPolicyToml>>,
     pub enforce_residency: Option<Sourced<ResidencyRequirement>>,
+    pub allowed_models: Option<Sourced<Vec<String>>>,
+    pub allowed_model_providers: Option<Sourced<Vec<String>>>,
+    pub denied_mcp_tools: Option<Sourced<Vec<String>>>,
+    pub shell_environment_policy: Option<Sourced<ShellEnvironmentPolicyRequirement>>,
+    pub max_additional_writable_roots: Option<Sourced<usize>>,
 }
 
 impl ConfigRequirementsWithSources {
@@ -151,6 +187,11 @@ impl ConfigRequirementsWithSources {
                 mcp_servers,
                 rules,
                 enforce_residency,
+                allowed_models,
+                allowed_model_providers,
+                denied_mcp_tools,
+                shell_environment_policy,
+                max_additional_writable_roots,
             }
         );
     }
@@ -162,6 +203,11 @@ impl ConfigRequirementsWithSources {
             mcp_servers,
             rules,
             enforce_residency,
+            allowed_models,
+            allowed_model_providers,
+            denied_mcp_tools,
+            shell_environment_policy,
+            max_additional_writable_roots,
         } = self;
         ConfigRequirementsToml {
             allowed_approval_policies: allowed_approval_policies.map(|sourced| sourced.value),
@@ -169,6 +215,12 @@ impl ConfigRequirementsWithSources {
             mcp_servers: mcp_servers.map(|sourced| sourced.value),
             rules: rules.map(|sourced| sourced.value),
             enforce_residency: enforce_residency.map(|sourced| sourced.value),
+            allowed_models: allowed_models.map(|sourced| sourced.value),
+            allowed_model_providers: allowed_model_providers.map(|sourced| sourced.value),
+            denied_mcp_tools: denied_mcp_tools.map(|sourced| sourced.value),
+            shell_environment_policy: shell_environment_policy.map(|sourced| sourced.value),
+            max_additional_writable_roots: max_additional_writable_roots
+                .map(|sourced| sourced.value),
         }
     }
 }
@@ -213,6 +265,11 @@ impl ConfigRequirementsToml {
             && self.mcp_servers.is_none()
             && self.rules.is_none()
             && self.enforce_residency.is_none()
+            && self.allowed_models.is_none()
+            && self.allowed_model_providers.is_none()
+            && self.denied_mcp_tools.is_none()
+            && self.shell_environment_policy.is_none()
+            && self.max_additional_writable_roots.is_none()
     }
 }
 
@@ -226,6 +283,11 @@ impl TryFrom<ConfigRequirementsWithSources> for ConfigRequirements {
             mcp_servers,
             rules,
             enforce_residency,
+            allowed_models,
+            allowed_model_providers,
+            denied_mcp_tools,
+            shell_environment_policy,
+            max_additional_writable_roots,
         } = toml;
 
         let approval_policy: Constrained<AskForApproval> = match allowed_approval_policies {
@@ -334,12 +396,117 @@ impl TryFrom<ConfigRequirementsWithSources> for ConfigRequirements {
             }
             None => Constrained::allow_any(None),
         };
+
+        let model: Constrained<Option<String>> = match allowed_models {
+            Some(Sourced {
+                value: models,
+                source: requirement_source,
+            }) => {
+                let Some(initial_value) = models.first().cloned() else {
+                    return Err(ConstraintError::empty_field("allowed_models"));
+                };
+                Constrained::new(Some(initial_value), move |candidate| {
+                    if candidate
+                        .as_ref()
+                        .is_some_and(|candidate| models.contains(candidate))
+                    {
+                        Ok(())
+                    } else {
+                        Err(ConstraintError::InvalidValue {
+                            field_name: "model",
+                            candidate: candidate.clone().unwrap_or_default(),
+                            allowed: format!("{models:?}"),
+                            requirement_source: requirement_source.clone(),
+                        })
+                    }
+                })?
+            }
+            None => Constrained::allow_any(None),
+        };
+
+        let model_provider: Constrained<String> = match allowed_model_providers {
+            Some(Sourced {
+                value: providers,
+                source: requirement_source,
+            }) => {
+                let Some(initial_value) = providers.first().cloned() else {
+                    return Err(ConstraintError::empty_field("allowed_model_providers"));
+                };
+                Constrained::new(initial_value, move |candidate| {
+                    if providers.contains(candidate) {
+                        Ok(())
+                    } else {
+                        Err(ConstraintError::InvalidValue {
+                            field_name: "model_provider",
+                            candidate: candidate.clone(),
+                            allowed: format!("{providers:?}"),
+                            requirement_source: requirement_source.clone(),
+                        })
+                    }
+                })?
+            }
+            None => Constrained::allow_any(DEFAULT_MODEL_PROVIDER_ID.to_string()),
+        };
+
+        let shell_environment_policy: Constrained<ShellEnvironmentPolicy> =
+            match shell_environment_policy {
+                Some(Sourced { value, .. }) if !value.exclude.is_empty() => {
+                    let forced_excludes: Vec<EnvironmentVariablePattern> = value
+                        .exclude
+                        .iter()
+                        .map(|pattern| EnvironmentVariablePattern::new_case_insensitive(pattern))
+                        .collect();
+                    Constrained::normalized(
+                        ShellEnvironmentPolicy::default(),
+                        move |mut policy| {
+                            for pattern in &forced_excludes {
+                                if !policy.exclude.contains(pattern) {
+                                    policy.exclude.push(pattern.clone());
+                                }
+                            }
+                            // `set` is applied after `exclude`, so it must not
+                            // reintroduce a forced exclude.
+                            policy.r#set.retain(|name, _| {
+                                !forced_excludes.iter().any(|pattern| pattern.matches(name))
+                            });
+                            policy
+                        },
+                    )?
+                }
+                _ => Constrained::allow_any_from_default(),
+            };
+
+        let additional_writable_roots: Constrained<Vec<AbsolutePathBuf>> =
+            match max_additional_writable_roots {
+                Some(Sourced {
+                    value: max_roots,
+                    source: requirement_source,
+                }) => Constrained::new(Vec::new(), move |candidate| {
+                    if candidate.len() <= max_roots {
+                        Ok(())
+                    } else {
+                        Err(ConstraintError::InvalidValue {
+                            field_name: "additional_writable_roots",
+                            candidate: format!("{} directories", candidate.len()),
+                            allowed: format!("at most {max_roots}"),
+                            requirement_source: requirement_source.clone(),
+                        })
+                    }
+                })?,
+                None => Constrained::allow_any_from_default(),
+            };
+
         Ok(ConfigRequirements {
             approval_policy,
             sandbox_policy,
             mcp_servers,
             exec_policy,
             enforce_residency,
+            model,
+            model_provider,
+            denied_mcp_tools,
+            shell_environment_policy,
+            additional_writable_roots,
         })
     }
 }
@@ -367,6 +534,11 @@ mod tests {
             mcp_servers,
             rules,
             enforce_residency,
+            allowed_models,
+            allowed_model_providers,
+            denied_mcp_tools,
+            shell_environment_policy,
+            max_additional_writable_roots,
         } = toml;
         ConfigRequirementsWithSources {
             allowed_approval_policies: allowed_approval_policies
@@ -377,6 +549,16 @@ mod tests {
             rules: rules.map(|value| Sourced::new(value, RequirementSource::Unknown)),
             enforce_residency: enforce_residency
                 .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            allowed_models: allowed_models
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            allowed_model_providers: allowed_model_providers
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            denied_mcp_tools: denied_mcp_tools
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            shell_environment_policy: shell_environment_policy
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
+            max_additional_writable_roots: max_additional_writable_roots
+                .map(|value| Sourced::new(value, RequirementSource::Unknown)),
         }
     }
 
@@ -391,7 +573,12 @@ mod tests {
             SandboxModeRequirement::DangerFullAccess,
         ];
         let enforce_residency = ResidencyRequirement::Us;
-        let enforce_source = source.clone();
+        let allowed_models = vec!["gpt-5.1".to_string()];
+        let allowed_model_providers = vec!["openai".to_string()];
+        let denied_mcp_tools = vec!["delete_repo".to_string()];
+        let shell_environment_policy = ShellEnvironmentPolicyRequirement {
+            exclude: vec!["AWS_*".to_string()],
+        };
 
         // Intentionally constructed without `..Default::default()` so adding a new field to
         // `ConfigRequirementsToml` forces this test to be updated.
@@ -401,6 +588,11 @@ mod tests {
             mcp_servers: None,
             rules: None,
             enforce_residency: Some(enforce_residency),
+            allowed_models: Some(allowed_models.clone()),
+            allowed_model_providers: Some(allowed_model_providers.clone()),
+            denied_mcp_tools: Some(denied_mcp_tools.clone()),
+            shell_environment_policy: Some(shell_environment_policy.clone()),
+            max_additional_writable_roots: Some(2),
         };
 
         target.merge_unset_fields(source.clone(), other);
@@ -412,10 +604,21 @@ mod tests {
                     allowed_approval_policies,
                     source.clone()
                 )),
-                allowed_sandbox_modes: Some(Sourced::new(allowed_sandbox_modes, source)),
+                allowed_sandbox_modes: Some(Sourced::new(allowed_sandbox_modes, source.clone())),
                 mcp_servers: None,
                 rules: None,
-                enforce_residency: Some(Sourced::new(enforce_residency, enforce_source)),
+                enforce_residency: Some(Sourced::new(enforce_residency, source.clone())),
+                allowed_models: Some(Sourced::new(allowed_models, source.clone())),
+                allowed_model_providers: Some(Sourced::new(
+                    allowed_model_providers,
+                    source.clone()
+                )),
+                denied_mcp_tools: Some(Sourced::new(denied_mcp_tools, source.clone())),
+                shell_environment_policy: Some(Sourced::new(
+                    shell_environment_policy,
+                    source.clone()
+                )),
+                max_additional_writable_roots: Some(Sourced::new(2, source)),
             }
         );
     }
@@ -446,6 +649,11 @@ mod tests {
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
+                allowed_models: None,
+                allowed_model_providers: None,
+                denied_mcp_tools: None,
+                shell_environment_policy: None,
+                max_additional_writable_roots: None,
             }
         );
         Ok(())
@@ -484,6 +692,11 @@ mod tests {
                 mcp_servers: None,
                 rules: None,
                 enforce_residency: None,
+                allowed_models: None,
+                allowed_model_providers: None,
+                denied_mcp_tools: None,
+                shell_environment_policy: None,
+                max_additional_writable_roots: None,
             }
         );
         Ok(())
@@ -672,6 +885,113 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn deserialize_allowed_models_and_providers() -> Result<()> {
+        let toml_str = r#"
+            allowed_models = ["gpt-5.1-codex", "gpt-5.1"]
+            allowed_model_providers = ["azure"]
+        "#;
+        let requirements: ConfigRequirements =
+            with_unknown_source(from_str(toml_str)?).try_into()?;
+
+        assert_eq!(
+            requirements.model.get(),
+            &Some("gpt-5.1-codex".to_string()),
+            "the first allowed model is the default"
+        );
+        assert!(
+            requirements
+                .model
+                .can_set(&Some("gpt-5.1".to_string()))
+                .is_ok()
+        );
+        assert_eq!(
+            requirements.model.can_set(&Some("o3".to_string())),
+            Err(ConstraintError::InvalidValue {
+                field_name: "model",
+                candidate: "o3".into(),
+                allowed: "[\"gpt-5.1-codex\", \"gpt-5.1\"]".into(),
+                requirement_source: RequirementSource::Unknown,
+            })
+        );
+        assert_eq!(requirements.model_provider.get(), "azure");
+        assert_eq!(
+            requirements.model_provider.can_set(&"openai".to_string()),
+            Err(ConstraintError::InvalidValue {
+                field_name: "model_provider",
+                candidate: "openai".into(),
+                allowed: "[\"azure\"]".into(),
+                requirement_source: RequirementSource::Unknown,
+            })
+        );
+
+        let empty: ConfigRequirementsToml = from_str("allowed_models = []")?;
+        assert_eq!(
+            ConfigRequirements::try_from(with_unknown_source(empty)),
+            Err(ConstraintError::empty_field("allowed_models"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shell_environment_policy_requirement_forces_excludes() -> Result<()> {
+        let toml_str = r#"
+            [shell_environment_policy]
+            exclude = ["AWS_*"]
+        "#;
+        let mut requirements: ConfigRequirements =
+            with_unknown_source(from_str(toml_str)?).try_into()?;
+
+        let policy = ShellEnvironmentPolicy {
+            r#set: [
+                ("AWS_PROFILE".to_string(), "prod".to_string()),
+                ("EDITOR".to_string(), "vim".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        requirements.shell_environment_policy.set(policy)?;
+
+        let policy = requirements.shell_environment_policy.get();
+        assert_eq!(
+            policy.exclude,
+            vec![EnvironmentVariablePattern::new_case_insensitive("AWS_*")]
+        );
+        assert_eq!(
+            policy.r#set.keys().collect::<Vec<_>>(),
+            vec![&"EDITOR".to_string()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn max_additional_writable_roots_limits_roots() -> Result<()> {
+        let requirements: ConfigRequirements =
+            with_unknown_source(from_str("max_additional_writable_roots = 1")?).try_into()?;
+
+        let root = if cfg!(windows) { "C:\\repo" } else { "/repo" };
+        let root = AbsolutePathBuf::from_absolute_path(root)?;
+        assert!(
+            requirements
+                .additional_writable_roots
+                .can_set(&vec![root.clone()])
+                .is_ok()
+        );
+        assert_eq!(
+            requirements
+                .additional_writable_roots
+                .can_set(&vec![root.clone(), root]),
+            Err(ConstraintError::InvalidValue {
+                field_name: "additional_writable_roots",
+                candidate: "2 directories".into(),
+                allowed: "at most 1".into(),
+                requirement_source: RequirementSource::Unknown,
+            })
+        );
+        Ok(())
+    }
+
     #[test]
     fn deserialize_mcp_server_requirements() -> Result<()> {
         let toml_str = r#"
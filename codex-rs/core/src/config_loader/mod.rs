@@ -39,6 +39,7 @@ pub use config_requirements::McpServerRequirement;
 pub use config_requirements::RequirementSource;
 pub use config_requirements::ResidencyRequirement;
 pub use config_requirements::SandboxModeRequirement;
+pub use config_requirements::ShellEnvironmentPolicyRequirement;
 pub use config_requirements::Sourced;
 pub use diagnostics::ConfigError;
 pub use diagnostics::ConfigLoadError;
@@ -508,6 +508,11 @@ allowed_approval_policies = ["on-request"]
             mcp_servers: None,
             rules: None,
             enforce_residency: None,
+            allowed_models: None,
+            allowed_model_providers: None,
+            denied_mcp_tools: None,
+            shell_environment_policy: None,
+            max_additional_writable_roots: None,
         },
     );
     load_requirements_toml(&mut config_requirements_toml, &requirements_file).await?;
@@ -543,6 +548,11 @@ async fn load_config_layers_includes_cloud_requirements() -> anyhow::Result<()>
         mcp_servers: None,
         rules: None,
         enforce_residency: None,
+        allowed_models: None,
+        allowed_model_providers: None,
+        denied_mcp_tools: None,
+        shell_environment_policy: None,
+        max_additional_writable_roots: None,
     };
     let expected = requirements.clone();
     let cloud_requirements = CloudRequirementsLoader::new(async move { Some(requirements) });
@@ -1,10 +1,11 @@
 use crate::codex::TurnContext;
+use crate::config::types::ToolOutputPruning;
 use crate::context_manager::normalize;
 use crate::instructions::SkillInstructions;
 use crate::instructions::UserInstructions;
 use crate::session_prefix::is_session_prefix;
+use crate::tokenizer::Tokenizer;
 use crate::truncate::TruncationPolicy;
-use crate::truncate::approx_token_count;
 use crate::truncate::approx_tokens_from_byte_count;
 use crate::truncate::truncate_function_output_items_with_policy;
 use crate::truncate::truncate_text;
@@ -13,8 +14,10 @@ use codex_protocol::models::ContentItem;
 use codex_protocol::models::FunctionCallOutputContentItem;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::PrunedToolOutput;
 use codex_protocol::protocol::TokenUsage;
 use codex_protocol::protocol::TokenUsageInfo;
+use std::collections::HashMap;
 use std::ops::Deref;
 
 /// Transcript of thread history
@@ -51,8 +54,12 @@ impl ContextManager {
     }
 
     /// `items` is ordered from oldest to newest.
-    pub(crate) fn record_items<I>(&mut self, items: I, policy: TruncationPolicy)
-    where
+    pub(crate) fn record_items<I>(
+        &mut self,
+        items: I,
+        policy: TruncationPolicy,
+        tokenizer: &Tokenizer,
+    ) where
         I: IntoIterator,
         I::Item: std::ops::Deref<Target = ResponseItem>,
     {
@@ -63,7 +70,7 @@ impl ContextManager {
                 continue;
             }
 
-            let processed = self.process_item(item_ref, policy);
+            let processed = self.process_item(item_ref, policy, tokenizer);
             self.items.push(processed);
         }
     }
@@ -82,18 +89,20 @@ impl ContextManager {
         &self.items
     }
 
-    // Estimate token usage using byte-based heuristics from the truncation helpers.
-    // This is a coarse lower bound, not a tokenizer-accurate count.
+    // Estimate token usage with the turn's tokenizer. Encrypted reasoning is
+    // always estimated from its size, so this stays a lower bound.
     pub(crate) fn estimate_token_count(&self, turn_context: &TurnContext) -> Option<i64> {
+        let tokenizer = &turn_context.tokenizer;
         let model_info = turn_context.client.get_model_info();
         let personality = turn_context
             .personality
             .or(turn_context.client.config().personality);
         let base_instructions = model_info.get_model_instructions(personality);
-        let base_tokens = i64::try_from(approx_token_count(&base_instructions)).unwrap_or(i64::MAX);
+        let base_tokens =
+            i64::try_from(tokenizer.count_tokens(&base_instructions)).unwrap_or(i64::MAX);
 
         let items_tokens = self.items.iter().fold(0i64, |acc, item| {
-            acc.saturating_add(estimate_item_token_count(item))
+            acc.saturating_add(estimate_item_token_count(item, tokenizer))
         });
 
         Some(base_tokens.saturating_add(items_tokens))
@@ -187,6 +196,76 @@ impl ContextManager {
         self.replace(snapshot[..cut_idx].to_vec());
     }
 
+    /// Replace tool outputs from earlier turns with short stubs according to
+    /// `pruning`. Outputs of the current turn and outputs that are already
+    /// stubs are left alone. Returns what was replaced so it can be persisted
+    /// and replayed with [`Self::apply_pruned_tool_outputs`].
+    pub(crate) fn prune_tool_outputs(
+        &mut self,
+        pruning: &ToolOutputPruning,
+    ) -> Vec<PrunedToolOutput> {
+        if !pruning.is_enabled() {
+            return Vec::new();
+        }
+
+        let tool_names = tool_names_by_call_id(&self.items);
+        let mut turns_after = 0u32;
+        let mut pruned = Vec::new();
+        for item in self.items.iter().rev() {
+            if is_user_turn_boundary(item) {
+                turns_after = turns_after.saturating_add(1);
+                continue;
+            }
+            if turns_after == 0 {
+                continue;
+            }
+            let Some((call_id, bytes)) = stubbable_tool_output(item) else {
+                continue;
+            };
+            let tool = tool_names.get(call_id).copied().unwrap_or("tool");
+            let rule = pruning.rule_for(tool);
+            let too_old = rule.after_turns.is_some_and(|turns| turns_after >= turns);
+            let too_large = rule.max_bytes.is_some_and(|max| bytes > max);
+            if !too_old && !too_large {
+                continue;
+            }
+            let stub = tool_output_stub(tool, bytes);
+            if stub.len() >= bytes {
+                continue;
+            }
+            pruned.push(PrunedToolOutput {
+                call_id: call_id.to_string(),
+                stub,
+            });
+        }
+        pruned.reverse();
+        self.apply_pruned_tool_outputs(&pruned);
+        pruned
+    }
+
+    /// Replace the outputs of the given tool calls with their recorded stubs.
+    pub(crate) fn apply_pruned_tool_outputs(&mut self, pruned: &[PrunedToolOutput]) {
+        if pruned.is_empty() {
+            return;
+        }
+        let stubs: HashMap<&str, &str> = pruned
+            .iter()
+            .map(|output| (output.call_id.as_str(), output.stub.as_str()))
+            .collect();
+        for item in &mut self.items {
+            let stub = match item {
+                ResponseItem::FunctionCallOutput { call_id, .. }
+                | ResponseItem::CustomToolCallOutput { call_id, .. } => {
+                    stubs.get(call_id.as_str()).copied()
+                }
+                _ => None,
+            };
+            if let Some(stub) = stub {
+                replace_tool_output(item, stub);
+            }
+        }
+    }
+
     pub(crate) fn update_token_info(
         &mut self,
         usage: &TokenUsage,
@@ -222,30 +301,35 @@ impl ContextManager {
                 )
             })
             .fold(0i64, |acc, item| {
-                acc.saturating_add(estimate_item_token_count(item))
+                acc.saturating_add(estimate_item_token_count(item, &Tokenizer::default()))
             })
     }
 
-    fn get_trailing_codex_generated_items_tokens(&self) -> i64 {
+    fn get_trailing_codex_generated_items_tokens(&self, tokenizer: &Tokenizer) -> i64 {
         let mut total = 0i64;
         for item in self.items.iter().rev() {
             if !is_codex_generated_item(item) {
                 break;
             }
-            total = total.saturating_add(estimate_item_token_count(item));
+            total = total.saturating_add(estimate_item_token_count(item, tokenizer));
         }
         total
     }
 
     /// When true, the server already accounted for past reasoning tokens and
     /// the client should not re-estimate them.
-    pub(crate) fn get_total_token_usage(&self, server_reasoning_included: bool) -> i64 {
+    pub(crate) fn get_total_token_usage(
+        &self,
+        server_reasoning_included: bool,
+        tokenizer: &Tokenizer,
+    ) -> i64 {
         let last_tokens = self
             .token_info
             .as_ref()
             .map(|info| info.last_token_usage.total_tokens)
             .unwrap_or(0);
-        let trailing_codex_generated_tokens = self.get_trailing_codex_generated_items_tokens();
+        let trailing_codex_generated_tokens =
+            self.get_trailing_codex_generated_items_tokens(tokenizer);
         if server_reasoning_included {
             last_tokens.saturating_add(trailing_codex_generated_tokens)
         } else {
@@ -266,16 +350,25 @@ impl ContextManager {
         normalize::remove_orphan_outputs(&mut self.items);
     }
 
-    fn process_item(&self, item: &ResponseItem, policy: TruncationPolicy) -> ResponseItem {
+    fn process_item(
+        &self,
+        item: &ResponseItem,
+        policy: TruncationPolicy,
+        tokenizer: &Tokenizer,
+    ) -> ResponseItem {
         let policy_with_serialization_budget = policy * 1.2;
         match item {
             ResponseItem::FunctionCallOutput { call_id, output } => {
-                let truncated =
-                    truncate_text(output.content.as_str(), policy_with_serialization_budget);
+                let truncated = truncate_text(
+                    output.content.as_str(),
+                    policy_with_serialization_budget,
+                    tokenizer,
+                );
                 let truncated_items = output.content_items.as_ref().map(|items| {
                     truncate_function_output_items_with_policy(
                         items,
                         policy_with_serialization_budget,
+                        tokenizer,
                     )
                 });
                 ResponseItem::FunctionCallOutput {
@@ -288,7 +381,7 @@ impl ContextManager {
                 }
             }
             ResponseItem::CustomToolCallOutput { call_id, output } => {
-                let truncated = truncate_text(output, policy_with_serialization_budget);
+                let truncated = truncate_text(output, policy_with_serialization_budget, tokenizer);
                 ResponseItem::CustomToolCallOutput {
                     call_id: call_id.clone(),
                     output: truncated,
@@ -333,7 +426,7 @@ fn estimate_reasoning_length(encoded_len: usize) -> usize {
         .saturating_sub(650)
 }
 
-fn estimate_item_token_count(item: &ResponseItem) -> i64 {
+pub(crate) fn estimate_item_token_count(item: &ResponseItem, tokenizer: &Tokenizer) -> i64 {
     match item {
         ResponseItem::GhostSnapshot { .. } => 0,
         ResponseItem::Reasoning {
@@ -348,11 +441,86 @@ fn estimate_item_token_count(item: &ResponseItem) -> i64 {
         }
         item => {
             let serialized = serde_json::to_string(item).unwrap_or_default();
-            i64::try_from(approx_token_count(&serialized)).unwrap_or(i64::MAX)
+            i64::try_from(tokenizer.count_tokens(&serialized)).unwrap_or(i64::MAX)
         }
     }
 }
 
+const STUBBED_OUTPUT_MARKER: &str = " output elided, ";
+
+/// Text that replaces a tool output, both when pruning before a turn and when
+/// compaction drops outputs.
+pub(crate) fn tool_output_stub(tool: &str, bytes: usize) -> String {
+    let size = if bytes >= 1024 {
+        format!("{} KB", bytes.div_ceil(1024))
+    } else {
+        format!("{bytes} bytes")
+    };
+    format!("[{tool}{STUBBED_OUTPUT_MARKER}{size}, re-run to see]")
+}
+
+fn is_tool_output_stub(text: &str) -> bool {
+    text.starts_with('[')
+        && text.ends_with(", re-run to see]")
+        && text.contains(STUBBED_OUTPUT_MARKER)
+}
+
+/// The call id and size in bytes of a tool output that is not a stub yet.
+pub(crate) fn stubbable_tool_output(item: &ResponseItem) -> Option<(&str, usize)> {
+    match item {
+        ResponseItem::FunctionCallOutput { call_id, output } => {
+            if is_tool_output_stub(&output.content) {
+                return None;
+            }
+            let item_bytes: usize = output
+                .content_items
+                .iter()
+                .flatten()
+                .map(|content_item| match content_item {
+                    FunctionCallOutputContentItem::InputText { text } => text.len(),
+                    FunctionCallOutputContentItem::InputImage { image_url } => image_url.len(),
+                })
+                .sum();
+            Some((call_id.as_str(), output.content.len().max(item_bytes)))
+        }
+        ResponseItem::CustomToolCallOutput { call_id, output } => {
+            (!is_tool_output_stub(output)).then_some((call_id.as_str(), output.len()))
+        }
+        _ => None,
+    }
+}
+
+/// Replaces the output of a tool output item with `stub`.
+pub(crate) fn replace_tool_output(item: &mut ResponseItem, stub: &str) {
+    match item {
+        ResponseItem::FunctionCallOutput { output, .. } => {
+            output.content = stub.to_string();
+            output.content_items = None;
+        }
+        ResponseItem::CustomToolCallOutput { output, .. } => {
+            *output = stub.to_string();
+        }
+        _ => {}
+    }
+}
+
+pub(crate) fn tool_names_by_call_id(items: &[ResponseItem]) -> HashMap<&str, &str> {
+    items
+        .iter()
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCall { call_id, name, .. }
+            | ResponseItem::CustomToolCall { call_id, name, .. } => {
+                Some((call_id.as_str(), name.as_str()))
+            }
+            ResponseItem::LocalShellCall {
+                call_id: Some(call_id),
+                ..
+            } => Some((call_id.as_str(), "local_shell")),
+            _ => None,
+        })
+        .collect()
+}
+
 pub(crate) fn is_codex_generated_item(item: &ResponseItem) -> bool {
     matches!(
         item,
@@ -1,4 +1,5 @@
 use super::*;
+use crate::config::types::ToolOutputPruningRule;
 use crate::truncate;
 use crate::truncate::TruncationPolicy;
 use codex_git::GhostCommit;
@@ -32,7 +33,11 @@ fn create_history_with_items(items: Vec<ResponseItem>) -> ContextManager {
     let mut h = ContextManager::new();
     // Use a generous but fixed token budget; tests only rely on truncation
     // behavior, not on a specific model's token limit.
-    h.record_items(items.iter(), TruncationPolicy::Tokens(10_000));
+    h.record_items(
+        items.iter(),
+        TruncationPolicy::Tokens(10_000),
+        &Tokenizer::default(),
+    );
     h
 }
 
@@ -102,7 +107,11 @@ fn reasoning_with_encrypted_content(len: usize) -> ResponseItem {
 }
 
 fn truncate_exec_output(content: &str) -> String {
-    truncate::truncate_text(content, TruncationPolicy::Tokens(EXEC_FORMAT_MAX_TOKENS))
+    truncate::truncate_text(
+        content,
+        TruncationPolicy::Tokens(EXEC_FORMAT_MAX_TOKENS),
+        &Tokenizer::default(),
+    )
 }
 
 #[test]
@@ -120,12 +129,16 @@ fn filters_non_api_messages() {
         phase: None,
     };
     let reasoning = reasoning_msg("thinking...");
-    h.record_items([&system, &reasoning, &ResponseItem::Other], policy);
+    h.record_items(
+        [&system, &reasoning, &ResponseItem::Other],
+        policy,
+        &Tokenizer::default(),
+    );
 
     // User and assistant should be retained.
     let u = user_msg("hi");
     let a = assistant_msg("hello");
-    h.record_items([&u, &a], policy);
+    h.record_items([&u, &a], policy, &Tokenizer::default());
 
     let items = h.raw_items();
     assert_eq!(
@@ -196,11 +209,13 @@ fn trailing_codex_generated_tokens_stop_at_first_non_generated_item() {
         trailing_function_output.clone(),
         trailing_custom_output.clone(),
     ]);
-    let expected_tokens = estimate_item_token_count(&trailing_function_output)
-        .saturating_add(estimate_item_token_count(&trailing_custom_output));
+    let expected_tokens =
+        estimate_item_token_count(&trailing_function_output, &Tokenizer::default()).saturating_add(
+            estimate_item_token_count(&trailing_custom_output, &Tokenizer::default()),
+        );
 
     assert_eq!(
-        history.get_trailing_codex_generated_items_tokens(),
+        history.get_trailing_codex_generated_items_tokens(&Tokenizer::default()),
         expected_tokens
     );
 }
@@ -214,7 +229,10 @@ fn trailing_codex_generated_tokens_exclude_function_call_tail() {
         call_id: "call-tail".to_string(),
     }]);
 
-    assert_eq!(history.get_trailing_codex_generated_items_tokens(), 0);
+    assert_eq!(
+        history.get_trailing_codex_generated_items_tokens(&Tokenizer::default()),
+        0
+    );
 }
 
 #[test]
@@ -237,8 +255,8 @@ fn total_token_usage_includes_only_trailing_codex_generated_items() {
     );
 
     assert_eq!(
-        history.get_total_token_usage(true),
-        100 + estimate_item_token_count(&trailing_output)
+        history.get_total_token_usage(true, &Tokenizer::default()),
+        100 + estimate_item_token_count(&trailing_output, &Tokenizer::default())
     );
 }
 
@@ -523,6 +541,130 @@ fn drop_last_n_user_turns_ignores_session_prefix_user_messages() {
     assert_eq!(history.for_prompt(), expected_prefix_only);
 }
 
+fn function_call(call_id: &str, name: &str) -> ResponseItem {
+    ResponseItem::FunctionCall {
+        id: None,
+        name: name.to_string(),
+        arguments: "{}".to_string(),
+        call_id: call_id.to_string(),
+    }
+}
+
+fn pruning(after_turns: Option<u32>, max_bytes: Option<usize>) -> ToolOutputPruning {
+    ToolOutputPruning {
+        after_turns,
+        max_bytes,
+        tools: HashMap::new(),
+    }
+}
+
+#[test]
+fn prune_tool_outputs_stubs_outputs_older_than_after_turns() {
+    let old_output = "a".repeat(43_000);
+    let mut history = create_history_with_items(vec![
+        user_msg("u1"),
+        function_call("call-1", "exec_command"),
+        function_call_output("call-1", &old_output),
+        user_msg("u2"),
+        function_call("call-2", "exec_command"),
+        function_call_output("call-2", &old_output),
+        user_msg("u3"),
+    ]);
+
+    let pruned = history.prune_tool_outputs(&pruning(Some(2), None));
+
+    assert_eq!(
+        pruned,
+        vec![PrunedToolOutput {
+            call_id: "call-1".to_string(),
+            stub: "[exec_command output elided, 42 KB, re-run to see]".to_string(),
+        }]
+    );
+    assert_eq!(
+        history.raw_items()[2],
+        function_call_output(
+            "call-1",
+            "[exec_command output elided, 42 KB, re-run to see]"
+        )
+    );
+    assert_eq!(
+        history.raw_items()[5],
+        function_call_output("call-2", &old_output)
+    );
+
+    // Stubs are never pruned again.
+    assert_eq!(
+        history.prune_tool_outputs(&pruning(Some(2), None)),
+        Vec::new()
+    );
+}
+
+#[test]
+fn prune_tool_outputs_uses_per_tool_size_limits_and_spares_current_turn() {
+    let large = "b".repeat(5_000);
+    let mut config = pruning(None, Some(100_000));
+    config.tools.insert(
+        "read_file".to_string(),
+        ToolOutputPruningRule {
+            after_turns: None,
+            max_bytes: Some(1_000),
+        },
+    );
+    let mut history = create_history_with_items(vec![
+        user_msg("u1"),
+        function_call("call-1", "read_file"),
+        function_call_output("call-1", &large),
+        function_call("call-2", "exec_command"),
+        function_call_output("call-2", &large),
+        ResponseItem::CustomToolCall {
+            id: None,
+            status: None,
+            call_id: "call-3".to_string(),
+            name: "read_file".to_string(),
+            input: String::new(),
+        },
+        custom_tool_call_output("call-3", "short"),
+        user_msg("u2"),
+        function_call("call-4", "read_file"),
+        function_call_output("call-4", &large),
+    ]);
+
+    let pruned = history.prune_tool_outputs(&config);
+
+    assert_eq!(
+        pruned,
+        vec![PrunedToolOutput {
+            call_id: "call-1".to_string(),
+            stub: "[read_file output elided, 5 KB, re-run to see]".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn apply_pruned_tool_outputs_replays_recorded_stubs() {
+    let output = "c".repeat(2_000);
+    let items = vec![
+        user_msg("u1"),
+        function_call("call-1", "exec_command"),
+        function_call_output("call-1", &output),
+        user_msg("u2"),
+    ];
+    let mut live = create_history_with_items(items.clone());
+    let pruned = live.prune_tool_outputs(&pruning(Some(1), None));
+
+    let mut replayed = create_history_with_items(items);
+    replayed.apply_pruned_tool_outputs(&pruned);
+
+    assert_eq!(live.raw_items(), replayed.raw_items());
+    assert_eq!(
+        replayed.raw_items()[2],
+        function_call_output(
+            "call-1",
+            "[exec_command output elided, 2 KB, re-run to see]"
+        )
+    );
+}
+
 #[test]
 fn remove_first_item_handles_custom_tool_pair() {
     let items = vec![
@@ -589,7 +731,7 @@ fn record_items_truncates_function_call_output_content() {
         },
     };
 
-    history.record_items([&item], policy);
+    history.record_items([&item], policy, &Tokenizer::default());
 
     assert_eq!(history.items.len(), 1);
     match &history.items[0] {
@@ -621,7 +763,7 @@ fn record_items_truncates_custom_tool_call_output_content() {
         output: long_output.clone(),
     };
 
-    history.record_items([&item], policy);
+    history.record_items([&item], policy, &Tokenizer::default());
 
     assert_eq!(history.items.len(), 1);
     match &history.items[0] {
@@ -654,7 +796,7 @@ fn record_items_respects_custom_token_limit() {
         },
     };
 
-    history.record_items([&item], policy);
+    history.record_items([&item], policy, &Tokenizer::default());
 
     let stored = match &history.items[0] {
         ResponseItem::FunctionCallOutput { output, .. } => output,
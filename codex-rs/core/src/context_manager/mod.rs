@@ -2,5 +2,10 @@ mod history;
 mod normalize;
 
 pub(crate) use history::ContextManager;
+pub(crate) use history::estimate_item_token_count;
 pub(crate) use history::is_codex_generated_item;
 pub(crate) use history::is_user_turn_boundary;
+pub(crate) use history::replace_tool_output;
+pub(crate) use history::stubbable_tool_output;
+pub(crate) use history::tool_names_by_call_id;
+pub(crate) use history::tool_output_stub;
@@ -1,6 +1,7 @@
 use crate::exec::ExecToolCallOutput;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
+use crate::tokenizer::Tokenizer;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::truncate_text;
 use chrono::DateTime;
@@ -81,6 +82,9 @@ pub enum CodexErr {
     #[error("agent thread limit reached (max {max_threads})")]
     AgentLimitReached { max_threads: usize },
 
+    #[error("agent tree budget exhausted ({0})")]
+    AgentBudgetExhausted(String),
+
     #[error("session configured event was not the first event in the stream")]
     SessionConfiguredNotFirstEvent,
 
@@ -206,6 +210,7 @@ impl CodexErr {
             | CodexErr::ContextWindowExceeded
             | CodexErr::ThreadNotFound(_)
             | CodexErr::AgentLimitReached { .. }
+            | CodexErr::AgentBudgetExhausted(_)
             | CodexErr::Spawn
             | CodexErr::SessionConfiguredNotFirstEvent
             | CodexErr::UsageLimitReached(_)
@@ -608,7 +613,8 @@ impl CodexErr {
             | CodexErr::InternalAgentDied => CodexErrorInfo::InternalServerError,
             CodexErr::UnsupportedOperation(_)
             | CodexErr::ThreadNotFound(_)
-            | CodexErr::AgentLimitReached { .. } => CodexErrorInfo::BadRequest,
+            | CodexErr::AgentLimitReached { .. }
+            | CodexErr::AgentBudgetExhausted(_) => CodexErrorInfo::BadRequest,
             CodexErr::Sandbox(_) => CodexErrorInfo::SandboxError,
             _ => CodexErrorInfo::Other,
         }
@@ -671,6 +677,7 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
     truncate_text(
         &message,
         TruncationPolicy::Bytes(ERROR_MESSAGE_UI_MAX_BYTES),
+        &Tokenizer::default(),
     )
 }
 
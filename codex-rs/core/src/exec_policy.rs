@@ -17,11 +17,13 @@ use codex_execpolicy::Policy;
 use codex_execpolicy::PolicyParser;
 use codex_execpolicy::RuleMatch;
 use codex_execpolicy::blocking_append_allow_prefix_rule;
+use codex_execpolicy::blocking_remove_allow_prefix_rule;
 use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::SandboxPolicy;
 use thiserror::Error;
 use tokio::fs;
+use tokio::sync::Mutex;
 use tokio::task::spawn_blocking;
 
 use crate::bash::parse_shell_lc_plain_commands;
@@ -85,6 +87,10 @@ pub enum ExecPolicyUpdateError {
 
 pub(crate) struct ExecPolicyManager {
     policy: ArcSwap<Policy>,
+    /// The policy as loaded, before any prefixes approved during the session.
+    base: Arc<Policy>,
+    /// Prefixes approved during the session, in approval order.
+    session_prefixes: Mutex<Vec<Vec<String>>>,
 }
 
 pub(crate) struct ExecApprovalRequest<'a> {
@@ -99,7 +105,9 @@ pub(crate) struct ExecApprovalRequest<'a> {
 impl ExecPolicyManager {
     pub(crate) fn new(policy: Arc<Policy>) -> Self {
         Self {
-            policy: ArcSwap::from(policy),
+            policy: ArcSwap::from(Arc::clone(&policy)),
+            base: policy,
+            session_prefixes: Mutex::new(Vec::new()),
         }
     }
 
@@ -188,14 +196,16 @@ impl ExecPolicyManager {
         }
     }
 
+    /// Returns whether this call added the rule. Rules the file already had are
+    /// applied but not tracked, so revoking session approvals never removes them.
     pub(crate) async fn append_amendment_and_update(
         &self,
         codex_home: &Path,
         amendment: &ExecPolicyAmendment,
-    ) -> Result<(), ExecPolicyUpdateError> {
+    ) -> Result<bool, ExecPolicyUpdateError> {
         let policy_path = default_policy_path(codex_home);
         let prefix = amendment.command.clone();
-        spawn_blocking({
+        let added = spawn_blocking({
             let policy_path = policy_path.clone();
             let prefix = prefix.clone();
             move || blocking_append_allow_prefix_rule(&policy_path, &prefix)
@@ -207,9 +217,48 @@ impl ExecPolicyManager {
             source,
         })?;
 
+        let mut session_prefixes = self.session_prefixes.lock().await;
         let mut updated_policy = self.current().as_ref().clone();
         updated_policy.add_prefix_rule(&prefix, Decision::Allow)?;
         self.policy.store(Arc::new(updated_policy));
+        if added && !session_prefixes.contains(&prefix) {
+            session_prefixes.push(prefix);
+        }
+        Ok(added)
+    }
+
+    /// Undoes [`Self::append_amendment_and_update`] for a prefix added
+    /// during this session, both on disk and in memory. Prefixes this session
+    /// did not add are left alone.
+    pub(crate) async fn remove_amendment_and_update(
+        &self,
+        codex_home: &Path,
+        amendment: &ExecPolicyAmendment,
+    ) -> Result<(), ExecPolicyUpdateError> {
+        let policy_path = default_policy_path(codex_home);
+        let prefix = amendment.command.clone();
+        let mut session_prefixes = self.session_prefixes.lock().await;
+        if !session_prefixes.contains(&prefix) {
+            return Ok(());
+        }
+        spawn_blocking({
+            let policy_path = policy_path.clone();
+            let prefix = prefix.clone();
+            move || blocking_remove_allow_prefix_rule(&policy_path, &prefix)
+        })
+        .await
+        .map_err(|source| ExecPolicyUpdateError::JoinBlockingTask { source })?
+        .map_err(|source| ExecPolicyUpdateError::AppendRule {
+            path: policy_path,
+            source,
+        })?;
+
+        session_prefixes.retain(|existing| existing != &prefix);
+        let mut updated_policy = self.base.as_ref().clone();
+        for prefix in session_prefixes.iter() {
+            updated_policy.add_prefix_rule(prefix, Decision::Allow)?;
+        }
+        self.policy.store(Arc::new(updated_policy));
         Ok(())
     }
 }
@@ -1052,6 +1101,67 @@ prefix_rule(
         );
     }
 
+    #[tokio::test]
+    async fn remove_execpolicy_amendment_restores_policy_and_file() {
+        let codex_home = tempdir().expect("create temp dir");
+        let echo = ExecPolicyAmendment::from(vec!["echo".to_string()]);
+        let ls = ExecPolicyAmendment::from(vec!["ls".to_string()]);
+        let manager = ExecPolicyManager::default();
+        for amendment in [&echo, &ls] {
+            manager
+                .append_amendment_and_update(codex_home.path(), amendment)
+                .await
+                .expect("update policy");
+        }
+
+        manager
+            .remove_amendment_and_update(codex_home.path(), &echo)
+            .await
+            .expect("revert policy");
+
+        let policy = manager.current();
+        let decision = |command: &str| {
+            policy
+                .check(&[command.to_string()], &|_| Decision::Prompt)
+                .decision
+        };
+        assert_eq!(decision("echo"), Decision::Prompt);
+        assert_eq!(decision("ls"), Decision::Allow);
+        let contents = fs::read_to_string(default_policy_path(codex_home.path()))
+            .expect("policy file should exist");
+        assert_eq!(
+            contents,
+            r#"prefix_rule(pattern=["ls"], decision="allow")
+"#
+        );
+    }
+
+    #[tokio::test]
+    async fn removing_a_prefix_the_rules_file_already_had_keeps_it() {
+        let codex_home = tempdir().expect("create temp dir");
+        let policy_path = default_policy_path(codex_home.path());
+        fs::create_dir_all(policy_path.parent().expect("rules dir")).expect("create rules dir");
+        let existing = "prefix_rule(pattern=[\"ls\"], decision=\"allow\")\n";
+        fs::write(&policy_path, existing).expect("write rules");
+        let ls = ExecPolicyAmendment::from(vec!["ls".to_string()]);
+        let manager = ExecPolicyManager::default();
+
+        let added = manager
+            .append_amendment_and_update(codex_home.path(), &ls)
+            .await
+            .expect("update policy");
+        assert!(!added);
+        manager
+            .remove_amendment_and_update(codex_home.path(), &ls)
+            .await
+            .expect("revert policy");
+
+        assert_eq!(
+            fs::read_to_string(&policy_path).expect("read rules"),
+            existing
+        );
+    }
+
     #[tokio::test]
     async fn append_execpolicy_amendment_rejects_empty_prefix() {
         let codex_home = tempdir().expect("create temp dir");
@@ -127,6 +127,16 @@ pub enum Feature {
     Personality,
     /// Use the Responses API WebSocket transport for OpenAI by default.
     ResponsesWebsockets,
+    /// Expose a local `fetch_url` tool that downloads pages as markdown.
+    FetchUrl,
+    /// Keep a persistent, watched index of each search root for `@` mentions
+    /// and fuzzy file search.
+    FileSearchIndex,
+    /// Expose tree-sitter backed `find_symbol`, `list_symbols` and
+    /// `find_references` tools.
+    SymbolTools,
+    /// Download missing BPE rank tables for exact token counts.
+    TokenizerDownload,
 }
 
 impl Feature {
@@ -577,6 +587,30 @@ pub const FEATURES: &[FeatureSpec] = &[
         stage: Stage::UnderDevelopment,
         default_enabled: false,
     },
+    FeatureSpec {
+        id: Feature::FetchUrl,
+        key: "fetch_url",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::FileSearchIndex,
+        key: "file_search_index",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::SymbolTools,
+        key: "symbol_tools",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
+    FeatureSpec {
+        id: Feature::TokenizerDownload,
+        key: "tokenizer_download",
+        stage: Stage::UnderDevelopment,
+        default_enabled: false,
+    },
 ];
 
 /// Push a warning event if any under-development features are enabled.
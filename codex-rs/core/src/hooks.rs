@@ -0,0 +1,561 @@
+//! User-configured lifecycle hooks.
+//!
+//! Hooks are commands declared under `[hooks]` in `config.toml` that run at
+//! fixed points in a session: `session_start`, `user_prompt_submit`,
+//! `pre_tool_use`, `post_tool_use` and `turn_complete`. Each hook receives a
+//! JSON description of the event on stdin and may answer on stdout to block
+//! the action, rewrite tool arguments, or add context for the model. Every run
+//! is reported to clients as an [`EventMsg::HookCompleted`].
+//!
+//! Stdout is interpreted as follows:
+//! - empty: no effect;
+//! - a JSON object: see [`HookResponse`];
+//! - anything else: added verbatim as context for the model.
+//!
+//! Exit code 2 blocks the action with stderr as the reason. Any other non-zero
+//! exit, a timeout or a failure to spawn is reported but never blocks.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::warn;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::types::HookCommand;
+use crate::config::types::Hooks;
+use crate::protocol::EventMsg;
+use crate::protocol::HookCompletedEvent;
+use crate::protocol::HookEventName;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use codex_protocol::models::ShellToolCallParams;
+
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Exit code a hook uses to block the action without printing JSON.
+const BLOCK_EXIT_CODE: i32 = 2;
+
+/// The event a hook is invoked for, serialized into the payload on stdin.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "hook", rename_all = "snake_case")]
+pub(crate) enum HookEvent {
+    SessionStart {
+        /// `startup` for a new session, `resume` when continuing a recorded one.
+        source: &'static str,
+    },
+    UserPromptSubmit {
+        turn_id: String,
+        prompt: String,
+    },
+    PreToolUse {
+        turn_id: String,
+        call_id: String,
+        tool_name: String,
+        arguments: String,
+    },
+    PostToolUse {
+        turn_id: String,
+        call_id: String,
+        tool_name: String,
+        arguments: String,
+        output: String,
+        success: bool,
+    },
+    TurnComplete {
+        turn_id: String,
+        last_assistant_message: Option<String>,
+    },
+}
+
+impl HookEvent {
+    fn name(&self) -> HookEventName {
+        match self {
+            HookEvent::SessionStart { .. } => HookEventName::SessionStart,
+            HookEvent::UserPromptSubmit { .. } => HookEventName::UserPromptSubmit,
+            HookEvent::PreToolUse { .. } => HookEventName::PreToolUse,
+            HookEvent::PostToolUse { .. } => HookEventName::PostToolUse,
+            HookEvent::TurnComplete { .. } => HookEventName::TurnComplete,
+        }
+    }
+
+    fn tool_name(&self) -> Option<&str> {
+        match self {
+            HookEvent::PreToolUse { tool_name, .. } | HookEvent::PostToolUse { tool_name, .. } => {
+                Some(tool_name)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HookInput<'a> {
+    thread_id: String,
+    cwd: &'a Path,
+    #[serde(flatten)]
+    event: &'a HookEvent,
+}
+
+/// JSON a hook may print on stdout.
+#[derive(Debug, Default, Deserialize, PartialEq)]
+pub(crate) struct HookResponse {
+    /// `"block"` stops the prompt or tool call; `"allow"` (the default) lets it proceed.
+    #[serde(default)]
+    pub(crate) decision: HookDecision,
+    /// Shown to the user, and to the model for tool calls, when blocking.
+    pub(crate) reason: Option<String>,
+    /// `pre_tool_use` only: replacement arguments for the tool call. Strings
+    /// are used verbatim; other values are serialized as JSON.
+    pub(crate) arguments: Option<serde_json::Value>,
+    /// Extra context for the model.
+    pub(crate) additional_context: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HookDecision {
+    #[default]
+    Allow,
+    Block,
+}
+
+/// Combined effect of every hook that ran for one event.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct HookOutcome {
+    /// Set when a hook blocked the action; holds the reason.
+    pub(crate) blocked: Option<String>,
+    /// Replacement tool arguments from `pre_tool_use` hooks.
+    pub(crate) arguments: Option<String>,
+    /// Context for the model, in hook order.
+    pub(crate) additional_context: Vec<String>,
+}
+
+impl HookOutcome {
+    /// Context from all hooks joined into one block, if any hook produced some.
+    pub(crate) fn context_text(&self) -> Option<String> {
+        (!self.additional_context.is_empty()).then(|| self.additional_context.join("\n\n"))
+    }
+}
+
+impl Hooks {
+    fn commands_for(&self, event: &HookEvent) -> Vec<&HookCommand> {
+        let commands = match event {
+            HookEvent::SessionStart { .. } => &self.session_start,
+            HookEvent::UserPromptSubmit { .. } => &self.user_prompt_submit,
+            HookEvent::PreToolUse { .. } => &self.pre_tool_use,
+            HookEvent::PostToolUse { .. } => &self.post_tool_use,
+            HookEvent::TurnComplete { .. } => &self.turn_complete,
+        };
+        let tool_name = event.tool_name();
+        commands
+            .iter()
+            .filter(|hook| !hook.command.is_empty() && hook.applies_to(tool_name))
+            .collect()
+    }
+
+    /// Whether any tool hook is configured for `tool_name`. Lets callers skip
+    /// building payloads on the hot path when no hooks are set.
+    pub(crate) fn has_tool_hooks(&self, tool_name: &str) -> bool {
+        self.pre_tool_use
+            .iter()
+            .chain(&self.post_tool_use)
+            .any(|hook| hook.applies_to(Some(tool_name)))
+    }
+}
+
+impl HookCommand {
+    fn applies_to(&self, tool_name: Option<&str>) -> bool {
+        match tool_name {
+            Some(tool_name) => self.tools.is_empty() || self.tools.iter().any(|t| t == tool_name),
+            None => true,
+        }
+    }
+}
+
+/// Run every hook configured for `event`, in order, reporting each run to
+/// clients. Stops at the first hook that blocks.
+pub(crate) async fn run_hooks(
+    sess: &Session,
+    turn_context: &TurnContext,
+    mut event: HookEvent,
+) -> HookOutcome {
+    let config = turn_context.client.config();
+    let mut outcome = HookOutcome::default();
+    for hook in config.hooks.commands_for(&event) {
+        let input = HookInput {
+            thread_id: sess.conversation_id.to_string(),
+            cwd: &turn_context.cwd,
+            event: &event,
+        };
+        let payload = match serde_json::to_string(&input) {
+            Ok(payload) => payload,
+            Err(err) => {
+                warn!("failed to serialize hook payload: {err}");
+                return outcome;
+            }
+        };
+
+        let run = run_hook_command(hook, &turn_context.cwd, &payload).await;
+        let effect = run.effect(event.name());
+        sess.send_event(
+            turn_context,
+            EventMsg::HookCompleted(HookCompletedEvent {
+                hook: event.name(),
+                command: hook.command.clone(),
+                tool_name: event.tool_name().map(str::to_string),
+                exit_code: run.exit_code,
+                stdout: run.stdout,
+                stderr: run.stderr,
+                duration: run.duration,
+                timed_out: run.timed_out,
+                blocked: effect.blocked.is_some(),
+                reason: effect.blocked.clone(),
+            }),
+        )
+        .await;
+
+        if let Some(context) = effect.additional_context {
+            outcome.additional_context.push(context);
+        }
+        if let Some(arguments) = effect.arguments
+            && let HookEvent::PreToolUse {
+                arguments: current, ..
+            } = &mut event
+        {
+            current.clone_from(&arguments);
+            outcome.arguments = Some(arguments);
+        }
+        if effect.blocked.is_some() {
+            outcome.blocked = effect.blocked;
+            break;
+        }
+    }
+    outcome
+}
+
+/// Tool arguments as shown to hooks: the raw JSON or freeform input the model sent.
+pub(crate) fn tool_arguments(payload: &ToolPayload) -> String {
+    match payload {
+        ToolPayload::Function { arguments } => arguments.clone(),
+        ToolPayload::Custom { input } => input.clone(),
+        ToolPayload::Mcp { raw_arguments, .. } => raw_arguments.clone(),
+        ToolPayload::LocalShell { params } => serde_json::json!({
+            "command": params.command,
+            "workdir": params.workdir,
+            "timeout_ms": params.timeout_ms,
+        })
+        .to_string(),
+    }
+}
+
+/// Replace the arguments of `payload` with those returned by a `pre_tool_use` hook.
+pub(crate) fn replace_tool_arguments(payload: &mut ToolPayload, arguments: String) {
+    match payload {
+        ToolPayload::Function { arguments: current } => *current = arguments,
+        ToolPayload::Custom { input } => *input = arguments,
+        ToolPayload::Mcp { raw_arguments, .. } => *raw_arguments = arguments,
+        ToolPayload::LocalShell { params } => {
+            match serde_json::from_str::<ShellToolCallParams>(&arguments) {
+                Ok(rewritten) => *params = rewritten,
+                Err(err) => warn!("ignoring hook arguments for local_shell: {err}"),
+            }
+        }
+    }
+}
+
+/// Tool output as shown to `post_tool_use` hooks.
+pub(crate) fn tool_output_text(output: &ToolOutput) -> String {
+    match output {
+        ToolOutput::Function { content, .. } => content.clone(),
+        ToolOutput::Mcp { result: Ok(result) } => serde_json::to_string(result).unwrap_or_default(),
+        ToolOutput::Mcp { result: Err(err) } => err.clone(),
+    }
+}
+
+/// Append hook context to the output the model sees for a tool call.
+pub(crate) fn append_tool_context(output: &mut ToolOutput, context: &str) {
+    match output {
+        ToolOutput::Function { content, .. } => {
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(context);
+        }
+        ToolOutput::Mcp { result: Ok(result) } => result
+            .content
+            .push(serde_json::json!({ "type": "text", "text": context })),
+        ToolOutput::Mcp { result: Err(err) } => {
+            err.push_str("\n\n");
+            err.push_str(context);
+        }
+    }
+}
+
+#[derive(Debug)]
+struct HookRun {
+    exit_code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    duration: Duration,
+    timed_out: bool,
+}
+
+/// What a single hook run asks for.
+#[derive(Debug, Default, PartialEq)]
+struct HookEffect {
+    blocked: Option<String>,
+    arguments: Option<String>,
+    additional_context: Option<String>,
+}
+
+impl HookRun {
+    fn effect(&self, hook: HookEventName) -> HookEffect {
+        if self.timed_out {
+            return HookEffect::default();
+        }
+        if self.exit_code == Some(BLOCK_EXIT_CODE) {
+            let reason = match self.stderr.trim() {
+                "" => "no reason given".to_string(),
+                reason => reason.to_string(),
+            };
+            return HookEffect {
+                blocked: Some(reason),
+                ..Default::default()
+            };
+        }
+        if self.exit_code != Some(0) {
+            return HookEffect::default();
+        }
+
+        let stdout = self.stdout.trim();
+        if stdout.is_empty() {
+            return HookEffect::default();
+        }
+        let Ok(response) = serde_json::from_str::<HookResponse>(stdout) else {
+            return HookEffect {
+                additional_context: Some(stdout.to_string()),
+                ..Default::default()
+            };
+        };
+
+        let arguments = match (hook, response.arguments) {
+            (HookEventName::PreToolUse, Some(serde_json::Value::String(arguments))) => {
+                Some(arguments)
+            }
+            (HookEventName::PreToolUse, Some(arguments)) => Some(arguments.to_string()),
+            _ => None,
+        };
+        HookEffect {
+            blocked: (response.decision == HookDecision::Block).then(|| {
+                response
+                    .reason
+                    .unwrap_or_else(|| "no reason given".to_string())
+            }),
+            arguments,
+            additional_context: response
+                .additional_context
+                .filter(|context| !context.trim().is_empty()),
+        }
+    }
+}
+
+async fn run_hook_command(hook: &HookCommand, cwd: &Path, payload: &str) -> HookRun {
+    let start = Instant::now();
+    let timeout = hook
+        .timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_HOOK_TIMEOUT);
+
+    let failed = |stderr: String| HookRun {
+        exit_code: None,
+        stdout: String::new(),
+        stderr,
+        duration: start.elapsed(),
+        timed_out: false,
+    };
+
+    let mut command = Command::new(&hook.command[0]);
+    command
+        .args(&hook.command[1..])
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            warn!("failed to spawn hook '{}': {err}", hook.command[0]);
+            return failed(format!("failed to spawn hook: {err}"));
+        }
+    };
+
+    // Feed stdin alongside waiting so a hook that never reads it cannot block
+    // the write past the timeout. Dropping stdin afterwards sends EOF.
+    let stdin = child.stdin.take();
+    let write_stdin = async move {
+        if let Some(mut stdin) = stdin {
+            // Hooks that ignore stdin may exit before reading it; that is not an error.
+            let _ = stdin.write_all(payload.as_bytes()).await;
+        }
+    };
+    let run = async {
+        let ((), output) = tokio::join!(write_stdin, child.wait_with_output());
+        output
+    };
+
+    match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(output)) => HookRun {
+            exit_code: output.status.code(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            duration: start.elapsed(),
+            timed_out: false,
+        },
+        Ok(Err(err)) => failed(format!("failed to wait for hook: {err}")),
+        Err(_) => HookRun {
+            exit_code: None,
+            stdout: String::new(),
+            stderr: format!("hook timed out after {}ms", timeout.as_millis()),
+            duration: start.elapsed(),
+            timed_out: true,
+        },
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn sh(script: &str) -> HookCommand {
+        HookCommand {
+            command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+            tools: Vec::new(),
+            timeout_ms: None,
+        }
+    }
+
+    async fn effect_of(hook: &HookCommand, event: HookEventName) -> HookEffect {
+        let cwd = std::env::temp_dir();
+        run_hook_command(hook, &cwd, r#"{"hook":"pre_tool_use"}"#)
+            .await
+            .effect(event)
+    }
+
+    #[tokio::test]
+    async fn plain_stdout_becomes_context() {
+        let effect = effect_of(
+            &sh("cat >/dev/null; echo 'lint: ok'"),
+            HookEventName::PostToolUse,
+        )
+        .await;
+        assert_eq!(
+            effect,
+            HookEffect {
+                additional_context: Some("lint: ok".to_string()),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn json_response_can_block_and_rewrite_arguments() {
+        let hook = sh(
+            r#"echo '{"decision":"block","reason":"no force pushes","arguments":{"command":["ls"]}}'"#,
+        );
+        let effect = effect_of(&hook, HookEventName::PreToolUse).await;
+        assert_eq!(
+            effect,
+            HookEffect {
+                blocked: Some("no force pushes".to_string()),
+                arguments: Some(r#"{"command":["ls"]}"#.to_string()),
+                additional_context: None,
+            }
+        );
+
+        // Arguments are only honored for `pre_tool_use`.
+        let effect = effect_of(&hook, HookEventName::PostToolUse).await;
+        assert_eq!(effect.arguments, None);
+    }
+
+    #[tokio::test]
+    async fn exit_code_two_blocks_with_stderr() {
+        let effect = effect_of(
+            &sh("echo 'prompt mentions secrets' >&2; exit 2"),
+            HookEventName::UserPromptSubmit,
+        )
+        .await;
+        assert_eq!(effect.blocked, Some("prompt mentions secrets".to_string()));
+    }
+
+    #[tokio::test]
+    async fn failures_and_timeouts_do_not_block() {
+        let effect = effect_of(&sh("echo broken; exit 1"), HookEventName::PreToolUse).await;
+        assert_eq!(effect, HookEffect::default());
+
+        let mut slow = sh("sleep 5");
+        slow.timeout_ms = Some(50);
+        let run = run_hook_command(&slow, &std::env::temp_dir(), "{}").await;
+        assert!(run.timed_out);
+        assert_eq!(run.effect(HookEventName::PreToolUse), HookEffect::default());
+    }
+
+    #[tokio::test]
+    async fn timeout_applies_when_hook_ignores_large_stdin() {
+        let mut hook = sh("sleep 5");
+        hook.timeout_ms = Some(50);
+        // Larger than any pipe buffer, so the write cannot finish on its own.
+        let payload = "x".repeat(1 << 20);
+
+        let run = run_hook_command(&hook, &std::env::temp_dir(), &payload).await;
+
+        assert!(run.timed_out);
+    }
+
+    #[test]
+    fn payload_includes_event_fields() {
+        let event = HookEvent::PreToolUse {
+            turn_id: "turn-1".to_string(),
+            call_id: "call-1".to_string(),
+            tool_name: "apply_patch".to_string(),
+            arguments: "*** Begin Patch".to_string(),
+        };
+        let input = HookInput {
+            thread_id: "thread-1".to_string(),
+            cwd: Path::new("/repo"),
+            event: &event,
+        };
+        assert_eq!(
+            serde_json::to_value(&input).expect("serialize"),
+            serde_json::json!({
+                "thread_id": "thread-1",
+                "cwd": "/repo",
+                "hook": "pre_tool_use",
+                "turn_id": "turn-1",
+                "call_id": "call-1",
+                "tool_name": "apply_patch",
+                "arguments": "*** Begin Patch",
+            })
+        );
+    }
+
+    #[test]
+    fn tool_filters_limit_tool_hooks() {
+        let mut lint = sh("true");
+        lint.tools = vec!["apply_patch".to_string()];
+        let hooks = Hooks {
+            post_tool_use: vec![lint],
+            ..Default::default()
+        };
+        assert!(hooks.has_tool_hooks("apply_patch"));
+        assert!(!hooks.has_tool_hooks("shell"));
+    }
+}
@@ -0,0 +1,477 @@
+//! Minimal HTML to markdown conversion for pages fetched by `fetch_url`.
+//!
+//! This is not a full HTML parser: it walks the tag stream, drops non-content
+//! elements (scripts, styles, forms, ...) and renders the common structural
+//! tags (headings, paragraphs, lists, links, code, emphasis) as markdown. The
+//! goal is readable text for the model, not a faithful round trip.
+
+/// The page title, if any, and the markdown rendering of its content.
+pub(crate) struct ConvertedPage {
+    pub(crate) title: Option<String>,
+    pub(crate) markdown: String,
+}
+
+/// Elements whose content is never useful to the model.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "object", "canvas", "select",
+    "button",
+];
+
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "header",
+    "footer",
+    "main",
+    "nav",
+    "aside",
+    "table",
+    "tr",
+    "ul",
+    "ol",
+    "dl",
+    "dt",
+    "dd",
+    "blockquote",
+    "figure",
+    "figcaption",
+    "form",
+    "details",
+    "summary",
+    "body",
+];
+
+pub(crate) fn html_to_markdown(html: &str) -> ConvertedPage {
+    let mut converter = Converter::default();
+    converter.run(html);
+    ConvertedPage {
+        title: converter
+            .title
+            .map(|title| collapse_whitespace(&title).trim().to_string())
+            .filter(|title| !title.is_empty()),
+        markdown: tidy(&converter.out),
+    }
+}
+
+#[derive(Default)]
+struct Converter {
+    out: String,
+    title: Option<String>,
+    in_title: bool,
+    pre_depth: usize,
+    /// One entry per open list: `Some(n)` for ordered lists, `None` otherwise.
+    lists: Vec<Option<usize>>,
+    /// Output offset of the `[` for each open link, with its target.
+    links: Vec<(usize, Option<String>)>,
+}
+
+impl Converter {
+    fn run(&mut self, html: &str) {
+        let mut rest = html;
+        while let Some(start) = rest.find('<') {
+            self.text(&rest[..start]);
+            rest = &rest[start..];
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                rest = comment
+                    .find("-->")
+                    .map_or("", |end| &comment[end + "-->".len()..]);
+                continue;
+            }
+            let starts_tag = rest[1..]
+                .chars()
+                .next()
+                .is_some_and(|ch| ch.is_ascii_alphabetic() || matches!(ch, '/' | '!' | '?'));
+            if !starts_tag {
+                // Not a tag, e.g. `a < b`.
+                self.text("<");
+                rest = &rest[1..];
+                continue;
+            }
+            let Some(end) = rest.find('>') else {
+                // An unterminated `<` is text.
+                self.text(rest);
+                return;
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+
+            if tag.starts_with('!') || tag.starts_with('?') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                self.close(&tag_name(name));
+                continue;
+            }
+            let name = tag_name(tag);
+            if SKIPPED_ELEMENTS.contains(&name.as_str()) && !tag.ends_with('/') {
+                rest = skip_element(rest, &name);
+                continue;
+            }
+            self.open(&name, tag);
+        }
+        self.text(rest);
+    }
+
+    fn text(&mut self, raw: &str) {
+        if raw.is_empty() {
+            return;
+        }
+        let decoded = decode_entities(raw);
+        if self.in_title {
+            self.title
+                .get_or_insert_with(String::new)
+                .push_str(&decoded);
+            return;
+        }
+        if self.pre_depth > 0 {
+            self.out.push_str(&decoded);
+            return;
+        }
+        let collapsed = collapse_whitespace(&decoded);
+        let collapsed = if self.out.is_empty()
+            || self.out.ends_with('\n')
+            || self.out.ends_with(' ')
+            || self.out.ends_with('[')
+        {
+            collapsed.trim_start()
+        } else {
+            collapsed.as_str()
+        };
+        self.out.push_str(collapsed);
+    }
+
+    fn open(&mut self, name: &str, tag: &str) {
+        match name {
+            "title" => self.in_title = true,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.blank_line();
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                self.out.push_str(&"#".repeat(level));
+                self.out.push(' ');
+            }
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.blank_line();
+                self.out.push_str("---");
+                self.blank_line();
+            }
+            "pre" => {
+                self.blank_line();
+                self.out.push_str("```\n");
+                self.pre_depth += 1;
+            }
+            "code" if self.pre_depth == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('*'),
+            "ul" => {
+                self.line_break();
+                self.lists.push(None);
+            }
+            "ol" => {
+                self.line_break();
+                self.lists.push(Some(0));
+            }
+            "li" => {
+                self.line_break();
+                let depth = self.lists.len().saturating_sub(1);
+                self.out.push_str(&"  ".repeat(depth));
+                match self.lists.last_mut() {
+                    Some(Some(counter)) => {
+                        *counter += 1;
+                        self.out.push_str(&format!("{counter}. "));
+                    }
+                    _ => self.out.push_str("- "),
+                }
+            }
+            "a" => {
+                let href = attribute(tag, "href").filter(|href| {
+                    !href.is_empty() && !href.starts_with('#') && !href.starts_with("javascript:")
+                });
+                self.links.push((self.out.len(), href));
+                self.out.push('[');
+            }
+            "img" => {
+                if let Some(alt) = attribute(tag, "alt").filter(|alt| !alt.trim().is_empty()) {
+                    self.out.push_str(alt.trim());
+                }
+            }
+            "td" | "th" => {
+                if !self.out.ends_with('\n') && !self.out.is_empty() {
+                    self.out.push_str(" | ");
+                }
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => self.blank_line(),
+            _ => {}
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "title" => self.in_title = false,
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => self.blank_line(),
+            "pre" => {
+                self.pre_depth = self.pre_depth.saturating_sub(1);
+                self.line_break();
+                self.out.push_str("```");
+                self.blank_line();
+            }
+            "code" if self.pre_depth == 0 => self.out.push('`'),
+            "strong" | "b" => self.out.push_str("**"),
+            "em" | "i" => self.out.push('*'),
+            "ul" | "ol" => {
+                self.lists.pop();
+                self.blank_line();
+            }
+            "a" => {
+                let Some((start, href)) = self.links.pop() else {
+                    return;
+                };
+                let text = self.out[start + 1..].trim().to_string();
+                match href {
+                    Some(href) if !text.is_empty() => {
+                        self.out.truncate(start);
+                        self.out.push_str(&format!("[{text}]({href})"));
+                    }
+                    _ => {
+                        self.out.remove(start);
+                    }
+                }
+            }
+            "tr" => self.line_break(),
+            _ if BLOCK_ELEMENTS.contains(&name) => self.blank_line(),
+            _ => {}
+        }
+    }
+
+    fn line_break(&mut self) {
+        trim_trailing_spaces(&mut self.out);
+        if !self.out.is_empty() && !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    fn blank_line(&mut self) {
+        self.line_break();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start()
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+/// Returns the input after the closing tag of `name`, or nothing if it is
+/// never closed.
+fn skip_element<'a>(rest: &'a str, name: &str) -> &'a str {
+    let closing = format!("</{name}");
+    let lower = rest.to_ascii_lowercase();
+    let Some(start) = lower.find(&closing) else {
+        return "";
+    };
+    rest[start..]
+        .find('>')
+        .map_or("", |end| &rest[start + end + 1..])
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut search_from = 0;
+    while let Some(found) = lower[search_from..].find(name) {
+        let start = search_from + found;
+        search_from = start + name.len();
+        let preceded_by_space = lower[..start]
+            .chars()
+            .last()
+            .is_some_and(char::is_whitespace);
+        let rest = lower[search_from..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+            _ => value
+                .split(|ch: char| ch.is_whitespace() || ch == '/')
+                .next()
+                .unwrap_or_default(),
+        };
+        return Some(decode_entities(value));
+    }
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| decode_entity(&rest[1..=end]).map(|ch| (ch, end + 2)));
+        match decoded {
+            Some((ch, len)) => {
+                out.push(ch);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    let ch = match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "ndash" => '–',
+        "mdash" => '—',
+        "hellip" => '…',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "copy" => '©',
+        "reg" => '®',
+        "trade" => '™',
+        _ => return None,
+    };
+    Some(ch)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_space = false;
+    for ch in text.chars() {
+        if ch.is_whitespace() {
+            if !in_space {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(ch);
+            in_space = false;
+        }
+    }
+    out
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let trimmed = out.trim_end_matches([' ', '\t']).len();
+    out.truncate(trimmed);
+}
+
+/// Trims trailing whitespace on every line and caps blank runs at one line.
+fn tidy(markdown: &str) -> String {
+    let mut out = String::with_capacity(markdown.len());
+    let mut blank_run = 0;
+    for line in markdown.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn converts_common_structure() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Release &amp; notes</title>
+<style>body { color: red }</style><script>alert("x")</script></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <h1>Version 2.0</h1>
+  <p>Adds <strong>faster</strong> builds and <em>fewer</em> bugs.
+     See <a href="https://example.com/docs">the docs</a>.</p>
+  <ul><li>One</li><li>Two<ol><li>Nested</li></ol></li></ul>
+  <pre><code>cargo build
+  --release</code></pre>
+  <p>Use <code>x &lt; y</code><br>on a new line.</p>
+</body></html>"#;
+
+        let page = html_to_markdown(html);
+        assert_eq!(page.title.as_deref(), Some("Release & notes"));
+        assert_eq!(
+            page.markdown,
+            [
+                "[Home](/)",
+                "",
+                "# Version 2.0",
+                "",
+                "Adds **faster** builds and *fewer* bugs. See [the docs](https://example.com/docs).",
+                "",
+                "- One",
+                "- Two",
+                "  1. Nested",
+                "",
+                "```",
+                "cargo build",
+                "  --release",
+                "```",
+                "",
+                "Use `x < y`",
+                "on a new line.",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn drops_links_without_targets_and_keeps_stray_angle_brackets() {
+        let page = html_to_markdown(
+            r##"<p><a href="#top">Back</a> <a href="javascript:void(0)">Click</a> 1 < 2</p>"##,
+        );
+        assert_eq!(page.markdown, "Back Click 1 < 2");
+        assert_eq!(page.title, None);
+    }
+
+    #[test]
+    fn decodes_numeric_entities_and_ignores_unknown_ones() {
+        assert_eq!(
+            decode_entities("&#8212; &#x41; &bogus; a&b"),
+            "— A &bogus; a&b"
+        );
+    }
+}
@@ -18,6 +18,8 @@ mod compact_remote;
 pub use codex_thread::CodexThread;
 pub use codex_thread::ThreadConfigSnapshot;
 mod agent;
+pub use agent::role::apply_agent_role;
+pub use agent::worktree::AgentWorktreeAction;
 mod codex_delegate;
 mod command_safety;
 pub mod config;
@@ -34,8 +36,10 @@ mod exec_policy;
 pub mod features;
 mod flags;
 pub mod git_info;
+mod html_to_markdown;
 pub mod instructions;
 pub mod landlock;
+mod lsp;
 pub mod mcp;
 mod mcp_connection_manager;
 pub mod models_manager;
@@ -58,6 +62,7 @@ mod stream_events_utils;
 mod tagged_block_parser;
 mod text_encoding;
 pub mod token_data;
+mod tokenizer;
 mod truncate;
 mod unified_exec;
 pub mod windows_sandbox;
@@ -123,6 +128,7 @@ pub use rollout::rollout_date_parts;
 pub use rollout::session_index::find_thread_names_by_ids;
 pub use transport_manager::TransportManager;
 mod function_tool;
+mod hooks;
 mod state;
 mod tasks;
 mod user_notification;
@@ -0,0 +1,790 @@
+//! Language servers configured under `[lsp.<language>]`.
+//!
+//! Servers start the first time `apply_patch` touches a file they handle and
+//! live as long as the session. After each patch the edited files are synced
+//! to their server, and any errors it publishes for them within the timeout
+//! are appended to the `apply_patch` result.
+//!
+//! Servers run outside the session's sandbox. Saving a document makes some
+//! of them build the project (rust-analyzer runs `cargo check`, which runs
+//! build scripts), so `didSave` is only sent when commands already run
+//! without Codex's sandbox.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use lsp_types::ClientCapabilities;
+use lsp_types::Diagnostic;
+use lsp_types::DiagnosticSeverity;
+use lsp_types::DidChangeTextDocumentParams;
+use lsp_types::DidCloseTextDocumentParams;
+use lsp_types::DidOpenTextDocumentParams;
+use lsp_types::DidSaveTextDocumentParams;
+use lsp_types::InitializeParams;
+use lsp_types::InitializedParams;
+use lsp_types::PublishDiagnosticsClientCapabilities;
+use lsp_types::PublishDiagnosticsParams;
+use lsp_types::TextDocumentClientCapabilities;
+use lsp_types::TextDocumentContentChangeEvent;
+use lsp_types::TextDocumentIdentifier;
+use lsp_types::TextDocumentItem;
+use lsp_types::TextDocumentSyncClientCapabilities;
+use lsp_types::Url;
+use lsp_types::VersionedTextDocumentIdentifier;
+use lsp_types::WorkspaceFolder;
+use lsp_types::notification::DidChangeTextDocument;
+use lsp_types::notification::DidCloseTextDocument;
+use lsp_types::notification::DidOpenTextDocument;
+use lsp_types::notification::DidSaveTextDocument;
+use lsp_types::notification::Initialized;
+use lsp_types::notification::Notification;
+use lsp_types::notification::PublishDiagnostics;
+use lsp_types::request::Initialize;
+use lsp_types::request::Request;
+use serde_json::Value as JsonValue;
+use serde_json::json;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::process::ChildStdout;
+use tokio::process::Command;
+use tokio::sync::Mutex;
+use tokio::sync::OnceCell;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tokio::time::timeout;
+
+use crate::config::types::LspServerConfig;
+use crate::protocol::FileChange;
+use crate::protocol::SandboxPolicy;
+
+/// How long a server may take to answer `initialize`.
+const INITIALIZE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default wait for diagnostics after an edit.
+const DEFAULT_DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// At most this many errors are reported per patch.
+const MAX_REPORTED_ERRORS: usize = 50;
+
+/// Extensions for common language names, used when `extensions` is not set.
+const DEFAULT_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("c", &["c", "h"]),
+    ("cpp", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    ("go", &["go"]),
+    ("java", &["java"]),
+    ("javascript", &["js", "jsx", "mjs", "cjs"]),
+    ("python", &["py", "pyi"]),
+    ("ruby", &["rb"]),
+    ("rust", &["rs"]),
+    ("typescript", &["ts", "tsx", "mts", "cts"]),
+];
+
+/// An error a language server reported for an edited file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileDiagnostic {
+    /// Path relative to the session's working directory where possible.
+    pub(crate) path: String,
+    /// 1-indexed.
+    pub(crate) line: u32,
+    /// 1-indexed.
+    pub(crate) column: u32,
+    pub(crate) message: String,
+}
+
+/// A server started at most once, by the first patch that needs it. `None`
+/// records a server that failed to start so it is not retried on every patch.
+type ServerSlot = Arc<OnceCell<Option<Arc<LspServer>>>>;
+
+/// Servers by language and workspace root.
+type ServerMap = HashMap<(String, PathBuf), ServerSlot>;
+
+/// Starts and talks to the session's language servers.
+pub(crate) struct LspManager {
+    configs: BTreeMap<String, LspServerConfig>,
+    servers: Mutex<ServerMap>,
+}
+
+impl LspManager {
+    pub(crate) fn new(configs: BTreeMap<String, LspServerConfig>) -> Self {
+        Self {
+            configs,
+            servers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Syncs the files a patch left on disk to their language servers and
+    /// returns the errors published for them.
+    pub(crate) async fn collect_errors(
+        &self,
+        cwd: &Path,
+        changes: &HashMap<PathBuf, FileChange>,
+        sandbox_policy: &SandboxPolicy,
+    ) -> Vec<FileDiagnostic> {
+        if self.configs.is_empty() {
+            return Vec::new();
+        }
+        let send_save = matches!(
+            sandbox_policy,
+            SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. }
+        );
+
+        let mut by_language: BTreeMap<&str, Vec<FileEdit>> = BTreeMap::new();
+        for (path, change) in changes {
+            let path = cwd.join(path);
+            let (path, closed) = match change {
+                FileChange::Add { .. } | FileChange::AddBinary { .. } => (path, None),
+                FileChange::Delete { .. } => (path.clone(), Some(path)),
+                FileChange::Update { move_path, .. } => match move_path {
+                    Some(dest) => (cwd.join(dest), Some(path)),
+                    None => (path, None),
+                },
+            };
+            if let Some(closed) = closed
+                && let Some(language) = self.language_for(&closed)
+            {
+                by_language
+                    .entry(language)
+                    .or_default()
+                    .push(FileEdit::Closed(closed));
+            }
+            if !matches!(change, FileChange::Delete { .. })
+                && let Some(language) = self.language_for(&path)
+            {
+                by_language
+                    .entry(language)
+                    .or_default()
+                    .push(FileEdit::Changed(path));
+            }
+        }
+
+        let mut errors = Vec::new();
+        for (language, edits) in by_language {
+            let Some(server) = self.server(language, cwd).await else {
+                continue;
+            };
+            let wait = self.configs[language]
+                .diagnostics_timeout_ms
+                .map_or(DEFAULT_DIAGNOSTICS_TIMEOUT, Duration::from_millis);
+            match server.sync_and_collect(&edits, wait, send_save).await {
+                Ok(diagnostics) => {
+                    for (path, diagnostics) in diagnostics {
+                        errors.extend(file_errors(cwd, &path, &diagnostics));
+                    }
+                }
+                Err(err) => {
+                    tracing::warn!("language server `{language}` failed: {err}");
+                    self.servers.lock().await.insert(
+                        (language.to_string(), cwd.to_path_buf()),
+                        Arc::new(OnceCell::from(None)),
+                    );
+                }
+            }
+        }
+        errors.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+        errors
+    }
+
+    fn language_for(&self, path: &Path) -> Option<&str> {
+        let extension = path.extension()?.to_str()?;
+        self.configs
+            .iter()
+            .find(|(language, config)| {
+                if config.extensions.is_empty() {
+                    default_extensions(language).contains(&extension)
+                } else {
+                    config.extensions.iter().any(|ext| ext == extension)
+                }
+            })
+            .map(|(language, _)| language.as_str())
+    }
+
+    async fn server(&self, language: &str, root: &Path) -> Option<Arc<LspServer>> {
+        // Only the lookup holds the map's lock, so starting one server does
+        // not block patches that need another.
+        let slot = self
+            .servers
+            .lock()
+            .await
+            .entry((language.to_string(), root.to_path_buf()))
+            .or_default()
+            .clone();
+        slot.get_or_init(|| async {
+            let config = &self.configs[language];
+            match LspServer::start(language, config, root).await {
+                Ok(server) => Some(Arc::new(server)),
+                Err(err) => {
+                    tracing::warn!(
+                        "failed to start language server `{language}` ({}): {err}",
+                        config.command
+                    );
+                    None
+                }
+            }
+        })
+        .await
+        .clone()
+    }
+}
+
+fn default_extensions(language: &str) -> &'static [&'static str] {
+    DEFAULT_EXTENSIONS
+        .iter()
+        .find(|(name, _)| *name == language)
+        .map_or(&[], |(_, extensions)| *extensions)
+}
+
+/// Renders errors so they can be appended to the `apply_patch` output the
+/// model sees.
+pub(crate) fn format_lsp_errors(errors: &[FileDiagnostic]) -> Option<String> {
+    if errors.is_empty() {
+        return None;
+    }
+    let mut report = String::from("\n\nLanguage servers reported errors in the edited files:");
+    for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+        report.push_str(&format!(
+            "\n{}:{}:{}: {}",
+            error.path, error.line, error.column, error.message
+        ));
+    }
+    if errors.len() > MAX_REPORTED_ERRORS {
+        report.push_str(&format!(
+            "\n... and {} more",
+            errors.len() - MAX_REPORTED_ERRORS
+        ));
+    }
+    Some(report)
+}
+
+fn file_errors(cwd: &Path, path: &Path, diagnostics: &[Diagnostic]) -> Vec<FileDiagnostic> {
+    let display_path = path
+        .strip_prefix(cwd)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned();
+    diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR))
+        .map(|diagnostic| FileDiagnostic {
+            path: display_path.clone(),
+            line: diagnostic.range.start.line + 1,
+            column: diagnostic.range.start.character + 1,
+            message: diagnostic
+                .message
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+enum FileEdit {
+    /// The file exists after the patch with new contents.
+    Changed(PathBuf),
+    /// The file was deleted or moved away.
+    Closed(PathBuf),
+}
+
+/// Diagnostics last published by a server, by document.
+#[derive(Default)]
+struct PublishedDiagnostics {
+    documents: HashMap<Url, (Option<i32>, Vec<Diagnostic>)>,
+}
+
+struct LspServer {
+    language_id: String,
+    /// Shared with the reader task, which answers requests from the server.
+    stdin: Arc<Mutex<ChildStdin>>,
+    next_id: AtomicI64,
+    pending: Arc<std::sync::Mutex<HashMap<i64, oneshot::Sender<JsonValue>>>>,
+    diagnostics: watch::Sender<PublishedDiagnostics>,
+    /// Open documents and their last synced version.
+    open_documents: Mutex<HashMap<Url, i32>>,
+    _child: Child,
+}
+
+impl LspServer {
+    async fn start(language: &str, config: &LspServerConfig, root: &Path) -> anyhow::Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("language server stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("language server stdout unavailable"))?;
+
+        let server = Self {
+            language_id: language.to_string(),
+            stdin: Arc::new(Mutex::new(stdin)),
+            next_id: AtomicI64::new(1),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            diagnostics: watch::Sender::new(PublishedDiagnostics::default()),
+            open_documents: Mutex::new(HashMap::new()),
+            _child: child,
+        };
+        tokio::spawn(read_messages(
+            stdout,
+            server.stdin.clone(),
+            server.pending.clone(),
+            server.diagnostics.clone(),
+        ));
+
+        let root_uri = Url::from_directory_path(root)
+            .map_err(|()| anyhow::anyhow!("invalid workspace root {}", root.display()))?;
+        let params = InitializeParams {
+            process_id: Some(std::process::id()),
+            root_uri: Some(root_uri.clone()),
+            workspace_folders: Some(vec![WorkspaceFolder {
+                uri: root_uri,
+                name: root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+            }]),
+            capabilities: ClientCapabilities {
+                text_document: Some(TextDocumentClientCapabilities {
+                    synchronization: Some(TextDocumentSyncClientCapabilities {
+                        did_save: Some(true),
+                        ..Default::default()
+                    }),
+                    publish_diagnostics: Some(PublishDiagnosticsClientCapabilities {
+                        version_support: Some(true),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        timeout(
+            INITIALIZE_TIMEOUT,
+            server.request(Initialize::METHOD, serde_json::to_value(params)?),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("initialize timed out"))??;
+        server
+            .notify(
+                Initialized::METHOD,
+                serde_json::to_value(InitializedParams {})?,
+            )
+            .await?;
+        Ok(server)
+    }
+
+    /// Sends the edits, then waits up to `wait` for diagnostics of every
+    /// changed file. Files the server stays silent about are left out.
+    async fn sync_and_collect(
+        &self,
+        edits: &[FileEdit],
+        wait: Duration,
+        send_save: bool,
+    ) -> anyhow::Result<Vec<(PathBuf, Vec<Diagnostic>)>> {
+        let mut expected = Vec::new();
+        for edit in edits {
+            match edit {
+                FileEdit::Changed(path) => {
+                    let Ok(text) = tokio::fs::read_to_string(path).await else {
+                        continue;
+                    };
+                    let Ok(uri) = Url::from_file_path(path) else {
+                        continue;
+                    };
+                    let version = self.sync_document(&uri, text, send_save).await?;
+                    expected.push((path.clone(), uri, version));
+                }
+                FileEdit::Closed(path) => {
+                    if let Ok(uri) = Url::from_file_path(path) {
+                        self.close_document(uri).await?;
+                    }
+                }
+            }
+        }
+
+        let deadline = Instant::now() + wait;
+        let mut receiver = self.diagnostics.subscribe();
+        loop {
+            let all_published = {
+                let published = receiver.borrow_and_update();
+                expected
+                    .iter()
+                    .all(|(_, uri, version)| is_current(published.documents.get(uri), *version))
+            };
+            if all_published
+                || tokio::time::timeout_at(deadline, receiver.changed())
+                    .await
+                    .is_err()
+            {
+                break;
+            }
+        }
+
+        let published = self.diagnostics.borrow();
+        Ok(expected
+            .into_iter()
+            .filter_map(|(path, uri, version)| {
+                let entry = published.documents.get(&uri);
+                is_current(entry, version)
+                    .then(|| (path, entry.map(|(_, d)| d.clone()).unwrap_or_default()))
+            })
+            .collect())
+    }
+
+    /// Opens `uri` or replaces its contents, returning the synced version.
+    async fn sync_document(&self, uri: &Url, text: String, send_save: bool) -> anyhow::Result<i32> {
+        // Drop what the server said about older versions so only fresh
+        // diagnostics count.
+        self.diagnostics.send_modify(|published| {
+            published.documents.remove(uri);
+        });
+        let mut open_documents = self.open_documents.lock().await;
+        let version = match open_documents.get(uri) {
+            Some(version) => {
+                let version = version + 1;
+                let params = DidChangeTextDocumentParams {
+                    text_document: VersionedTextDocumentIdentifier::new(uri.clone(), version),
+                    content_changes: vec![TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text,
+                    }],
+                };
+                self.notify(DidChangeTextDocument::METHOD, serde_json::to_value(params)?)
+                    .await?;
+                version
+            }
+            None => {
+                let params = DidOpenTextDocumentParams {
+                    text_document: TextDocumentItem::new(
+                        uri.clone(),
+                        self.language_id.clone(),
+                        1,
+                        text,
+                    ),
+                };
+                self.notify(DidOpenTextDocument::METHOD, serde_json::to_value(params)?)
+                    .await?;
+                1
+            }
+        };
+        open_documents.insert(uri.clone(), version);
+        if !send_save {
+            return Ok(version);
+        }
+        // The file is already on disk, so servers that only check on save
+        // (e.g. rust-analyzer's `cargo check`) run now.
+        let params = DidSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            text: None,
+        };
+        self.notify(DidSaveTextDocument::METHOD, serde_json::to_value(params)?)
+            .await?;
+        Ok(version)
+    }
+
+    async fn close_document(&self, uri: Url) -> anyhow::Result<()> {
+        if self.open_documents.lock().await.remove(&uri).is_none() {
+            return Ok(());
+        }
+        let params = DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri),
+        };
+        self.notify(DidCloseTextDocument::METHOD, serde_json::to_value(params)?)
+            .await
+    }
+
+    async fn request(&self, method: &str, params: JsonValue) -> anyhow::Result<JsonValue> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        #[expect(clippy::unwrap_used)]
+        self.pending.lock().unwrap().insert(id, tx);
+        self.send(json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params}))
+            .await?;
+        let response = rx
+            .await
+            .map_err(|_| anyhow::anyhow!("language server exited"))?;
+        if let Some(error) = response.get("error") {
+            anyhow::bail!("{method} failed: {error}");
+        }
+        Ok(response.get("result").cloned().unwrap_or(JsonValue::Null))
+    }
+
+    async fn notify(&self, method: &str, params: JsonValue) -> anyhow::Result<()> {
+        self.send(json!({"jsonrpc": "2.0", "method": method, "params": params}))
+            .await
+    }
+
+    async fn send(&self, message: JsonValue) -> anyhow::Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        write_message(&mut *stdin, &message).await?;
+        Ok(())
+    }
+}
+
+fn is_current(entry: Option<&(Option<i32>, Vec<Diagnostic>)>, version: i32) -> bool {
+    match entry {
+        Some((Some(published), _)) => *published >= version,
+        Some((None, _)) => true,
+        None => false,
+    }
+}
+
+/// Dispatches messages from the server until it exits: responses complete
+/// their pending request, diagnostics are recorded, and server requests get
+/// an empty result so servers waiting on them keep going.
+async fn read_messages(
+    stdout: ChildStdout,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: Arc<std::sync::Mutex<HashMap<i64, oneshot::Sender<JsonValue>>>>,
+    diagnostics: watch::Sender<PublishedDiagnostics>,
+) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(err) => {
+                tracing::warn!("invalid message from language server: {err}");
+                break;
+            }
+        };
+        let method = message.get("method").and_then(JsonValue::as_str);
+        let id = message.get("id");
+        match (method, id) {
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    // One `null` per requested item: use the defaults.
+                    "workspace/configuration" => JsonValue::Array(
+                        message
+                            .pointer("/params/items")
+                            .and_then(JsonValue::as_array)
+                            .map(|items| vec![JsonValue::Null; items.len()])
+                            .unwrap_or_default(),
+                    ),
+                    _ => JsonValue::Null,
+                };
+                let reply = json!({"jsonrpc": "2.0", "id": id, "result": result});
+                if write_message(&mut *stdin.lock().await, &reply)
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            (Some(PublishDiagnostics::METHOD), None) => {
+                let Some(params) = message.get("params").cloned().and_then(|params| {
+                    serde_json::from_value::<PublishDiagnosticsParams>(params).ok()
+                }) else {
+                    continue;
+                };
+                diagnostics.send_modify(|published| {
+                    published
+                        .documents
+                        .insert(params.uri, (params.version, params.diagnostics));
+                });
+            }
+            (Some(_), None) => {}
+            (None, Some(id)) => {
+                #[expect(clippy::unwrap_used)]
+                let sender = id
+                    .as_i64()
+                    .and_then(|id| pending.lock().unwrap().remove(&id));
+                if let Some(sender) = sender {
+                    let _ = sender.send(message);
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    // Fail requests still waiting on a server that is gone.
+    #[expect(clippy::unwrap_used)]
+    pending.lock().unwrap().clear();
+}
+
+async fn read_message(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+) -> anyhow::Result<Option<JsonValue>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("Content-Length")
+        {
+            content_length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+    let content_length =
+        content_length.ok_or_else(|| anyhow::anyhow!("missing Content-Length header"))?;
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+async fn write_message(
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    message: &JsonValue,
+) -> std::io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+    use lsp_types::Range;
+    use pretty_assertions::assert_eq;
+
+    fn diagnostic(line: u32, severity: DiagnosticSeverity, message: &str) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, 4), Position::new(line, 8)),
+            severity: Some(severity),
+            message: message.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn messages_round_trip_through_framing() {
+        let message = json!({"jsonrpc": "2.0", "id": 1, "result": {"ok": true}});
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &message).await.unwrap();
+        write_message(&mut buffer, &message).await.unwrap();
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        assert_eq!(
+            read_message(&mut reader).await.unwrap(),
+            Some(message.clone())
+        );
+        assert_eq!(read_message(&mut reader).await.unwrap(), Some(message));
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn starting_one_server_does_not_block_others() {
+        let manager = Arc::new(LspManager::new(BTreeMap::from([
+            (
+                "slow".to_string(),
+                LspServerConfig {
+                    // Never answers `initialize`.
+                    command: "sleep".to_string(),
+                    args: vec!["5".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "missing".to_string(),
+                LspServerConfig {
+                    command: "codex-test-missing-language-server".to_string(),
+                    ..Default::default()
+                },
+            ),
+        ])));
+        let root = std::env::temp_dir();
+        let slow = tokio::spawn({
+            let manager = manager.clone();
+            let root = root.clone();
+            async move { manager.server("slow", &root).await.is_none() }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let missing = timeout(Duration::from_secs(2), manager.server("missing", &root))
+            .await
+            .expect("lookup waited for another server to start");
+        assert!(missing.is_none());
+        slow.abort();
+    }
+
+    #[test]
+    fn languages_match_default_or_configured_extensions() {
+        let manager = LspManager::new(BTreeMap::from([
+            (
+                "rust".to_string(),
+                LspServerConfig {
+                    command: "rust-analyzer".to_string(),
+                    ..Default::default()
+                },
+            ),
+            (
+                "templates".to_string(),
+                LspServerConfig {
+                    command: "template-lsp".to_string(),
+                    extensions: vec!["tmpl".to_string()],
+                    ..Default::default()
+                },
+            ),
+        ]));
+        assert_eq!(manager.language_for(Path::new("src/lib.rs")), Some("rust"));
+        assert_eq!(
+            manager.language_for(Path::new("page.tmpl")),
+            Some("templates")
+        );
+        assert_eq!(manager.language_for(Path::new("main.py")), None);
+    }
+
+    #[test]
+    fn only_errors_are_reported() {
+        let cwd = Path::new("/repo");
+        let errors = file_errors(
+            cwd,
+            Path::new("/repo/src/lib.rs"),
+            &[
+                diagnostic(
+                    2,
+                    DiagnosticSeverity::ERROR,
+                    "mismatched types\nexpected u32",
+                ),
+                diagnostic(5, DiagnosticSeverity::WARNING, "unused variable"),
+            ],
+        );
+        assert_eq!(
+            errors,
+            vec![FileDiagnostic {
+                path: "src/lib.rs".to_string(),
+                line: 3,
+                column: 5,
+                message: "mismatched types".to_string(),
+            }]
+        );
+        assert_eq!(
+            format_lsp_errors(&errors).as_deref(),
+            Some(
+                "\n\nLanguage servers reported errors in the edited files:\nsrc/lib.rs:3:5: mismatched types"
+            )
+        );
+        assert_eq!(format_lsp_errors(&[]), None);
+    }
+}
@@ -19,6 +19,7 @@ use tokio_util::sync::CancellationToken;
 use crate::AuthManager;
 use crate::CodexAuth;
 use crate::config::Config;
+use crate::config::disable_denied_mcp_tools;
 use crate::config::types::McpServerConfig;
 use crate::config::types::McpServerTransportConfig;
 use crate::features::Feature;
@@ -122,6 +123,16 @@ pub(crate) fn with_codex_apps_mcp(
     } else {
         servers.remove(CODEX_APPS_MCP_SERVER_NAME);
     }
+    // Servers added here or passed in at refresh never went through the
+    // config's requirements, so apply the denied tools again.
+    disable_denied_mcp_tools(
+        &mut servers,
+        config
+            .config_layer_stack
+            .requirements()
+            .denied_mcp_tools
+            .as_ref(),
+    );
     servers
 }
 
@@ -610,6 +610,7 @@ impl McpConnectionManager {
     }
 
     /// Invoke the tool indicated by the (server, tool) pair.
+    #[instrument(name = "mcp_call", level = "trace", skip(self, arguments))]
     pub async fn call_tool(
         &self,
         server: &str,
@@ -7,6 +7,7 @@ use codex_protocol::openai_models::ModelMessages;
 use codex_protocol::openai_models::ModelVisibility;
 use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::openai_models::ReasoningEffortPreset;
+use codex_protocol::openai_models::TokenizerEncoding;
 use codex_protocol::openai_models::TruncationMode;
 use codex_protocol::openai_models::TruncationPolicyConfig;
 use codex_protocol::openai_models::default_input_modalities;
@@ -68,6 +69,7 @@ macro_rules! model_info {
             effective_context_window_percent: 95,
             experimental_supported_tools: Vec::new(),
             input_modalities: default_input_modalities(),
+            tokenizer: Some(TokenizerEncoding::O200kBase),
         };
 
         $(
@@ -318,7 +320,8 @@ pub(crate) fn find_model_info_for_slug(slug: &str) -> ModelInfo {
             slug,
             context_window: None,
             supported_reasoning_levels: Vec::new(),
-            default_reasoning_level: None
+            default_reasoning_level: None,
+            tokenizer: None,
         )
     }
 }
@@ -19,6 +19,14 @@ const COMMIT_PROMPT_WITH_TITLE: &str = "Review the code changes introduced by co
 const COMMIT_PROMPT: &str =
     "Review the code changes introduced by commit {sha}. Provide prioritized, actionable findings.";
 
+const COMMIT_RANGE_PROMPT: &str = "Review the code changes between commits {from} and {to}. Run `git diff {from}..{to}` to inspect them and `git log {from}..{to}` for the commit messages. Provide prioritized, actionable findings.";
+
+const PATCH_FILE_PROMPT: &str = "Review the code changes in the patch below, read from {path}. The patch may not be applied to the working tree; use the repository only for surrounding context. Provide prioritized, actionable findings.\n\n{fence}diff\n{patch}\n{fence}";
+const PATCH_FILE_PROMPT_LARGE: &str = "Review the code changes in the patch file {path}. It is too large to include here, so read it in parts. The patch may not be applied to the working tree; use the repository only for surrounding context. Provide prioritized, actionable findings.";
+
+/// Patches larger than this are referenced by path instead of inlined.
+const MAX_INLINE_PATCH_BYTES: usize = 200 * 1024;
+
 pub fn resolve_review_request(
     request: ReviewRequest,
     cwd: &Path,
@@ -57,6 +65,28 @@ pub fn review_prompt(target: &ReviewTarget, cwd: &Path) -> anyhow::Result<String
                 Ok(COMMIT_PROMPT.replace("{sha}", sha))
             }
         }
+        ReviewTarget::CommitRange { from, to } => Ok(COMMIT_RANGE_PROMPT
+            .replace("{from}", from)
+            .replace("{to}", to)),
+        ReviewTarget::PatchFile { path } => {
+            let path = cwd.join(path);
+            let patch = std::fs::read_to_string(&path).map_err(|err| {
+                anyhow::anyhow!("failed to read patch file {}: {err}", path.display())
+            })?;
+            if patch.trim().is_empty() {
+                anyhow::bail!("Patch file {} is empty", path.display());
+            }
+            let display = path.display().to_string();
+            if patch.len() > MAX_INLINE_PATCH_BYTES {
+                Ok(PATCH_FILE_PROMPT_LARGE.replace("{path}", &display))
+            } else {
+                let patch = patch.trim_end();
+                Ok(PATCH_FILE_PROMPT
+                    .replace("{fence}", &code_fence(patch))
+                    .replace("{path}", &display)
+                    .replace("{patch}", patch))
+            }
+        }
         ReviewTarget::Custom { instructions } => {
             let prompt = instructions.trim();
             if prompt.is_empty() {
@@ -67,6 +97,17 @@ pub fn review_prompt(target: &ReviewTarget, cwd: &Path) -> anyhow::Result<String
     }
 }
 
+/// A backtick fence longer than any backtick run in `text`, so a patch that
+/// touches Markdown cannot close the block early.
+fn code_fence(text: &str) -> String {
+    let longest_run = text
+        .split(|ch| ch != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    "`".repeat(longest_run.max(2) + 1)
+}
+
 pub fn user_facing_hint(target: &ReviewTarget) -> String {
     match target {
         ReviewTarget::UncommittedChanges => "current changes".to_string(),
@@ -79,10 +120,44 @@ pub fn user_facing_hint(target: &ReviewTarget) -> String {
                 format!("commit {short_sha}")
             }
         }
+        ReviewTarget::CommitRange { from, to } => {
+            format!("commits {}..{}", short_revision(from), short_revision(to))
+        }
+        ReviewTarget::PatchFile { path } => {
+            let name = path.file_name().unwrap_or(path.as_os_str());
+            format!("patch {}", name.to_string_lossy())
+        }
         ReviewTarget::Custom { instructions } => instructions.trim().to_string(),
     }
 }
 
+/// Abbreviates full hex SHAs to 7 characters; branch, tag and other ref
+/// names are shown as typed.
+fn short_revision(revision: &str) -> &str {
+    if revision.len() >= 7 && revision.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        &revision[..7]
+    } else {
+        revision
+    }
+}
+
+/// Parses a `FROM..TO` commit range into a [`ReviewTarget::CommitRange`].
+pub fn parse_commit_range(range: &str) -> anyhow::Result<ReviewTarget> {
+    let range = range.trim();
+    if range.contains("...") {
+        anyhow::bail!("Symmetric ranges (`A...B`) are not supported; use `A..B`");
+    }
+    match range.split_once("..") {
+        Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+            Ok(ReviewTarget::CommitRange {
+                from: from.trim().to_string(),
+                to: to.trim().to_string(),
+            })
+        }
+        _ => anyhow::bail!("Expected a commit range like `main..HEAD`, got `{range}`"),
+    }
+}
+
 impl From<ResolvedReviewRequest> for ReviewRequest {
     fn from(resolved: ResolvedReviewRequest) -> Self {
         ReviewRequest {
@@ -91,3 +166,82 @@ impl From<ResolvedReviewRequest> for ReviewRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parses_commit_ranges() {
+        assert_eq!(
+            parse_commit_range(" v1.0..HEAD ").expect("valid range"),
+            ReviewTarget::CommitRange {
+                from: "v1.0".to_string(),
+                to: "HEAD".to_string(),
+            }
+        );
+        for invalid in ["main", "main..", "..HEAD", "main...HEAD"] {
+            assert!(parse_commit_range(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn commit_range_hint_only_shortens_shas() {
+        let hint = |from: &str, to: &str| {
+            user_facing_hint(&ReviewTarget::CommitRange {
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+        };
+        assert_eq!(hint("main", "feature/login"), "commits main..feature/login");
+        assert_eq!(
+            hint("0123456789abcdef0123", "HEAD~3"),
+            "commits 0123456..HEAD~3"
+        );
+        assert_eq!(hint("deadbeef", "abc12"), "commits deadbee..abc12");
+    }
+
+    #[test]
+    fn patch_file_prompt_inlines_the_patch() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(
+            dir.path().join("fix.diff"),
+            "--- a/x\n+++ b/x\n@@\n-a\n+b\n",
+        )
+        .expect("write patch");
+        let target = ReviewTarget::PatchFile {
+            path: PathBuf::from("fix.diff"),
+        };
+
+        let prompt = review_prompt(&target, dir.path()).expect("prompt");
+        assert!(
+            prompt.contains("```diff\n--- a/x\n+++ b/x\n@@\n-a\n+b\n```"),
+            "{prompt}"
+        );
+        assert_eq!(user_facing_hint(&target), "patch fix.diff");
+
+        let missing = ReviewTarget::PatchFile {
+            path: PathBuf::from("missing.diff"),
+        };
+        assert!(review_prompt(&missing, dir.path()).is_err());
+    }
+
+    #[test]
+    fn patch_file_fence_outlasts_backticks_in_the_patch() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let patch = "--- a/README.md\n+++ b/README.md\n@@\n+````sh\n+make\n+````\n";
+        std::fs::write(dir.path().join("docs.diff"), patch).expect("write patch");
+        let target = ReviewTarget::PatchFile {
+            path: PathBuf::from("docs.diff"),
+        };
+
+        let prompt = review_prompt(&target, dir.path()).expect("prompt");
+
+        assert!(
+            prompt.ends_with(&format!("\n\n`````diff\n{}\n`````", patch.trim_end())),
+            "{prompt}"
+        );
+    }
+}
@@ -1001,7 +1001,9 @@ async fn read_head_summary(path: &Path, head_limit: usize) -> io::Result<HeadTai
             RolloutItem::TurnContext(_) => {
                 // Not included in `head`; skip.
             }
-            RolloutItem::Compacted(_) => {
+            RolloutItem::Compacted(_)
+            | RolloutItem::Checkpoint(_)
+            | RolloutItem::ToolOutputsPruned(_) => {
                 // Not included in `head`; skip.
             }
             RolloutItem::EventMsg(ev) => {
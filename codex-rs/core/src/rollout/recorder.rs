@@ -426,6 +426,12 @@ impl RolloutRecorder {
                     RolloutItem::EventMsg(_ev) => {
                         items.push(RolloutItem::EventMsg(_ev));
                     }
+                    RolloutItem::Checkpoint(item) => {
+                        items.push(RolloutItem::Checkpoint(item));
+                    }
+                    RolloutItem::ToolOutputsPruned(item) => {
+                        items.push(RolloutItem::ToolOutputsPruned(item));
+                    }
                 },
                 Err(e) => {
                     warn!("failed to parse rollout line: {e}");
@@ -1,4 +1,4 @@
-//! Helpers for truncating rollouts based on "user turn" boundaries.
+//! Helpers for truncating rollouts based on "user turn" boundaries and checkpoints.
 //!
 //! In core, "user turns" are detected by scanning `ResponseItem::Message` items and
 //! interpreting them via `event_mapping::parse_turn_item(...)`.
@@ -68,6 +68,43 @@ pub(crate) fn truncate_rollout_before_nth_user_message_from_start(
     items[..cut_idx].to_vec()
 }
 
+/// Where an interrupted rollout can be continued from.
+#[derive(Debug)]
+pub(crate) enum CheckpointCut {
+    /// History to replay before asking the model to continue.
+    Continue(Vec<RolloutItem>),
+    /// The last turn finished; there is nothing to continue.
+    Finished,
+    /// The rollout has no checkpoints, so it cannot tell whether its last turn finished.
+    Missing,
+}
+
+/// Cuts `items` after the last point that is safe to replay: the last tool-call checkpoint,
+/// or the user message of a turn that was interrupted before it reached one.
+pub(crate) fn truncate_rollout_after_last_checkpoint(items: &[RolloutItem]) -> CheckpointCut {
+    let Some((checkpoint_idx, checkpoint)) =
+        items
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(idx, item)| match item {
+                RolloutItem::Checkpoint(checkpoint) => Some((idx, checkpoint)),
+                _ => None,
+            })
+    else {
+        return CheckpointCut::Missing;
+    };
+    if let Some(&user_idx) = user_message_positions_in_rollout(items).last()
+        && user_idx > checkpoint_idx
+    {
+        return CheckpointCut::Continue(items[..=user_idx].to_vec());
+    }
+    if checkpoint.call_id.is_none() {
+        return CheckpointCut::Finished;
+    }
+    CheckpointCut::Continue(items[..=checkpoint_idx].to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +112,7 @@ mod tests {
     use assert_matches::assert_matches;
     use codex_protocol::models::ContentItem;
     use codex_protocol::models::ReasoningItemReasoningSummary;
+    use codex_protocol::protocol::CheckpointItem;
     use codex_protocol::protocol::ThreadRolledBackEvent;
     use pretty_assertions::assert_eq;
 
@@ -218,4 +256,79 @@ mod tests {
             serde_json::to_value(&expected).unwrap()
         );
     }
+
+    fn checkpoint(call_id: Option<&str>) -> RolloutItem {
+        RolloutItem::Checkpoint(CheckpointItem {
+            turn_id: "turn".to_string(),
+            call_id: call_id.map(str::to_string),
+        })
+    }
+
+    fn function_call(call_id: &str) -> RolloutItem {
+        RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+            id: None,
+            name: "tool".to_string(),
+            arguments: "{}".to_string(),
+            call_id: call_id.to_string(),
+        })
+    }
+
+    #[test]
+    fn checkpoint_cut_drops_work_after_last_tool_checkpoint() {
+        let rollout = vec![
+            RolloutItem::ResponseItem(user_msg("u1")),
+            function_call("c1"),
+            checkpoint(Some("c1")),
+            RolloutItem::ResponseItem(assistant_msg("partial")),
+            function_call("c2"),
+        ];
+
+        let CheckpointCut::Continue(kept) = truncate_rollout_after_last_checkpoint(&rollout) else {
+            panic!("expected a continuation point");
+        };
+
+        assert_eq!(
+            serde_json::to_value(&kept).unwrap(),
+            serde_json::to_value(&rollout[..3]).unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_cut_keeps_prompt_of_turn_without_checkpoint() {
+        let rollout = vec![
+            RolloutItem::ResponseItem(user_msg("u1")),
+            checkpoint(None),
+            RolloutItem::ResponseItem(user_msg("u2")),
+            RolloutItem::ResponseItem(assistant_msg("partial")),
+        ];
+
+        let CheckpointCut::Continue(kept) = truncate_rollout_after_last_checkpoint(&rollout) else {
+            panic!("expected a continuation point");
+        };
+
+        assert_eq!(
+            serde_json::to_value(&kept).unwrap(),
+            serde_json::to_value(&rollout[..3]).unwrap()
+        );
+    }
+
+    #[test]
+    fn checkpoint_cut_reports_finished_and_missing() {
+        let finished = vec![
+            RolloutItem::ResponseItem(user_msg("u1")),
+            checkpoint(Some("c1")),
+            RolloutItem::ResponseItem(assistant_msg("a1")),
+            checkpoint(None),
+        ];
+        assert_matches!(
+            truncate_rollout_after_last_checkpoint(&finished),
+            CheckpointCut::Finished
+        );
+
+        let missing = vec![RolloutItem::ResponseItem(user_msg("u1"))];
+        assert_matches!(
+            truncate_rollout_after_last_checkpoint(&missing),
+            CheckpointCut::Missing
+        );
+    }
 }
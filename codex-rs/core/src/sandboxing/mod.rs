@@ -21,6 +21,7 @@ use crate::seatbelt::create_seatbelt_command_args;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
 use crate::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use crate::tools::sandboxing::SandboxablePreference;
+use codex_otel::otel_provider::current_span_trace_env;
 use codex_protocol::config_types::WindowsSandboxLevel;
 pub use codex_protocol::models::SandboxPermissions;
 use std::collections::HashMap;
@@ -118,6 +119,8 @@ impl SandboxManager {
                 "1".to_string(),
             );
         }
+        // Let traced tools spawned by this command join the current trace.
+        env.extend(current_span_trace_env());
 
         let mut command = Vec::with_capacity(1 + spec.args.len());
         command.push(spec.program);
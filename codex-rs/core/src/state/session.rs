@@ -5,10 +5,13 @@ use std::collections::HashMap;
 use std::collections::HashSet;
 
 use crate::codex::SessionConfiguration;
+use crate::config::types::ToolOutputPruning;
 use crate::context_manager::ContextManager;
+use crate::protocol::PrunedToolOutput;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::tokenizer::Tokenizer;
 use crate::truncate::TruncationPolicy;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
@@ -42,12 +45,16 @@ impl SessionState {
     }
 
     // History helpers
-    pub(crate) fn record_items<I>(&mut self, items: I, policy: TruncationPolicy)
-    where
+    pub(crate) fn record_items<I>(
+        &mut self,
+        items: I,
+        policy: TruncationPolicy,
+        tokenizer: &Tokenizer,
+    ) where
         I: IntoIterator,
         I::Item: std::ops::Deref<Target = ResponseItem>,
     {
-        self.history.record_items(items, policy);
+        self.history.record_items(items, policy, tokenizer);
     }
 
     pub(crate) fn clone_history(&self) -> ContextManager {
@@ -58,6 +65,13 @@ impl SessionState {
         self.history.replace(items);
     }
 
+    pub(crate) fn prune_tool_outputs(
+        &mut self,
+        pruning: &ToolOutputPruning,
+    ) -> Vec<PrunedToolOutput> {
+        self.history.prune_tool_outputs(pruning)
+    }
+
     pub(crate) fn set_token_info(&mut self, info: Option<TokenUsageInfo>) {
         self.history.set_token_info(info);
     }
@@ -92,9 +106,13 @@ impl SessionState {
         self.history.set_token_usage_full(context_window);
     }
 
-    pub(crate) fn get_total_token_usage(&self, server_reasoning_included: bool) -> i64 {
+    pub(crate) fn get_total_token_usage(
+        &self,
+        server_reasoning_included: bool,
+        tokenizer: &Tokenizer,
+    ) -> i64 {
         self.history
-            .get_total_token_usage(server_reasoning_included)
+            .get_total_token_usage(server_reasoning_included, tokenizer)
     }
 
     pub(crate) fn set_server_reasoning_included(&mut self, included: bool) {
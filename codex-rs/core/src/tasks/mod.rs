@@ -14,7 +14,7 @@ use tokio::sync::Notify;
 use tokio_util::sync::CancellationToken;
 use tokio_util::task::AbortOnDropHandle;
 use tracing::Instrument;
-use tracing::Span;
+use tracing::info_span;
 use tracing::trace;
 use tracing::warn;
 
@@ -132,7 +132,15 @@ impl Session {
             let ctx = Arc::clone(&turn_context);
             let task_for_run = Arc::clone(&task);
             let task_cancellation_token = cancellation_token.child_token();
-            let session_span = Span::current();
+            // Each turn is its own trace, linked back to the session; tool
+            // calls, approvals and model requests nest under it.
+            let turn_span = info_span!(
+                "turn",
+                thread_id = %self.conversation_id,
+                turn_id = %turn_context.sub_id,
+                task_kind = ?task_kind,
+            );
+            self.services.otel_manager.start_trace(&turn_span);
             tokio::spawn(
                 async move {
                     let ctx_for_finish = Arc::clone(&ctx);
@@ -153,7 +161,7 @@ impl Session {
                     }
                     done_clone.notify_waiters();
                 }
-                .instrument(session_span),
+                .instrument(turn_span),
             )
         };
 
@@ -187,6 +195,9 @@ impl Session {
         turn_context: Arc<TurnContext>,
         last_agent_message: Option<String>,
     ) {
+        // Recorded before the turn is cleared: dropping the running task aborts
+        // this future at its next suspension point.
+        self.record_checkpoint(turn_context.as_ref(), None).await;
         let mut active = self.active_turn.lock().await;
         let should_close_processes = if let Some(at) = active.as_mut()
             && at.remove_task(&turn_context.sub_id)
@@ -31,9 +31,6 @@ impl SessionTask for RegularTask {
         let sess = session.clone_session();
         let run_turn_span = trace_span!("run_turn");
         sess.set_server_reasoning_included(false).await;
-        sess.services
-            .otel_manager
-            .apply_traceparent_parent(&run_turn_span);
         run_turn(sess, ctx, input, cancellation_token)
             .instrument(run_turn_span)
             .await
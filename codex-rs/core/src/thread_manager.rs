@@ -4,6 +4,11 @@ use crate::CodexAuth;
 #[cfg(any(test, feature = "test-support"))]
 use crate::ModelProviderInfo;
 use crate::agent::AgentControl;
+use crate::agent::status::is_final;
+use crate::agent::worktree::AgentWorktreeAction;
+use crate::agent::worktree::agent_merge_message;
+use crate::agent::worktree::git_error;
+use crate::agent::worktree::merge_summary;
 use crate::codex::Codex;
 use crate::codex::CodexSpawnOk;
 use crate::codex::INITIAL_SUBMIT_ID;
@@ -17,7 +22,11 @@ use crate::protocol::EventMsg;
 use crate::protocol::SessionConfiguredEvent;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::truncation;
+use crate::rollout::truncation::CheckpointCut;
 use crate::skills::SkillsManager;
+use codex_git::AgentWorktree;
+use codex_git::GitToolingError;
+use codex_git::WorktreeMergeMode;
 use codex_protocol::ThreadId;
 use codex_protocol::config_types::CollaborationModeMask;
 use codex_protocol::openai_models::ModelPreset;
@@ -31,6 +40,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 #[cfg(any(test, feature = "test-support"))]
 use tempfile::TempDir;
+use tokio::sync::Mutex;
 use tokio::sync::RwLock;
 use tokio::sync::broadcast;
 use tracing::warn;
@@ -63,6 +73,8 @@ pub(crate) struct ThreadManagerState {
     models_manager: Arc<ModelsManager>,
     skills_manager: Arc<SkillsManager>,
     session_source: SessionSource,
+    /// Worktrees of sub-agents spawned with `worktree: true`, until merged or discarded.
+    agent_worktrees: Mutex<HashMap<ThreadId, AgentWorktree>>,
     #[cfg(any(test, feature = "test-support"))]
     #[allow(dead_code)]
     // Captures submitted ops for testing purpose.
@@ -87,6 +99,7 @@ impl ThreadManager {
                 skills_manager: Arc::new(SkillsManager::new(codex_home)),
                 auth_manager,
                 session_source,
+                agent_worktrees: Mutex::new(HashMap::new()),
                 #[cfg(any(test, feature = "test-support"))]
                 ops_log: Arc::new(std::sync::Mutex::new(Vec::new())),
             }),
@@ -128,6 +141,7 @@ impl ThreadManager {
                 skills_manager: Arc::new(SkillsManager::new(codex_home)),
                 auth_manager,
                 session_source: SessionSource::Exec,
+                agent_worktrees: Mutex::new(HashMap::new()),
                 #[cfg(any(test, feature = "test-support"))]
                 ops_log: Arc::new(std::sync::Mutex::new(Vec::new())),
             }),
@@ -250,13 +264,26 @@ impl ThreadManager {
         self.state.threads.write().await.remove(thread_id)
     }
 
-    /// Closes all threads open in this ThreadManager
-    pub async fn remove_and_close_all_threads(&self) -> CodexResult<()> {
+    /// Closes all threads open in this ThreadManager and releases the
+    /// worktrees of their sub-agents. Returns the paths of worktrees that were
+    /// kept because they hold work that was never merged.
+    pub async fn remove_and_close_all_threads(&self) -> CodexResult<Vec<PathBuf>> {
         for thread in self.state.threads.read().await.values() {
             thread.submit(Op::Shutdown).await?;
         }
         self.state.threads.write().await.clear();
-        Ok(())
+        Ok(self.release_agent_worktrees().await)
+    }
+
+    /// Stops tracking every sub-agent worktree, removing those whose changes
+    /// are already merged and returning the paths of the ones left on disk.
+    pub async fn release_agent_worktrees(&self) -> Vec<PathBuf> {
+        self.state
+            .release_agent_worktrees()
+            .await
+            .into_iter()
+            .map(|worktree| worktree.path().to_path_buf())
+            .collect()
     }
 
     /// Fork an existing thread by taking messages up to the given position (not including
@@ -282,6 +309,53 @@ impl ThreadManager {
             .await
     }
 
+    /// Start a new thread from the rollout at `path`, replaying its history up to the last
+    /// checkpoint so an interrupted `codex exec` turn can be continued. Returns `None` when
+    /// the rollout's last turn already finished.
+    pub async fn continue_thread_from_checkpoint(
+        &self,
+        config: Config,
+        path: PathBuf,
+    ) -> CodexResult<Option<NewThread>> {
+        let history = RolloutRecorder::get_rollout_history(&path).await?;
+        let items = match truncation::truncate_rollout_after_last_checkpoint(
+            &history.get_rollout_items(),
+        ) {
+            CheckpointCut::Continue(items) => items,
+            CheckpointCut::Finished => return Ok(None),
+            CheckpointCut::Missing => {
+                return Err(CodexErr::UnsupportedOperation(format!(
+                    "{} has no checkpoints to continue from",
+                    path.display()
+                )));
+            }
+        };
+        self.state
+            .spawn_thread(
+                config,
+                InitialHistory::Forked(items),
+                Arc::clone(&self.state.auth_manager),
+                self.agent_control(),
+                Vec::new(),
+            )
+            .await
+            .map(Some)
+    }
+
+    /// Sub-agent threads running in their own git worktree, with the worktree path.
+    pub async fn agent_worktree_threads(&self) -> Vec<(ThreadId, PathBuf)> {
+        self.state.agent_worktree_threads().await
+    }
+
+    /// Diff, merge, cherry-pick or discard the worktree of a sub-agent thread.
+    pub async fn resolve_agent_worktree(
+        &self,
+        thread_id: ThreadId,
+        action: AgentWorktreeAction,
+    ) -> CodexResult<String> {
+        self.state.resolve_agent_worktree(thread_id, action).await
+    }
+
     pub(crate) fn agent_control(&self) -> AgentControl {
         AgentControl::new(Arc::downgrade(&self.state))
     }
@@ -426,6 +500,154 @@ impl ThreadManagerState {
         })
     }
 
+    pub(crate) async fn register_agent_worktree(
+        &self,
+        thread_id: ThreadId,
+        worktree: AgentWorktree,
+    ) {
+        self.agent_worktrees
+            .lock()
+            .await
+            .insert(thread_id, worktree);
+    }
+
+    pub(crate) async fn agent_worktree_threads(&self) -> Vec<(ThreadId, PathBuf)> {
+        self.agent_worktrees
+            .lock()
+            .await
+            .iter()
+            .map(|(thread_id, worktree)| (*thread_id, worktree.path().to_path_buf()))
+            .collect()
+    }
+
+    fn agent_worktree_not_found(thread_id: ThreadId) -> CodexErr {
+        CodexErr::UnsupportedOperation(format!("agent {thread_id} does not run in a worktree"))
+    }
+
+    /// The worktree of `thread_id`, once its agent has finished. Changes can
+    /// only be applied from a worktree nobody is still writing to.
+    pub(crate) async fn finished_agent_worktree(
+        &self,
+        thread_id: ThreadId,
+    ) -> CodexResult<AgentWorktree> {
+        let worktree = self
+            .agent_worktrees
+            .lock()
+            .await
+            .get(&thread_id)
+            .cloned()
+            .ok_or_else(|| Self::agent_worktree_not_found(thread_id))?;
+        if let Ok(thread) = self.get_thread(thread_id).await
+            && !is_final(&thread.agent_status().await)
+        {
+            return Err(CodexErr::UnsupportedOperation(format!(
+                "agent {thread_id} is still running; wait for it or close it first"
+            )));
+        }
+        Ok(worktree)
+    }
+
+    /// Applies `action` to the worktree of `thread_id`. `Diff` returns the
+    /// agent's diff; other actions require the agent to be idle and return a
+    /// short summary once the worktree is gone.
+    ///
+    /// Merges run in-process, so this is only for actions the user picked
+    /// themselves; the model goes through `merge_agent`, which runs them under
+    /// the turn's approval policy and sandbox.
+    pub(crate) async fn resolve_agent_worktree(
+        &self,
+        thread_id: ThreadId,
+        action: AgentWorktreeAction,
+    ) -> CodexResult<String> {
+        if action == AgentWorktreeAction::Diff {
+            let worktree = self
+                .agent_worktrees
+                .lock()
+                .await
+                .get(&thread_id)
+                .cloned()
+                .ok_or_else(|| Self::agent_worktree_not_found(thread_id))?;
+            return tokio::task::spawn_blocking(move || codex_git::agent_worktree_diff(&worktree))
+                .await?
+                .map_err(git_error);
+        }
+
+        let worktree = self.finished_agent_worktree(thread_id).await?;
+        let branch = worktree.branch().to_string();
+        let message = agent_merge_message(thread_id);
+        let summary = tokio::task::spawn_blocking(move || {
+            let mode = match action {
+                AgentWorktreeAction::Merge => Some(WorktreeMergeMode::Merge),
+                AgentWorktreeAction::CherryPick => Some(WorktreeMergeMode::CherryPick),
+                AgentWorktreeAction::Diff | AgentWorktreeAction::Discard => None,
+            };
+            let summary = match mode {
+                Some(mode) => {
+                    let commits = codex_git::merge_agent_worktree(&worktree, mode, &message)?;
+                    merge_summary(&branch, commits)
+                }
+                None => format!("discarded {branch}"),
+            };
+            codex_git::remove_agent_worktree(&worktree)?;
+            Ok::<_, GitToolingError>(summary)
+        })
+        .await?
+        .map_err(git_error)?;
+        self.agent_worktrees.lock().await.remove(&thread_id);
+        Ok(summary)
+    }
+
+    /// Removes the worktree of `thread_id` and its branch, discarding anything
+    /// that was not applied.
+    pub(crate) async fn remove_agent_worktree(&self, thread_id: ThreadId) -> CodexResult<()> {
+        let worktree = self
+            .agent_worktrees
+            .lock()
+            .await
+            .get(&thread_id)
+            .cloned()
+            .ok_or_else(|| Self::agent_worktree_not_found(thread_id))?;
+        tokio::task::spawn_blocking(move || codex_git::remove_agent_worktree(&worktree))
+            .await?
+            .map_err(git_error)?;
+        self.agent_worktrees.lock().await.remove(&thread_id);
+        Ok(())
+    }
+
+    /// Forgets every remaining agent worktree when the session ends. Worktrees
+    /// with nothing left to merge are removed along with their branch; the rest
+    /// are kept so no work is lost, and returned.
+    pub(crate) async fn release_agent_worktrees(&self) -> Vec<AgentWorktree> {
+        let worktrees: Vec<AgentWorktree> = self
+            .agent_worktrees
+            .lock()
+            .await
+            .drain()
+            .map(|(_, worktree)| worktree)
+            .collect();
+        let mut kept = Vec::new();
+        for worktree in worktrees {
+            let result = tokio::task::spawn_blocking({
+                let worktree = worktree.clone();
+                move || codex_git::remove_agent_worktree_if_merged(&worktree)
+            })
+            .await;
+            match result {
+                Ok(Ok(true)) => {}
+                Ok(Ok(false)) => kept.push(worktree),
+                Ok(Err(err)) => {
+                    warn!("failed to check agent worktree for unmerged work: {err}");
+                    kept.push(worktree);
+                }
+                Err(err) => {
+                    warn!("failed to check agent worktree for unmerged work: {err}");
+                    kept.push(worktree);
+                }
+            }
+        }
+        kept
+    }
+
     pub(crate) fn notify_thread_created(&self, thread_id: ThreadId) {
         let _ = self.thread_created_tx.send(thread_id);
     }
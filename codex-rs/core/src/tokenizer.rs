@@ -0,0 +1,350 @@
+//! Token counting for truncation budgets and context-window estimates.
+//!
+//! Models whose [`ModelInfo::tokenizer`] names a known encoding are counted
+//! with the matching BPE rank table, read from
+//! `$CODEX_HOME/tokenizers/<encoding>.tiktoken`. With the `tokenizer_download`
+//! feature enabled, a missing table is downloaded in the background (through
+//! `[network_proxy]` when it is on) and used once it has arrived. Until then,
+//! and for models with no known encoding, counts use the bytes-per-token
+//! heuristic from [`crate::truncate`].
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::config::network_proxy::network_proxy_url;
+use crate::features::Feature;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::openai_models::TokenizerEncoding;
+use codex_utils_tokenizer::Bpe;
+use codex_utils_tokenizer::Encoding;
+use sha2::Digest;
+use sha2::Sha256;
+use tracing::debug;
+use tracing::warn;
+
+use crate::truncate::approx_token_count;
+
+const TOKENIZERS_DIR: &str = "tokenizers";
+const TABLES_URL: &str = "https://openaipublic.blob.core.windows.net/encodings";
+
+/// Counts tokens for one model. Cheap to clone.
+#[derive(Clone, Default)]
+pub(crate) struct Tokenizer {
+    bpe: Option<Arc<Bpe>>,
+}
+
+impl fmt::Debug for Tokenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.bpe {
+            Some(bpe) => write!(f, "Tokenizer({})", bpe.encoding().name()),
+            None => f.write_str("Tokenizer(heuristic)"),
+        }
+    }
+}
+
+impl Tokenizer {
+    /// Selects the tokenizer for `model_info`, falling back to the heuristic
+    /// when the model has no known encoding or its table is unavailable.
+    pub(crate) fn for_model(model_info: &ModelInfo, config: &Config) -> Self {
+        let download = config
+            .features
+            .enabled(Feature::TokenizerDownload)
+            .then(|| TableDownload {
+                proxy_url: network_proxy_url(config),
+            });
+        Self::from_tables(model_info, &config.codex_home, download)
+    }
+
+    fn from_tables(
+        model_info: &ModelInfo,
+        codex_home: &Path,
+        download: Option<TableDownload>,
+    ) -> Self {
+        let bpe = model_info
+            .tokenizer
+            .and_then(|encoding| load_encoding(codex_home, encoding, download));
+        Self { bpe }
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_bpe(bpe: Arc<Bpe>) -> Self {
+        Self { bpe: Some(bpe) }
+    }
+
+    /// Whether counts come from a real tokenizer rather than the heuristic.
+    pub(crate) fn is_exact(&self) -> bool {
+        self.bpe.is_some()
+    }
+
+    pub(crate) fn count_tokens(&self, text: &str) -> usize {
+        match &self.bpe {
+            Some(bpe) => bpe.count(text),
+            None => approx_token_count(text),
+        }
+    }
+}
+
+fn encoding(encoding: TokenizerEncoding) -> Encoding {
+    match encoding {
+        TokenizerEncoding::O200kBase => Encoding::O200kBase,
+        TokenizerEncoding::Cl100kBase => Encoding::Cl100kBase,
+    }
+}
+
+/// SHA-256 of the published rank table, as pinned by tiktoken.
+fn table_sha256(encoding: Encoding) -> &'static str {
+    match encoding {
+        Encoding::O200kBase => "446a9538cb6c348e3516120d7c08b09f57c36495e2acfffe59a5bf8b0cfb1a2d",
+        Encoding::Cl100kBase => "223921b76ee99bde995b7ff738513eef100fb51d18c93597a113bcffe865b2a7",
+    }
+}
+
+/// Whether and how a missing rank table may be fetched.
+#[derive(Clone, Debug, Default)]
+struct TableDownload {
+    /// Route the download through `[network_proxy]` when it is enabled.
+    proxy_url: Option<String>,
+}
+
+/// The parsed table at one path, filled in by whichever caller gets there first.
+type TableSlot = Arc<OnceLock<Option<Arc<Bpe>>>>;
+
+/// Rank tables are large, so each one is parsed at most once per process.
+/// Failed loads are cached too, to avoid retrying on every turn. The map lock
+/// is only held to find a table's slot; parsing happens outside it.
+fn loaded_tables() -> std::sync::MutexGuard<'static, HashMap<PathBuf, TableSlot>> {
+    static LOADED: OnceLock<Mutex<HashMap<PathBuf, TableSlot>>> = OnceLock::new();
+    LOADED
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn load_encoding(
+    codex_home: &Path,
+    encoding_id: TokenizerEncoding,
+    download: Option<TableDownload>,
+) -> Option<Arc<Bpe>> {
+    let encoding = encoding(encoding_id);
+    let path = codex_home.join(TOKENIZERS_DIR).join(encoding.file_name());
+    let slot = Arc::clone(loaded_tables().entry(path.clone()).or_default());
+    slot.get_or_init(|| read_table(encoding, &path, download))
+        .clone()
+}
+
+fn read_table(
+    encoding: Encoding,
+    path: &Path,
+    download: Option<TableDownload>,
+) -> Option<Arc<Bpe>> {
+    match std::fs::read_to_string(path) {
+        Ok(data) => match Bpe::from_tiktoken(encoding, &data) {
+            Ok(bpe) => Some(Arc::new(bpe)),
+            Err(err) => {
+                warn!("ignoring tokenizer table {}: {err}", path.display());
+                None
+            }
+        },
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            match download {
+                Some(download) => spawn_table_download(encoding, path.to_path_buf(), download),
+                None => debug!(
+                    "tokenizer table {} not installed and tokenizer_download is off, using heuristic",
+                    path.display()
+                ),
+            }
+            None
+        }
+        Err(err) => {
+            debug!(
+                "tokenizer table {} unavailable, using heuristic: {err}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Fetches a missing table into `path` and makes it available to tokenizers
+/// created afterwards. Does nothing outside a Tokio runtime.
+fn spawn_table_download(encoding: Encoding, path: PathBuf, download: TableDownload) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    runtime.spawn(async move {
+        let url = format!("{TABLES_URL}/{}", encoding.file_name());
+        let result = download_table(
+            &url,
+            table_sha256(encoding),
+            encoding,
+            &path,
+            download.proxy_url.as_deref(),
+        )
+        .await;
+        match result {
+            Ok(bpe) => {
+                let slot = Arc::new(OnceLock::from(Some(Arc::new(bpe))));
+                loaded_tables().insert(path, slot);
+            }
+            Err(err) => {
+                debug!("failed to download tokenizer table {url}, using heuristic: {err}");
+            }
+        }
+    });
+}
+
+async fn download_table(
+    url: &str,
+    sha256: &str,
+    encoding: Encoding,
+    path: &Path,
+    proxy_url: Option<&str>,
+) -> Result<Bpe, String> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy_url) = proxy_url {
+        let proxy = reqwest::Proxy::all(proxy_url).map_err(|err| err.to_string())?;
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build().map_err(|err| err.to_string())?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| err.to_string())?;
+    let data = response.bytes().await.map_err(|err| err.to_string())?;
+    let digest = format!("{:x}", Sha256::digest(&data));
+    if digest != sha256 {
+        return Err(format!("unexpected sha256 {digest}"));
+    }
+    let text = std::str::from_utf8(&data).map_err(|err| err.to_string())?;
+    let bpe = Bpe::from_tiktoken(encoding, text).map_err(|err| err.to_string())?;
+
+    // Write to a sibling file first so a concurrent reader never sees a
+    // partial table.
+    let dir = path.parent().ok_or("table path has no parent")?;
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|err| err.to_string())?;
+    let partial = path.with_extension(format!("partial-{}", std::process::id()));
+    tokio::fs::write(&partial, &data)
+        .await
+        .map_err(|err| err.to_string())?;
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|err| err.to_string())?;
+    Ok(bpe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models_manager::model_info::find_model_info_for_slug;
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use pretty_assertions::assert_eq;
+
+    fn write_table(codex_home: &Path, encoding: Encoding, merges: &[&str]) {
+        let mut data = String::new();
+        for byte in 0..=u8::MAX {
+            data.push_str(&format!("{} {byte}\n", BASE64.encode([byte])));
+        }
+        for (offset, merge) in merges.iter().enumerate() {
+            data.push_str(&format!("{} {}\n", BASE64.encode(merge), 256 + offset));
+        }
+        let dir = codex_home.join(TOKENIZERS_DIR);
+        std::fs::create_dir_all(&dir).expect("create tokenizers dir");
+        std::fs::write(dir.join(encoding.file_name()), data).expect("write table");
+    }
+
+    fn model_info(tokenizer: Option<TokenizerEncoding>) -> ModelInfo {
+        let mut model_info = find_model_info_for_slug("gpt-5.1");
+        model_info.tokenizer = tokenizer;
+        model_info
+    }
+
+    #[test]
+    fn uses_installed_table_for_model_encoding() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        write_table(
+            codex_home.path(),
+            Encoding::O200kBase,
+            &["he", "ll", "hell", "hello"],
+        );
+
+        let tokenizer = Tokenizer::from_tables(
+            &model_info(Some(TokenizerEncoding::O200kBase)),
+            codex_home.path(),
+            None,
+        );
+        assert!(tokenizer.is_exact());
+        assert_eq!(tokenizer.count_tokens("hellohello"), 2);
+    }
+
+    #[tokio::test]
+    async fn downloads_and_verifies_missing_tables() {
+        let mut data = String::new();
+        for byte in 0..=u8::MAX {
+            data.push_str(&format!("{} {byte}\n", BASE64.encode([byte])));
+        }
+        data.push_str(&format!("{} 256\n", BASE64.encode("hi")));
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::path("/o200k_base.tiktoken"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(data.clone()))
+            .mount(&server)
+            .await;
+        let url = format!("{}/o200k_base.tiktoken", server.uri());
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let path = codex_home
+            .path()
+            .join(TOKENIZERS_DIR)
+            .join(Encoding::O200kBase.file_name());
+
+        let err = download_table(&url, "0", Encoding::O200kBase, &path, None)
+            .await
+            .expect_err("hash mismatch");
+        assert!(err.starts_with("unexpected sha256"), "{err}");
+        assert!(!path.exists());
+
+        let sha256 = format!("{:x}", Sha256::digest(data.as_bytes()));
+        let bpe = download_table(&url, &sha256, Encoding::O200kBase, &path, None)
+            .await
+            .expect("download");
+        assert_eq!(bpe.encode("hi"), vec![256]);
+        assert_eq!(std::fs::read_to_string(&path).expect("cached table"), data);
+
+        // With a proxy configured, the request goes to the proxy instead of the table host.
+        std::fs::remove_file(&path).expect("remove cached table");
+        let proxied = download_table(
+            "http://tables.invalid/o200k_base.tiktoken",
+            &sha256,
+            Encoding::O200kBase,
+            &path,
+            Some(&server.uri()),
+        )
+        .await
+        .expect("download through proxy");
+        assert_eq!(proxied.encode("hi"), vec![256]);
+    }
+
+    #[test]
+    fn falls_back_to_heuristic() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let missing_table = Tokenizer::from_tables(
+            &model_info(Some(TokenizerEncoding::Cl100kBase)),
+            codex_home.path(),
+            None,
+        );
+        let unknown_model = Tokenizer::from_tables(&model_info(None), codex_home.path(), None);
+        for tokenizer in [missing_table, unknown_model] {
+            assert!(!tokenizer.is_exact());
+            assert_eq!(tokenizer.count_tokens("hello hello"), 3);
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use crate::apply_patch::format_post_edit_results;
+use crate::apply_patch::run_post_edit_commands;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::exec::ExecToolCallOutput;
 use crate::function_tool::FunctionCallError;
+use crate::lsp::format_lsp_errors;
 use crate::parse_command::parse_command;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandBeginEvent;
@@ -12,9 +15,12 @@ use crate::protocol::ExecCommandSource;
 use crate::protocol::FileChange;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::PatchApplyOutcome;
+use crate::protocol::PostEditCommandResult;
 use crate::protocol::TurnDiffEvent;
 use crate::tools::context::SharedTurnDiffTracker;
 use crate::tools::sandboxing::ToolError;
+use codex_apply_patch::TransactionOutcome;
 use codex_protocol::parse_command::ParsedCommand;
 use std::collections::HashMap;
 use std::path::Path;
@@ -194,7 +200,8 @@ impl ToolEmitter {
                     changes.clone(),
                     output.stdout.text.clone(),
                     output.stderr.text.clone(),
-                    output.exit_code == 0,
+                    TransactionOutcome::from_exit_code(output.exit_code),
+                    Vec::new(),
                 )
                 .await;
             }
@@ -207,7 +214,8 @@ impl ToolEmitter {
                     changes.clone(),
                     output.stdout.text.clone(),
                     output.stderr.text.clone(),
-                    output.exit_code == 0,
+                    TransactionOutcome::from_exit_code(output.exit_code),
+                    Vec::new(),
                 )
                 .await;
             }
@@ -220,7 +228,8 @@ impl ToolEmitter {
                     changes.clone(),
                     String::new(),
                     (*message).to_string(),
-                    false,
+                    TransactionOutcome::NotApplied,
+                    Vec::new(),
                 )
                 .await;
             }
@@ -261,10 +270,16 @@ impl ToolEmitter {
         ctx: ToolEventCtx<'_>,
     ) -> String {
         match self {
-            Self::Shell { freeform: true, .. } => {
-                super::format_exec_output_for_model_freeform(output, ctx.turn.truncation_policy)
-            }
-            _ => super::format_exec_output_for_model_structured(output, ctx.turn.truncation_policy),
+            Self::Shell { freeform: true, .. } => super::format_exec_output_for_model_freeform(
+                output,
+                ctx.turn.truncation_policy,
+                &ctx.turn.tokenizer,
+            ),
+            _ => super::format_exec_output_for_model_structured(
+                output,
+                ctx.turn.truncation_policy,
+                &ctx.turn.tokenizer,
+            ),
         }
     }
 
@@ -273,6 +288,30 @@ impl ToolEmitter {
         ctx: ToolEventCtx<'_>,
         out: Result<ExecToolCallOutput, ToolError>,
     ) -> Result<String, FunctionCallError> {
+        // Formatters and linters from `[post_edit]` run once a patch has
+        // landed; their results go to the model and the PatchApplyEnd event.
+        // Language servers then check what is on disk, and their errors go
+        // to the model.
+        let mut post_edit = Vec::new();
+        let out = match (self, out) {
+            (Self::ApplyPatch { changes, .. }, Ok(mut output)) if output.exit_code == 0 => {
+                post_edit = run_post_edit_commands(ctx.session, ctx.turn, changes).await;
+                if let Some(report) = format_post_edit_results(&post_edit) {
+                    output.aggregated_output.text.push_str(&report);
+                }
+                let lsp_errors = ctx
+                    .session
+                    .services
+                    .lsp_manager
+                    .collect_errors(&ctx.turn.cwd, changes, &ctx.turn.sandbox_policy)
+                    .await;
+                if let Some(report) = format_lsp_errors(&lsp_errors) {
+                    output.aggregated_output.text.push_str(&report);
+                }
+                Ok(output)
+            }
+            (_, out) => out,
+        };
         let (event, result) = match out {
             Ok(output) => {
                 let content = self.format_exec_output_for_model(&output, ctx);
@@ -316,7 +355,20 @@ impl ToolEmitter {
                 (event, result)
             }
         };
-        self.emit(ctx, event).await;
+        match (self, event) {
+            (Self::ApplyPatch { changes, .. }, ToolEventStage::Success(output)) => {
+                emit_patch_end(
+                    ctx,
+                    changes.clone(),
+                    output.stdout.text,
+                    output.stderr.text,
+                    TransactionOutcome::from_exit_code(output.exit_code),
+                    post_edit,
+                )
+                .await;
+            }
+            (_, event) => self.emit(ctx, event).await,
+        }
         result
     }
 }
@@ -385,7 +437,11 @@ async fn emit_exec_stage(
                 aggregated_output: output.aggregated_output.text.clone(),
                 exit_code: output.exit_code,
                 duration: output.duration,
-                formatted_output: format_exec_output_str(&output, ctx.turn.truncation_policy),
+                formatted_output: format_exec_output_str(
+                    &output,
+                    ctx.turn.truncation_policy,
+                    &ctx.turn.tokenizer,
+                ),
             };
             emit_exec_end(ctx, exec_input, exec_result).await;
         }
@@ -432,12 +488,22 @@ async fn emit_exec_end(
         .await;
 }
 
+fn patch_apply_outcome(outcome: TransactionOutcome) -> PatchApplyOutcome {
+    match outcome {
+        TransactionOutcome::Committed => PatchApplyOutcome::Committed,
+        TransactionOutcome::NotApplied => PatchApplyOutcome::NotApplied,
+        TransactionOutcome::RolledBack => PatchApplyOutcome::RolledBack,
+        TransactionOutcome::RollbackFailed => PatchApplyOutcome::RollbackFailed,
+    }
+}
+
 async fn emit_patch_end(
     ctx: ToolEventCtx<'_>,
     changes: HashMap<PathBuf, FileChange>,
     stdout: String,
     stderr: String,
-    success: bool,
+    outcome: TransactionOutcome,
+    post_edit: Vec<PostEditCommandResult>,
 ) {
     ctx.session
         .send_event(
@@ -445,10 +511,12 @@ async fn emit_patch_end(
             EventMsg::PatchApplyEnd(PatchApplyEndEvent {
                 call_id: ctx.call_id.to_string(),
                 turn_id: ctx.turn.sub_id.clone(),
+                outcome: Some(patch_apply_outcome(outcome)),
                 stdout,
                 stderr,
-                success,
+                success: outcome == TransactionOutcome::Committed,
                 changes,
+                post_edit,
             }),
         )
         .await;
@@ -297,13 +297,15 @@ Your patch language is a stripped‑down, file‑oriented diff format designed t
 
 Within that envelope, you get a sequence of file operations.
 You MUST include a header to specify the action you are taking.
-Each operation starts with one of three headers:
+Each operation starts with one of four headers:
 
 *** Add File: <path> - create a new file. Every following line is a + line (the initial contents).
+*** Add Binary File: <path> - create a binary file. Every following line is a + line of base64 (the initial contents).
 *** Delete File: <path> - remove an existing file. Nothing follows.
 *** Update File: <path> - patch an existing file in place (optionally with a rename).
 
 May be immediately followed by *** Move to: <new path> if you want to rename the file.
+An Add File, Add Binary File or Update File header (after any Move to) may be followed by *** Set Mode: <octal>, such as 755 to make a script executable. An Update File with only a Set Mode line changes just the permissions.
 Then one or more “hunks”, each introduced by @@ (optionally followed by a hunk header).
 Within a hunk each line starts with:
 
@@ -329,11 +331,13 @@ The full grammar definition is below:
 Patch := Begin { FileOp } End
 Begin := "*** Begin Patch" NEWLINE
 End := "*** End Patch" NEWLINE
-FileOp := AddFile | DeleteFile | UpdateFile
-AddFile := "*** Add File: " path NEWLINE { "+" line NEWLINE }
+FileOp := AddFile | AddBinaryFile | DeleteFile | UpdateFile
+AddFile := "*** Add File: " path NEWLINE [ SetMode ] { "+" line NEWLINE }
+AddBinaryFile := "*** Add Binary File: " path NEWLINE [ SetMode ] { "+" base64 NEWLINE }
 DeleteFile := "*** Delete File: " path NEWLINE
-UpdateFile := "*** Update File: " path NEWLINE [ MoveTo ] { Hunk }
+UpdateFile := "*** Update File: " path NEWLINE [ MoveTo ] [ SetMode ] { Hunk }
 MoveTo := "*** Move to: " newPath NEWLINE
+SetMode := "*** Set Mode: " octal NEWLINE
 Hunk := "@@" [ header ] NEWLINE { HunkLine } [ "*** End of File" NEWLINE ]
 HunkLine := (" " | "-" | "+") text NEWLINE
 
@@ -1,4 +1,5 @@
 use crate::agent::AgentStatus;
+use crate::agent::AgentTreeBudget;
 use crate::agent::exceeds_thread_spawn_depth_limit;
 use crate::codex::Session;
 use crate::codex::TurnContext;
@@ -15,6 +16,7 @@ use crate::tools::registry::ToolKind;
 use async_trait::async_trait;
 use codex_protocol::ThreadId;
 use codex_protocol::models::BaseInstructions;
+use codex_protocol::protocol::AgentTreeUsage;
 use codex_protocol::protocol::CollabAgentInteractionBeginEvent;
 use codex_protocol::protocol::CollabAgentInteractionEndEvent;
 use codex_protocol::protocol::CollabAgentSpawnBeginEvent;
@@ -72,6 +74,7 @@ impl ToolHandler for CollabHandler {
             "send_input" => send_input::handle(session, turn, call_id, arguments).await,
             "wait" => wait::handle(session, turn, call_id, arguments).await,
             "close_agent" => close_agent::handle(session, turn, call_id, arguments).await,
+            "merge_agent" => merge_agent::handle(session, turn, call_id, arguments).await,
             other => Err(FunctionCallError::RespondToModel(format!(
                 "unsupported collab tool {other}"
             ))),
@@ -81,10 +84,14 @@ impl ToolHandler for CollabHandler {
 
 mod spawn {
     use super::*;
-    use crate::agent::AgentRole;
+    use crate::agent::role::DEFAULT_AGENT_ROLE;
+    use crate::agent::role::apply_agent_role;
+    use crate::agent::worktree::create_agent_worktree;
+    use crate::agent::worktree::discard_agent_worktree;
 
     use crate::agent::exceeds_thread_spawn_depth_limit;
     use crate::agent::next_thread_spawn_depth;
+    use crate::agent::output_schema::check_output_schema;
     use codex_protocol::protocol::SessionSource;
     use codex_protocol::protocol::SubAgentSource;
     use std::sync::Arc;
@@ -92,12 +99,17 @@ mod spawn {
     #[derive(Debug, Deserialize)]
     struct SpawnAgentArgs {
         message: String,
-        agent_type: Option<AgentRole>,
+        agent_type: Option<String>,
+        #[serde(default)]
+        worktree: bool,
+        output_schema: Option<serde_json::Value>,
     }
 
     #[derive(Debug, Serialize)]
     struct SpawnAgentResult {
         agent_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        worktree_branch: Option<String>,
     }
 
     pub async fn handle(
@@ -107,16 +119,19 @@ mod spawn {
         arguments: String,
     ) -> Result<ToolOutput, FunctionCallError> {
         let args: SpawnAgentArgs = parse_arguments(&arguments)?;
-        let agent_role = args.agent_type.unwrap_or(AgentRole::Default);
+        let agent_role = args.agent_type.as_deref().unwrap_or(DEFAULT_AGENT_ROLE);
         let prompt = args.message;
         if prompt.trim().is_empty() {
             return Err(FunctionCallError::RespondToModel(
                 "Empty message can't be sent to an agent".to_string(),
             ));
         }
+        if let Some(output_schema) = &args.output_schema {
+            check_output_schema(output_schema).map_err(FunctionCallError::RespondToModel)?;
+        }
         let session_source = turn.client.get_session_source();
         let child_depth = next_thread_spawn_depth(&session_source);
-        if exceeds_thread_spawn_depth_limit(child_depth) {
+        if exceeds_thread_spawn_depth_limit(child_depth, turn.client.config().agent_max_depth) {
             return Err(FunctionCallError::RespondToModel(
                 "Agent depth limit reached. Solve the task yourself.".to_string(),
             ));
@@ -137,9 +152,19 @@ mod spawn {
             turn.as_ref(),
             child_depth,
         )?;
-        agent_role
-            .apply_to_config(&mut config)
-            .map_err(FunctionCallError::RespondToModel)?;
+        apply_agent_role(&mut config, agent_role).map_err(FunctionCallError::RespondToModel)?;
+        let worktree = if args.worktree {
+            let worktree = create_agent_worktree(&config).await.map_err(|err| {
+                FunctionCallError::RespondToModel(format!("failed to create worktree: {err}"))
+            })?;
+            config.cwd = worktree.cwd();
+            Some(worktree)
+        } else {
+            None
+        };
+        let worktree_branch = worktree
+            .as_ref()
+            .map(|worktree| worktree.branch().to_string());
 
         let result = session
             .services
@@ -151,9 +176,22 @@ mod spawn {
                     parent_thread_id: session.conversation_id,
                     depth: child_depth,
                 })),
+                args.output_schema,
             )
             .await
             .map_err(collab_spawn_error);
+        if let Some(worktree) = worktree {
+            match &result {
+                Ok(thread_id) => {
+                    session
+                        .services
+                        .agent_control
+                        .register_worktree(*thread_id, worktree)
+                        .await;
+                }
+                Err(_) => discard_agent_worktree(worktree).await,
+            }
+        }
         let (new_thread_id, status) = match &result {
             Ok(thread_id) => (
                 Some(*thread_id),
@@ -178,6 +216,7 @@ mod spawn {
 
         let content = serde_json::to_string(&SpawnAgentResult {
             agent_id: new_thread_id.to_string(),
+            worktree_branch,
         })
         .map_err(|err| {
             FunctionCallError::Fatal(format!("failed to serialize spawn_agent result: {err}"))
@@ -282,6 +321,7 @@ mod send_input {
 
 mod wait {
     use super::*;
+    use crate::agent::output_schema::parse_structured_result;
     use crate::agent::status::is_final;
     use futures::FutureExt;
     use futures::StreamExt;
@@ -303,6 +343,12 @@ mod wait {
     #[derive(Debug, Serialize)]
     struct WaitResult {
         status: HashMap<ThreadId, AgentStatus>,
+        /// Final answers of completed agents spawned with an `output_schema`.
+        #[serde(skip_serializing_if = "HashMap::is_empty")]
+        results: HashMap<ThreadId, serde_json::Value>,
+        /// Why a completed agent's final answer did not match its `output_schema`.
+        #[serde(skip_serializing_if = "HashMap::is_empty")]
+        result_errors: HashMap<ThreadId, String>,
         timed_out: bool,
     }
 
@@ -373,6 +419,7 @@ mod wait {
                                 sender_thread_id: session.conversation_id,
                                 call_id: call_id.clone(),
                                 statuses,
+                                tree_usage: Some(tree_usage(&session, &turn)),
                             }
                             .into(),
                         )
@@ -418,8 +465,28 @@ mod wait {
 
         // Convert payload.
         let statuses_map = statuses.clone().into_iter().collect::<HashMap<_, _>>();
+        let mut results = HashMap::new();
+        let mut result_errors = HashMap::new();
+        for (id, status) in &statuses {
+            let AgentStatus::Completed(Some(message)) = status else {
+                continue;
+            };
+            let Some(schema) = session.services.agent_control.output_schema(*id) else {
+                continue;
+            };
+            match parse_structured_result(&schema, message) {
+                Ok(value) => {
+                    results.insert(*id, value);
+                }
+                Err(err) => {
+                    result_errors.insert(*id, err);
+                }
+            }
+        }
         let result = WaitResult {
             status: statuses_map.clone(),
+            results,
+            result_errors,
             timed_out: statuses.is_empty(),
         };
 
@@ -431,6 +498,7 @@ mod wait {
                     sender_thread_id: session.conversation_id,
                     call_id,
                     statuses: statuses_map,
+                    tree_usage: Some(tree_usage(&session, &turn)),
                 }
                 .into(),
             )
@@ -559,11 +627,214 @@ pub mod close_agent {
     }
 }
 
+mod merge_agent {
+    use super::*;
+    use crate::agent::worktree::AgentWorktreeAction;
+    use crate::agent::worktree::agent_merge_message;
+    use crate::agent::worktree::git_error;
+    use crate::agent::worktree::merge_summary;
+    use crate::exec::ExecToolCallOutput;
+    use crate::exec_env::create_env;
+    use crate::exec_policy::ExecApprovalRequest;
+    use crate::protocol::ExecCommandSource;
+    use crate::sandboxing::SandboxPermissions;
+    use crate::tools::events::ToolEmitter;
+    use crate::tools::events::ToolEventCtx;
+    use crate::tools::orchestrator::ToolOrchestrator;
+    use crate::tools::runtimes::shell::ShellRequest;
+    use crate::tools::runtimes::shell::ShellRuntime;
+    use crate::tools::sandboxing::ExecApprovalRequirement;
+    use crate::tools::sandboxing::ToolCtx;
+    use crate::tools::sandboxing::ToolError;
+    use codex_git::WorktreeMerge;
+    use codex_git::WorktreeMergeMode;
+    use std::sync::Arc;
+
+    #[derive(Debug, Deserialize)]
+    struct MergeAgentArgs {
+        id: String,
+        action: AgentWorktreeAction,
+    }
+
+    pub async fn handle(
+        session: Arc<Session>,
+        turn: Arc<TurnContext>,
+        call_id: String,
+        arguments: String,
+    ) -> Result<ToolOutput, FunctionCallError> {
+        let args: MergeAgentArgs = parse_arguments(&arguments)?;
+        let agent_id = agent_id(&args.id)?;
+        let agent_control = &session.services.agent_control;
+        let mode = match args.action {
+            AgentWorktreeAction::Merge => WorktreeMergeMode::Merge,
+            AgentWorktreeAction::CherryPick => WorktreeMergeMode::CherryPick,
+            AgentWorktreeAction::Diff | AgentWorktreeAction::Discard => {
+                let content = agent_control
+                    .resolve_worktree(agent_id, args.action)
+                    .await
+                    .map_err(merge_agent_error)?;
+                let content = if args.action == AgentWorktreeAction::Diff && content.is_empty() {
+                    "no changes".to_string()
+                } else {
+                    content
+                };
+                return Ok(ToolOutput::Function {
+                    content,
+                    success: Some(true),
+                    content_items: None,
+                });
+            }
+        };
+
+        let worktree = agent_control
+            .finished_worktree(agent_id)
+            .await
+            .map_err(merge_agent_error)?;
+        let branch = worktree.branch().to_string();
+        let message = agent_merge_message(agent_id);
+        let merge = tokio::task::spawn_blocking(move || {
+            codex_git::prepare_agent_worktree_merge(&worktree, mode, &message)
+        })
+        .await
+        .map_err(|err| merge_agent_error(err.into()))?
+        .map_err(|err| merge_agent_error(git_error(err)))?;
+        let commits = match merge {
+            Some(merge) => {
+                let commits = merge.commits;
+                run_merge(&session, &turn, &call_id, merge).await?;
+                commits
+            }
+            None => 0,
+        };
+        agent_control
+            .remove_worktree(agent_id)
+            .await
+            .map_err(merge_agent_error)?;
+
+        Ok(ToolOutput::Function {
+            content: merge_summary(&branch, commits),
+            success: Some(true),
+            content_items: None,
+        })
+    }
+
+    /// Applies `merge` to the parent's checkout like any other command the
+    /// model runs: under the turn's approval policy and sandbox. A merge that
+    /// stops on a conflict is aborted so the checkout is left as it was.
+    async fn run_merge(
+        session: &Arc<Session>,
+        turn: &Arc<TurnContext>,
+        call_id: &str,
+        merge: WorktreeMerge,
+    ) -> Result<(), FunctionCallError> {
+        let command = git_command(&merge.args);
+        let mut env = create_env(
+            &turn.shell_environment_policy,
+            Some(session.conversation_id),
+        );
+        env.extend(merge.env);
+        let features = session.features();
+        let exec_approval_requirement = session
+            .services
+            .exec_policy
+            .create_exec_approval_requirement_for_command(ExecApprovalRequest {
+                features: &features,
+                command: &command,
+                approval_policy: turn.approval_policy,
+                sandbox_policy: &turn.sandbox_policy,
+                sandbox_permissions: SandboxPermissions::UseDefault,
+                prefix_rule: None,
+            })
+            .await;
+        let req = ShellRequest {
+            command: command.clone(),
+            cwd: merge.cwd.clone(),
+            timeout_ms: None,
+            env,
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            justification: Some("Apply the changes a sub-agent made in its worktree.".to_string()),
+            exec_approval_requirement,
+        };
+
+        let emitter = ToolEmitter::shell(command, merge.cwd, ExecCommandSource::Agent, false);
+        emitter
+            .begin(ToolEventCtx::new(
+                session.as_ref(),
+                turn.as_ref(),
+                call_id,
+                None,
+            ))
+            .await;
+        let out = run_git(session, turn, call_id, &req).await;
+        let stopped = matches!(&out, Ok(output) if output.exit_code != 0);
+        let result = emitter
+            .finish(
+                ToolEventCtx::new(session.as_ref(), turn.as_ref(), call_id, None),
+                out,
+            )
+            .await;
+        if stopped {
+            let abort = ShellRequest {
+                command: git_command(&merge.abort_args),
+                justification: None,
+                exec_approval_requirement: ExecApprovalRequirement::Skip {
+                    bypass_sandbox: false,
+                    proposed_execpolicy_amendment: None,
+                },
+                ..req
+            };
+            if let Err(err) = run_git(session, turn, call_id, &abort).await {
+                tracing::warn!("failed to abort agent worktree merge: {err:?}");
+            }
+        }
+        result.map(|_| ())
+    }
+
+    async fn run_git(
+        session: &Arc<Session>,
+        turn: &Arc<TurnContext>,
+        call_id: &str,
+        req: &ShellRequest,
+    ) -> Result<ExecToolCallOutput, ToolError> {
+        let tool_ctx = ToolCtx {
+            session: session.as_ref(),
+            turn: turn.as_ref(),
+            call_id: call_id.to_string(),
+            tool_name: "merge_agent".to_string(),
+        };
+        ToolOrchestrator::new()
+            .run(
+                &mut ShellRuntime::new(),
+                req,
+                &tool_ctx,
+                turn,
+                turn.approval_policy,
+            )
+            .await
+    }
+
+    fn git_command(args: &[String]) -> Vec<String> {
+        std::iter::once("git".to_string())
+            .chain(args.iter().cloned())
+            .collect()
+    }
+
+    fn merge_agent_error(err: CodexErr) -> FunctionCallError {
+        FunctionCallError::RespondToModel(format!("merge_agent failed: {err}"))
+    }
+}
+
 fn agent_id(id: &str) -> Result<ThreadId, FunctionCallError> {
     ThreadId::from_string(id)
         .map_err(|e| FunctionCallError::RespondToModel(format!("invalid agent id {id}: {e:?}")))
 }
 
+/// Usage of the caller's agent tree, reported alongside wait results.
+fn tree_usage(session: &Session, turn: &TurnContext) -> AgentTreeUsage {
+    let budget = AgentTreeBudget::from_config(&turn.client.config());
+    session.services.agent_control.tree_usage(&budget)
+}
+
 fn collab_spawn_error(err: CodexErr) -> FunctionCallError {
     match err {
         CodexErr::UnsupportedOperation(_) => {
@@ -619,7 +890,7 @@ fn build_agent_spawn_config(
         })?;
 
     // If the new agent will be at max depth:
-    if exceeds_thread_spawn_depth_limit(child_depth + 1) {
+    if exceeds_thread_spawn_depth_limit(child_depth + 1, config.agent_max_depth) {
         config.features.disable(Feature::Collab);
     }
 
@@ -631,7 +902,6 @@ mod tests {
     use super::*;
     use crate::CodexAuth;
     use crate::ThreadManager;
-    use crate::agent::MAX_THREAD_SPAWN_DEPTH;
     use crate::built_in_model_providers;
     use crate::client::ModelClient;
     use crate::codex::make_session_and_context;
@@ -743,6 +1013,29 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn spawn_agent_rejects_non_object_output_schema() {
+        let (session, turn) = make_session_and_context().await;
+        let invocation = invocation(
+            Arc::new(session),
+            Arc::new(turn),
+            "spawn_agent",
+            function_payload(json!({
+                "message": "summarize",
+                "output_schema": {"type": "string"}
+            })),
+        );
+        let Err(err) = CollabHandler.handle(invocation).await else {
+            panic!("non-object schema should be rejected");
+        };
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel(
+                "output_schema must have \"type\": \"object\"".to_string()
+            )
+        );
+    }
+
     #[tokio::test]
     async fn spawn_agent_errors_when_manager_dropped() {
         let (session, turn) = make_session_and_context().await;
@@ -769,7 +1062,7 @@ mod tests {
 
         let session_source = SessionSource::SubAgent(SubAgentSource::ThreadSpawn {
             parent_thread_id: session.conversation_id,
-            depth: MAX_THREAD_SPAWN_DEPTH,
+            depth: turn.client.config().agent_max_depth,
         });
         turn.client = ModelClient::new(
             turn.client.config(),
@@ -1174,6 +1467,137 @@ mod tests {
         assert_eq!(status_after, AgentStatus::NotFound);
     }
 
+    fn init_git_repo(dir: &std::path::Path) -> std::path::PathBuf {
+        let repo = dir.join("repo");
+        std::fs::create_dir(&repo).expect("create repo");
+        for args in [
+            vec!["init", "--initial-branch=main"],
+            vec!["config", "user.name", "Tester"],
+            vec!["config", "user.email", "test@example.com"],
+            vec!["commit", "--allow-empty", "-m", "init"],
+        ] {
+            let status = std::process::Command::new("git")
+                .current_dir(&repo)
+                .args(&args)
+                .status()
+                .expect("git");
+            assert!(status.success(), "git {args:?} failed");
+        }
+        repo
+    }
+
+    #[tokio::test]
+    async fn merge_agent_diffs_and_discards_worktree() {
+        let (mut session, turn) = make_session_and_context().await;
+        let manager = thread_manager();
+        session.services.agent_control = manager.agent_control();
+        let session = Arc::new(session);
+        let turn = Arc::new(turn);
+        let config = turn.client.config().as_ref().clone();
+        let agent_id = manager
+            .start_thread(config)
+            .await
+            .expect("start thread")
+            .thread_id;
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let repo = init_git_repo(temp.path());
+        let worktree =
+            codex_git::create_agent_worktree(&repo, &temp.path().join("agent"), "codex/agent-test")
+                .expect("create worktree");
+        std::fs::write(worktree.path().join("notes.txt"), "hello\n").expect("write");
+        manager
+            .agent_control()
+            .register_worktree(agent_id, worktree.clone())
+            .await;
+
+        let merge_agent = |action: &str| {
+            invocation(
+                session.clone(),
+                turn.clone(),
+                "merge_agent",
+                function_payload(json!({"id": agent_id.to_string(), "action": action})),
+            )
+        };
+        let ToolOutput::Function { content, .. } = CollabHandler
+            .handle(merge_agent("diff"))
+            .await
+            .expect("diff should succeed")
+        else {
+            panic!("expected function output");
+        };
+        assert!(content.contains("+hello"), "{content}");
+
+        let Err(err) = CollabHandler.handle(merge_agent("merge")).await else {
+            panic!("merge should wait for the agent");
+        };
+        assert_eq!(
+            err,
+            FunctionCallError::RespondToModel(format!(
+                "merge_agent failed: unsupported operation: agent {agent_id} is still running; wait for it or close it first"
+            ))
+        );
+
+        let _ = manager.agent_control().shutdown_agent(agent_id).await;
+        let ToolOutput::Function { content, .. } = CollabHandler
+            .handle(merge_agent("discard"))
+            .await
+            .expect("discard should succeed")
+        else {
+            panic!("expected function output");
+        };
+        assert_eq!(content, "discarded codex/agent-test");
+        assert!(!worktree.path().exists());
+        assert!(!repo.join("notes.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn merge_agent_applies_changes_under_turn_policy() {
+        let (mut session, mut turn) = make_session_and_context().await;
+        turn.approval_policy = AskForApproval::Never;
+        turn.sandbox_policy = SandboxPolicy::DangerFullAccess;
+        let manager = thread_manager();
+        session.services.agent_control = manager.agent_control();
+        let config = turn.client.config().as_ref().clone();
+        let agent_id = manager
+            .start_thread(config)
+            .await
+            .expect("start thread")
+            .thread_id;
+        let _ = manager.agent_control().shutdown_agent(agent_id).await;
+
+        let temp = tempfile::tempdir().expect("temp dir");
+        let repo = init_git_repo(temp.path());
+        let worktree =
+            codex_git::create_agent_worktree(&repo, &temp.path().join("agent"), "codex/agent-test")
+                .expect("create worktree");
+        std::fs::write(worktree.path().join("notes.txt"), "hello\n").expect("write");
+        manager
+            .agent_control()
+            .register_worktree(agent_id, worktree.clone())
+            .await;
+
+        let ToolOutput::Function { content, .. } = CollabHandler
+            .handle(invocation(
+                Arc::new(session),
+                Arc::new(turn),
+                "merge_agent",
+                function_payload(json!({"id": agent_id.to_string(), "action": "merge"})),
+            ))
+            .await
+            .expect("merge should succeed")
+        else {
+            panic!("expected function output");
+        };
+
+        assert_eq!(content, "applied 1 commit from codex/agent-test");
+        assert_eq!(
+            std::fs::read_to_string(repo.join("notes.txt")).expect("merged file"),
+            "hello\n"
+        );
+        assert!(!worktree.path().exists());
+    }
+
     #[tokio::test]
     async fn build_agent_spawn_config_uses_turn_context_values() {
         let (_session, mut turn) = make_session_and_context().await;
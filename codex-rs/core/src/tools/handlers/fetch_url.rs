@@ -0,0 +1,447 @@
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use codex_protocol::items::TurnItem;
+use codex_protocol::items::WebSearchItem;
+use codex_protocol::models::WebSearchAction;
+use reqwest::dns::Addrs;
+use reqwest::dns::Name;
+use reqwest::dns::Resolve;
+use reqwest::dns::Resolving;
+use reqwest::redirect;
+use serde::Deserialize;
+use url::Host;
+use url::Url;
+
+use crate::config::network_proxy::network_proxy_url;
+use crate::default_client::get_codex_user_agent;
+use crate::function_tool::FunctionCallError;
+use crate::html_to_markdown::html_to_markdown;
+use crate::tokenizer::Tokenizer;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+use crate::truncate::TruncationPolicy;
+use crate::truncate::truncate_text;
+
+pub struct FetchUrlHandler;
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Bodies are read up to this size; anything after it is dropped.
+const MAX_BODY_BYTES: usize = 5 * 1024 * 1024;
+/// Matches reqwest's default redirect limit.
+const MAX_REDIRECTS: usize = 10;
+
+#[derive(Deserialize)]
+struct FetchUrlArgs {
+    url: String,
+}
+
+#[async_trait]
+impl ToolHandler for FetchUrlHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            session,
+            turn,
+            payload,
+            call_id,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "fetch_url handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+        let args: FetchUrlArgs = parse_arguments(&arguments)?;
+        let url = parse_url(&args.url).map_err(FunctionCallError::RespondToModel)?;
+
+        if !turn.sandbox_policy.has_full_network_access() {
+            return Err(FunctionCallError::RespondToModel(
+                "fetch_url is unavailable: network access is disabled by the sandbox policy"
+                    .to_string(),
+            ));
+        }
+        let proxy = network_proxy_url(&turn.client.config());
+        if proxy.is_none() {
+            check_public_host(&url).map_err(FunctionCallError::RespondToModel)?;
+        }
+
+        let item = TurnItem::WebSearch(WebSearchItem {
+            id: call_id,
+            query: url.to_string(),
+            action: WebSearchAction::OpenPage {
+                url: Some(url.to_string()),
+            },
+        });
+        session.emit_turn_item_started(&turn, &item).await;
+        let result = fetch(&url, proxy.as_deref()).await;
+        session.emit_turn_item_completed(&turn, item).await;
+
+        let page = result.map_err(FunctionCallError::RespondToModel)?;
+        Ok(ToolOutput::Function {
+            content: page.render(turn.truncation_policy, &turn.tokenizer),
+            content_items: None,
+            success: Some(true),
+        })
+    }
+}
+
+struct FetchedPage {
+    url: String,
+    content_type: String,
+    title: Option<String>,
+    body: String,
+    body_truncated: bool,
+}
+
+impl FetchedPage {
+    fn render(&self, policy: TruncationPolicy, tokenizer: &Tokenizer) -> String {
+        let mut header = format!("URL: {}\n", self.url);
+        if let Some(title) = &self.title {
+            header.push_str(&format!("Title: {title}\n"));
+        }
+        header.push_str(&format!("Content-Type: {}\n", self.content_type));
+        if self.body_truncated {
+            header.push_str(&format!(
+                "Note: only the first {MAX_BODY_BYTES} bytes were downloaded\n"
+            ));
+        }
+        format!("{header}\n{}", truncate_text(&self.body, policy, tokenizer))
+    }
+}
+
+fn parse_url(raw: &str) -> Result<Url, String> {
+    let url = Url::parse(raw.trim()).map_err(|err| format!("invalid url `{raw}`: {err}"))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(format!(
+            "unsupported url scheme `{scheme}`; only http and https are allowed"
+        )),
+    }
+}
+
+/// Without the network proxy in front of it, refuse requests that would reach
+/// the local machine or private networks. Domains are checked again once
+/// resolved, see [`PublicOnlyResolver`].
+fn check_public_host(url: &Url) -> Result<(), String> {
+    let ip = match url.host() {
+        Some(Host::Domain(domain)) => {
+            let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") {
+                return Err(format!("refusing to fetch local address {url}"));
+            }
+            return Ok(());
+        }
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
+        None => return Err(format!("url {url} has no host")),
+    };
+    if is_non_public_ip(ip) {
+        Err(format!("refusing to fetch non-public address {url}"))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_non_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.octets()[0] == 0
+                // CGNAT (RFC 6598)
+                || (ip.octets()[0] == 100 && (ip.octets()[1] & 0xc0) == 64)
+        }
+        IpAddr::V6(ip) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_unique_local()
+                || ip.is_unicast_link_local()
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|v4| is_non_public_ip(IpAddr::V4(v4)))
+        }
+    }
+}
+
+/// Resolves hostnames with the system resolver and fails when any address is
+/// non-public, so a public-looking domain cannot point the fetch at the local
+/// network.
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            if let Some(addr) = addrs.iter().find(|addr| is_non_public_ip(addr.ip())) {
+                return Err(format!(
+                    "refusing to fetch {host}: it resolves to non-public address {}",
+                    addr.ip()
+                )
+                .into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Follows redirects only to hosts that pass [`check_public_host`].
+fn public_redirect_policy() -> redirect::Policy {
+    redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_REDIRECTS {
+            return attempt.error("too many redirects");
+        }
+        match check_public_host(attempt.url()) {
+            Ok(()) => attempt.follow(),
+            Err(err) => attempt.error(err),
+        }
+    })
+}
+
+async fn fetch(url: &Url, proxy: Option<&str>) -> Result<FetchedPage, String> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(get_codex_user_agent())
+        .timeout(FETCH_TIMEOUT);
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|err| format!("invalid network proxy url `{proxy}`: {err}"))?;
+        builder = builder.proxy(proxy);
+    } else {
+        // Ignore HTTP(S)_PROXY/ALL_PROXY from the environment: a proxy would
+        // resolve the host itself and bypass the public-address check.
+        builder = builder
+            .no_proxy()
+            .redirect(public_redirect_policy())
+            .dns_resolver(Arc::new(PublicOnlyResolver));
+    }
+    let client = builder
+        .build()
+        .map_err(|err| format!("failed to build http client: {err}"))?;
+
+    let mut response = client
+        .get(url.clone())
+        .header(
+            reqwest::header::ACCEPT,
+            "text/html, text/*;q=0.9, */*;q=0.5",
+        )
+        .send()
+        .await
+        .map_err(|err| format!("failed to fetch {url}: {err}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("fetching {url} returned HTTP {status}"));
+    }
+    let final_url = response.url().to_string();
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(';')
+                .next()
+                .unwrap_or(value)
+                .trim()
+                .to_ascii_lowercase()
+        })
+        .unwrap_or_default();
+
+    let mut bytes = Vec::new();
+    let mut body_truncated = false;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|err| format!("failed to read response from {url}: {err}"))?
+    {
+        let remaining = MAX_BODY_BYTES - bytes.len();
+        if chunk.len() > remaining {
+            bytes.extend_from_slice(&chunk[..remaining]);
+            body_truncated = true;
+            break;
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    let text = String::from_utf8_lossy(&bytes);
+
+    let (title, body) = match classify(&content_type, &text) {
+        PageKind::Html => {
+            let page = html_to_markdown(&text);
+            (page.title, page.markdown)
+        }
+        PageKind::Text => (None, text.into_owned()),
+        PageKind::Unsupported => {
+            return Err(format!(
+                "{url} returned unsupported content type `{content_type}`"
+            ));
+        }
+    };
+
+    Ok(FetchedPage {
+        url: final_url,
+        content_type: if content_type.is_empty() {
+            "unknown".to_string()
+        } else {
+            content_type
+        },
+        title,
+        body,
+        body_truncated,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PageKind {
+    Html,
+    Text,
+    Unsupported,
+}
+
+fn classify(content_type: &str, body: &str) -> PageKind {
+    match content_type {
+        "text/html" | "application/xhtml+xml" => PageKind::Html,
+        "" if body.trim_start().starts_with('<') => PageKind::Html,
+        "" => PageKind::Text,
+        _ if content_type.starts_with("text/")
+            || content_type.ends_with("+json")
+            || content_type.ends_with("+xml")
+            || matches!(
+                content_type,
+                "application/json" | "application/xml" | "application/javascript"
+            ) =>
+        {
+            PageKind::Text
+        }
+        _ => PageKind::Unsupported,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_http_urls_are_accepted() {
+        assert!(parse_url(" https://example.com/a?b=c ").is_ok());
+        assert_eq!(
+            parse_url("file:///etc/passwd").expect_err("file urls are rejected"),
+            "unsupported url scheme `file`; only http and https are allowed"
+        );
+        assert!(parse_url("not a url").is_err());
+    }
+
+    #[test]
+    fn local_and_private_hosts_are_refused() {
+        for url in [
+            "http://localhost:8080/",
+            "http://api.localhost/",
+            "http://127.0.0.1/",
+            "http://10.1.2.3/",
+            "http://192.168.0.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+        ] {
+            let url = Url::parse(url).expect("valid url");
+            assert!(check_public_host(&url).is_err(), "{url}");
+        }
+        for url in ["https://example.com/", "http://93.184.216.34/"] {
+            let url = Url::parse(url).expect("valid url");
+            assert_eq!(check_public_host(&url), Ok(()), "{url}");
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped_private_addresses_are_non_public() {
+        for ip in ["::ffff:127.0.0.1", "::ffff:100.64.0.1", "0.0.0.0"] {
+            let ip: IpAddr = ip.parse().expect("valid ip");
+            assert!(is_non_public_ip(ip), "{ip}");
+        }
+        let ip: IpAddr = "93.184.216.34".parse().expect("valid ip");
+        assert!(!is_non_public_ip(ip));
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_names_that_resolve_locally() {
+        let name: Name = "localhost".parse().expect("valid name");
+        let err = PublicOnlyResolver
+            .resolve(name)
+            .await
+            .err()
+            .expect("localhost is refused");
+        assert!(err.to_string().contains("non-public address"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn redirects_to_private_hosts_are_refused() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(
+                wiremock::ResponseTemplate::new(302)
+                    .insert_header("location", "http://169.254.169.254/latest/meta-data"),
+            )
+            .mount(&server)
+            .await;
+        let client = reqwest::Client::builder()
+            .redirect(public_redirect_policy())
+            .build()
+            .expect("client");
+        let err = client
+            .get(server.uri())
+            .send()
+            .await
+            .expect_err("redirect is refused");
+        assert!(
+            format!("{err:?}").contains("refusing to fetch non-public address"),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn classifies_content_types() {
+        assert_eq!(classify("text/html", ""), PageKind::Html);
+        assert_eq!(classify("", "  <!doctype html>"), PageKind::Html);
+        assert_eq!(classify("text/plain", ""), PageKind::Text);
+        assert_eq!(classify("application/vnd.api+json", ""), PageKind::Text);
+        assert_eq!(classify("image/png", ""), PageKind::Unsupported);
+    }
+
+    #[test]
+    fn render_truncates_the_body() {
+        let page = FetchedPage {
+            url: "https://example.com/".to_string(),
+            content_type: "text/html".to_string(),
+            title: Some("Example".to_string()),
+            body: "word ".repeat(1000),
+            body_truncated: false,
+        };
+        let rendered = page.render(TruncationPolicy::Bytes(100), &Tokenizer::default());
+        assert!(
+            rendered.starts_with(
+                "URL: https://example.com/\nTitle: Example\nContent-Type: text/html\n\n"
+            ),
+            "{rendered}"
+        );
+        assert!(rendered.len() < 400, "{rendered}");
+    }
+}
@@ -1,6 +1,7 @@
 pub mod apply_patch;
 pub(crate) mod collab;
 mod dynamic;
+mod fetch_url;
 mod grep_files;
 mod list_dir;
 mod mcp;
@@ -9,6 +10,7 @@ mod plan;
 mod read_file;
 mod request_user_input;
 mod shell;
+mod symbols;
 mod test_sync;
 mod unified_exec;
 mod view_image;
@@ -20,6 +22,7 @@ use crate::function_tool::FunctionCallError;
 pub use apply_patch::ApplyPatchHandler;
 pub use collab::CollabHandler;
 pub use dynamic::DynamicToolHandler;
+pub use fetch_url::FetchUrlHandler;
 pub use grep_files::GrepFilesHandler;
 pub use list_dir::ListDirHandler;
 pub use mcp::McpHandler;
@@ -30,6 +33,7 @@ pub use request_user_input::RequestUserInputHandler;
 pub(crate) use request_user_input::request_user_input_tool_description;
 pub use shell::ShellCommandHandler;
 pub use shell::ShellHandler;
+pub use symbols::SymbolsHandler;
 pub use test_sync::TestSyncHandler;
 pub use unified_exec::UnifiedExecHandler;
 pub use view_image::ViewImageHandler;
@@ -0,0 +1,574 @@
+use std::path::Path;
+use std::sync::OnceLock;
+
+use async_trait::async_trait;
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use tree_sitter::Language;
+use tree_sitter::Parser;
+use tree_sitter::Query;
+use tree_sitter::QueryCursor;
+use tree_sitter::StreamingIterator;
+
+use crate::function_tool::FunctionCallError;
+use crate::tools::context::ToolInvocation;
+use crate::tools::context::ToolOutput;
+use crate::tools::context::ToolPayload;
+use crate::tools::handlers::parse_arguments;
+use crate::tools::registry::ToolHandler;
+use crate::tools::registry::ToolKind;
+
+/// Serves `find_symbol`, `list_symbols` and `find_references` from
+/// tree-sitter tag queries.
+pub struct SymbolsHandler;
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+/// Larger files are skipped; they are almost always generated.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+fn default_limit() -> usize {
+    DEFAULT_LIMIT
+}
+
+/// A grammar and the tag query that finds its definitions and references.
+///
+/// Queries follow tree-sitter's tagging conventions: `@name` captures the
+/// identifier, and a `@definition.<kind>` or `@reference.<kind>` capture
+/// marks the enclosing node.
+struct SymbolLanguage {
+    extensions: &'static [&'static str],
+    language: fn() -> Language,
+    tags_query: &'static str,
+    /// `tags_query` compiled on first use; `None` if it does not compile.
+    compiled_query: OnceLock<Option<Query>>,
+}
+
+impl SymbolLanguage {
+    const fn new(
+        extensions: &'static [&'static str],
+        language: fn() -> Language,
+        tags_query: &'static str,
+    ) -> Self {
+        Self {
+            extensions,
+            language,
+            tags_query,
+            compiled_query: OnceLock::new(),
+        }
+    }
+
+    fn query(&self) -> Option<&Query> {
+        self.compiled_query
+            .get_or_init(|| match Query::new(&(self.language)(), self.tags_query) {
+                Ok(query) => Some(query),
+                Err(err) => {
+                    tracing::warn!("invalid tags query for .{}: {err}", self.extensions[0]);
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+const BASH_TAGS_QUERY: &str = r#"
+(function_definition name: (word) @name) @definition.function
+(command name: (command_name (word) @name)) @reference.call
+"#;
+
+const RUST_TAGS_QUERY: &str = r#"
+(struct_item name: (type_identifier) @name) @definition.struct
+(enum_item name: (type_identifier) @name) @definition.enum
+(union_item name: (type_identifier) @name) @definition.union
+(type_item name: (type_identifier) @name) @definition.type
+(trait_item name: (type_identifier) @name) @definition.trait
+(function_item name: (identifier) @name) @definition.function
+(function_signature_item name: (identifier) @name) @definition.function
+(const_item name: (identifier) @name) @definition.constant
+(static_item name: (identifier) @name) @definition.constant
+(mod_item name: (identifier) @name) @definition.module
+(macro_definition name: (identifier) @name) @definition.macro
+(call_expression function: (identifier) @name) @reference.call
+(call_expression function: (field_expression field: (field_identifier) @name)) @reference.call
+(call_expression function: (scoped_identifier name: (identifier) @name)) @reference.call
+(macro_invocation macro: (identifier) @name) @reference.call
+(impl_item trait: (type_identifier) @name) @reference.implementation
+(impl_item type: (type_identifier) @name !trait) @reference.implementation
+"#;
+
+const PYTHON_TAGS_QUERY: &str = r#"
+(class_definition name: (identifier) @name) @definition.class
+(function_definition name: (identifier) @name) @definition.function
+(module (expression_statement (assignment left: (identifier) @name) @definition.constant))
+(call function: [
+  (identifier) @name
+  (attribute attribute: (identifier) @name)
+]) @reference.call
+"#;
+
+/// Shared by the TypeScript and TSX grammars.
+const TYPESCRIPT_TAGS_QUERY: &str = r#"
+(function_declaration name: (identifier) @name) @definition.function
+(generator_function_declaration name: (identifier) @name) @definition.function
+(function_signature name: (identifier) @name) @definition.function
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @definition.function
+(class_declaration name: (type_identifier) @name) @definition.class
+(abstract_class_declaration name: (type_identifier) @name) @definition.class
+(method_definition name: (property_identifier) @name) @definition.method
+(method_signature name: (property_identifier) @name) @definition.method
+(abstract_method_signature name: (property_identifier) @name) @definition.method
+(interface_declaration name: (type_identifier) @name) @definition.interface
+(type_alias_declaration name: (type_identifier) @name) @definition.type
+(enum_declaration name: (identifier) @name) @definition.enum
+(internal_module name: (identifier) @name) @definition.module
+(call_expression function: [
+  (identifier) @name
+  (member_expression property: (property_identifier) @name)
+]) @reference.call
+(new_expression constructor: (identifier) @name) @reference.class
+(type_annotation (type_identifier) @name) @reference.type
+"#;
+
+static LANGUAGES: [SymbolLanguage; 5] = [
+    SymbolLanguage::new(
+        &["sh", "bash"],
+        || tree_sitter_bash::LANGUAGE.into(),
+        BASH_TAGS_QUERY,
+    ),
+    SymbolLanguage::new(
+        &["rs"],
+        || tree_sitter_rust::LANGUAGE.into(),
+        RUST_TAGS_QUERY,
+    ),
+    SymbolLanguage::new(
+        &["py", "pyi"],
+        || tree_sitter_python::LANGUAGE.into(),
+        PYTHON_TAGS_QUERY,
+    ),
+    SymbolLanguage::new(
+        &["ts", "mts", "cts"],
+        || tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        TYPESCRIPT_TAGS_QUERY,
+    ),
+    SymbolLanguage::new(
+        &["tsx"],
+        || tree_sitter_typescript::LANGUAGE_TSX.into(),
+        TYPESCRIPT_TAGS_QUERY,
+    ),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Tag {
+    name: String,
+    /// The `<kind>` of the `@definition.<kind>` or `@reference.<kind>`
+    /// capture, such as `function` or `call`.
+    kind: String,
+    is_definition: bool,
+    /// 1-indexed line of the name.
+    line: usize,
+    /// The trimmed source line holding the name.
+    context: String,
+}
+
+#[derive(Deserialize)]
+struct FindSymbolArgs {
+    name: String,
+    #[serde(default)]
+    path: Option<String>,
+    #[serde(default = "default_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct ListSymbolsArgs {
+    path: String,
+}
+
+#[async_trait]
+impl ToolHandler for SymbolsHandler {
+    fn kind(&self) -> ToolKind {
+        ToolKind::Function
+    }
+
+    async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
+        let ToolInvocation {
+            turn,
+            tool_name,
+            payload,
+            ..
+        } = invocation;
+
+        let arguments = match payload {
+            ToolPayload::Function { arguments } => arguments,
+            _ => {
+                return Err(FunctionCallError::RespondToModel(
+                    "symbols handler received unsupported payload".to_string(),
+                ));
+            }
+        };
+
+        let cwd = turn.cwd.clone();
+        let lines = match tool_name.as_str() {
+            "find_symbol" | "find_references" => {
+                let args: FindSymbolArgs = parse_arguments(&arguments)?;
+                let name = args.name.trim().to_string();
+                if name.is_empty() {
+                    return Err(FunctionCallError::RespondToModel(
+                        "name must not be empty".to_string(),
+                    ));
+                }
+                if args.limit == 0 {
+                    return Err(FunctionCallError::RespondToModel(
+                        "limit must be greater than zero".to_string(),
+                    ));
+                }
+                let limit = args.limit.min(MAX_LIMIT);
+                let search_path = turn.resolve_path(args.path);
+                verify_path_exists(&search_path).await?;
+                let definitions = tool_name == "find_symbol";
+                tokio::task::spawn_blocking(move || {
+                    find_tags(&search_path, &cwd, &name, definitions, limit)
+                })
+                .await
+                .map_err(|err| {
+                    FunctionCallError::RespondToModel(format!("symbol search failed: {err}"))
+                })?
+            }
+            "list_symbols" => {
+                let args: ListSymbolsArgs = parse_arguments(&arguments)?;
+                let path = turn.resolve_path(Some(args.path));
+                tokio::task::spawn_blocking(move || list_symbols(&path))
+                    .await
+                    .map_err(|err| {
+                        FunctionCallError::RespondToModel(format!("symbol listing failed: {err}"))
+                    })??
+            }
+            other => {
+                return Err(FunctionCallError::RespondToModel(format!(
+                    "unsupported symbol tool: {other}"
+                )));
+            }
+        };
+
+        if lines.is_empty() {
+            Ok(ToolOutput::Function {
+                content: "No symbols found.".to_string(),
+                content_items: None,
+                success: Some(false),
+            })
+        } else {
+            Ok(ToolOutput::Function {
+                content: lines.join("\n"),
+                content_items: None,
+                success: Some(true),
+            })
+        }
+    }
+}
+
+async fn verify_path_exists(path: &Path) -> Result<(), FunctionCallError> {
+    tokio::fs::metadata(path).await.map_err(|err| {
+        FunctionCallError::RespondToModel(format!("unable to access `{}`: {err}", path.display()))
+    })?;
+    Ok(())
+}
+
+/// Lists the definitions in one file, in source order.
+fn list_symbols(path: &Path) -> Result<Vec<String>, FunctionCallError> {
+    let Some(language) = language_for_path(path) else {
+        return Err(FunctionCallError::RespondToModel(format!(
+            "symbols are not supported for `{}`; supported extensions: {}",
+            path.display(),
+            supported_extensions()
+        )));
+    };
+    let source = std::fs::read_to_string(path).map_err(|err| {
+        FunctionCallError::RespondToModel(format!("unable to read `{}`: {err}", path.display()))
+    })?;
+    Ok(extract_tags(language, &source)
+        .into_iter()
+        .filter(|tag| tag.is_definition)
+        .map(|tag| format!("L{}: {} {}", tag.line, tag.kind, tag.name))
+        .collect())
+}
+
+/// Walks `search_path`, honoring ignore files, and reports the definitions
+/// of (or references to) `name`.
+fn find_tags(
+    search_path: &Path,
+    cwd: &Path,
+    name: &str,
+    definitions: bool,
+    limit: usize,
+) -> Vec<String> {
+    let mut results = Vec::new();
+    for entry in WalkBuilder::new(search_path).build().flatten() {
+        let path = entry.path();
+        if !entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_file())
+            || entry
+                .metadata()
+                .is_ok_and(|metadata| metadata.len() > MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        let Some(language) = language_for_path(path) else {
+            continue;
+        };
+        // Cheap pre-filter before parsing.
+        let Ok(source) = std::fs::read_to_string(path) else {
+            continue;
+        };
+        if !source.contains(name) {
+            continue;
+        }
+        let display_path = path.strip_prefix(cwd).unwrap_or(path).display();
+        for tag in extract_tags(language, &source) {
+            if tag.name != name || tag.is_definition != definitions {
+                continue;
+            }
+            results.push(format!(
+                "{display_path}:{}: [{}] {}",
+                tag.line, tag.kind, tag.context
+            ));
+            if results.len() == limit {
+                return results;
+            }
+        }
+    }
+    results
+}
+
+fn language_for_path(path: &Path) -> Option<&'static SymbolLanguage> {
+    let extension = path.extension()?.to_str()?;
+    LANGUAGES
+        .iter()
+        .find(|language| language.extensions.contains(&extension))
+}
+
+fn supported_extensions() -> String {
+    LANGUAGES
+        .iter()
+        .flat_map(|language| language.extensions.iter())
+        .map(|extension| format!(".{extension}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn extract_tags(symbol_language: &SymbolLanguage, source: &str) -> Vec<Tag> {
+    let Some(query) = symbol_language.query() else {
+        return Vec::new();
+    };
+    let mut parser = Parser::new();
+    if parser.set_language(&(symbol_language.language)()).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    let capture_names = query.capture_names();
+    let source_lines: Vec<&str> = source.lines().collect();
+
+    let mut tags = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut matches = cursor.matches(query, tree.root_node(), source.as_bytes());
+    while let Some(query_match) = matches.next() {
+        let mut name_node = None;
+        let mut role = None;
+        for capture in query_match.captures {
+            let capture_name = capture_names[capture.index as usize];
+            if capture_name == "name" {
+                name_node = Some(capture.node);
+            } else if let Some(kind) = capture_name.strip_prefix("definition.") {
+                role = Some((kind, true));
+            } else if let Some(kind) = capture_name.strip_prefix("reference.") {
+                role = Some((kind, false));
+            }
+        }
+        let (Some(node), Some((kind, is_definition))) = (name_node, role) else {
+            continue;
+        };
+        let Ok(name) = node.utf8_text(source.as_bytes()) else {
+            continue;
+        };
+        let row = node.start_position().row;
+        tags.push(Tag {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            is_definition,
+            line: row + 1,
+            context: source_lines
+                .get(row)
+                .map(|line| line.trim().to_string())
+                .unwrap_or_default(),
+        });
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    const SCRIPT: &str =
+        "#!/bin/sh\ngreet() {\n  echo \"hi $1\"\n}\n\nmain() {\n  greet world\n}\n\nmain\n";
+
+    fn bash() -> &'static SymbolLanguage {
+        language_for_path(Path::new("script.sh")).expect("bash is supported")
+    }
+
+    #[test]
+    fn extracts_definitions_and_references() {
+        let tags = extract_tags(bash(), SCRIPT);
+        let summary: Vec<_> = tags
+            .iter()
+            .map(|tag| {
+                (
+                    tag.name.as_str(),
+                    tag.kind.as_str(),
+                    tag.is_definition,
+                    tag.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("greet", "function", true, 2),
+                ("echo", "call", false, 3),
+                ("main", "function", true, 6),
+                ("greet", "call", false, 7),
+                ("main", "call", false, 10),
+            ]
+        );
+        assert_eq!(tags[3].context, "greet world");
+    }
+
+    fn summarize(file_name: &str, source: &str) -> Vec<(String, String, bool, usize)> {
+        let language = language_for_path(Path::new(file_name)).expect("language is supported");
+        extract_tags(language, source)
+            .into_iter()
+            .map(|tag| (tag.name, tag.kind, tag.is_definition, tag.line))
+            .collect()
+    }
+
+    fn tag(
+        name: &str,
+        kind: &str,
+        is_definition: bool,
+        line: usize,
+    ) -> (String, String, bool, usize) {
+        (name.to_string(), kind.to_string(), is_definition, line)
+    }
+
+    #[test]
+    fn every_tags_query_compiles() {
+        for language in &LANGUAGES {
+            assert!(
+                language.query().is_some(),
+                "tags query for .{} does not compile",
+                language.extensions[0]
+            );
+        }
+    }
+
+    #[test]
+    fn extracts_rust_tags() {
+        let source = "struct Config;\n\nimpl Config {\n    fn load() -> Self {\n        parse(read())\n    }\n}\n\nfn parse(s: String) -> Config {\n    println!(\"{s}\");\n    Config\n}\n";
+        assert_eq!(
+            summarize("lib.rs", source),
+            vec![
+                tag("Config", "struct", true, 1),
+                tag("Config", "implementation", false, 3),
+                tag("load", "function", true, 4),
+                tag("parse", "call", false, 5),
+                tag("read", "call", false, 5),
+                tag("parse", "function", true, 9),
+                tag("println", "call", false, 10),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_python_tags() {
+        let source = "LIMIT = 3\n\nclass Cache:\n    def get(self, key):\n        return self.store.lookup(key)\n\nprint(Cache().get(1))\n";
+        assert_eq!(
+            summarize("cache.py", source),
+            vec![
+                tag("LIMIT", "constant", true, 1),
+                tag("Cache", "class", true, 3),
+                tag("get", "function", true, 4),
+                tag("lookup", "call", false, 5),
+                tag("print", "call", false, 7),
+                tag("Cache", "call", false, 7),
+                tag("get", "call", false, 7),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_typescript_tags() {
+        let source = "interface Shape {\n  area(): number;\n}\n\nclass Square implements Shape {\n  area(): number {\n    return Math.pow(this.side, 2);\n  }\n}\n\nconst describe = (shape: Shape) => shape.area();\nnew Square();\n";
+        let expected = vec![
+            tag("Shape", "interface", true, 1),
+            tag("area", "method", true, 2),
+            tag("Square", "class", true, 5),
+            tag("area", "method", true, 6),
+            tag("pow", "call", false, 7),
+            tag("describe", "function", true, 11),
+            tag("Shape", "type", false, 11),
+            tag("area", "call", false, 11),
+            tag("Square", "class", false, 12),
+        ];
+        assert_eq!(summarize("shapes.ts", source), expected);
+        assert_eq!(summarize("shapes.tsx", source), expected);
+    }
+
+    #[test]
+    fn find_tags_respects_gitignore() {
+        let dir = tempdir().unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "ignored/\n").unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::create_dir(dir.path().join("ignored")).unwrap();
+        std::fs::write(dir.path().join("run.sh"), SCRIPT).unwrap();
+        std::fs::write(dir.path().join("ignored/run.sh"), SCRIPT).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "greet() {}\n").unwrap();
+
+        assert_eq!(
+            find_tags(dir.path(), dir.path(), "greet", true, 10),
+            vec!["run.sh:2: [function] greet() {".to_string()]
+        );
+        assert_eq!(
+            find_tags(dir.path(), dir.path(), "greet", false, 10),
+            vec!["run.sh:7: [call] greet world".to_string()]
+        );
+        assert_eq!(find_tags(dir.path(), dir.path(), "main", false, 1).len(), 1);
+    }
+
+    #[test]
+    fn list_symbols_reports_definitions_or_unsupported_files() {
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, SCRIPT).unwrap();
+        assert_eq!(
+            list_symbols(&script).unwrap(),
+            vec![
+                "L2: function greet".to_string(),
+                "L6: function main".to_string()
+            ]
+        );
+
+        let Err(FunctionCallError::RespondToModel(message)) =
+            list_symbols(&dir.path().join("notes.txt"))
+        else {
+            panic!("expected an unsupported file error");
+        };
+        assert!(message.contains("supported extensions: .sh, .bash"));
+    }
+}
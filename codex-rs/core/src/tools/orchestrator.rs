@@ -20,6 +20,10 @@ use crate::tools::sandboxing::default_exec_approval_requirement;
 use codex_otel::ToolDecisionSource;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
+use tracing::Instrument;
+use tracing::Span;
+use tracing::field;
+use tracing::trace_span;
 
 pub(crate) struct ToolOrchestrator {
     sandbox: SandboxManager,
@@ -69,7 +73,12 @@ impl ToolOrchestrator {
                     call_id: &tool_ctx.call_id,
                     retry_reason: reason,
                 };
-                let decision = tool.start_approval_async(req, approval_ctx).await;
+                let approval_span = approval_wait_span(otel_tn, otel_ci);
+                let decision = tool
+                    .start_approval_async(req, approval_ctx)
+                    .instrument(approval_span.clone())
+                    .await;
+                record_decision(&approval_span, &decision);
 
                 otel.tool_decision(otel_tn, otel_ci, &decision, otel_user.clone());
 
@@ -106,7 +115,11 @@ impl ToolOrchestrator {
             windows_sandbox_level: turn_ctx.windows_sandbox_level,
         };
 
-        match tool.run(req, &initial_attempt, tool_ctx).await {
+        match tool
+            .run(req, &initial_attempt, tool_ctx)
+            .instrument(sandbox_attempt_span(initial_sandbox))
+            .await
+        {
             Ok(out) => {
                 // We have a successful initial result
                 Ok(out)
@@ -135,7 +148,12 @@ impl ToolOrchestrator {
                         retry_reason: Some(reason_msg),
                     };
 
-                    let decision = tool.start_approval_async(req, approval_ctx).await;
+                    let approval_span = approval_wait_span(otel_tn, otel_ci);
+                    let decision = tool
+                        .start_approval_async(req, approval_ctx)
+                        .instrument(approval_span.clone())
+                        .await;
+                    record_decision(&approval_span, &decision);
                     otel.tool_decision(otel_tn, otel_ci, &decision, otel_user);
 
                     match decision {
@@ -158,13 +176,39 @@ impl ToolOrchestrator {
                 };
 
                 // Second attempt.
-                (*tool).run(req, &escalated_attempt, tool_ctx).await
+                (*tool)
+                    .run(req, &escalated_attempt, tool_ctx)
+                    .instrument(sandbox_attempt_span(escalated_attempt.sandbox))
+                    .await
             }
             other => other,
         }
     }
 }
 
+/// Time spent waiting on the user; `decision` is recorded once they answer.
+fn approval_wait_span(tool_name: &str, call_id: &str) -> Span {
+    trace_span!(
+        "approval_wait",
+        tool_name = tool_name,
+        call_id = call_id,
+        decision = field::Empty,
+    )
+}
+
+fn record_decision(span: &Span, decision: &ReviewDecision) {
+    span.record(
+        "decision",
+        field::display(decision.to_string().to_lowercase()),
+    );
+}
+
+/// Parent of any process spawned by one attempt, so it shows which sandbox
+/// the command ran under.
+fn sandbox_attempt_span(sandbox: crate::exec::SandboxType) -> Span {
+    trace_span!("sandbox_attempt", sandbox = ?sandbox)
+}
+
 fn build_denial_reason_from_output(_output: &ExecToolCallOutput) -> String {
     // Keep approval reason terse and stable for UX/tests, but accept the
     // output so we can evolve heuristics later without touching call sites.
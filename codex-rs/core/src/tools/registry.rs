@@ -4,6 +4,9 @@ use std::time::Duration;
 
 use crate::client_common::tools::ToolSpec;
 use crate::function_tool::FunctionCallError;
+use crate::hooks;
+use crate::hooks::HookEvent;
+use crate::hooks::run_hooks;
 use crate::tools::context::ToolInvocation;
 use crate::tools::context::ToolOutput;
 use crate::tools::context::ToolPayload;
@@ -104,6 +107,47 @@ impl ToolRegistry {
             return Err(FunctionCallError::Fatal(message));
         }
 
+        let mut invocation = invocation;
+        let hooks_enabled = invocation
+            .turn
+            .client
+            .config()
+            .hooks
+            .has_tool_hooks(tool_name.as_ref());
+        let mut hook_context = Vec::new();
+        if hooks_enabled {
+            let outcome = run_hooks(
+                &invocation.session,
+                &invocation.turn,
+                HookEvent::PreToolUse {
+                    turn_id: invocation.turn.sub_id.clone(),
+                    call_id: call_id_owned.clone(),
+                    tool_name: tool_name.clone(),
+                    arguments: hooks::tool_arguments(&invocation.payload),
+                },
+            )
+            .await;
+            if let Some(reason) = outcome.blocked {
+                let message = format!("tool call blocked by pre_tool_use hook: {reason}");
+                otel.tool_result(
+                    tool_name.as_ref(),
+                    &call_id_owned,
+                    log_payload.as_ref(),
+                    Duration::ZERO,
+                    false,
+                    &message,
+                );
+                return Err(FunctionCallError::RespondToModel(message));
+            }
+            if let Some(arguments) = outcome.arguments {
+                hooks::replace_tool_arguments(&mut invocation.payload, arguments);
+            }
+            hook_context.extend(outcome.additional_context);
+        }
+        let session = Arc::clone(&invocation.session);
+        let turn = Arc::clone(&invocation.turn);
+        let hook_arguments = hooks_enabled.then(|| hooks::tool_arguments(&invocation.payload));
+
         let output_cell = tokio::sync::Mutex::new(None);
 
         let result = otel
@@ -138,10 +182,33 @@ impl ToolRegistry {
 
         match result {
             Ok(_) => {
-                let mut guard = output_cell.lock().await;
-                let output = guard.take().ok_or_else(|| {
+                let output = output_cell.lock().await.take();
+                let mut output = output.ok_or_else(|| {
                     FunctionCallError::Fatal("tool produced no output".to_string())
                 })?;
+                if let Some(arguments) = hook_arguments {
+                    let outcome = run_hooks(
+                        &session,
+                        &turn,
+                        HookEvent::PostToolUse {
+                            turn_id: turn.sub_id.clone(),
+                            call_id: call_id_owned.clone(),
+                            tool_name: tool_name.clone(),
+                            arguments,
+                            output: hooks::tool_output_text(&output),
+                            success: output.success_for_logging(),
+                        },
+                    )
+                    .await;
+                    hook_context.extend(outcome.additional_context);
+                    if let Some(reason) = outcome.blocked {
+                        hook_context
+                            .push(format!("post_tool_use hook flagged this result: {reason}"));
+                    }
+                }
+                if !hook_context.is_empty() {
+                    hooks::append_tool_context(&mut output, &hook_context.join("\n\n"));
+                }
                 Ok(output.into_response(&call_id_owned, &payload_for_response))
             }
             Err(err) => Err(err),
@@ -219,6 +286,21 @@ impl ToolRegistryBuilder {
     //     }
     // }
 
+    /// Drops every spec and handler whose name is not in `names`, returning
+    /// the names that did not match any registered tool.
+    pub fn retain_tools(&mut self, names: &[String]) -> Vec<String> {
+        let unknown = names
+            .iter()
+            .filter(|name| !self.specs.iter().any(|spec| spec.spec.name() == *name))
+            .cloned()
+            .collect();
+        self.specs
+            .retain(|spec| names.iter().any(|name| name == spec.spec.name()));
+        self.handlers
+            .retain(|tool_name, _| names.iter().any(|name| name == tool_name));
+        unknown
+    }
+
     pub fn build(self) -> (Vec<ConfiguredToolSpec>, ToolRegistry) {
         let registry = ToolRegistry::new(self.handlers);
         (self.specs, registry)
@@ -19,11 +19,13 @@ use crate::tools::sandboxing::SandboxAttempt;
 use crate::tools::sandboxing::SandboxOverride;
 use crate::tools::sandboxing::Sandboxable;
 use crate::tools::sandboxing::SandboxablePreference;
+use crate::tools::sandboxing::SessionApprovalKey;
 use crate::tools::sandboxing::ToolCtx;
 use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
 use crate::tools::sandboxing::with_cached_approval;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SessionApprovalKind;
 use futures::future::BoxFuture;
 use std::path::PathBuf;
 
@@ -48,6 +50,15 @@ pub(crate) struct ApprovalKey {
     sandbox_permissions: SandboxPermissions,
 }
 
+impl SessionApprovalKey for ApprovalKey {
+    fn session_approval_kind(&self) -> SessionApprovalKind {
+        SessionApprovalKind::Command {
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+        }
+    }
+}
+
 impl ShellRuntime {
     pub fn new() -> Self {
         Self
@@ -20,6 +20,7 @@ use crate::tools::sandboxing::SandboxAttempt;
 use crate::tools::sandboxing::SandboxOverride;
 use crate::tools::sandboxing::Sandboxable;
 use crate::tools::sandboxing::SandboxablePreference;
+use crate::tools::sandboxing::SessionApprovalKey;
 use crate::tools::sandboxing::ToolCtx;
 use crate::tools::sandboxing::ToolError;
 use crate::tools::sandboxing::ToolRuntime;
@@ -28,6 +29,7 @@ use crate::unified_exec::UnifiedExecError;
 use crate::unified_exec::UnifiedExecProcess;
 use crate::unified_exec::UnifiedExecProcessManager;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SessionApprovalKind;
 use futures::future::BoxFuture;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -51,6 +53,15 @@ pub struct UnifiedExecApprovalKey {
     pub sandbox_permissions: SandboxPermissions,
 }
 
+impl SessionApprovalKey for UnifiedExecApprovalKey {
+    fn session_approval_kind(&self) -> SessionApprovalKind {
+        SessionApprovalKind::Command {
+            command: self.command.clone(),
+            cwd: self.cwd.clone(),
+        }
+    }
+}
+
 pub struct UnifiedExecRuntime<'a> {
     manager: &'a UnifiedExecProcessManager,
 }
@@ -12,41 +12,172 @@ use crate::sandboxing::CommandSpec;
 use crate::sandboxing::SandboxManager;
 use crate::sandboxing::SandboxTransformError;
 use crate::state::SessionServices;
+use chrono::DateTime;
+use chrono::TimeDelta;
+use chrono::Utc;
 use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::SessionApprovalEntry;
+use codex_protocol::protocol::SessionApprovalKind;
+use codex_utils_absolute_path::AbsolutePathBuf;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::path::Path;
+use std::time::Duration;
 
 use futures::Future;
 use futures::future::BoxFuture;
 use serde::Serialize;
 
+/// Approval keys that can be remembered for the session. Each key describes
+/// itself so the user can review (and revoke) what they approved.
+pub(crate) trait SessionApprovalKey: Serialize {
+    fn session_approval_kind(&self) -> SessionApprovalKind;
+}
+
+#[derive(Clone, Debug)]
+struct StoredApproval {
+    id: u64,
+    decision: ReviewDecision,
+    kind: SessionApprovalKind,
+    approved_at: DateTime<Utc>,
+}
+
+impl StoredApproval {
+    fn expires_at(&self, ttl: Option<Duration>) -> Option<DateTime<Utc>> {
+        // Prefix rules are saved to the rules file, so they outlive any TTL.
+        if matches!(self.kind, SessionApprovalKind::CommandPrefix { .. }) {
+            return None;
+        }
+        expires_at(self.approved_at, ttl)
+    }
+
+    fn is_expired(&self, ttl: Option<Duration>, now: DateTime<Utc>) -> bool {
+        self.expires_at(ttl).is_some_and(|at| now >= at)
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub(crate) struct ApprovalStore {
     // Store serialized keys for generic caching across requests.
-    map: HashMap<String, ReviewDecision>,
+    map: HashMap<String, StoredApproval>,
+    next_id: u64,
+    /// How long a stored approval stays valid; `None` keeps it for the whole session.
+    ttl: Option<Duration>,
 }
 
 impl ApprovalStore {
+    pub(crate) fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            ..Default::default()
+        }
+    }
+
     pub fn get<K>(&self, key: &K) -> Option<ReviewDecision>
+    where
+        K: Serialize,
+    {
+        self.get_at(key, Utc::now())
+    }
+
+    fn get_at<K>(&self, key: &K, now: DateTime<Utc>) -> Option<ReviewDecision>
     where
         K: Serialize,
     {
         let s = serde_json::to_string(key).ok()?;
-        self.map.get(&s).cloned()
+        let stored = self.map.get(&s)?;
+        if self.is_expired(stored, now) {
+            return None;
+        }
+        Some(stored.decision.clone())
     }
 
     pub fn put<K>(&mut self, key: K, value: ReviewDecision)
     where
-        K: Serialize,
+        K: SessionApprovalKey,
+    {
+        self.put_at(key, value, Utc::now());
+    }
+
+    fn put_at<K>(&mut self, key: K, value: ReviewDecision, now: DateTime<Utc>)
+    where
+        K: SessionApprovalKey,
     {
         if let Ok(s) = serde_json::to_string(&key) {
-            self.map.insert(s, value);
+            self.next_id += 1;
+            self.map.insert(
+                s,
+                StoredApproval {
+                    id: self.next_id,
+                    decision: value,
+                    kind: key.session_approval_kind(),
+                    approved_at: now,
+                },
+            );
+        }
+    }
+
+    /// Approvals still in effect, oldest first. Expired entries are dropped.
+    /// Keys that describe themselves the same way (e.g. one command approved
+    /// with and without escalated permissions) are listed once, under the id
+    /// of the first approval and the time of the latest.
+    pub(crate) fn list(&mut self) -> Vec<SessionApprovalEntry> {
+        self.list_at(Utc::now())
+    }
+
+    fn list_at(&mut self, now: DateTime<Utc>) -> Vec<SessionApprovalEntry> {
+        let ttl = self.ttl;
+        self.map.retain(|_, stored| !stored.is_expired(ttl, now));
+        let mut stored: Vec<&StoredApproval> = self.map.values().collect();
+        stored.sort_by_key(|stored| stored.id);
+        let mut entries: Vec<SessionApprovalEntry> = Vec::new();
+        for stored in stored {
+            let approved_at = stored.approved_at.timestamp();
+            let expires_at = stored.expires_at(ttl).map(|at| at.timestamp());
+            match entries.iter_mut().find(|entry| entry.kind == stored.kind) {
+                Some(entry) => {
+                    entry.approved_at = entry.approved_at.max(approved_at);
+                    entry.expires_at = entry.expires_at.max(expires_at);
+                }
+                None => entries.push(SessionApprovalEntry {
+                    id: stored.id.to_string(),
+                    kind: stored.kind.clone(),
+                    approved_at,
+                    expires_at,
+                }),
+            }
         }
+        entries
+    }
+
+    /// Forget the entry with `id`, along with any other approval listed under
+    /// it. Returns what was revoked, if anything.
+    pub(crate) fn revoke(&mut self, id: &str) -> Option<SessionApprovalKind> {
+        let kind = self
+            .map
+            .values()
+            .find(|stored| stored.id.to_string() == id)?
+            .kind
+            .clone();
+        self.map.retain(|_, stored| stored.kind != kind);
+        Some(kind)
     }
+
+    fn is_expired(&self, stored: &StoredApproval, now: DateTime<Utc>) -> bool {
+        stored.is_expired(self.ttl, now)
+    }
+}
+
+fn expires_at(approved_at: DateTime<Utc>, ttl: Option<Duration>) -> Option<DateTime<Utc>> {
+    let ttl = TimeDelta::from_std(ttl?).unwrap_or(TimeDelta::MAX);
+    Some(
+        approved_at
+            .checked_add_signed(ttl)
+            .unwrap_or(DateTime::<Utc>::MAX_UTC),
+    )
 }
 
 /// Takes a vector of approval keys and returns a ReviewDecision.
@@ -63,7 +194,7 @@ pub(crate) async fn with_cached_approval<K, F, Fut>(
     fetch: F,
 ) -> ReviewDecision
 where
-    K: Serialize,
+    K: SessionApprovalKey,
     F: FnOnce() -> Fut,
     Fut: Future<Output = ReviewDecision>,
 {
@@ -185,8 +316,24 @@ pub(crate) enum SandboxOverride {
     BypassSandboxFirstAttempt,
 }
 
+impl SessionApprovalKey for ExecPolicyAmendment {
+    fn session_approval_kind(&self) -> SessionApprovalKind {
+        SessionApprovalKind::CommandPrefix {
+            prefix: self.command.clone(),
+        }
+    }
+}
+
+impl SessionApprovalKey for AbsolutePathBuf {
+    fn session_approval_kind(&self) -> SessionApprovalKind {
+        SessionApprovalKind::PatchPath {
+            path: self.to_path_buf(),
+        }
+    }
+}
+
 pub(crate) trait Approvable<Req> {
-    type ApprovalKey: Hash + Eq + Clone + Debug + Serialize;
+    type ApprovalKey: Hash + Eq + Clone + Debug + SessionApprovalKey;
 
     // In most cases (shell, unified_exec), a request will have a single approval key.
     //
@@ -325,4 +472,137 @@ mod tests {
             }
         );
     }
+
+    #[derive(Serialize)]
+    struct TestKey(&'static str);
+
+    impl SessionApprovalKey for TestKey {
+        fn session_approval_kind(&self) -> SessionApprovalKind {
+            SessionApprovalKind::Command {
+                command: vec![self.0.to_string()],
+                cwd: "/repo".into(),
+            }
+        }
+    }
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).expect("valid timestamp")
+    }
+
+    #[test]
+    fn approval_store_lists_and_revokes_entries() {
+        let mut store = ApprovalStore::default();
+        store.put_at(TestKey("ls"), ReviewDecision::ApprovedForSession, at(10));
+        store.put_at(TestKey("pwd"), ReviewDecision::ApprovedForSession, at(20));
+
+        assert_eq!(
+            store.list_at(at(30)),
+            vec![
+                SessionApprovalEntry {
+                    id: "1".to_string(),
+                    kind: TestKey("ls").session_approval_kind(),
+                    approved_at: 10,
+                    expires_at: None,
+                },
+                SessionApprovalEntry {
+                    id: "2".to_string(),
+                    kind: TestKey("pwd").session_approval_kind(),
+                    approved_at: 20,
+                    expires_at: None,
+                },
+            ]
+        );
+
+        assert_eq!(
+            store.revoke("1"),
+            Some(TestKey("ls").session_approval_kind())
+        );
+        assert_eq!(store.revoke("1"), None);
+        assert_eq!(store.get_at(&TestKey("ls"), at(30)), None);
+        assert_eq!(
+            store.get_at(&TestKey("pwd"), at(30)),
+            Some(ReviewDecision::ApprovedForSession)
+        );
+    }
+
+    #[test]
+    fn approval_store_expires_entries_after_ttl() {
+        let mut store = ApprovalStore::new(Some(Duration::from_secs(60)));
+        store.put_at(TestKey("ls"), ReviewDecision::ApprovedForSession, at(100));
+
+        assert_eq!(
+            store.get_at(&TestKey("ls"), at(159)),
+            Some(ReviewDecision::ApprovedForSession)
+        );
+        assert_eq!(store.list_at(at(159))[0].expires_at, Some(160));
+        assert_eq!(store.get_at(&TestKey("ls"), at(160)), None);
+        assert_eq!(store.list_at(at(160)), Vec::new());
+    }
+
+    /// Same command as [`TestKey`], approved with different permissions.
+    #[derive(Serialize)]
+    struct EscalatedTestKey {
+        command: &'static str,
+    }
+
+    impl SessionApprovalKey for EscalatedTestKey {
+        fn session_approval_kind(&self) -> SessionApprovalKind {
+            TestKey(self.command).session_approval_kind()
+        }
+    }
+
+    #[test]
+    fn approval_store_lists_matching_keys_once() {
+        let mut store = ApprovalStore::default();
+        store.put_at(TestKey("ls"), ReviewDecision::ApprovedForSession, at(10));
+        store.put_at(
+            EscalatedTestKey { command: "ls" },
+            ReviewDecision::ApprovedForSession,
+            at(20),
+        );
+
+        assert_eq!(
+            store.list_at(at(30)),
+            vec![SessionApprovalEntry {
+                id: "1".to_string(),
+                kind: TestKey("ls").session_approval_kind(),
+                approved_at: 20,
+                expires_at: None,
+            }]
+        );
+
+        assert_eq!(
+            store.revoke("1"),
+            Some(TestKey("ls").session_approval_kind())
+        );
+        assert_eq!(
+            store.get_at(&EscalatedTestKey { command: "ls" }, at(30)),
+            None
+        );
+    }
+
+    #[test]
+    fn approval_store_keeps_command_prefixes_past_ttl() {
+        let mut store = ApprovalStore::new(Some(Duration::from_secs(60)));
+        let amendment = ExecPolicyAmendment::from(vec!["cargo".to_string(), "test".to_string()]);
+        store.put_at(
+            amendment.clone(),
+            ReviewDecision::ApprovedExecpolicyAmendment {
+                proposed_execpolicy_amendment: amendment.clone(),
+            },
+            at(100),
+        );
+
+        assert_eq!(
+            store.list_at(at(1_000)),
+            vec![SessionApprovalEntry {
+                id: "1".to_string(),
+                kind: SessionApprovalKind::CommandPrefix {
+                    prefix: amendment.command,
+                },
+                approved_at: 100,
+                expires_at: None,
+            }]
+        );
+    }
 }
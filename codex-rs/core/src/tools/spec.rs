@@ -1,6 +1,8 @@
-use crate::agent::AgentRole;
+use crate::agent::role::agent_type_values;
+use crate::agent::role::builtin_agent_roles;
 use crate::client_common::tools::ResponsesApiTool;
 use crate::client_common::tools::ToolSpec;
+use crate::config::types::AgentRoleConfig;
 use crate::features::Feature;
 use crate::features::Features;
 use crate::tools::handlers::PLAN_TOOL;
@@ -29,10 +31,14 @@ pub(crate) struct ToolsConfig {
     pub shell_type: ConfigShellToolType,
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_mode: Option<WebSearchMode>,
+    pub fetch_url: bool,
+    pub symbol_tools: bool,
     pub collab_tools: bool,
     pub collaboration_modes_tools: bool,
     pub request_rule_enabled: bool,
     pub experimental_supported_tools: Vec<String>,
+    pub agent_types: Vec<String>,
+    pub tool_allowlist: Option<Vec<String>>,
 }
 
 pub(crate) struct ToolsConfigParams<'a> {
@@ -82,12 +88,28 @@ impl ToolsConfig {
             shell_type,
             apply_patch_tool_type,
             web_search_mode: *web_search_mode,
+            fetch_url: features.enabled(Feature::FetchUrl),
+            symbol_tools: features.enabled(Feature::SymbolTools),
             collab_tools: include_collab_tools,
             collaboration_modes_tools: include_collaboration_modes_tools,
             request_rule_enabled,
             experimental_supported_tools: model_info.experimental_supported_tools.clone(),
+            agent_types: agent_type_values(&builtin_agent_roles()),
+            tool_allowlist: None,
         }
     }
+
+    /// Advertises `roles` as the `agent_type` values accepted by `spawn_agent`.
+    pub fn with_agent_roles(mut self, roles: &BTreeMap<String, AgentRoleConfig>) -> Self {
+        self.agent_types = agent_type_values(roles);
+        self
+    }
+
+    /// Restricts the exposed tools to `tool_allowlist` when set.
+    pub fn with_tool_allowlist(mut self, tool_allowlist: Option<Vec<String>>) -> Self {
+        self.tool_allowlist = tool_allowlist;
+        self
+    }
 }
 
 /// Generic JSON‑Schema subset needed for our tool definitions
@@ -446,7 +468,88 @@ fn create_view_image_tool() -> ToolSpec {
     })
 }
 
-fn create_spawn_agent_tool() -> ToolSpec {
+fn create_fetch_url_tool() -> ToolSpec {
+    let properties = BTreeMap::from([(
+        "url".to_string(),
+        JsonSchema::String {
+            description: Some("Absolute http(s) URL of the page to fetch.".to_string()),
+        },
+    )]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "fetch_url".to_string(),
+        description: "Fetches a web page and returns its content as markdown. HTML is converted to markdown; plain text and JSON are returned as is. Long pages are truncated."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["url".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_find_symbol_tool(name: &str, description: &str) -> ToolSpec {
+    let properties = BTreeMap::from([
+        (
+            "name".to_string(),
+            JsonSchema::String {
+                description: Some("Exact symbol name, such as a function name.".to_string()),
+            },
+        ),
+        (
+            "path".to_string(),
+            JsonSchema::String {
+                description: Some(
+                    "Directory or file path to search. Defaults to the session's working directory."
+                        .to_string(),
+                ),
+            },
+        ),
+        (
+            "limit".to_string(),
+            JsonSchema::Number {
+                description: Some(
+                    "Maximum number of locations to return (defaults to 50).".to_string(),
+                ),
+            },
+        ),
+    ]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: name.to_string(),
+        description: description.to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["name".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_list_symbols_tool() -> ToolSpec {
+    let properties = BTreeMap::from([(
+        "path".to_string(),
+        JsonSchema::String {
+            description: Some("Path of the source file to outline.".to_string()),
+        },
+    )]);
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "list_symbols".to_string(),
+        description: "Lists the symbols defined in a source file with their line numbers."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["path".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
+fn create_spawn_agent_tool(agent_types: &[String]) -> ToolSpec {
     let mut properties = BTreeMap::new();
     properties.insert(
         "message".to_string(),
@@ -462,15 +565,32 @@ fn create_spawn_agent_tool() -> ToolSpec {
         JsonSchema::String {
             description: Some(format!(
                 "Optional agent type ({}). Use an explicit type when delegating.",
-                AgentRole::enum_values().join(", ")
+                agent_types.join(", ")
             )),
         },
     );
+    properties.insert(
+        "worktree".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Run the agent in its own git worktree on a throwaway branch so its edits do not touch your checkout. Review and apply them later with merge_agent."
+                    .to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "output_schema".to_string(),
+        JsonSchema::Object {
+            properties: BTreeMap::new(),
+            required: None,
+            additional_properties: Some(true.into()),
+        },
+    );
 
     ToolSpec::Function(ResponsesApiTool {
         name: "spawn_agent".to_string(),
         description:
-            "Spawn a sub-agent for a well-scoped task. Returns the agent id to use to communicate with this agent."
+            "Spawn a sub-agent for a well-scoped task. Returns the agent id to use to communicate with this agent. Pass `output_schema` (a strict-mode JSON Schema with `\"type\": \"object\"`, every property listed in `required` and `\"additionalProperties\": false`) to make the agent answer with JSON matching it; `wait` then returns the parsed answer under `results`."
                 .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
@@ -542,7 +662,7 @@ fn create_wait_tool() -> ToolSpec {
 
     ToolSpec::Function(ResponsesApiTool {
         name: "wait".to_string(),
-        description: "Wait for agents to reach a final status. Completed statuses may include the agent's final message. Returns empty status when timed out."
+        description: "Wait for agents to reach a final status. Completed statuses may include the agent's final message; for agents spawned with an output_schema, the validated JSON answer is under `results` and schema mismatches under `result_errors`. Returns empty status when timed out."
             .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
@@ -656,6 +776,40 @@ fn create_close_agent_tool() -> ToolSpec {
     })
 }
 
+fn create_merge_agent_tool() -> ToolSpec {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "id".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Id of an agent spawned with `worktree: true` (from spawn_agent).".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "action".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "One of `diff` (show the agent's changes), `merge` (merge its branch into your checkout), `cherry_pick` (apply its commits without a merge commit) or `discard` (drop its changes). Every action but `diff` removes the worktree and needs the agent to be finished."
+                    .to_string(),
+            ),
+        },
+    );
+
+    ToolSpec::Function(ResponsesApiTool {
+        name: "merge_agent".to_string(),
+        description:
+            "Review, merge, cherry-pick or discard the changes an agent made in its own worktree."
+                .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["id".to_string(), "action".to_string()]),
+            additional_properties: Some(false.into()),
+        },
+    })
+}
+
 fn create_test_sync_tool() -> ToolSpec {
     let barrier_properties = BTreeMap::from([
         (
@@ -1228,6 +1382,7 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::ApplyPatchHandler;
     use crate::tools::handlers::CollabHandler;
     use crate::tools::handlers::DynamicToolHandler;
+    use crate::tools::handlers::FetchUrlHandler;
     use crate::tools::handlers::GrepFilesHandler;
     use crate::tools::handlers::ListDirHandler;
     use crate::tools::handlers::McpHandler;
@@ -1237,6 +1392,7 @@ pub(crate) fn build_specs(
     use crate::tools::handlers::RequestUserInputHandler;
     use crate::tools::handlers::ShellCommandHandler;
     use crate::tools::handlers::ShellHandler;
+    use crate::tools::handlers::SymbolsHandler;
     use crate::tools::handlers::TestSyncHandler;
     use crate::tools::handlers::UnifiedExecHandler;
     use crate::tools::handlers::ViewImageHandler;
@@ -1371,19 +1527,48 @@ pub(crate) fn build_specs(
         Some(WebSearchMode::Disabled) | None => {}
     }
 
+    if config.fetch_url {
+        builder.push_spec_with_parallel_support(create_fetch_url_tool(), true);
+        builder.register_handler("fetch_url", Arc::new(FetchUrlHandler));
+    }
+
+    if config.symbol_tools {
+        let symbols_handler = Arc::new(SymbolsHandler);
+        builder.push_spec_with_parallel_support(
+            create_find_symbol_tool(
+                "find_symbol",
+                "Finds where a symbol is defined. Ignored files are skipped. Returns `path:line: [kind] source line` for each definition.",
+            ),
+            true,
+        );
+        builder.push_spec_with_parallel_support(
+            create_find_symbol_tool(
+                "find_references",
+                "Finds where a symbol is used, such as calls of a function. Ignored files are skipped. Returns `path:line: [kind] source line` for each reference.",
+            ),
+            true,
+        );
+        builder.push_spec_with_parallel_support(create_list_symbols_tool(), true);
+        builder.register_handler("find_symbol", symbols_handler.clone());
+        builder.register_handler("find_references", symbols_handler.clone());
+        builder.register_handler("list_symbols", symbols_handler);
+    }
+
     builder.push_spec_with_parallel_support(create_view_image_tool(), true);
     builder.register_handler("view_image", view_image_handler);
 
     if config.collab_tools {
         let collab_handler = Arc::new(CollabHandler);
-        builder.push_spec(create_spawn_agent_tool());
+        builder.push_spec(create_spawn_agent_tool(&config.agent_types));
         builder.push_spec(create_send_input_tool());
         builder.push_spec(create_wait_tool());
         builder.push_spec(create_close_agent_tool());
+        builder.push_spec(create_merge_agent_tool());
         builder.register_handler("spawn_agent", collab_handler.clone());
         builder.register_handler("send_input", collab_handler.clone());
         builder.register_handler("wait", collab_handler.clone());
-        builder.register_handler("close_agent", collab_handler);
+        builder.register_handler("close_agent", collab_handler.clone());
+        builder.register_handler("merge_agent", collab_handler);
     }
 
     if let Some(mcp_tools) = mcp_tools {
@@ -1420,6 +1605,13 @@ pub(crate) fn build_specs(
         }
     }
 
+    if let Some(tool_allowlist) = &config.tool_allowlist {
+        let unknown = builder.retain_tools(tool_allowlist);
+        if !unknown.is_empty() {
+            tracing::warn!("tool allowlist names unknown tools: {}", unknown.join(", "));
+        }
+    }
+
     builder
 }
 
@@ -1638,10 +1830,124 @@ mod tests {
         let (tools, _) = build_specs(&tools_config, None, &[]).build();
         assert_contains_tool_names(
             &tools,
-            &["spawn_agent", "send_input", "wait", "close_agent"],
+            &[
+                "spawn_agent",
+                "send_input",
+                "wait",
+                "close_agent",
+                "merge_agent",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_build_specs_fetch_url_enabled() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: None,
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        assert!(
+            !tools
+                .iter()
+                .any(|tool| tool_name(&tool.spec) == "fetch_url")
+        );
+
+        features.enable(Feature::FetchUrl);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: None,
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        let tool = find_tool(&tools, "fetch_url");
+        assert!(tool.supports_parallel_tool_calls);
+    }
+
+    #[test]
+    fn test_build_specs_symbol_tools_enabled() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::SymbolTools);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: None,
+        });
+        let (tools, _) = build_specs(&tools_config, None, &[]).build();
+        for name in ["find_symbol", "find_references", "list_symbols"] {
+            assert!(find_tool(&tools, name).supports_parallel_tool_calls);
+        }
+    }
+
+    #[test]
+    fn agent_roles_and_tool_allowlist_shape_specs() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let mut features = Features::with_defaults();
+        features.enable(Feature::Collab);
+        let roles = BTreeMap::from([(
+            "test-writer".to_string(),
+            AgentRoleConfig {
+                description: "Writes tests.".to_string(),
+                ..Default::default()
+            },
+        )]);
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        })
+        .with_agent_roles(&roles)
+        .with_tool_allowlist(Some(vec!["spawn_agent".to_string(), "wait".to_string()]));
+        let (tools, registry) = build_specs(&tools_config, None, &[]).build();
+
+        let names: Vec<&str> = tools.iter().map(|tool| tool_name(&tool.spec)).collect();
+        assert_eq!(names, vec!["spawn_agent", "wait"]);
+        assert!(registry.handler("shell_command").is_none());
+        let ToolSpec::Function(ResponsesApiTool {
+            parameters: JsonSchema::Object { properties, .. },
+            ..
+        }) = &tools[0].spec
+        else {
+            panic!("spawn_agent should be a function tool");
+        };
+        assert_eq!(
+            properties.get("agent_type"),
+            Some(&JsonSchema::String {
+                description: Some(
+                    r#"Optional agent type ({"name":"test-writer","description":"Writes tests."}). Use an explicit type when delegating."#
+                        .to_string()
+                ),
+            })
         );
     }
 
+    #[test]
+    fn retain_tools_reports_unknown_names() {
+        let config = test_config();
+        let model_info = ModelsManager::construct_model_info_offline("gpt-5-codex", &config);
+        let features = Features::with_defaults();
+        let tools_config = ToolsConfig::new(&ToolsConfigParams {
+            model_info: &model_info,
+            features: &features,
+            web_search_mode: Some(WebSearchMode::Cached),
+        });
+        let mut builder = build_specs(&tools_config, None, &[]);
+
+        let unknown = builder.retain_tools(&["shell_command".to_string(), "shel".to_string()]);
+
+        assert_eq!(unknown, vec!["shel".to_string()]);
+        let (tools, _) = builder.build();
+        let names: Vec<&str> = tools.iter().map(|tool| tool_name(&tool.spec)).collect();
+        assert_eq!(names, vec!["shell_command"]);
+    }
+
     #[test]
     fn request_user_input_requires_collaboration_modes_feature() {
         let config = test_config();
@@ -7,7 +7,15 @@ use codex_protocol::openai_models::TruncationMode;
 use codex_protocol::openai_models::TruncationPolicyConfig;
 use codex_protocol::protocol::TruncationPolicy as ProtocolTruncationPolicy;
 
+use crate::tokenizer::Tokenizer;
+
 const APPROX_BYTES_PER_TOKEN: usize = 4;
+/// Exact token counts cost time proportional to the input, so only this many
+/// bytes per budgeted token (and at least [`MIN_EXACT_COUNT_BYTES`]) are
+/// tokenized. The middle of larger inputs is estimated from the measured
+/// prefix and suffix.
+const MAX_EXACT_COUNT_BYTES_PER_TOKEN: usize = 4 * APPROX_BYTES_PER_TOKEN;
+const MIN_EXACT_COUNT_BYTES: usize = 64 * 1024;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TruncationPolicy {
@@ -76,20 +84,28 @@ impl std::ops::Mul<f64> for TruncationPolicy {
     }
 }
 
-pub(crate) fn formatted_truncate_text(content: &str, policy: TruncationPolicy) -> String {
-    if content.len() <= policy.byte_budget() {
-        return content.to_string();
+pub(crate) fn formatted_truncate_text(
+    content: &str,
+    policy: TruncationPolicy,
+    tokenizer: &Tokenizer,
+) -> String {
+    let result = truncate_text(content, policy, tokenizer);
+    if result == content {
+        return result;
     }
     let total_lines = content.lines().count();
-    let result = truncate_text(content, policy);
     format!("Total output lines: {total_lines}\n\n{result}")
 }
 
-pub(crate) fn truncate_text(content: &str, policy: TruncationPolicy) -> String {
+pub(crate) fn truncate_text(
+    content: &str,
+    policy: TruncationPolicy,
+    tokenizer: &Tokenizer,
+) -> String {
     match policy {
         TruncationPolicy::Bytes(_) => truncate_with_byte_estimate(content, policy),
         TruncationPolicy::Tokens(_) => {
-            let (truncated, _) = truncate_with_token_budget(content, policy);
+            let (truncated, _) = truncate_with_token_budget(content, policy, tokenizer);
             truncated
         }
     }
@@ -100,6 +116,7 @@ pub(crate) fn truncate_text(content: &str, policy: TruncationPolicy) -> String {
 pub(crate) fn truncate_function_output_items_with_policy(
     items: &[FunctionCallOutputContentItem],
     policy: TruncationPolicy,
+    tokenizer: &Tokenizer,
 ) -> Vec<FunctionCallOutputContentItem> {
     let mut out: Vec<FunctionCallOutputContentItem> = Vec::with_capacity(items.len());
     let mut remaining_budget = match policy {
@@ -118,7 +135,9 @@ pub(crate) fn truncate_function_output_items_with_policy(
 
                 let cost = match policy {
                     TruncationPolicy::Bytes(_) => text.len(),
-                    TruncationPolicy::Tokens(_) => approx_token_count(text),
+                    TruncationPolicy::Tokens(_) => {
+                        count_tokens_bounded(text, remaining_budget, tokenizer)
+                    }
                 };
 
                 if cost <= remaining_budget {
@@ -129,7 +148,7 @@ pub(crate) fn truncate_function_output_items_with_policy(
                         TruncationPolicy::Bytes(_) => TruncationPolicy::Bytes(remaining_budget),
                         TruncationPolicy::Tokens(_) => TruncationPolicy::Tokens(remaining_budget),
                     };
-                    let snippet = truncate_text(text, snippet_policy);
+                    let snippet = truncate_text(text, snippet_policy, tokenizer);
                     if snippet.is_empty() {
                         omitted_text_items += 1;
                     } else {
@@ -159,27 +178,78 @@ pub(crate) fn truncate_function_output_items_with_policy(
 /// preserving the beginning and the end. Returns the possibly truncated string
 /// and `Some(original_token_count)` if truncation occurred; otherwise returns
 /// the original string and `None`.
-fn truncate_with_token_budget(s: &str, policy: TruncationPolicy) -> (String, Option<u64>) {
+fn truncate_with_token_budget(
+    s: &str,
+    policy: TruncationPolicy,
+    tokenizer: &Tokenizer,
+) -> (String, Option<u64>) {
     if s.is_empty() {
         return (String::new(), None);
     }
     let max_tokens = policy.token_budget();
+    // Every token covers at least one byte.
+    if max_tokens > 0 && s.len() <= max_tokens {
+        return (s.to_string(), None);
+    }
 
-    let byte_len = s.len();
-    if max_tokens > 0 && byte_len <= approx_bytes_for_tokens(max_tokens) {
+    let total_tokens = count_tokens_bounded(s, max_tokens, tokenizer);
+    if max_tokens > 0 && total_tokens <= max_tokens {
         return (s.to_string(), None);
     }
 
-    let truncated = truncate_with_byte_estimate(s, policy);
-    let approx_total_usize = approx_token_count(s);
-    let approx_total = u64::try_from(approx_total_usize).unwrap_or(u64::MAX);
+    let truncated = if tokenizer.is_exact() {
+        truncate_with_tokenizer(s, max_tokens, total_tokens, tokenizer)
+    } else {
+        truncate_with_byte_estimate(s, policy)
+    };
+    let total_tokens = u64::try_from(total_tokens).unwrap_or(u64::MAX);
     if truncated == s {
         (truncated, None)
     } else {
-        (truncated, Some(approx_total))
+        (truncated, Some(total_tokens))
     }
 }
 
+/// Counts the tokens in `s` when it is small relative to `max_tokens`.
+/// Otherwise only a prefix and suffix are tokenized and the total is
+/// extrapolated from their bytes-per-token ratio.
+fn count_tokens_bounded(s: &str, max_tokens: usize, tokenizer: &Tokenizer) -> usize {
+    let cap = max_tokens
+        .saturating_mul(MAX_EXACT_COUNT_BYTES_PER_TOKEN)
+        .max(MIN_EXACT_COUNT_BYTES);
+    if !tokenizer.is_exact() || s.len() <= cap {
+        return tokenizer.count_tokens(s);
+    }
+    let (left_budget, right_budget) = split_budget(cap);
+    let (_, left, right) = split_string(s, left_budget, right_budget);
+    let sampled_bytes = left.len() + right.len();
+    let sampled_tokens = tokenizer
+        .count_tokens(left)
+        .saturating_add(tokenizer.count_tokens(right));
+    (sampled_tokens as u128 * s.len() as u128 / sampled_bytes.max(1) as u128) as usize
+}
+
+/// Truncate a string to `max_tokens` using the text's own bytes-per-token
+/// ratio to size the kept prefix and suffix, and report the exact number of
+/// tokens removed.
+fn truncate_with_tokenizer(
+    s: &str,
+    max_tokens: usize,
+    total_tokens: usize,
+    tokenizer: &Tokenizer,
+) -> String {
+    let max_bytes = (s.len() as u128 * max_tokens as u128 / total_tokens.max(1) as u128) as usize;
+    let (left_budget, right_budget) = split_budget(max_bytes);
+    let (_, left, right) = split_string(s, left_budget, right_budget);
+    let kept_tokens = tokenizer
+        .count_tokens(left)
+        .saturating_add(tokenizer.count_tokens(right));
+    let removed_tokens =
+        u64::try_from(total_tokens.saturating_sub(kept_tokens)).unwrap_or(u64::MAX);
+    let marker = format_truncation_marker(TruncationPolicy::Tokens(max_tokens), removed_tokens);
+    assemble_truncated_output(left, right, &marker)
+}
+
 /// Truncate a string using a byte budget derived from the token budget, without
 /// performing any real tokenization. This keeps the logic purely byte-based and
 /// uses a bytes placeholder in the truncated output.
@@ -309,11 +379,13 @@ mod tests {
 
     use super::TruncationPolicy;
     use super::approx_token_count;
+    use super::count_tokens_bounded;
     use super::formatted_truncate_text;
     use super::split_string;
     use super::truncate_function_output_items_with_policy;
     use super::truncate_text;
     use super::truncate_with_token_budget;
+    use crate::tokenizer::Tokenizer;
     use codex_protocol::models::FunctionCallOutputContentItem;
     use pretty_assertions::assert_eq;
 
@@ -358,7 +430,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 1\n\n…13 chars truncated…t",
-            formatted_truncate_text(content, TruncationPolicy::Bytes(1)),
+            formatted_truncate_text(content, TruncationPolicy::Bytes(1), &Tokenizer::default()),
         );
     }
 
@@ -368,7 +440,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 1\n\nex…3 tokens truncated…ut",
-            formatted_truncate_text(content, TruncationPolicy::Tokens(1)),
+            formatted_truncate_text(content, TruncationPolicy::Tokens(1), &Tokenizer::default()),
         );
     }
 
@@ -378,7 +450,7 @@ mod tests {
 
         assert_eq!(
             content,
-            formatted_truncate_text(content, TruncationPolicy::Tokens(10)),
+            formatted_truncate_text(content, TruncationPolicy::Tokens(10), &Tokenizer::default()),
         );
     }
 
@@ -388,7 +460,7 @@ mod tests {
 
         assert_eq!(
             content,
-            formatted_truncate_text(content, TruncationPolicy::Bytes(20)),
+            formatted_truncate_text(content, TruncationPolicy::Bytes(20), &Tokenizer::default()),
         );
     }
 
@@ -398,7 +470,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 1\n\nthis is an…10 tokens truncated… truncated",
-            formatted_truncate_text(content, TruncationPolicy::Tokens(5)),
+            formatted_truncate_text(content, TruncationPolicy::Tokens(5), &Tokenizer::default()),
         );
     }
 
@@ -408,7 +480,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 1\n\nthis is an exam…30 chars truncated…ld be truncated",
-            formatted_truncate_text(content, TruncationPolicy::Bytes(30)),
+            formatted_truncate_text(content, TruncationPolicy::Bytes(30), &Tokenizer::default()),
         );
     }
 
@@ -419,7 +491,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 2\n\nthis is an exam…51 chars truncated…some other line",
-            formatted_truncate_text(content, TruncationPolicy::Bytes(30)),
+            formatted_truncate_text(content, TruncationPolicy::Bytes(30), &Tokenizer::default()),
         );
     }
 
@@ -430,7 +502,7 @@ mod tests {
 
         assert_eq!(
             "Total output lines: 2\n\nthis is an example o…11 tokens truncated…also some other line",
-            formatted_truncate_text(content, TruncationPolicy::Tokens(10)),
+            formatted_truncate_text(content, TruncationPolicy::Tokens(10), &Tokenizer::default()),
         );
     }
 
@@ -438,7 +510,8 @@ mod tests {
     fn truncate_with_token_budget_returns_original_when_under_limit() {
         let s = "short output";
         let limit = 100;
-        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(limit));
+        let (out, original) =
+            truncate_with_token_budget(s, TruncationPolicy::Tokens(limit), &Tokenizer::default());
         assert_eq!(out, s);
         assert_eq!(original, None);
     }
@@ -446,7 +519,8 @@ mod tests {
     #[test]
     fn truncate_with_token_budget_reports_truncation_at_zero_limit() {
         let s = "abcdef";
-        let (out, original) = truncate_with_token_budget(s, TruncationPolicy::Tokens(0));
+        let (out, original) =
+            truncate_with_token_budget(s, TruncationPolicy::Tokens(0), &Tokenizer::default());
         assert_eq!(out, "…2 tokens truncated…");
         assert_eq!(original, Some(2));
     }
@@ -454,7 +528,8 @@ mod tests {
     #[test]
     fn truncate_middle_tokens_handles_utf8_content() {
         let s = "😀😀😀😀😀😀😀😀😀😀\nsecond line with text\n";
-        let (out, tokens) = truncate_with_token_budget(s, TruncationPolicy::Tokens(8));
+        let (out, tokens) =
+            truncate_with_token_budget(s, TruncationPolicy::Tokens(8), &Tokenizer::default());
         assert_eq!(out, "😀😀😀😀…8 tokens truncated… line with text\n");
         assert_eq!(tokens, Some(16));
     }
@@ -462,7 +537,7 @@ mod tests {
     #[test]
     fn truncate_middle_bytes_handles_utf8_content() {
         let s = "😀😀😀😀😀😀😀😀😀😀\nsecond line with text\n";
-        let out = truncate_text(s, TruncationPolicy::Bytes(20));
+        let out = truncate_text(s, TruncationPolicy::Bytes(20), &Tokenizer::default());
         assert_eq!(out, "😀😀…21 chars truncated…with text\n");
     }
 
@@ -489,8 +564,11 @@ mod tests {
             FunctionCallOutputContentItem::InputText { text: t5 },
         ];
 
-        let output =
-            truncate_function_output_items_with_policy(&items, TruncationPolicy::Tokens(limit));
+        let output = truncate_function_output_items_with_policy(
+            &items,
+            TruncationPolicy::Tokens(limit),
+            &Tokenizer::default(),
+        );
 
         // Expect: t1 (full), t2 (full), image, t3 (truncated), summary mentioning 2 omitted.
         assert_eq!(output.len(), 5);
@@ -529,4 +607,52 @@ mod tests {
         };
         assert!(summary_text.contains("omitted 2 text items"));
     }
+
+    #[test]
+    fn truncate_tokens_uses_exact_tokenizer() {
+        let mut ranks: std::collections::HashMap<Vec<u8>, u32> = (0..=u8::MAX)
+            .map(|byte| (vec![byte], u32::from(byte)))
+            .collect();
+        ranks.insert(b"ab".to_vec(), 256);
+        let bpe = codex_utils_tokenizer::Bpe::from_ranks(
+            codex_utils_tokenizer::Encoding::O200kBase,
+            ranks,
+        )
+        .expect("valid ranks");
+        let tokenizer = Tokenizer::from_bpe(std::sync::Arc::new(bpe));
+
+        // Two bytes per token: the heuristic would keep twice as much text.
+        let s = "ab".repeat(100);
+        let (out, total) = truncate_with_token_budget(&s, TruncationPolicy::Tokens(10), &tokenizer);
+        assert_eq!(out, "ababababab…90 tokens truncated…ababababab");
+        assert_eq!(total, Some(100));
+
+        let (out, total) =
+            truncate_with_token_budget(&s, TruncationPolicy::Tokens(100), &tokenizer);
+        assert_eq!(out, s);
+        assert_eq!(total, None);
+    }
+
+    #[test]
+    fn exact_counts_sample_large_inputs() {
+        let mut ranks: std::collections::HashMap<Vec<u8>, u32> = (0..=u8::MAX)
+            .map(|byte| (vec![byte], u32::from(byte)))
+            .collect();
+        ranks.insert(b"ab".to_vec(), 256);
+        let bpe = codex_utils_tokenizer::Bpe::from_ranks(
+            codex_utils_tokenizer::Encoding::O200kBase,
+            ranks,
+        )
+        .expect("valid ranks");
+        let tokenizer = Tokenizer::from_bpe(std::sync::Arc::new(bpe));
+
+        let s = "ab ".repeat(100_000);
+        assert_eq!(tokenizer.count_tokens(&s), 200_000);
+        let estimate = count_tokens_bounded(&s, 10, &tokenizer);
+        assert!(estimate.abs_diff(200_000) < 100, "{estimate}");
+
+        let (out, total) = truncate_with_token_budget(&s, TruncationPolicy::Tokens(10), &tokenizer);
+        assert_eq!(total, Some(estimate as u64));
+        assert!(out.len() < 100, "{out}");
+    }
 }
@@ -515,6 +515,7 @@ mod tests {
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -545,6 +546,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -620,6 +622,7 @@ index {left_oid}..{ZERO_OID}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: Some(dest.clone()),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -660,6 +663,7 @@ index {left_oid}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: Some(dest.clone()),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv_changes);
@@ -682,6 +686,7 @@ index {left_oid}..{right_oid}
             FileChange::Update {
                 unified_diff: "".into(),
                 move_path: Some(dest.clone()),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&mv);
@@ -722,6 +727,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_a);
@@ -802,6 +808,7 @@ index {left_oid_b}..{ZERO_OID}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
@@ -838,6 +845,7 @@ Binary files differ
             file.clone(),
             FileChange::Add {
                 content: "foo\n".to_string(),
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&add_changes);
@@ -868,6 +876,7 @@ index {ZERO_OID}..{right_oid}
             FileChange::Update {
                 unified_diff: "".to_owned(),
                 move_path: None,
+                mode: None,
             },
         )]);
         acc.on_patch_begin(&update_changes);
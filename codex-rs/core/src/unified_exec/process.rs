@@ -13,6 +13,7 @@ use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StreamOutput;
 use crate::exec::is_likely_sandbox_denied;
+use crate::tokenizer::Tokenizer;
 use crate::truncate::TruncationPolicy;
 use crate::truncate::formatted_truncate_text;
 use codex_utils_pty::ExecCommandSession;
@@ -162,6 +163,7 @@ impl UnifiedExecProcess {
             let snippet = formatted_truncate_text(
                 text,
                 TruncationPolicy::Tokens(UNIFIED_EXEC_OUTPUT_MAX_TOKENS),
+                &Tokenizer::default(),
             );
             let message = if snippet.is_empty() {
                 format!("Process exited with code {exit_code}")
@@ -14,6 +14,7 @@ use crate::exec_env::create_env;
 use crate::exec_policy::ExecApprovalRequest;
 use crate::protocol::ExecCommandSource;
 use crate::sandboxing::ExecEnv;
+use crate::tokenizer::Tokenizer;
 use crate::tools::events::ToolEmitter;
 use crate::tools::events::ToolEventCtx;
 use crate::tools::events::ToolEventStage;
@@ -174,7 +175,11 @@ impl UnifiedExecProcessManager {
         let wall_time = Instant::now().saturating_duration_since(start);
 
         let text = String::from_utf8_lossy(&collected).to_string();
-        let output = formatted_truncate_text(&text, TruncationPolicy::Tokens(max_tokens));
+        let output = formatted_truncate_text(
+            &text,
+            TruncationPolicy::Tokens(max_tokens),
+            &context.turn.tokenizer,
+        );
         let exit_code = process.exit_code();
         let has_exited = process.has_exited() || exit_code.is_some();
         let chunk_id = generate_chunk_id();
@@ -218,7 +223,7 @@ impl UnifiedExecProcessManager {
             .await;
         };
 
-        let original_token_count = approx_token_count(&text);
+        let original_token_count = context.turn.tokenizer.count_tokens(&text);
         let response = UnifiedExecResponse {
             event_call_id: context.call_id.clone(),
             chunk_id,
@@ -286,7 +291,11 @@ impl UnifiedExecProcessManager {
         let wall_time = Instant::now().saturating_duration_since(start);
 
         let text = String::from_utf8_lossy(&collected).to_string();
-        let output = formatted_truncate_text(&text, TruncationPolicy::Tokens(max_tokens));
+        let output = formatted_truncate_text(
+            &text,
+            TruncationPolicy::Tokens(max_tokens),
+            &Tokenizer::default(),
+        );
         let original_token_count = approx_token_count(&text);
         let chunk_id = generate_chunk_id();
 
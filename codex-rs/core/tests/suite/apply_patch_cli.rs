@@ -6,14 +6,17 @@ use core_test_support::responses::ev_apply_patch_custom_tool_call;
 use core_test_support::responses::ev_shell_command_call;
 use core_test_support::test_codex::ApplyPatchModelOutput;
 use pretty_assertions::assert_eq;
+use std::collections::BTreeMap;
 use std::fs;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
 
+use codex_core::config::types::LspServerConfig;
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchApplyOutcome;
 use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::user_input::UserInput;
@@ -131,6 +134,85 @@ D delete.txt
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[test_case(ApplyPatchModelOutput::Freeform)]
+#[test_case(ApplyPatchModelOutput::Function)]
+async fn apply_patch_cli_runs_post_edit_commands(output_type: ApplyPatchModelOutput) -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let harness = apply_patch_harness_with(|builder| {
+        builder.with_model("gpt-5.1").with_config(|config| {
+            config.post_edit = BTreeMap::from([
+                (
+                    "*.txt".to_string(),
+                    "sh -c 'echo \"lint: $0 looks fine\"' {files}".to_string(),
+                ),
+                ("*.rs".to_string(), "false {files}".to_string()),
+            ]);
+        })
+    })
+    .await?;
+
+    let patch = "*** Begin Patch\n*** Add File: notes.txt\n+hello\n*** End Patch";
+    let call_id = "apply-post-edit";
+    mount_apply_patch(&harness, call_id, patch, "done", output_type).await;
+
+    harness.submit("add notes").await?;
+
+    let out = harness.apply_patch_output(call_id, output_type).await;
+    assert!(
+        out.contains("Post-edit commands ran on the edited files and may have changed them:"),
+        "{out}"
+    );
+    assert!(
+        out.contains("notes.txt\nexit code: 0\nlint: notes.txt looks fine"),
+        "{out}"
+    );
+    assert!(!out.contains("false"), "{out}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[test_case(ApplyPatchModelOutput::Freeform)]
+#[test_case(ApplyPatchModelOutput::Function)]
+async fn apply_patch_cli_reports_lsp_errors(output_type: ApplyPatchModelOutput) -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = codex_utils_cargo_bin::cargo_bin("test_lsp_server")?;
+    let harness = apply_patch_harness_with(move |builder| {
+        builder.with_model("gpt-5.1").with_config(move |config| {
+            config.lsp = BTreeMap::from([(
+                "plaintext".to_string(),
+                LspServerConfig {
+                    command: server.to_string_lossy().into_owned(),
+                    extensions: vec!["txt".to_string()],
+                    diagnostics_timeout_ms: Some(10_000),
+                    ..Default::default()
+                },
+            )]);
+        })
+    })
+    .await?;
+
+    let patch = "*** Begin Patch\n*** Add File: notes.txt\n+fine\n+ERROR: unknown word\n*** Add File: other.md\n+ERROR: not checked\n*** End Patch";
+    let call_id = "apply-lsp";
+    mount_apply_patch(&harness, call_id, patch, "done", output_type).await;
+
+    harness.submit("add notes").await?;
+
+    let out = harness.apply_patch_output(call_id, output_type).await;
+    assert!(
+        out.contains(
+            "Language servers reported errors in the edited files:\nnotes.txt:2:1: unknown word"
+        ),
+        "{out}"
+    );
+    assert!(!out.contains("not checked"), "{out}");
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 #[test_case(ApplyPatchModelOutput::Freeform)]
 #[test_case(ApplyPatchModelOutput::Function)]
@@ -908,6 +990,7 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
     let mut saw_turn_diff = None;
     let mut saw_patch_begin = false;
     let mut patch_end_success = None;
+    let mut patch_end_outcome = None;
     wait_for_event(&codex, |event| match event {
         EventMsg::PatchApplyBegin(begin) => {
             saw_patch_begin = true;
@@ -917,6 +1000,7 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
         EventMsg::PatchApplyEnd(end) => {
             assert_eq!(end.call_id, call_id);
             patch_end_success = Some(end.success);
+            patch_end_outcome = end.outcome;
             false
         }
         EventMsg::TurnDiff(ev) => {
@@ -932,6 +1016,7 @@ async fn apply_patch_shell_command_heredoc_with_cd_emits_turn_diff() -> Result<(
     let patch_end_success =
         patch_end_success.expect("expected PatchApplyEnd event to capture success flag");
     assert!(patch_end_success);
+    assert_eq!(patch_end_outcome, Some(PatchApplyOutcome::Committed));
 
     let diff = saw_turn_diff.expect("expected TurnDiff event");
     assert!(diff.contains("diff --git"), "diff header missing: {diff:?}");
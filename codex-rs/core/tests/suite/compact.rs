@@ -7,6 +7,7 @@ use codex_core::compact::SUMMARY_PREFIX;
 use codex_core::config::Config;
 use codex_core::features::Feature;
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::CompactionPreviewAction;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ItemCompletedEvent;
 use codex_core::protocol::ItemStartedEvent;
@@ -15,8 +16,10 @@ use codex_core::protocol::RolloutItem;
 use codex_core::protocol::RolloutLine;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::WarningEvent;
+use codex_protocol::config_types::CompactionStrategy;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::items::TurnItem;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::user_input::UserInput;
 use core_test_support::responses::ev_local_shell_call;
 use core_test_support::responses::ev_reasoning_item;
@@ -385,6 +388,175 @@ async fn manual_compact_uses_custom_prompt() {
     }
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn manual_compact_uses_configured_summarizer_model() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let request_log = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_assistant_message("m1", FIRST_REPLY),
+                ev_completed("r1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m2", SUMMARY_TEXT),
+                ev_completed("r2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let model_provider = non_openai_model_provider(&server);
+    let mut builder = test_codex().with_config(move |config| {
+        config.model_provider = model_provider;
+        config.model_reasoning_effort = Some(ReasoningEffort::High);
+        config.compaction.model = Some("gpt-5.1-codex-mini".to_string());
+    });
+    let test = builder.build(&server).await.expect("create conversation");
+    let codex = test.codex.clone();
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "hello world".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+    codex.submit(Op::Compact).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+
+    let requests = request_log.requests();
+    assert_eq!(requests.len(), 2, "expected a turn and a summary request");
+    assert_eq!(
+        requests[0].body_json()["model"].as_str(),
+        Some(test.session_configured.model.as_str())
+    );
+    assert_eq!(
+        requests[1].body_json()["model"].as_str(),
+        Some("gpt-5.1-codex-mini")
+    );
+    // The summarizer model uses its own default effort, not the session's.
+    assert_eq!(
+        requests[0].body_json()["reasoning"]["effort"].as_str(),
+        Some("high")
+    );
+    assert_eq!(
+        requests[1].body_json()["reasoning"]["effort"].as_str(),
+        Some("medium")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn hybrid_compact_keeps_recent_turns_verbatim() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let request_log = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_assistant_message("m1", FIRST_REPLY),
+                ev_completed("r1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m2", SECOND_LARGE_REPLY),
+                ev_completed("r2"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m3", SUMMARY_TEXT),
+                ev_completed("r3"),
+            ]),
+            sse(vec![ev_completed("r4")]),
+        ],
+    )
+    .await;
+
+    let model_provider = non_openai_model_provider(&server);
+    let mut builder = test_codex().with_config(move |config| {
+        config.model_provider = model_provider;
+        set_test_compact_prompt(config);
+        config.compaction.strategy = CompactionStrategy::Hybrid;
+        config.compaction.keep_recent_turns = 1;
+    });
+    let codex = builder.build(&server).await.unwrap().codex;
+
+    for text in ["first task", "second task"] {
+        codex
+            .submit(Op::UserInput {
+                items: vec![UserInput::Text {
+                    text: text.into(),
+                    text_elements: Vec::new(),
+                }],
+                final_output_json_schema: None,
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+    }
+
+    codex.submit(Op::PreviewCompact).await.unwrap();
+    let preview = wait_for_event_match(&codex, |ev| match ev {
+        EventMsg::CompactionPreview(preview) => Some(preview.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(preview.strategy, CompactionStrategy::Hybrid);
+    assert_eq!(preview.estimated_tokens_after, None);
+    let action_of = |description: &str| {
+        preview
+            .items
+            .iter()
+            .find(|item| item.description == description)
+            .map(|item| item.action)
+    };
+    assert_eq!(
+        action_of(&format!("assistant: {FIRST_REPLY}")),
+        Some(CompactionPreviewAction::Summarize)
+    );
+    assert_eq!(
+        action_of(&format!("assistant: {SECOND_LARGE_REPLY}")),
+        Some(CompactionPreviewAction::Keep)
+    );
+    assert_eq!(
+        request_log.requests().len(),
+        2,
+        "preview must not call the model"
+    );
+
+    codex.submit(Op::Compact).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+    codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: THIRD_USER_MSG.into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnComplete(_))).await;
+
+    let requests = request_log.requests();
+    assert_eq!(requests.len(), 4);
+    let summary_request = requests[2].body_json().to_string();
+    assert!(body_contains_text(&summary_request, FIRST_REPLY));
+    assert!(
+        !body_contains_text(&summary_request, SECOND_LARGE_REPLY),
+        "the kept turn should not be summarized"
+    );
+    let follow_up = requests[3].body_json().to_string();
+    assert!(body_contains_text(&follow_up, SUMMARY_TEXT));
+    assert!(body_contains_text(&follow_up, SECOND_LARGE_REPLY));
+    assert!(!body_contains_text(&follow_up, FIRST_REPLY));
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn manual_compact_emits_api_and_local_token_usage_events() {
     skip_if_no_network!();
@@ -0,0 +1,162 @@
+#![cfg(not(target_os = "windows"))]
+
+use codex_core::config::types::HookCommand;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::HookEventName;
+use codex_core::protocol::Op;
+use codex_protocol::user_input::UserInput;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_match;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+fn sh(script: &str) -> HookCommand {
+    HookCommand {
+        command: vec!["sh".to_string(), "-c".to_string(), script.to_string()],
+        tools: Vec::new(),
+        timeout_ms: None,
+    }
+}
+
+fn shell_call_then_done(call_id: &str) -> anyhow::Result<Vec<String>> {
+    let arguments = serde_json::to_string(&json!({
+        "command": "echo ran > ran.txt",
+        "timeout_ms": 10_000,
+    }))?;
+    Ok(vec![
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_function_call(call_id, "shell_command", &arguments),
+            ev_completed("resp-1"),
+        ]),
+        sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-2"),
+        ]),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pre_tool_use_hook_blocks_tool_call() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let call_id = "blocked-call";
+    let mock = mount_sse_sequence(&server, shell_call_then_done(call_id)?).await;
+
+    let mut hook = sh("cat >/dev/null; echo 'shell is disabled here' >&2; exit 2");
+    hook.tools = vec!["shell_command".to_string()];
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(move |config| config.hooks.pre_tool_use = vec![hook])
+        .build(&server)
+        .await?;
+
+    test.submit_turn("run the command").await?;
+
+    let output = mock
+        .function_call_output_text(call_id)
+        .expect("function_call_output for blocked call");
+    assert_eq!(
+        output,
+        "tool call blocked by pre_tool_use hook: shell is disabled here"
+    );
+    assert!(!test.workspace_path("ran.txt").exists());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn post_tool_use_hook_output_reaches_model() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let call_id = "linted-call";
+    let mock = mount_sse_sequence(&server, shell_call_then_done(call_id)?).await;
+
+    let test = test_codex()
+        .with_model("gpt-5.1")
+        .with_config(|config| {
+            config.hooks.post_tool_use = vec![sh(
+                r#"grep -q '"tool_name":"shell_command"' && echo 'lint: 0 problems'"#,
+            )]
+        })
+        .build(&server)
+        .await?;
+
+    test.submit_turn("run the command").await?;
+
+    let output = mock
+        .function_call_output_text(call_id)
+        .expect("function_call_output for linted call");
+    assert!(output.ends_with("\n\nlint: 0 problems"), "{output}");
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn user_prompt_submit_hook_adds_context_and_reports_run() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = start_mock_server().await;
+    let mock = mount_sse_sequence(
+        &server,
+        vec![sse(vec![
+            ev_assistant_message("msg-1", "done"),
+            ev_completed("resp-1"),
+        ])],
+    )
+    .await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.hooks.user_prompt_submit =
+                vec![sh("cat >/dev/null; echo 'The build uses make, not cargo.'")]
+        })
+        .build(&server)
+        .await?;
+
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "how do I build this?".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+
+    let hook = wait_for_event_match(&test.codex, |event| match event {
+        EventMsg::HookCompleted(event) => Some(event.clone()),
+        _ => None,
+    })
+    .await;
+    assert_eq!(hook.hook, HookEventName::UserPromptSubmit);
+    assert_eq!(hook.exit_code, Some(0));
+    assert!(!hook.blocked);
+    wait_for_event(&test.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    let request = mock.single_request();
+    assert!(
+        request
+            .message_input_texts("developer")
+            .iter()
+            .any(|text| text == "The build uses make, not cargo."),
+        "developer messages: {:?}",
+        request.message_input_texts("developer")
+    );
+
+    Ok(())
+}
@@ -75,6 +75,7 @@ mod exec_policy;
 mod fork_thread;
 mod grep_files;
 mod hierarchical_agents;
+mod hooks;
 mod image_rollout;
 mod items;
 mod json_result;
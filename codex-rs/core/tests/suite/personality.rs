@@ -514,6 +514,7 @@ async fn ignores_remote_personality_if_remote_models_disabled() -> anyhow::Resul
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
         input_modalities: default_input_modalities(),
+        tokenizer: None,
     };
 
     let _models_mock = mount_models_once(
@@ -630,6 +631,7 @@ async fn remote_model_friendly_personality_instructions_with_feature() -> anyhow
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
         input_modalities: default_input_modalities(),
+        tokenizer: None,
     };
 
     let _models_mock = mount_models_once(
@@ -741,6 +743,7 @@ async fn user_turn_personality_remote_model_template_includes_update_message() -
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
         input_modalities: default_input_modalities(),
+        tokenizer: None,
     };
 
     let _models_mock = mount_models_once(
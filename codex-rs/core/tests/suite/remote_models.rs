@@ -92,6 +92,7 @@ async fn remote_models_remote_model_uses_unified_exec() -> Result<()> {
         auto_compact_token_limit: None,
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
+        tokenizer: None,
     };
 
     let models_mock = mount_models_once(
@@ -330,6 +331,7 @@ async fn remote_models_apply_remote_base_instructions() -> Result<()> {
         auto_compact_token_limit: None,
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
+        tokenizer: None,
     };
     mount_models_once(
         &server,
@@ -805,5 +807,6 @@ fn test_remote_model_with_policy(
         auto_compact_token_limit: None,
         effective_context_window_percent: 95,
         experimental_supported_tools: Vec::new(),
+        tokenizer: None,
     }
 }
@@ -15,6 +15,7 @@ use core_test_support::assert_regex_match;
 use core_test_support::responses;
 use core_test_support::responses::ev_assistant_message;
 use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_completed_with_tokens;
 use core_test_support::responses::ev_function_call;
 use core_test_support::responses::ev_response_created;
 use core_test_support::responses::mount_sse_once;
@@ -833,3 +834,91 @@ async fn mcp_tool_call_output_not_truncated_with_custom_limit() -> Result<()> {
 
     Ok(())
 }
+
+// Outputs from earlier turns are replaced with stubs once they are old enough.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn old_tool_outputs_are_pruned_after_configured_turns() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+    const REPORTED_TOKENS: i64 = 1_000_000;
+
+    let server = start_mock_server().await;
+    let mut builder = test_codex()
+        .with_model("gpt-5.1-codex")
+        .with_config(|config| {
+            config.tool_output_pruning.after_turns = Some(1);
+        });
+    let fixture = builder.build(&server).await?;
+
+    let call_id = "shell-pruned";
+    let args = json!({
+        "command": "seq 1 1000",
+        "timeout_ms": 5_000,
+    });
+
+    let mocks = mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_response_created("resp-1"),
+                ev_function_call(call_id, "shell_command", &serde_json::to_string(&args)?),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "done"),
+                ev_completed_with_tokens("resp-2", REPORTED_TOKENS),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-2", "still done"),
+                ev_completed("resp-3"),
+            ]),
+        ],
+    )
+    .await;
+
+    fixture
+        .submit_turn_with_policy("print some numbers", SandboxPolicy::DangerFullAccess)
+        .await?;
+    fixture
+        .codex
+        .submit(Op::UserInput {
+            items: vec![UserInput::Text {
+                text: "thanks".into(),
+                text_elements: Vec::new(),
+            }],
+            final_output_json_schema: None,
+        })
+        .await?;
+    // Pruning re-estimates usage from the smaller history before the next
+    // request, instead of keeping the last reported count.
+    wait_for_event(&fixture.codex, |event| match event {
+        EventMsg::TokenCount(event) => event.info.as_ref().is_some_and(|info| {
+            let tokens = info.last_token_usage.total_tokens;
+            tokens > 0 && tokens < REPORTED_TOKENS
+        }),
+        _ => false,
+    })
+    .await;
+    wait_for_event(&fixture.codex, |event| {
+        matches!(event, EventMsg::TurnComplete(_))
+    })
+    .await;
+
+    let requests = mocks.requests();
+    assert_eq!(requests.len(), 3);
+    let first_turn_output = requests[1]
+        .function_call_output_text(call_id)
+        .context("shell output present in first turn")?;
+    assert!(
+        first_turn_output.contains("1000"),
+        "output of the current turn is kept: {first_turn_output}"
+    );
+    let pruned_output = requests[2]
+        .function_call_output_text(call_id)
+        .context("shell output present in second turn")?;
+    assert_regex_match(
+        r"^\[shell_command output elided, \d+ (KB|bytes), re-run to see\]$",
+        &pruned_output,
+    );
+
+    Ok(())
+}
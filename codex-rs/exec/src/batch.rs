@@ -0,0 +1,513 @@
+//! `codex exec --batch`: runs the same kind of task across many working
+//! directories. Each line of the batch file is a task with its own cwd and
+//! prompt; tasks share one `ThreadManager` (and therefore one auth session)
+//! and each writes its events to a separate JSONL file.
+
+use std::io::BufWriter;
+use std::io::Write;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use codex_common::CliConfigOverrides;
+use codex_common::elapsed::format_duration;
+use codex_core::NewThread;
+use codex_core::ThreadManager;
+use codex_core::config::ConfigBuilder;
+use codex_core::config::ConfigOverrides;
+use codex_core::config_loader::CloudRequirementsLoader;
+use codex_core::git_info::get_git_repo_root;
+use codex_core::models_manager::manager::RefreshStrategy;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_protocol::ThreadId;
+use codex_protocol::approvals::ElicitationAction;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::user_input::UserInput;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+
+/// One line of a batch file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BatchTask {
+    /// Names the task's event file and summary row. Defaults to `task-<line>`.
+    #[serde(default)]
+    pub(crate) id: String,
+    /// Working directory, relative to the batch file's directory.
+    pub(crate) cwd: PathBuf,
+    pub(crate) prompt: String,
+    /// Overrides `--model` for this task.
+    pub(crate) model: Option<String>,
+    /// Overrides `--sandbox` for this task.
+    pub(crate) sandbox: Option<SandboxMode>,
+    /// JSON Schema file for the final response, relative to the batch file's directory.
+    pub(crate) output_schema: Option<PathBuf>,
+}
+
+/// Everything a task needs that is shared across the batch.
+pub(crate) struct BatchContext {
+    pub(crate) thread_manager: Arc<ThreadManager>,
+    pub(crate) config_overrides: CliConfigOverrides,
+    pub(crate) harness_overrides: ConfigOverrides,
+    pub(crate) cloud_requirements: CloudRequirementsLoader,
+    pub(crate) check_git_repo: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TaskStatus {
+    /// The turn completed without errors.
+    Ok,
+    /// The turn ran but reported an error or was aborted.
+    Failed,
+    /// The task could not be started.
+    Error,
+}
+
+impl TaskStatus {
+    fn label(self) -> &'static str {
+        match self {
+            TaskStatus::Ok => "ok",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Error => "error",
+        }
+    }
+}
+
+/// Outcome of one task, printed as a summary row (or a JSON line with `--json`).
+#[derive(Debug, Serialize)]
+struct TaskSummary {
+    id: String,
+    cwd: PathBuf,
+    status: TaskStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thread_id: Option<ThreadId>,
+    elapsed_secs: u64,
+    events: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip)]
+    elapsed: Duration,
+}
+
+struct TaskRun {
+    thread_id: ThreadId,
+    succeeded: bool,
+    last_message: Option<String>,
+}
+
+/// Reads and validates a batch file. Blank lines are skipped; relative paths
+/// are resolved against the file's directory.
+pub(crate) fn parse_batch_file(contents: &str, base_dir: &Path) -> anyhow::Result<Vec<BatchTask>> {
+    let mut tasks: Vec<BatchTask> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut task: BatchTask = serde_json::from_str(line)
+            .with_context(|| format!("invalid batch task on line {line_number}"))?;
+        if task.prompt.trim().is_empty() {
+            anyhow::bail!("batch task on line {line_number} has an empty prompt");
+        }
+        task.id = if task.id.is_empty() {
+            format!("task-{line_number}")
+        } else {
+            sanitize_id(&task.id)
+        };
+        if tasks.iter().any(|existing| existing.id == task.id) {
+            anyhow::bail!("batch task on line {line_number} reuses id `{}`", task.id);
+        }
+        task.cwd = base_dir.join(&task.cwd);
+        task.output_schema = task.output_schema.map(|path| base_dir.join(path));
+        tasks.push(task);
+    }
+    if tasks.is_empty() {
+        anyhow::bail!("batch file has no tasks");
+    }
+    Ok(tasks)
+}
+
+/// Keeps ids usable as file names.
+fn sanitize_id(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Default directory for per-task event files: `<stem>.events` next to the batch file.
+pub(crate) fn default_output_dir(batch_path: &Path) -> PathBuf {
+    let stem = batch_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "batch".to_string());
+    batch_path.with_file_name(format!("{stem}.events"))
+}
+
+/// Runs every task in `batch_path`, at most `concurrency` at a time, then
+/// prints a summary. Returns whether every task succeeded.
+pub(crate) async fn run_batch(
+    context: BatchContext,
+    batch_path: &Path,
+    output_dir: PathBuf,
+    concurrency: NonZeroUsize,
+    json_mode: bool,
+) -> anyhow::Result<bool> {
+    let contents = std::fs::read_to_string(batch_path)
+        .with_context(|| format!("failed to read batch file {}", batch_path.display()))?;
+    let base_dir = batch_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let tasks = parse_batch_file(&contents, &base_dir)?;
+    std::fs::create_dir_all(&output_dir)
+        .with_context(|| format!("failed to create {}", output_dir.display()))?;
+
+    let context = Arc::new(context);
+    let semaphore = Arc::new(Semaphore::new(concurrency.get()));
+    let mut join_set = JoinSet::new();
+    for (index, task) in tasks.into_iter().enumerate() {
+        let context = Arc::clone(&context);
+        let semaphore = Arc::clone(&semaphore);
+        let events_path = output_dir.join(format!("{}.jsonl", task.id));
+        join_set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            eprintln!("[{}] started in {}", task.id, task.cwd.display());
+            let summary = run_task(&context, task, events_path).await;
+            eprintln!("[{}] {}", summary.id, summary.status.label());
+            (index, summary)
+        });
+    }
+
+    let mut summaries = Vec::new();
+    while let Some(result) = join_set.join_next().await {
+        summaries.push(result.context("batch task panicked")?);
+    }
+    summaries.sort_by_key(|(index, _)| *index);
+    let summaries: Vec<TaskSummary> = summaries.into_iter().map(|(_, summary)| summary).collect();
+
+    print_summary(&summaries, json_mode);
+    Ok(summaries
+        .iter()
+        .all(|summary| summary.status == TaskStatus::Ok))
+}
+
+async fn run_task(context: &BatchContext, task: BatchTask, events_path: PathBuf) -> TaskSummary {
+    let started = Instant::now();
+    let result = run_task_inner(context, &task, &events_path).await;
+    let elapsed = started.elapsed();
+    let (status, thread_id, last_message, error) = match result {
+        Ok(run) => (
+            if run.succeeded {
+                TaskStatus::Ok
+            } else {
+                TaskStatus::Failed
+            },
+            Some(run.thread_id),
+            run.last_message,
+            None,
+        ),
+        Err(err) => (TaskStatus::Error, None, None, Some(format!("{err:#}"))),
+    };
+    TaskSummary {
+        id: task.id,
+        cwd: task.cwd,
+        status,
+        thread_id,
+        elapsed_secs: elapsed.as_secs(),
+        events: events_path,
+        last_message,
+        error,
+        elapsed,
+    }
+}
+
+async fn run_task_inner(
+    context: &BatchContext,
+    task: &BatchTask,
+    events_path: &Path,
+) -> anyhow::Result<TaskRun> {
+    let cwd = task
+        .cwd
+        .canonicalize()
+        .with_context(|| format!("cwd {} does not exist", task.cwd.display()))?;
+    if context.check_git_repo && get_git_repo_root(&cwd).is_none() {
+        anyhow::bail!("not inside a git repository and --skip-git-repo-check was not specified");
+    }
+    let output_schema = task
+        .output_schema
+        .as_deref()
+        .map(read_output_schema)
+        .transpose()?;
+
+    let mut harness_overrides = context.harness_overrides.clone();
+    harness_overrides.cwd = Some(cwd);
+    if let Some(model) = &task.model {
+        harness_overrides.model = Some(model.clone());
+    }
+    if let Some(sandbox) = task.sandbox {
+        harness_overrides.sandbox_mode = Some(sandbox);
+    }
+    let cli_overrides = context
+        .config_overrides
+        .parse_overrides()
+        .map_err(|err| anyhow::anyhow!("invalid -c override: {err}"))?;
+    let config = ConfigBuilder::default()
+        .cli_overrides(cli_overrides)
+        .harness_overrides(harness_overrides)
+        .cloud_requirements(context.cloud_requirements.clone())
+        .build()
+        .await?;
+
+    let model = context
+        .thread_manager
+        .get_models_manager()
+        .get_default_model(&config.model, &config, RefreshStrategy::OnlineIfUncached)
+        .await;
+    let NewThread {
+        thread_id,
+        thread,
+        session_configured,
+    } = context.thread_manager.start_thread(config.clone()).await?;
+
+    let mut processor = EventProcessorWithJsonOutput::new(None);
+    let mut events = BufWriter::new(
+        std::fs::File::create(events_path)
+            .with_context(|| format!("failed to create {}", events_path.display()))?,
+    );
+    write_events(
+        &mut processor,
+        &mut events,
+        &Event {
+            id: String::new(),
+            msg: EventMsg::SessionConfigured(session_configured),
+        },
+    )?;
+
+    thread
+        .submit(Op::UserTurn {
+            items: vec![UserInput::Text {
+                text: task.prompt.clone(),
+                // Batch input doesn't track UI element ranges, so none are available here.
+                text_elements: Vec::new(),
+            }],
+            cwd: config.cwd.clone(),
+            approval_policy: config.approval_policy.value(),
+            sandbox_policy: config.sandbox_policy.get().clone(),
+            model,
+            effort: config.model_reasoning_effort,
+            summary: config.model_reasoning_summary,
+            final_output_json_schema: output_schema,
+            collaboration_mode: None,
+            personality: None,
+        })
+        .await?;
+
+    let mut succeeded = true;
+    let mut last_message = None;
+    loop {
+        let event = thread.next_event().await?;
+        write_events(&mut processor, &mut events, &event)?;
+        match &event.msg {
+            EventMsg::ElicitationRequest(ev) => {
+                // Nobody can answer elicitations in batch mode, same as a single exec run.
+                thread
+                    .submit(Op::ResolveElicitation {
+                        server_name: ev.server_name.clone(),
+                        request_id: ev.id.clone(),
+                        decision: ElicitationAction::Cancel,
+                    })
+                    .await?;
+            }
+            EventMsg::Error(_) => succeeded = false,
+            EventMsg::TurnComplete(ev) => {
+                last_message = ev.last_agent_message.clone();
+                thread.submit(Op::Shutdown).await?;
+            }
+            EventMsg::TurnAborted(_) => {
+                succeeded = false;
+                thread.submit(Op::Shutdown).await?;
+            }
+            EventMsg::ShutdownComplete => break,
+            _ => {}
+        }
+    }
+    events.flush()?;
+    context.thread_manager.remove_thread(&thread_id).await;
+
+    Ok(TaskRun {
+        thread_id,
+        succeeded,
+        last_message,
+    })
+}
+
+fn write_events(
+    processor: &mut EventProcessorWithJsonOutput,
+    out: &mut impl Write,
+    event: &Event,
+) -> anyhow::Result<()> {
+    for thread_event in processor.collect_thread_events(event) {
+        serde_json::to_writer(&mut *out, &thread_event)?;
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+fn read_output_schema(path: &Path) -> anyhow::Result<Value> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read output schema {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .with_context(|| format!("output schema {} is not valid JSON", path.display()))
+}
+
+#[allow(clippy::print_stdout)]
+fn print_summary(summaries: &[TaskSummary], json_mode: bool) {
+    if json_mode {
+        for summary in summaries {
+            match serde_json::to_string(summary) {
+                Ok(line) => println!("{line}"),
+                Err(err) => tracing::error!("Failed to serialize batch summary: {err:?}"),
+            }
+        }
+        return;
+    }
+    print!("{}", format_summary_table(summaries));
+}
+
+fn format_summary_table(summaries: &[TaskSummary]) -> String {
+    let id_width = summaries
+        .iter()
+        .map(|summary| summary.id.len())
+        .chain(std::iter::once("TASK".len()))
+        .max()
+        .unwrap_or_default();
+    let mut table = format!(
+        "{:<id_width$}  {:<6}  {:>8}  CWD\n",
+        "TASK", "STATUS", "TIME"
+    );
+    for summary in summaries {
+        let elapsed = format_duration(summary.elapsed);
+        table.push_str(&format!(
+            "{:<id_width$}  {:<6}  {elapsed:>8}  {}",
+            summary.id,
+            summary.status.label(),
+            summary.cwd.display(),
+        ));
+        if let Some(error) = &summary.error {
+            table.push_str(&format!("  ({error})"));
+        }
+        table.push('\n');
+    }
+    let ok = summaries
+        .iter()
+        .filter(|summary| summary.status == TaskStatus::Ok)
+        .count();
+    table.push_str(&format!("{ok}/{} tasks succeeded\n", summaries.len()));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_tasks_relative_to_batch_file() {
+        let contents = r#"
+{"cwd": "repo-a", "prompt": "bump the version"}
+
+{"id": "b/second", "cwd": "/abs/repo-b", "prompt": "bump the version", "model": "gpt-5.1", "sandbox": "read-only", "output_schema": "schema.json"}
+"#;
+        let tasks = parse_batch_file(contents, Path::new("/work")).expect("valid batch");
+        assert_eq!(
+            tasks,
+            vec![
+                BatchTask {
+                    id: "task-2".to_string(),
+                    cwd: PathBuf::from("/work/repo-a"),
+                    prompt: "bump the version".to_string(),
+                    model: None,
+                    sandbox: None,
+                    output_schema: None,
+                },
+                BatchTask {
+                    id: "b-second".to_string(),
+                    cwd: PathBuf::from("/abs/repo-b"),
+                    prompt: "bump the version".to_string(),
+                    model: Some("gpt-5.1".to_string()),
+                    sandbox: Some(SandboxMode::ReadOnly),
+                    output_schema: Some(PathBuf::from("/work/schema.json")),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_batches() {
+        let cases = [
+            ("", "batch file has no tasks"),
+            (
+                r#"{"cwd": "a", "prompt": "x", "unknown": 1}"#,
+                "invalid batch task on line 1",
+            ),
+            (
+                r#"{"cwd": "a", "prompt": "  "}"#,
+                "batch task on line 1 has an empty prompt",
+            ),
+            (
+                "{\"id\": \"x\", \"cwd\": \"a\", \"prompt\": \"p\"}\n{\"id\": \"x\", \"cwd\": \"b\", \"prompt\": \"p\"}",
+                "batch task on line 2 reuses id `x`",
+            ),
+        ];
+        for (contents, expected) in cases {
+            let err = parse_batch_file(contents, Path::new("/work")).expect_err(contents);
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn summary_table_lists_each_task() {
+        let summary = |id: &str, status, error: Option<&str>| TaskSummary {
+            id: id.to_string(),
+            cwd: PathBuf::from(format!("/work/{id}")),
+            status,
+            thread_id: None,
+            elapsed_secs: 2,
+            events: PathBuf::from(format!("/work/tasks.events/{id}.jsonl")),
+            last_message: None,
+            error: error.map(str::to_string),
+            elapsed: Duration::from_secs(2),
+        };
+        let table = format_summary_table(&[
+            summary("api", TaskStatus::Ok, None),
+            summary("frontend", TaskStatus::Error, Some("cwd missing")),
+        ]);
+        assert_eq!(
+            table,
+            "TASK      STATUS      TIME  CWD\n\
+             api       ok         2.00s  /work/api\n\
+             frontend  error      2.00s  /work/frontend  (cwd missing)\n\
+             1/2 tasks succeeded\n"
+        );
+    }
+}
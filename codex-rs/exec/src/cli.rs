@@ -3,6 +3,7 @@ use clap::FromArgMatches;
 use clap::Parser;
 use clap::ValueEnum;
 use codex_common::CliConfigOverrides;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
@@ -95,6 +96,30 @@ pub struct Cli {
     #[arg(long = "output-last-message", short = 'o', value_name = "FILE")]
     pub last_message_file: Option<PathBuf>,
 
+    /// Run every task in a JSONL file instead of a single prompt. Each line is an
+    /// object with `cwd` and `prompt`, plus optional `id`, `model`, `sandbox` and
+    /// `output_schema`.
+    #[arg(
+        long = "batch",
+        value_name = "FILE",
+        conflicts_with_all = ["prompt", "images", "cwd", "output_schema", "last_message_file"]
+    )]
+    pub batch: Option<PathBuf>,
+
+    /// Maximum number of batch tasks to run at the same time.
+    #[arg(
+        long = "batch-concurrency",
+        value_name = "N",
+        default_value = "4",
+        requires = "batch"
+    )]
+    pub batch_concurrency: NonZeroUsize,
+
+    /// Directory for the per-task JSONL event files of a batch. Defaults to
+    /// `<FILE stem>.events` next to the batch file.
+    #[arg(long = "batch-output-dir", value_name = "DIR", requires = "batch")]
+    pub batch_output_dir: Option<PathBuf>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
@@ -127,6 +152,14 @@ struct ResumeArgsRaw {
     #[arg(long = "all", default_value_t = false)]
     all: bool,
 
+    /// Continue an interrupted run from its last checkpoint instead of sending a new prompt.
+    #[arg(
+        long = "continue",
+        default_value_t = false,
+        conflicts_with_all = ["prompt", "images"]
+    )]
+    continue_turn: bool,
+
     /// Optional image(s) to attach to the prompt sent after resuming.
     #[arg(
         long = "image",
@@ -154,6 +187,9 @@ pub struct ResumeArgs {
     /// Show all sessions (disables cwd filtering).
     pub all: bool,
 
+    /// Continue an interrupted run from its last checkpoint instead of sending a new prompt.
+    pub continue_turn: bool,
+
     /// Optional image(s) to attach to the prompt sent after resuming.
     pub images: Vec<PathBuf>,
 
@@ -165,7 +201,7 @@ impl From<ResumeArgsRaw> for ResumeArgs {
     fn from(raw: ResumeArgsRaw) -> Self {
         // When --last is used without an explicit prompt, treat the positional as the prompt
         // (clap can’t express this conditional positional meaning cleanly).
-        let (session_id, prompt) = if raw.last && raw.prompt.is_none() {
+        let (session_id, prompt) = if raw.last && raw.prompt.is_none() && !raw.continue_turn {
             (None, raw.session_id)
         } else {
             (raw.session_id, raw.prompt)
@@ -174,6 +210,7 @@ impl From<ResumeArgsRaw> for ResumeArgs {
             session_id,
             last: raw.last,
             all: raw.all,
+            continue_turn: raw.continue_turn,
             images: raw.images,
             prompt,
         }
@@ -207,7 +244,7 @@ pub struct ReviewArgs {
     #[arg(
         long = "uncommitted",
         default_value_t = false,
-        conflicts_with_all = ["base", "commit", "prompt"]
+        conflicts_with_all = ["base", "commit", "range", "patch", "prompt"]
     )]
     pub uncommitted: bool,
 
@@ -215,7 +252,7 @@ pub struct ReviewArgs {
     #[arg(
         long = "base",
         value_name = "BRANCH",
-        conflicts_with_all = ["uncommitted", "commit", "prompt"]
+        conflicts_with_all = ["uncommitted", "commit", "range", "patch", "prompt"]
     )]
     pub base: Option<String>,
 
@@ -223,7 +260,7 @@ pub struct ReviewArgs {
     #[arg(
         long = "commit",
         value_name = "SHA",
-        conflicts_with_all = ["uncommitted", "base", "prompt"]
+        conflicts_with_all = ["uncommitted", "base", "range", "patch", "prompt"]
     )]
     pub commit: Option<String>,
 
@@ -231,9 +268,45 @@ pub struct ReviewArgs {
     #[arg(long = "title", value_name = "TITLE", requires = "commit")]
     pub commit_title: Option<String>,
 
+    /// Review the changes in a commit range, e.g. `main..HEAD`.
+    #[arg(
+        long = "range",
+        value_name = "FROM..TO",
+        conflicts_with_all = ["uncommitted", "base", "commit", "patch", "prompt"]
+    )]
+    pub range: Option<String>,
+
+    /// Review the changes in a patch file without applying it.
+    #[arg(
+        long = "patch",
+        value_name = "FILE",
+        conflicts_with_all = ["uncommitted", "base", "commit", "range", "prompt"]
+    )]
+    pub patch: Option<PathBuf>,
+
     /// Custom review instructions. If `-` is used, read from stdin.
     #[arg(value_name = "PROMPT", value_hint = clap::ValueHint::Other)]
     pub prompt: Option<String>,
+
+    /// Format of the report written to `--output`.
+    #[arg(long = "format", value_enum, default_value_t = ReviewReportFormat::Json, requires = "report_file")]
+    pub format: ReviewReportFormat,
+
+    /// Write the review findings to FILE in the `--format` format.
+    #[arg(long = "output", value_name = "FILE")]
+    pub report_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum ReviewReportFormat {
+    /// The review output as emitted by the reviewer.
+    #[default]
+    Json,
+    /// SARIF 2.1.0, for code-scanning UIs.
+    Sarif,
+    /// JUnit XML, one failing test case per finding.
+    Junit,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
@@ -277,4 +350,39 @@ mod tests {
         });
         assert_eq!(effective_prompt.as_deref(), Some(PROMPT));
     }
+
+    #[test]
+    fn resume_continue_keeps_positional_as_session_id() {
+        let cli = Cli::parse_from(["codex-exec", "resume", "--continue", "my-session"]);
+
+        let Some(Command::Resume(args)) = cli.command else {
+            panic!("expected resume command");
+        };
+        assert!(args.continue_turn);
+        assert_eq!(args.session_id.as_deref(), Some("my-session"));
+        assert_eq!(args.prompt, None);
+    }
+
+    #[test]
+    fn review_report_format_requires_output() {
+        let cli = Cli::parse_from([
+            "codex-exec",
+            "review",
+            "--uncommitted",
+            "--format",
+            "sarif",
+            "--output",
+            "review.sarif",
+        ]);
+        let Some(Command::Review(args)) = cli.command else {
+            panic!("expected review command");
+        };
+        assert_eq!(args.format, ReviewReportFormat::Sarif);
+        assert_eq!(args.report_file, Some(PathBuf::from("review.sarif")));
+
+        let err =
+            Cli::try_parse_from(["codex-exec", "review", "--uncommitted", "--format", "junit"])
+                .expect_err("--format without --output should fail");
+        assert_eq!(err.kind(), clap::error::ErrorKind::MissingRequiredArgument);
+    }
 }
@@ -20,6 +20,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::HookCompletedEvent;
 use codex_core::protocol::ItemCompletedEvent;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
@@ -426,7 +427,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 // it's easy to scan in the terminal output.
                 for (path, change) in changes.iter() {
                     match change {
-                        FileChange::Add { content } => {
+                        FileChange::Add { content, .. } => {
                             let header = format!(
                                 "{} {}",
                                 format_file_change(change),
@@ -448,9 +449,22 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                                 eprintln!("{}", line.style(self.red));
                             }
                         }
+                        FileChange::AddBinary { size, .. } => {
+                            let header = format!(
+                                "{} {}",
+                                format_file_change(change),
+                                path.to_string_lossy()
+                            );
+                            eprintln!("{}", header.style(self.magenta));
+                            eprintln!(
+                                "{}",
+                                format!("binary file, {size} bytes").style(self.dimmed)
+                            );
+                        }
                         FileChange::Update {
                             unified_diff,
                             move_path,
+                            ..
                         } => {
                             let header = if let Some(dest) = move_path {
                                 format!(
@@ -487,6 +501,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 stdout,
                 stderr,
                 success,
+                post_edit,
                 ..
             }) => {
                 let patch_begin = self.call_id_to_patch.remove(&call_id);
@@ -516,6 +531,23 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 for line in output.lines() {
                     eprintln!("{}", line.style(self.dimmed));
                 }
+                for result in post_edit {
+                    let title_style = if result.exit_code == 0 {
+                        self.green
+                    } else {
+                        self.red
+                    };
+                    ts_msg!(
+                        self,
+                        "{} {} {}",
+                        "post_edit".style(self.magenta),
+                        escape_command(&result.command).style(self.bold),
+                        format!("exited {}:", result.exit_code).style(title_style),
+                    );
+                    for line in result.output.lines() {
+                        eprintln!("{}", line.style(self.dimmed));
+                    }
+                }
             }
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
                 ts_msg!(
@@ -593,6 +625,55 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     view.path.display()
                 );
             }
+            EventMsg::HookCompleted(HookCompletedEvent {
+                hook,
+                command,
+                tool_name,
+                exit_code,
+                stdout,
+                stderr,
+                duration,
+                timed_out,
+                blocked,
+                reason,
+            }) => {
+                let duration = format!(" in {}", format_duration(duration));
+                let (status, title_style) = if blocked {
+                    ("blocked".to_string(), self.red)
+                } else if timed_out {
+                    ("timed out".to_string(), self.red)
+                } else {
+                    match exit_code {
+                        Some(0) => ("succeeded".to_string(), self.green),
+                        Some(code) => (format!("exited {code}"), self.red),
+                        None => ("failed to start".to_string(), self.red),
+                    }
+                };
+                let target = tool_name
+                    .map(|name| format!(" ({name})"))
+                    .unwrap_or_default();
+                ts_msg!(
+                    self,
+                    "{} {}{} {}",
+                    format!("hook {hook}").style(self.magenta),
+                    escape_command(&command).style(self.bold),
+                    target,
+                    format!("{status}{duration}").style(title_style)
+                );
+                let output = match reason {
+                    Some(reason) => reason,
+                    None if stdout.trim().is_empty() => stderr,
+                    None => stdout,
+                };
+                let truncated_output = output
+                    .lines()
+                    .take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL)
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                if !truncated_output.is_empty() {
+                    eprintln!("{}", truncated_output.style(self.dimmed));
+                }
+            }
             EventMsg::TurnAborted(abort_reason) => {
                 match abort_reason.reason {
                     TurnAbortReason::Interrupted => {
@@ -700,6 +781,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 sender_thread_id: _,
                 call_id,
                 statuses,
+                tree_usage,
             }) => {
                 if statuses.is_empty() {
                     ts_msg!(
@@ -730,6 +812,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                         format_collab_status(&status).style(style_for_agent_status(&status, self))
                     );
                 }
+                if let Some(tree_usage) = tree_usage {
+                    eprintln!(
+                        "  {}",
+                        format!("agent tree: {tree_usage}").style(self.dimmed)
+                    );
+                }
             }
             EventMsg::CollabCloseBegin(CollabCloseBeginEvent {
                 call_id,
@@ -768,6 +856,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             }
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ThreadNameUpdated(_)
+            | EventMsg::AgentTreeUsage(_)
             | EventMsg::ExecApprovalRequest(_)
             | EventMsg::ApplyPatchApprovalRequest(_)
             | EventMsg::TerminalInteraction(_)
@@ -777,6 +866,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             | EventMsg::ListCustomPromptsResponse(_)
             | EventMsg::ListSkillsResponse(_)
             | EventMsg::ListRemoteSkillsResponse(_)
+            | EventMsg::ListSessionApprovalsResponse(_)
+            | EventMsg::CompactionPreview(_)
             | EventMsg::RemoteSkillDownloaded(_)
             | EventMsg::RawResponseItem(_)
             | EventMsg::UserMessage(_)
@@ -831,7 +922,7 @@ fn escape_command(command: &[String]) -> String {
 
 fn format_file_change(change: &FileChange) -> &'static str {
     match change {
-        FileChange::Add { .. } => "A",
+        FileChange::Add { .. } | FileChange::AddBinary { .. } => "A",
         FileChange::Delete { .. } => "D",
         FileChange::Update {
             move_path: Some(_), ..
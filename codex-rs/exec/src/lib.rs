@@ -4,11 +4,13 @@
 // For both modes, any other output must be written to stderr.
 #![deny(clippy::print_stdout)]
 
+mod batch;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+mod review_report;
 
 pub use cli::Cli;
 pub use cli::Command;
@@ -69,6 +71,10 @@ use codex_core::default_client::set_default_originator;
 use codex_core::find_thread_path_by_id_str;
 use codex_core::find_thread_path_by_name_str;
 
+/// Sent after replaying an interrupted run up to its last checkpoint.
+const CONTINUE_PROMPT: &str =
+    "Your previous run was interrupted. Continue the task from where you left off.";
+
 enum InitialOperation {
     UserTurn {
         items: Vec<UserInput>,
@@ -109,9 +115,16 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
+        batch,
+        batch_concurrency,
+        batch_output_dir,
         config_overrides,
     } = cli;
 
+    if batch.is_some() && command.is_some() {
+        anyhow::bail!("--batch cannot be combined with the resume or review subcommands");
+    }
+
     let (stdout_with_ansi, stderr_with_ansi) = match color {
         cli::Color::Always => (true, true),
         cli::Color::Never => (false, false),
@@ -258,6 +271,9 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         additional_writable_roots: add_dir,
     };
 
+    let batch_overrides = batch
+        .is_some()
+        .then(|| (overrides.clone(), cloud_requirements.clone()));
     let config = ConfigBuilder::default()
         .cli_overrides(cli_kv_overrides)
         .harness_overrides(overrides)
@@ -329,10 +345,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
 
     // When --yolo (dangerously_bypass_approvals_and_sandbox) is set, also skip the git repo check
     // since the user is explicitly running in an externally sandboxed environment.
-    if !skip_git_repo_check
-        && !dangerously_bypass_approvals_and_sandbox
-        && get_git_repo_root(&default_cwd).is_none()
-    {
+    let check_git_repo = !skip_git_repo_check && !dangerously_bypass_approvals_and_sandbox;
+    if check_git_repo && batch.is_none() && get_git_repo_root(&default_cwd).is_none() {
         eprintln!("Not inside a trusted directory and --skip-git-repo-check was not specified.");
         std::process::exit(1);
     }
@@ -347,6 +361,33 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         auth_manager.clone(),
         SessionSource::Exec,
     ));
+
+    // Batch mode runs its own threads, one per task, on the shared thread manager.
+    if let (Some(batch_path), Some((harness_overrides, cloud_requirements))) =
+        (batch, batch_overrides)
+    {
+        let output_dir = batch_output_dir.unwrap_or_else(|| batch::default_output_dir(&batch_path));
+        let context = batch::BatchContext {
+            thread_manager,
+            config_overrides,
+            harness_overrides,
+            cloud_requirements,
+            check_git_repo,
+        };
+        let all_succeeded = batch::run_batch(
+            context,
+            &batch_path,
+            output_dir,
+            batch_concurrency,
+            json_mode,
+        )
+        .await?;
+        if !all_succeeded {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let default_model = thread_manager
         .get_models_manager()
         .get_default_model(&config.model, &config, RefreshStrategy::OnlineIfUncached)
@@ -360,7 +401,21 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     } = if let Some(ExecCommand::Resume(args)) = command.as_ref() {
         let resume_path = resolve_resume_path(&config, args).await?;
 
-        if let Some(path) = resume_path {
+        if args.continue_turn {
+            let Some(path) = resume_path else {
+                anyhow::bail!("No recorded session found to continue.");
+            };
+            match thread_manager
+                .continue_thread_from_checkpoint(config.clone(), path)
+                .await?
+            {
+                Some(new_thread) => new_thread,
+                None => {
+                    eprintln!("Nothing to continue: the last turn already finished.");
+                    return Ok(());
+                }
+            }
+        } else if let Some(path) = resume_path {
             thread_manager
                 .resume_thread_from_rollout(config.clone(), path, auth_manager.clone())
                 .await?
@@ -370,12 +425,33 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     } else {
         thread_manager.start_thread(config.clone()).await?
     };
+    let review_report = match &command {
+        Some(ExecCommand::Review(args)) => args
+            .report_file
+            .clone()
+            .map(|report_file| (args.format, report_file)),
+        _ => None,
+    };
     let (initial_operation, prompt_summary) = match (command, prompt, images) {
         (Some(ExecCommand::Review(review_cli)), _, _) => {
             let review_request = build_review_request(review_cli)?;
             let summary = codex_core::review_prompts::user_facing_hint(&review_request.target);
             (InitialOperation::Review { review_request }, summary)
         }
+        (Some(ExecCommand::Resume(args)), _, _) if args.continue_turn => {
+            let items = vec![UserInput::Text {
+                text: CONTINUE_PROMPT.to_string(),
+                text_elements: Vec::new(),
+            }];
+            let output_schema = load_output_schema(output_schema_path.clone());
+            (
+                InitialOperation::UserTurn {
+                    items,
+                    output_schema,
+                },
+                CONTINUE_PROMPT.to_string(),
+            )
+        }
         (Some(ExecCommand::Resume(args)), root_prompt, imgs) => {
             let prompt_arg = args
                 .prompt
@@ -515,6 +591,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     // Track whether a fatal error was reported by the server so we can
     // exit with a non-zero status for automation-friendly signaling.
     let mut error_seen = false;
+    let mut review_output = None;
     while let Some(envelope) = rx.recv().await {
         let ThreadEventEnvelope {
             thread_id,
@@ -534,6 +611,11 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         if matches!(event.msg, EventMsg::Error(_)) {
             error_seen = true;
         }
+        if thread_id == primary_thread_id
+            && let EventMsg::ExitedReviewMode(ev) = &event.msg
+        {
+            review_output = ev.review_output.clone();
+        }
         if thread_id != primary_thread_id && matches!(&event.msg, EventMsg::TurnComplete(_)) {
             continue;
         }
@@ -551,6 +633,23 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
     event_processor.print_final_output();
+    if let Some((format, report_file)) = review_report {
+        match review_output {
+            Some(output) => review_report::write_review_report(
+                format,
+                &output,
+                config.cwd.as_path(),
+                &report_file,
+            )?,
+            None => {
+                eprintln!(
+                    "Review finished without output; no report written to {}.",
+                    report_file.display()
+                );
+                error_seen = true;
+            }
+        }
+    }
     if error_seen {
         std::process::exit(1);
     }
@@ -785,6 +884,13 @@ fn build_review_request(args: ReviewArgs) -> anyhow::Result<ReviewRequest> {
             sha,
             title: args.commit_title,
         }
+    } else if let Some(range) = args.range {
+        codex_core::review_prompts::parse_commit_range(&range)?
+    } else if let Some(path) = args.patch {
+        // Resolve against the shell's cwd rather than `-C`, like other path flags.
+        ReviewTarget::PatchFile {
+            path: std::path::absolute(&path)?,
+        }
     } else if let Some(prompt_arg) = args.prompt {
         let prompt = resolve_prompt(Some(prompt_arg)).trim().to_string();
         if prompt.is_empty() {
@@ -795,7 +901,7 @@ fn build_review_request(args: ReviewArgs) -> anyhow::Result<ReviewRequest> {
         }
     } else {
         anyhow::bail!(
-            "Specify --uncommitted, --base, --commit, or provide custom review instructions"
+            "Specify --uncommitted, --base, --commit, --range, --patch, or provide custom review instructions"
         );
     };
 
@@ -808,6 +914,7 @@ fn build_review_request(args: ReviewArgs) -> anyhow::Result<ReviewRequest> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::ReviewReportFormat;
     use pretty_assertions::assert_eq;
 
     #[test]
@@ -817,7 +924,11 @@ mod tests {
             base: None,
             commit: None,
             commit_title: None,
+            range: None,
+            patch: None,
             prompt: None,
+            format: ReviewReportFormat::Json,
+            report_file: None,
         })
         .expect("builds uncommitted review request");
 
@@ -836,7 +947,11 @@ mod tests {
             base: None,
             commit: Some("123456789".to_string()),
             commit_title: Some("Add review command".to_string()),
+            range: None,
+            patch: None,
             prompt: None,
+            format: ReviewReportFormat::Json,
+            report_file: None,
         })
         .expect("builds commit review request");
 
@@ -858,7 +973,11 @@ mod tests {
             base: None,
             commit: None,
             commit_title: None,
+            range: None,
+            patch: None,
             prompt: Some("  custom review instructions  ".to_string()),
+            format: ReviewReportFormat::Json,
+            report_file: None,
         })
         .expect("builds custom review request");
 
@@ -872,6 +991,30 @@ mod tests {
         assert_eq!(request, expected);
     }
 
+    #[test]
+    fn builds_commit_range_review_request() {
+        let request = build_review_request(ReviewArgs {
+            uncommitted: false,
+            base: None,
+            commit: None,
+            commit_title: None,
+            range: Some("main..feature".to_string()),
+            patch: None,
+            prompt: None,
+            format: ReviewReportFormat::Json,
+            report_file: None,
+        })
+        .expect("builds commit range review request");
+
+        assert_eq!(
+            request.target,
+            ReviewTarget::CommitRange {
+                from: "main".to_string(),
+                to: "feature".to_string(),
+            }
+        );
+    }
+
     #[test]
     fn decode_prompt_bytes_strips_utf8_bom() {
         let input = [0xEF, 0xBB, 0xBF, b'h', b'i', b'\n'];
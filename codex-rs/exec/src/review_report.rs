@@ -0,0 +1,308 @@
+//! Machine-readable reports for `codex exec review --output FILE`.
+
+use std::path::Path;
+
+use codex_core::git_info::get_git_repo_root;
+use codex_protocol::protocol::ReviewFinding;
+use codex_protocol::protocol::ReviewOutputEvent;
+use serde_json::json;
+
+use crate::cli::ReviewReportFormat;
+
+const TOOL_NAME: &str = "codex-review";
+const RULE_ID: &str = "codex-review/finding";
+
+/// Writes `output` to `path`. File paths in the findings are made relative to
+/// the repository containing `cwd` (or `cwd` itself outside a repository) so
+/// code-scanning UIs can match them to the checkout.
+pub(crate) fn write_review_report(
+    format: ReviewReportFormat,
+    output: &ReviewOutputEvent,
+    cwd: &Path,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let root = get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf());
+    let contents = match format {
+        ReviewReportFormat::Json => serde_json::to_string_pretty(output)?,
+        ReviewReportFormat::Sarif => serde_json::to_string_pretty(&render_sarif(output, &root))?,
+        ReviewReportFormat::Junit => render_junit(output, &root),
+    };
+    std::fs::write(path, contents + "\n").map_err(|err| {
+        anyhow::anyhow!("failed to write review report to {}: {err}", path.display())
+    })
+}
+
+fn render_sarif(output: &ReviewOutputEvent, root: &Path) -> serde_json::Value {
+    let results: Vec<serde_json::Value> = output
+        .findings
+        .iter()
+        .map(|finding| {
+            let artifact_location = match relative_path(finding, root) {
+                Some(relative) => json!({
+                    "uri": encode_uri_path(&relative),
+                    "uriBaseId": "%SRCROOT%",
+                }),
+                None => json!({ "uri": file_uri(&display_path(finding)) }),
+            };
+            let range = &finding.code_location.line_range;
+            let start_line = range.start.max(1);
+            json!({
+                "ruleId": RULE_ID,
+                "level": sarif_level(finding.priority),
+                "message": { "text": finding_message(finding) },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location,
+                        "region": {
+                            "startLine": start_line,
+                            "endLine": range.end.max(start_line),
+                        },
+                    },
+                }],
+                "properties": {
+                    "title": finding.title,
+                    "priority": finding.priority,
+                    "confidence": finding.confidence_score,
+                },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": TOOL_NAME,
+                    "informationUri": "https://github.com/openai/codex",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": RULE_ID,
+                        "shortDescription": { "text": "Issue found by Codex code review" },
+                    }],
+                },
+            },
+            "results": results,
+            "properties": {
+                "overallCorrectness": output.overall_correctness,
+                "overallExplanation": output.overall_explanation,
+                "overallConfidence": output.overall_confidence_score,
+            },
+        }],
+    })
+}
+
+fn render_junit(output: &ReviewOutputEvent, root: &Path) -> String {
+    let failures = output.findings.len();
+    // A review without findings still reports one passing case so CI shows it ran.
+    let tests = failures.max(1);
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        format!(r#"<testsuites name="{TOOL_NAME}" tests="{tests}" failures="{failures}">"#),
+        format!(r#"  <testsuite name="{TOOL_NAME}" tests="{tests}" failures="{failures}">"#),
+    ];
+    if output.findings.is_empty() {
+        lines.push(format!(
+            r#"    <testcase classname="{TOOL_NAME}" name="no findings"/>"#
+        ));
+    }
+    for finding in &output.findings {
+        let uri = relative_path(finding, root).unwrap_or_else(|| display_path(finding));
+        let range = &finding.code_location.line_range;
+        lines.push(format!(
+            r#"    <testcase classname="{}" name="{}">"#,
+            xml_escape(&uri),
+            xml_escape(&finding.title)
+        ));
+        lines.push(format!(
+            r#"      <failure message="{}" type="P{}">{}</failure>"#,
+            xml_escape(&finding.title),
+            finding.priority,
+            xml_escape(&format!(
+                "{uri}:{}-{}\n\n{}",
+                range.start, range.end, finding.body
+            ))
+        ));
+        lines.push("    </testcase>".to_string());
+    }
+    lines.push("  </testsuite>".to_string());
+    lines.push("</testsuites>".to_string());
+    lines.join("\n")
+}
+
+/// The finding's path relative to `root`, with `/` separators, when it is
+/// inside `root`.
+fn relative_path(finding: &ReviewFinding, root: &Path) -> Option<String> {
+    finding
+        .code_location
+        .absolute_file_path
+        .strip_prefix(root)
+        .ok()
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn display_path(finding: &ReviewFinding) -> String {
+    finding
+        .code_location
+        .absolute_file_path
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// `file://` URI for an absolute path with `/` separators.
+fn file_uri(path: &str) -> String {
+    let path = encode_uri_path(path);
+    if path.starts_with('/') {
+        format!("file://{path}")
+    } else {
+        // Windows drive paths such as `C:/repo` need an empty authority.
+        format!("file:///{path}")
+    }
+}
+
+/// Percent-encodes everything but unreserved characters, `/` and a drive `:`.
+fn encode_uri_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                encoded.push(char::from(byte))
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+fn finding_message(finding: &ReviewFinding) -> String {
+    let body = finding.body.trim();
+    if body.is_empty() {
+        finding.title.clone()
+    } else {
+        format!("{}\n\n{body}", finding.title)
+    }
+}
+
+/// Maps the reviewer's P0-P3 priority onto SARIF levels.
+fn sarif_level(priority: i32) -> &'static str {
+    match priority {
+        i32::MIN..=1 => "error",
+        2 => "warning",
+        _ => "note",
+    }
+}
+
+/// Escapes markup and drops characters XML 1.0 does not allow, such as the
+/// control characters terminal output can leave in a finding.
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\t' | '\n' | '\r' => escaped.push(ch),
+            '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => {}
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::protocol::ReviewCodeLocation;
+    use codex_protocol::protocol::ReviewLineRange;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    fn review_output() -> ReviewOutputEvent {
+        ReviewOutputEvent {
+            findings: vec![ReviewFinding {
+                title: "[P1] Unchecked <input> & friends".to_string(),
+                body: "The value is used before validation.".to_string(),
+                confidence_score: 0.8,
+                priority: 1,
+                code_location: ReviewCodeLocation {
+                    absolute_file_path: PathBuf::from("/repo/src/lib.rs"),
+                    line_range: ReviewLineRange { start: 10, end: 12 },
+                },
+            }],
+            overall_correctness: "patch is incorrect".to_string(),
+            overall_explanation: "One blocking issue.".to_string(),
+            overall_confidence_score: 0.7,
+        }
+    }
+
+    #[test]
+    fn sarif_results_point_at_relative_paths() {
+        let sarif = render_sarif(&review_output(), Path::new("/repo"));
+        let result = &sarif["runs"][0]["results"][0];
+        assert_eq!(result["level"], "error");
+        assert_eq!(
+            result["locations"][0]["physicalLocation"],
+            json!({
+                "artifactLocation": {"uri": "src/lib.rs", "uriBaseId": "%SRCROOT%"},
+                "region": {"startLine": 10, "endLine": 12},
+            })
+        );
+        assert_eq!(
+            result["message"]["text"],
+            "[P1] Unchecked <input> & friends\n\nThe value is used before validation."
+        );
+
+        let outside = render_sarif(&review_output(), Path::new("/elsewhere"));
+        assert_eq!(
+            outside["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"],
+            json!({"uri": "file:///repo/src/lib.rs"})
+        );
+    }
+
+    #[test]
+    fn junit_reports_one_failure_per_finding() {
+        let xml = render_junit(&review_output(), Path::new("/repo"));
+        assert_eq!(
+            xml,
+            [
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                r#"<testsuites name="codex-review" tests="1" failures="1">"#,
+                r#"  <testsuite name="codex-review" tests="1" failures="1">"#,
+                r#"    <testcase classname="src/lib.rs" name="[P1] Unchecked &lt;input&gt; &amp; friends">"#,
+                "      <failure message=\"[P1] Unchecked &lt;input&gt; &amp; friends\" type=\"P1\">src/lib.rs:10-12\n\nThe value is used before validation.</failure>",
+                "    </testcase>",
+                "  </testsuite>",
+                "</testsuites>",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn uris_are_percent_encoded() {
+        assert_eq!(encode_uri_path("src/my file#1.rs"), "src/my%20file%231.rs");
+        assert_eq!(file_uri("/repo/a b.rs"), "file:///repo/a%20b.rs");
+        assert_eq!(file_uri("C:/repo/a.rs"), "file:///C:/repo/a.rs");
+    }
+
+    #[test]
+    fn junit_drops_characters_xml_cannot_hold() {
+        assert_eq!(
+            xml_escape("bad\u{1b}[31m\u{0}byte\tand\nlines"),
+            "bad[31mbyte\tand\nlines"
+        );
+    }
+
+    #[test]
+    fn junit_without_findings_has_a_passing_case() {
+        let xml = render_junit(&ReviewOutputEvent::default(), Path::new("/repo"));
+        assert!(xml.contains(r#"tests="1" failures="0""#), "{xml}");
+        assert!(
+            xml.contains(r#"<testcase classname="codex-review" name="no findings"/>"#),
+            "{xml}"
+        );
+    }
+}
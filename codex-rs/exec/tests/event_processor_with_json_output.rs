@@ -619,6 +619,7 @@ fn collab_wait_end_without_begin_synthesizes_failed_item() {
             sender_thread_id,
             call_id: "call-11".to_string(),
             statuses: statuses.clone(),
+            tree_usage: None,
         }),
     );
     let events = ep.collect_thread_events(&end);
@@ -1113,6 +1114,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
         PathBuf::from("a/added.txt"),
         FileChange::Add {
             content: "+hello".to_string(),
+            mode: None,
         },
     );
     changes.insert(
@@ -1126,6 +1128,7 @@ fn patch_apply_success_produces_item_completed_patchapply() {
         FileChange::Update {
             unified_diff: "--- c/modified.txt\n+++ c/modified.txt\n@@\n-old\n+new\n".to_string(),
             move_path: Some(PathBuf::from("c/renamed.txt")),
+            mode: None,
         },
     );
 
@@ -1152,6 +1155,8 @@ fn patch_apply_success_produces_item_completed_patchapply() {
             stderr: String::new(),
             success: true,
             changes: changes.clone(),
+            post_edit: Vec::new(),
+            outcome: None,
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -1198,6 +1203,7 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
         FileChange::Update {
             unified_diff: "--- file.txt\n+++ file.txt\n@@\n-old\n+new\n".to_string(),
             move_path: None,
+            mode: None,
         },
     );
 
@@ -1223,6 +1229,8 @@ fn patch_apply_failure_produces_item_completed_patchapply_failed() {
             stderr: "failed to apply".to_string(),
             success: false,
             changes: changes.clone(),
+            post_edit: Vec::new(),
+            outcome: None,
         }),
     );
     let out_end = ep.collect_thread_events(&end);
@@ -0,0 +1,87 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn batch_runs_each_task_in_its_cwd() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let root = test.cwd_path();
+    std::fs::create_dir(root.join("repo-a"))?;
+    std::fs::create_dir(root.join("repo-b"))?;
+    let tasks_path = root.join("tasks.jsonl");
+    std::fs::write(
+        &tasks_path,
+        concat!(
+            "{\"id\": \"a\", \"cwd\": \"repo-a\", \"prompt\": \"first task\"}\n",
+            "{\"id\": \"b\", \"cwd\": \"repo-b\", \"prompt\": \"second task\"}\n",
+            "{\"id\": \"missing\", \"cwd\": \"repo-c\", \"prompt\": \"third task\"}\n",
+        ),
+    )?;
+
+    let server = responses::start_mock_server().await;
+    let body = |id: &str| {
+        responses::sse(vec![
+            responses::ev_response_created(id),
+            responses::ev_assistant_message(&format!("m-{id}"), "done"),
+            responses::ev_completed(id),
+        ])
+    };
+    let response_mock =
+        responses::mount_sse_sequence(&server, vec![body("resp1"), body("resp2")]).await;
+
+    let output = test
+        .cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--batch")
+        .arg(&tasks_path)
+        .arg("--batch-concurrency")
+        .arg("1")
+        .arg("--json")
+        .output()?;
+    assert_eq!(
+        output.status.code(),
+        Some(1),
+        "the missing cwd fails the batch"
+    );
+
+    let summaries: Vec<Value> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let statuses: Vec<(&str, &str)> = summaries
+        .iter()
+        .map(|summary| {
+            (
+                summary["id"].as_str().unwrap(),
+                summary["status"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![("a", "ok"), ("b", "ok"), ("missing", "error")]
+    );
+    assert_eq!(summaries[0]["last_message"], "done");
+
+    for id in ["a", "b"] {
+        let events = std::fs::read_to_string(root.join(format!("tasks.events/{id}.jsonl")))?;
+        let types: Vec<String> = events
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap()["type"].to_string())
+            .collect();
+        assert_eq!(
+            types.first().map(String::as_str),
+            Some("\"thread.started\"")
+        );
+        assert!(
+            types.iter().any(|ty| ty == "\"turn.completed\""),
+            "{types:?}"
+        );
+    }
+    assert_eq!(response_mock.requests().len(), 2);
+
+    Ok(())
+}
@@ -2,6 +2,7 @@
 mod add_dir;
 mod apply_patch;
 mod auth_env;
+mod batch;
 mod originator;
 mod output_schema;
 mod resume;
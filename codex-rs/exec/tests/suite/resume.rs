@@ -3,6 +3,7 @@ use anyhow::Context;
 use codex_utils_cargo_bin::find_resource;
 use core_test_support::test_codex_exec::test_codex_exec;
 use pretty_assertions::assert_eq;
+use pretty_assertions::assert_ne;
 use serde_json::Value;
 use std::fs::FileTimes;
 use std::fs::OpenOptions;
@@ -546,3 +547,70 @@ fn exec_resume_accepts_images_after_subcommand() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn exec_resume_continue_replays_up_to_last_checkpoint() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+    let fixture = exec_fixture()?;
+    let repo_root = exec_repo_root()?;
+
+    let marker = format!("resume-continue-{}", Uuid::new_v4());
+    test.cmd()
+        .env("CODEX_RS_SSE_FIXTURE", &fixture)
+        .env("OPENAI_BASE_URL", "http://unused.local")
+        .arg("--skip-git-repo-check")
+        .arg("-C")
+        .arg(&repo_root)
+        .arg(format!("echo {marker}"))
+        .assert()
+        .success();
+
+    let sessions_dir = test.home_path().join("sessions");
+    let path = find_session_file_containing_marker(&sessions_dir, &marker)
+        .expect("no session file found after first run");
+
+    // The run finished its turn, so there is nothing left to continue.
+    test.cmd()
+        .env("CODEX_RS_SSE_FIXTURE", &fixture)
+        .env("OPENAI_BASE_URL", "http://unused.local")
+        .arg("--skip-git-repo-check")
+        .arg("-C")
+        .arg(&repo_root)
+        .arg("resume")
+        .arg("--last")
+        .arg("--continue")
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("Nothing to continue"));
+
+    // Simulate a run that was interrupted right after a tool call finished.
+    let mut content = std::fs::read_to_string(&path)?;
+    content.push_str(
+        r#"{"timestamp":"2025-01-01T00:00:00.000Z","type":"checkpoint","payload":{"turn_id":"interrupted","call_id":"call-1"}}"#,
+    );
+    content.push('\n');
+    std::fs::write(&path, content)?;
+
+    test.cmd()
+        .env("CODEX_RS_SSE_FIXTURE", &fixture)
+        .env("OPENAI_BASE_URL", "http://unused.local")
+        .arg("--skip-git-repo-check")
+        .arg("-C")
+        .arg(&repo_root)
+        .arg("resume")
+        .arg("--last")
+        .arg("--continue")
+        .assert()
+        .success();
+
+    let continued_path =
+        find_session_file_containing_marker(&sessions_dir, "previous run was interrupted")
+            .expect("no session file containing the continue prompt");
+    assert_ne!(
+        continued_path, path,
+        "--continue should record into a new session file"
+    );
+    let continued = std::fs::read_to_string(&continued_path)?;
+    assert!(continued.contains(&marker));
+    Ok(())
+}
@@ -56,21 +56,13 @@ pub enum AmendError {
 
 /// Note this thread uses advisory file locking and performs blocking I/O, so it should be used with
 /// [`tokio::task::spawn_blocking`] when called from an async context.
+///
+/// Returns whether the rule was written; `false` means the file already had it.
 pub fn blocking_append_allow_prefix_rule(
     policy_path: &Path,
     prefix: &[String],
-) -> Result<(), AmendError> {
-    if prefix.is_empty() {
-        return Err(AmendError::EmptyPrefix);
-    }
-
-    let tokens = prefix
-        .iter()
-        .map(serde_json::to_string)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|source| AmendError::SerializePrefix { source })?;
-    let pattern = format!("[{}]", tokens.join(", "));
-    let rule = format!(r#"prefix_rule(pattern={pattern}, decision="allow")"#);
+) -> Result<bool, AmendError> {
+    let rule = allow_prefix_rule_line(prefix)?;
 
     let dir = policy_path
         .parent()
@@ -90,7 +82,80 @@ pub fn blocking_append_allow_prefix_rule(
     append_locked_line(policy_path, &rule)
 }
 
-fn append_locked_line(policy_path: &Path, line: &str) -> Result<(), AmendError> {
+/// Removes a rule previously added by [`blocking_append_allow_prefix_rule`].
+/// Returns whether the rule was present. Like the append, this performs
+/// blocking I/O under an advisory lock.
+pub fn blocking_remove_allow_prefix_rule(
+    policy_path: &Path,
+    prefix: &[String],
+) -> Result<bool, AmendError> {
+    let rule = allow_prefix_rule_line(prefix)?;
+    let mut file = match OpenOptions::new().read(true).write(true).open(policy_path) {
+        Ok(file) => file,
+        Err(source) if source.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(source) => {
+            return Err(AmendError::OpenPolicyFile {
+                path: policy_path.to_path_buf(),
+                source,
+            });
+        }
+    };
+    file.lock().map_err(|source| AmendError::LockPolicyFile {
+        path: policy_path.to_path_buf(),
+        source,
+    })?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(|source| AmendError::ReadPolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+    if !contents.lines().any(|existing| existing == rule) {
+        return Ok(false);
+    }
+
+    let remaining: String = contents
+        .lines()
+        .filter(|existing| *existing != rule)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    file.set_len(0)
+        .map_err(|source| AmendError::WritePolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+    file.seek(SeekFrom::Start(0))
+        .map_err(|source| AmendError::SeekPolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+    file.write_all(remaining.as_bytes())
+        .map_err(|source| AmendError::WritePolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+
+    Ok(true)
+}
+
+fn allow_prefix_rule_line(prefix: &[String]) -> Result<String, AmendError> {
+    if prefix.is_empty() {
+        return Err(AmendError::EmptyPrefix);
+    }
+
+    let tokens = prefix
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| AmendError::SerializePrefix { source })?;
+    let pattern = format!("[{}]", tokens.join(", "));
+    Ok(format!(
+        r#"prefix_rule(pattern={pattern}, decision="allow")"#
+    ))
+}
+
+fn append_locked_line(policy_path: &Path, line: &str) -> Result<bool, AmendError> {
     let mut file = OpenOptions::new()
         .create(true)
         .read(true)
@@ -118,7 +183,7 @@ fn append_locked_line(policy_path: &Path, line: &str) -> Result<(), AmendError>
         })?;
 
     if contents.lines().any(|existing| existing == line) {
-        return Ok(());
+        return Ok(false);
     }
 
     if !contents.is_empty() && !contents.ends_with('\n') {
@@ -135,7 +200,7 @@ fn append_locked_line(policy_path: &Path, line: &str) -> Result<(), AmendError>
             source,
         })?;
 
-    Ok(())
+    Ok(true)
 }
 
 #[cfg(test)]
@@ -215,4 +280,39 @@ prefix_rule(pattern=["echo", "Hello, world!"], decision="allow")
 "#
         );
     }
+
+    #[test]
+    fn removes_only_the_matching_rule() {
+        let tmp = tempdir().expect("create temp dir");
+        let policy_path = tmp.path().join("rules").join("default.rules");
+        let prefix = [String::from("echo"), String::from("Hello, world!")];
+        std::fs::create_dir_all(policy_path.parent().unwrap()).expect("create policy dir");
+        std::fs::write(
+            &policy_path,
+            r#"prefix_rule(pattern=["ls"], decision="allow")"#,
+        )
+        .expect("write seed rule");
+        blocking_append_allow_prefix_rule(&policy_path, &prefix).expect("append rule");
+
+        assert!(blocking_remove_allow_prefix_rule(&policy_path, &prefix).expect("remove rule"));
+        assert!(!blocking_remove_allow_prefix_rule(&policy_path, &prefix).expect("remove rule"));
+
+        let contents = std::fs::read_to_string(&policy_path).expect("read policy");
+        assert_eq!(
+            contents,
+            r#"prefix_rule(pattern=["ls"], decision="allow")
+"#
+        );
+    }
+
+    #[test]
+    fn removing_from_missing_file_is_a_no_op() {
+        let tmp = tempdir().expect("create temp dir");
+        let policy_path = tmp.path().join("rules").join("default.rules");
+
+        assert!(
+            !blocking_remove_allow_prefix_rule(&policy_path, &[String::from("ls")])
+                .expect("remove rule")
+        );
+    }
 }
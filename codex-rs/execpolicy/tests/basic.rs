@@ -55,8 +55,8 @@ fn append_allow_prefix_rule_dedupes_existing_rule() -> Result<()> {
     let policy_path = tmp.path().join("rules").join("default.rules");
     let prefix = tokens(&["python3"]);
 
-    blocking_append_allow_prefix_rule(&policy_path, &prefix)?;
-    blocking_append_allow_prefix_rule(&policy_path, &prefix)?;
+    assert!(blocking_append_allow_prefix_rule(&policy_path, &prefix)?);
+    assert!(!blocking_append_allow_prefix_rule(&policy_path, &prefix)?);
 
     let contents = fs::read_to_string(&policy_path).context("read policy")?;
     assert_eq!(
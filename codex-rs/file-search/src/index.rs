@@ -0,0 +1,873 @@
+//! A persistent index of the files under a search root.
+//!
+//! Walking a very large tree for every search session is slow. When
+//! [`crate::FileSearchOptions::index_dir`] is set, each root is walked once,
+//! the file list is saved in that directory, and a file watcher keeps it up to
+//! date for as long as the process runs. Only the directories the walk visits
+//! are watched, so ignored trees such as `node_modules` or `target` never use
+//! up the system's watch limit; if watches cannot be added anyway, the index
+//! is rebuilt periodically instead. Sessions then read the indexed paths
+//! instead of walking, and files that were modified recently or touched in git
+//! rank higher.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::MAIN_SEPARATOR_STR;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::sync::RwLock;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+
+use crate::configure_walk_builder;
+
+const INDEX_HEADER: &str = "codex-file-search-index 2";
+
+/// Files modified within this window rank higher.
+const RECENT_WINDOW: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Watcher events are batched for this long before the index is updated.
+const EVENT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How long the index may hold unsaved changes.
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often the root is walked again when its directories cannot be watched.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Minimum time between two refreshes of the git-touched files.
+const GIT_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of recent commits whose files count as touched.
+const GIT_LOG_COMMITS: &str = "20";
+
+/// An index that is opened at most once, by whichever caller gets there first.
+type IndexSlot = Arc<OnceLock<Arc<FileIndex>>>;
+
+/// Indexes opened by this process, keyed by their file on disk. They live
+/// until the process exits so their watchers keep them fresh.
+static INDEXES: LazyLock<Mutex<HashMap<PathBuf, IndexSlot>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+pub(crate) struct FileIndex {
+    inner: Arc<IndexInner>,
+}
+
+struct IndexInner {
+    root: PathBuf,
+    index_path: PathBuf,
+    respect_gitignore: bool,
+    state: RwLock<IndexState>,
+    /// Watches each indexed directory on its own. `None` once a watch could
+    /// not be added, after which the index is kept fresh by periodic rescans.
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+#[derive(Debug, Default)]
+struct IndexState {
+    /// Modification time, in seconds since the epoch, of every file keyed by
+    /// its path relative to the root.
+    files: BTreeMap<String, u64>,
+    /// Relative paths with uncommitted changes or changed in recent commits.
+    git_touched: HashSet<String>,
+    /// Whether `files` changed since the index was last saved.
+    dirty: bool,
+    /// Set while a rebuild walks the root. Directories the watcher reports in
+    /// the meantime are collected here and reconciled once the walk's result
+    /// is in place, so the walk cannot overwrite them.
+    changed_during_rebuild: Option<HashSet<PathBuf>>,
+}
+
+impl FileIndex {
+    /// Returns the index of `root` stored in `index_dir`, loading it from disk
+    /// or walking `root` the first time it is opened in this process.
+    pub(crate) fn open(
+        index_dir: &Path,
+        root: &Path,
+        respect_gitignore: bool,
+    ) -> anyhow::Result<Arc<Self>> {
+        let index_path = index_dir.join(index_file_name(root, respect_gitignore));
+        // The global lock only guards the lookup; the first walk of a root
+        // blocks callers of that root alone.
+        let slot = {
+            #[expect(clippy::unwrap_used)]
+            let mut indexes = INDEXES.lock().unwrap();
+            indexes.entry(index_path.clone()).or_default().clone()
+        };
+        Ok(slot
+            .get_or_init(|| Self::load(index_path, root, respect_gitignore))
+            .clone())
+    }
+
+    fn load(index_path: PathBuf, root: &Path, respect_gitignore: bool) -> Arc<Self> {
+        let saved = load_index(&index_path, root);
+        let has_saved = saved.is_some();
+        let inner = Arc::new(IndexInner {
+            root: root.to_path_buf(),
+            index_path,
+            respect_gitignore,
+            state: RwLock::new(IndexState {
+                files: saved.unwrap_or_default(),
+                changed_during_rebuild: Some(HashSet::new()),
+                ..IndexState::default()
+            }),
+            watcher: Mutex::new(None),
+        });
+
+        // Start the watcher before walking: the walk adds a watch to each
+        // directory before listing it, so changes made meanwhile are not lost.
+        if let Err(err) = start_watcher(&inner) {
+            tracing::warn!(
+                "file search index for {} will be rescanned every {}s: {err}",
+                root.display(),
+                RESCAN_INTERVAL.as_secs()
+            );
+            let rescan = inner.clone();
+            thread::spawn(move || rescan_periodically(&rescan));
+        }
+        if has_saved {
+            // Pick up whatever changed while no watcher was running.
+            let refresh = inner.clone();
+            thread::spawn(move || refresh.rebuild());
+        } else {
+            inner.rebuild();
+        }
+        Arc::new(Self { inner })
+    }
+
+    /// Calls `f` with the relative path of every indexed file.
+    pub(crate) fn for_each_file(&self, mut f: impl FnMut(&str)) {
+        for path in self.inner.read_state().files.keys() {
+            f(path);
+        }
+    }
+
+    /// `score` raised for files touched in git or modified recently.
+    pub(crate) fn boosted_score(&self, relative_path: &str, score: u32) -> u32 {
+        let state = self.inner.read_state();
+        let mut boosted = score;
+        if state.git_touched.contains(relative_path) {
+            boosted += score / 4;
+        }
+        let recent_cutoff = unix_seconds(SystemTime::now()).saturating_sub(RECENT_WINDOW.as_secs());
+        if state
+            .files
+            .get(relative_path)
+            .is_some_and(|modified| *modified >= recent_cutoff)
+        {
+            boosted += score / 8;
+        }
+        boosted
+    }
+}
+
+impl IndexInner {
+    #[expect(clippy::unwrap_used)]
+    fn read_state(&self) -> std::sync::RwLockReadGuard<'_, IndexState> {
+        self.state.read().unwrap()
+    }
+
+    #[expect(clippy::unwrap_used)]
+    fn write_state(&self) -> std::sync::RwLockWriteGuard<'_, IndexState> {
+        self.state.write().unwrap()
+    }
+
+    /// Replaces the index with a fresh walk of the root and saves it.
+    fn rebuild(&self) {
+        self.write_state()
+            .changed_during_rebuild
+            .get_or_insert_default();
+        let files = walk_files(&self.root, self.respect_gitignore, None, &|dir| {
+            self.watch_dir(dir);
+        });
+        let git_touched = git_touched_files(&self.root);
+        let changed = {
+            let mut state = self.write_state();
+            if state.files != files {
+                state.files = files;
+                state.dirty = true;
+            }
+            state.git_touched = git_touched;
+            state.changed_during_rebuild.take()
+        };
+        if let Some(changed) = changed {
+            self.reconcile_changed_dirs(changed);
+        }
+        self.save_if_dirty();
+    }
+
+    /// Updates the index for directories the watcher reported as changed.
+    /// Ignored directories are skipped, and a directory the index has no
+    /// files under is handled by reconciling its nearest indexed ancestor,
+    /// which walks whatever is new below it.
+    fn reconcile_changed_dirs(&self, changed: HashSet<PathBuf>) {
+        let dirs: HashSet<PathBuf> = {
+            let mut state = self.write_state();
+            if let Some(deferred) = state.changed_during_rebuild.as_mut() {
+                deferred.extend(changed);
+                return;
+            }
+            let ignore = root_ignore_matcher(&self.root, self.respect_gitignore);
+            changed
+                .into_iter()
+                .filter(|dir| {
+                    dir == &self.root
+                        || !ignore.as_ref().is_some_and(|ignore| {
+                            ignore.matched_path_or_any_parents(dir, true).is_ignore()
+                        })
+                })
+                .filter_map(|dir| nearest_indexed_dir(&self.root, &state.files, &dir))
+                .collect()
+        };
+        for dir in dirs {
+            self.reconcile_dir(&dir);
+        }
+    }
+
+    /// Brings the entries for the direct children of `dir` in line with the
+    /// filesystem, walking directories that are new to the index.
+    fn reconcile_dir(&self, dir: &Path) {
+        let Some(prefix) = relative_prefix(&self.root, dir) else {
+            return;
+        };
+        let (files, dirs) = list_dir(dir, self.respect_gitignore);
+        let new_dirs: Vec<String> = {
+            let state = self.read_state();
+            dirs.iter()
+                .filter(|name| {
+                    let dir_prefix = format!("{prefix}{name}{MAIN_SEPARATOR_STR}");
+                    !has_prefix(&state.files, &dir_prefix)
+                })
+                .cloned()
+                .collect()
+        };
+        let mut added: BTreeMap<String, u64> = files
+            .into_iter()
+            .map(|(name, modified)| (format!("{prefix}{name}"), modified))
+            .collect();
+        for name in new_dirs {
+            let dir_prefix = format!("{prefix}{name}{MAIN_SEPARATOR_STR}");
+            added.extend(walk_files(
+                &dir.join(&name),
+                self.respect_gitignore,
+                Some(&dir_prefix),
+                &|dir| self.watch_dir(dir),
+            ));
+        }
+
+        let mut state = self.write_state();
+        let stale: Vec<String> = state
+            .files
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .map(|(path, _)| path)
+            .filter(
+                |path| match path[prefix.len()..].split_once(MAIN_SEPARATOR_STR) {
+                    None => !added.contains_key(*path),
+                    Some((first, _)) => !dirs.contains(first),
+                },
+            )
+            .cloned()
+            .collect();
+        for path in stale {
+            state.files.remove(&path);
+            state.dirty = true;
+        }
+        for (path, modified) in added {
+            if state.files.insert(path, modified) != Some(modified) {
+                state.dirty = true;
+            }
+        }
+    }
+
+    /// Watches `dir`, but not its subdirectories, for changes. A directory
+    /// that is already gone is skipped; any other failure, such as running
+    /// into the system's watch limit, drops the watcher so the index falls
+    /// back to periodic rescans.
+    fn watch_dir(&self, dir: &Path) {
+        #[expect(clippy::unwrap_used)]
+        let mut watcher = self.watcher.lock().unwrap();
+        let Some(active) = watcher.as_mut() else {
+            return;
+        };
+        match active.watch(dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {}
+            Err(err) if matches!(err.kind, notify::ErrorKind::PathNotFound) => {}
+            Err(err) => {
+                tracing::warn!(
+                    "file search index for {} will be rescanned every {}s: cannot watch {}: {err}",
+                    self.root.display(),
+                    RESCAN_INTERVAL.as_secs(),
+                    dir.display()
+                );
+                *watcher = None;
+            }
+        }
+    }
+
+    fn refresh_git_touched(&self) {
+        let git_touched = git_touched_files(&self.root);
+        self.write_state().git_touched = git_touched;
+    }
+
+    fn save_if_dirty(&self) {
+        let mut state = self.write_state();
+        if !state.dirty {
+            return;
+        }
+        match save_index(&self.index_path, &self.root, &state.files) {
+            Ok(()) => state.dirty = false,
+            Err(err) => tracing::warn!(
+                "failed to save file search index {}: {err}",
+                self.index_path.display()
+            ),
+        }
+    }
+}
+
+/// Names the index file after a hash of the root, so every root gets its own
+/// file in the same directory.
+fn index_file_name(root: &Path, respect_gitignore: bool) -> String {
+    // FNV-1a: stable across builds, unlike `DefaultHasher`.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in root.as_os_str().as_encoded_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let suffix = if respect_gitignore { "" } else { "-all" };
+    format!("{hash:016x}{suffix}.idx")
+}
+
+/// Reads an index written by [`save_index`]: a header line, then the root and
+/// one `<mtime>\t<path>` record per file, each terminated by a NUL byte so
+/// paths may contain newlines.
+fn load_index(index_path: &Path, root: &Path) -> Option<BTreeMap<String, u64>> {
+    let contents = fs::read(index_path).ok()?;
+    let body = contents.strip_prefix(format!("{INDEX_HEADER}\n").as_bytes())?;
+    let mut records = body.split(|byte| *byte == 0);
+    if records.next()? != root.as_os_str().as_encoded_bytes() {
+        return None;
+    }
+    let mut files = BTreeMap::new();
+    for record in records.filter(|record| !record.is_empty()) {
+        let (modified, path) = std::str::from_utf8(record).ok()?.split_once('\t')?;
+        files.insert(path.to_string(), modified.parse().ok()?);
+    }
+    Some(files)
+}
+
+/// Writes the index next to its final path and renames it into place, so a
+/// crash never leaves a truncated index behind.
+fn save_index(index_path: &Path, root: &Path, files: &BTreeMap<String, u64>) -> io::Result<()> {
+    if let Some(parent) = index_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = index_path.with_extension("idx.tmp");
+    let mut out = BufWriter::new(fs::File::create(&temp_path)?);
+    writeln!(out, "{INDEX_HEADER}")?;
+    out.write_all(root.as_os_str().as_encoded_bytes())?;
+    out.write_all(b"\0")?;
+    for (path, modified) in files {
+        write!(out, "{modified}\t{path}\0")?;
+    }
+    out.into_inner().map_err(io::IntoInnerError::into_error)?;
+    fs::rename(&temp_path, index_path)
+}
+
+/// Walks `dir` and returns every file with its modification time, keyed by
+/// `prefix` followed by its path relative to `dir`. `on_dir` is called with
+/// each directory the walk visits, `dir` included, before it is listed.
+fn walk_files(
+    dir: &Path,
+    respect_gitignore: bool,
+    prefix: Option<&str>,
+    on_dir: &(dyn Fn(&Path) + Sync),
+) -> BTreeMap<String, u64> {
+    let mut walk_builder = WalkBuilder::new(dir);
+    configure_walk_builder(&mut walk_builder, respect_gitignore);
+    let threads = thread::available_parallelism().map_or(1, std::num::NonZero::get);
+    walk_builder.threads(threads);
+
+    let (tx, rx) = mpsc::channel::<(String, u64)>();
+    walk_builder.build_parallel().run(|| {
+        let tx = tx.clone();
+        Box::new(move |entry| {
+            let Ok(entry) = entry else {
+                return ignore::WalkState::Continue;
+            };
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                on_dir(entry.path());
+                return ignore::WalkState::Continue;
+            }
+            let Some(relative_path) = entry.path().strip_prefix(dir).ok().and_then(Path::to_str)
+            else {
+                return ignore::WalkState::Continue;
+            };
+            let path = format!("{}{relative_path}", prefix.unwrap_or_default());
+            let _ = tx.send((path, modified_seconds(&entry)));
+            ignore::WalkState::Continue
+        })
+    });
+    drop(tx);
+    rx.into_iter().collect()
+}
+
+/// The files, with modification times, and the directories directly inside
+/// `dir` that the walker would visit.
+fn list_dir(dir: &Path, respect_gitignore: bool) -> (BTreeMap<String, u64>, HashSet<String>) {
+    let mut files = BTreeMap::new();
+    let mut dirs = HashSet::new();
+    let mut walk_builder = WalkBuilder::new(dir);
+    configure_walk_builder(&mut walk_builder, respect_gitignore);
+    walk_builder.max_depth(Some(1));
+    for entry in walk_builder.build().flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str() else {
+            continue;
+        };
+        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+            dirs.insert(name.to_string());
+        } else {
+            files.insert(name.to_string(), modified_seconds(&entry));
+        }
+    }
+    (files, dirs)
+}
+
+fn modified_seconds(entry: &ignore::DirEntry) -> u64 {
+    entry
+        .metadata()
+        .ok()
+        .and_then(|metadata| metadata.modified().ok())
+        .map_or(0, unix_seconds)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// The key prefix shared by the children of `dir`: empty for the root,
+/// otherwise the relative path followed by a separator.
+fn relative_prefix(root: &Path, dir: &Path) -> Option<String> {
+    let relative = dir.strip_prefix(root).ok()?.to_str()?;
+    if relative.is_empty() {
+        Some(String::new())
+    } else {
+        Some(format!("{relative}{MAIN_SEPARATOR_STR}"))
+    }
+}
+
+/// `dir` or its closest ancestor that the index has files under, stopping at
+/// the root. `None` outside the root.
+fn nearest_indexed_dir(root: &Path, files: &BTreeMap<String, u64>, dir: &Path) -> Option<PathBuf> {
+    let mut dir = dir;
+    loop {
+        let prefix = relative_prefix(root, dir)?;
+        if prefix.is_empty() || has_prefix(files, &prefix) {
+            return Some(dir.to_path_buf());
+        }
+        dir = dir.parent()?;
+    }
+}
+
+/// The ignore rules at the top of `root`, or `None` when ignore files are not
+/// respected. Rules in nested ignore files are left to the walker.
+fn root_ignore_matcher(root: &Path, respect_gitignore: bool) -> Option<Gitignore> {
+    if !respect_gitignore {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(root);
+    for file in [".gitignore", ".ignore", ".git/info/exclude"] {
+        // Missing files are expected; anything unreadable is skipped.
+        let _ = builder.add(root.join(file));
+    }
+    builder.build().ok()
+}
+
+fn has_prefix(files: &BTreeMap<String, u64>, prefix: &str) -> bool {
+    files
+        .range(prefix.to_string()..)
+        .next()
+        .is_some_and(|(path, _)| path.starts_with(prefix))
+}
+
+/// Files with uncommitted changes, untracked files, and files changed in the
+/// last few commits, relative to `root`. Empty outside a git repository.
+fn git_touched_files(root: &Path) -> HashSet<String> {
+    let commands: [&[&str]; 3] = [
+        &["diff", "--name-only", "--relative", "-z", "HEAD"],
+        &["ls-files", "--others", "--exclude-standard", "-z"],
+        &[
+            "log",
+            "-n",
+            GIT_LOG_COMMITS,
+            "--name-only",
+            "--relative",
+            "-z",
+            "--pretty=format:",
+        ],
+    ];
+    let mut touched = HashSet::new();
+    for args in commands {
+        let Ok(output) = Command::new("git").args(args).current_dir(root).output() else {
+            break;
+        };
+        if !output.status.success() {
+            continue;
+        }
+        for path in String::from_utf8_lossy(&output.stdout).split(['\0', '\n']) {
+            if !path.is_empty() {
+                touched.insert(path.replace('/', MAIN_SEPARATOR_STR));
+            }
+        }
+    }
+    touched
+}
+
+/// Creates the watcher that [`IndexInner::watch_dir`] adds directories to and
+/// the thread that applies its events.
+fn start_watcher(inner: &Arc<IndexInner>) -> notify::Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let watcher = notify::recommended_watcher(move |event| {
+        let _ = tx.send(event);
+    })?;
+    #[expect(clippy::unwrap_used)]
+    let mut slot = inner.watcher.lock().unwrap();
+    *slot = Some(watcher);
+    let inner = inner.clone();
+    thread::spawn(move || {
+        watch_events(&inner, &rx);
+        rescan_periodically(&inner);
+    });
+    Ok(())
+}
+
+/// Rebuilds the index every [`RESCAN_INTERVAL`], for roots whose directories
+/// cannot all be watched.
+fn rescan_periodically(inner: &IndexInner) {
+    loop {
+        thread::sleep(RESCAN_INTERVAL);
+        inner.rebuild();
+    }
+}
+
+/// Applies batches of watcher events to the index until the watcher is dropped.
+fn watch_events(inner: &IndexInner, rx: &mpsc::Receiver<notify::Result<notify::Event>>) {
+    let mut git_refreshed_at: Option<Instant> = None;
+    let mut git_changed = false;
+    loop {
+        let first = match rx.recv_timeout(SAVE_INTERVAL) {
+            Ok(event) => event,
+            Err(RecvTimeoutError::Timeout) => {
+                if git_changed {
+                    inner.refresh_git_touched();
+                    git_refreshed_at = Some(Instant::now());
+                    git_changed = false;
+                }
+                inner.save_if_dirty();
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let mut dirs = HashSet::new();
+        let mut collect = |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            for path in event.paths {
+                let Ok(relative) = path.strip_prefix(&inner.root) else {
+                    continue;
+                };
+                if relative.components().any(|c| c.as_os_str() == ".git") {
+                    git_changed = true;
+                } else if !relative.as_os_str().is_empty()
+                    && let Some(parent) = path.parent()
+                {
+                    dirs.insert(parent.to_path_buf());
+                }
+            }
+        };
+        collect(first);
+        let deadline = Instant::now() + EVENT_DEBOUNCE;
+        while let Ok(event) = rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+            collect(event);
+        }
+
+        if !dirs.is_empty() {
+            git_changed = true;
+            inner.reconcile_changed_dirs(dirs);
+        }
+        if git_changed && git_refreshed_at.is_none_or(|at| at.elapsed() >= GIT_REFRESH_INTERVAL) {
+            inner.refresh_git_touched();
+            git_refreshed_at = Some(Instant::now());
+            git_changed = false;
+        }
+    }
+    inner.save_if_dirty();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn test_index(root: &Path, index_dir: &Path) -> IndexInner {
+        IndexInner {
+            root: root.to_path_buf(),
+            index_path: index_dir.join(index_file_name(root, true)),
+            respect_gitignore: true,
+            state: RwLock::new(IndexState::default()),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    fn indexed_paths(inner: &IndexInner) -> Vec<String> {
+        inner.read_state().files.keys().cloned().collect()
+    }
+
+    fn native(path: &str) -> String {
+        path.replace('/', MAIN_SEPARATOR_STR)
+    }
+
+    #[test]
+    fn saved_index_loads_only_for_its_root() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        let index_path = dir.path().join("index.idx");
+        let files = BTreeMap::from([
+            ("a.rs".to_string(), 10),
+            (native("notes/line\nbreak.md"), 15),
+            (native("src/b.rs"), 20),
+        ]);
+
+        save_index(&index_path, &root, &files).unwrap();
+
+        assert_eq!(load_index(&index_path, &root), Some(files));
+        assert_eq!(load_index(&index_path, &dir.path().join("other")), None);
+    }
+
+    #[test]
+    fn reconcile_dir_tracks_added_and_removed_entries() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("src/old.rs"), "").unwrap();
+        let inner = test_index(&root, dir.path());
+        inner.rebuild();
+        assert_eq!(
+            indexed_paths(&inner),
+            vec![
+                ".gitignore".to_string(),
+                native("src/lib.rs"),
+                native("src/old.rs")
+            ]
+        );
+
+        fs::remove_file(root.join("src/old.rs")).unwrap();
+        fs::write(root.join("src/new.rs"), "").unwrap();
+        fs::write(root.join("src/debug.log"), "").unwrap();
+        fs::create_dir_all(root.join("src/nested/deeper")).unwrap();
+        fs::write(root.join("src/nested/deeper/mod.rs"), "").unwrap();
+        inner.reconcile_dir(&root.join("src"));
+
+        assert_eq!(
+            indexed_paths(&inner),
+            vec![
+                ".gitignore".to_string(),
+                native("src/lib.rs"),
+                native("src/nested/deeper/mod.rs"),
+                native("src/new.rs"),
+            ]
+        );
+
+        fs::remove_dir_all(root.join("src/nested")).unwrap();
+        inner.reconcile_dir(&root.join("src"));
+        assert_eq!(
+            indexed_paths(&inner),
+            vec![
+                ".gitignore".to_string(),
+                native("src/lib.rs"),
+                native("src/new.rs")
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_dirs_skip_ignored_dirs_and_walk_new_ones() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        let inner = test_index(&root, dir.path());
+        inner.rebuild();
+
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join("target/debug/build.rs"), "").unwrap();
+        fs::create_dir_all(root.join("vendor/dep")).unwrap();
+        fs::write(root.join("vendor/dep/lib.rs"), "").unwrap();
+        inner.reconcile_changed_dirs(HashSet::from([
+            root.join("target/debug"),
+            root.join("vendor/dep"),
+        ]));
+
+        assert_eq!(
+            indexed_paths(&inner),
+            vec![
+                ".gitignore".to_string(),
+                native("src/lib.rs"),
+                native("vendor/dep/lib.rs"),
+            ]
+        );
+    }
+
+    #[test]
+    fn changes_seen_during_a_rebuild_are_applied_after_it() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        let inner = test_index(&root, dir.path());
+        inner.write_state().changed_during_rebuild = Some(HashSet::new());
+
+        inner.reconcile_changed_dirs(HashSet::from([root.join("src")]));
+        assert_eq!(indexed_paths(&inner), Vec::<String>::new());
+        assert_eq!(
+            inner.read_state().changed_during_rebuild,
+            Some(HashSet::from([root.join("src")]))
+        );
+
+        inner.rebuild();
+        assert_eq!(indexed_paths(&inner), vec![native("src/lib.rs")]);
+        assert_eq!(inner.read_state().changed_during_rebuild, None);
+    }
+
+    #[test]
+    fn watcher_adds_new_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("first.txt"), "").unwrap();
+        let index = FileIndex::open(&dir.path().join("indexes"), &root, true).unwrap();
+
+        fs::write(root.join("second.txt"), "").unwrap();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("src/nested/lib.rs"), "").unwrap();
+        wait_for_paths(&index, &["first.txt", "second.txt", "src/nested/lib.rs"]);
+
+        // New directories are watched too.
+        fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+        wait_for_paths(
+            &index,
+            &[
+                "first.txt",
+                "second.txt",
+                "src/nested/lib.rs",
+                "src/nested/mod.rs",
+            ],
+        );
+    }
+
+    fn wait_for_paths(index: &FileIndex, expected: &[&str]) {
+        let expected: Vec<String> = expected.iter().map(|path| native(path)).collect();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let mut paths = Vec::new();
+            index.for_each_file(|path| paths.push(path.to_string()));
+            if paths == expected {
+                break;
+            }
+            assert!(
+                Instant::now() < deadline,
+                "index never saw {expected:?}: {paths:?}"
+            );
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn walk_visits_only_directories_that_are_not_ignored() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("node_modules/dep")).unwrap();
+        fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(root.join("node_modules/dep/index.js"), "").unwrap();
+
+        let visited = Mutex::new(Vec::new());
+        walk_files(&root, true, None, &|dir| {
+            visited.lock().unwrap().push(dir.to_path_buf());
+        });
+
+        let mut visited = visited.into_inner().unwrap();
+        visited.sort();
+        assert_eq!(visited, vec![root.clone(), root.join("src")]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_watch_drops_the_watcher() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path().join("repo");
+        fs::create_dir_all(&root).unwrap();
+        let inner = test_index(&root, dir.path());
+        *inner.watcher.lock().unwrap() = Some(notify::recommended_watcher(|_| {}).unwrap());
+
+        // Directories deleted before they are watched are skipped.
+        inner.watch_dir(&root.join("missing"));
+        assert!(inner.watcher.lock().unwrap().is_some());
+
+        // Any other failure stands in for hitting the watch limit.
+        inner.watch_dir(&root.join("x".repeat(300)));
+        assert!(inner.watcher.lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn boost_favors_git_touched_and_recent_files() {
+        let dir = TempDir::new().unwrap();
+        let inner = test_index(dir.path(), dir.path());
+        {
+            let mut state = inner.write_state();
+            let now = unix_seconds(SystemTime::now());
+            state.files.insert("old.rs".to_string(), 0);
+            state.files.insert("recent.rs".to_string(), now);
+            state.files.insert("touched.rs".to_string(), 0);
+            state.git_touched.insert("touched.rs".to_string());
+        }
+        let index = FileIndex {
+            inner: Arc::new(inner),
+        };
+
+        assert_eq!(index.boosted_score("old.rs", 80), 80);
+        assert_eq!(index.boosted_score("recent.rs", 80), 90);
+        assert_eq!(index.boosted_score("touched.rs", 80), 100);
+    }
+}
@@ -20,6 +20,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Condvar;
 use std::sync::Mutex;
+use std::sync::OnceLock;
 use std::sync::RwLock;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
@@ -35,8 +36,18 @@ use nucleo::pattern::AtomKind;
 use nucleo::pattern::Pattern;
 
 mod cli;
+mod index;
 
 pub use cli::Cli;
+use index::FileIndex;
+
+/// Name of the directory under `CODEX_HOME` that holds persistent search
+/// indexes.
+pub const INDEX_DIR_NAME: &str = "file_search_index";
+
+/// How many of the best matches are re-ranked with the index's boosts before
+/// the top `limit` are reported.
+const RERANK_FACTOR: usize = 4;
 
 /// A single match result returned from the search.
 ///
@@ -93,6 +104,10 @@ pub struct FileSearchOptions {
     pub threads: NonZero<usize>,
     pub compute_indices: bool,
     pub respect_gitignore: bool,
+    /// Where to keep persistent indexes of the search roots. When set, each
+    /// root is walked once per process and then served from an index kept
+    /// fresh by a file watcher.
+    pub index_dir: Option<PathBuf>,
 }
 
 impl Default for FileSearchOptions {
@@ -105,6 +120,7 @@ impl Default for FileSearchOptions {
             threads: NonZero::new(2).unwrap(),
             compute_indices: false,
             respect_gitignore: true,
+            index_dir: None,
         }
     }
 }
@@ -163,6 +179,7 @@ fn create_session_inner(
         threads,
         compute_indices,
         respect_gitignore,
+        index_dir,
     } = options;
 
     let Some(primary_search_directory) = search_directories.first() else {
@@ -191,6 +208,8 @@ fn create_session_inner(
         threads: threads.get(),
         compute_indices,
         respect_gitignore,
+        index_dir,
+        indexes: OnceLock::new(),
         cancelled: cancelled.clone(),
         shutdown: Arc::new(AtomicBool::new(false)),
         reporter,
@@ -266,6 +285,7 @@ pub async fn run_main<T: Reporter>(
             threads,
             compute_indices,
             respect_gitignore: true,
+            index_dir: None,
         },
         None,
     )?;
@@ -344,6 +364,10 @@ struct SessionInner {
     threads: usize,
     compute_indices: bool,
     respect_gitignore: bool,
+    index_dir: Option<PathBuf>,
+    /// The index of each search directory, if it has one. Set by the walker
+    /// before it injects any path.
+    indexes: OnceLock<Vec<Option<Arc<FileIndex>>>>,
     cancelled: Arc<AtomicBool>,
     shutdown: Arc<AtomicBool>,
     reporter: Arc<dyn SessionReporter>,
@@ -392,29 +416,16 @@ fn get_file_path<'a>(path: &'a Path, search_directories: &[PathBuf]) -> Option<(
     rel_path.to_str().map(|p| (root_idx, p))
 }
 
-fn walker_worker(
-    inner: Arc<SessionInner>,
-    override_matcher: Option<ignore::overrides::Override>,
-    injector: Injector<Arc<str>>,
-) {
-    let Some(first_root) = inner.search_directories.first() else {
-        let _ = inner.work_tx.send(WorkSignal::WalkComplete);
-        return;
-    };
-
-    let mut walk_builder = WalkBuilder::new(first_root);
-    for root in inner.search_directories.iter().skip(1) {
-        walk_builder.add(root);
-    }
+/// Applies the walk settings shared by searches and indexes.
+fn configure_walk_builder(walk_builder: &mut WalkBuilder, respect_gitignore: bool) {
     walk_builder
-        .threads(inner.threads)
         // Allow hidden entries.
         .hidden(false)
         // Follow symlinks to search their contents.
         .follow_links(true)
         // Don't require git to be present to apply to apply git-related ignore rules.
         .require_git(false);
-    if !inner.respect_gitignore {
+    if !respect_gitignore {
         walk_builder
             .git_ignore(false)
             .git_global(false)
@@ -422,6 +433,69 @@ fn walker_worker(
             .ignore(false)
             .parents(false);
     }
+}
+
+fn walker_worker(
+    inner: Arc<SessionInner>,
+    override_matcher: Option<ignore::overrides::Override>,
+    injector: Injector<Arc<str>>,
+) {
+    let search_directories = &inner.search_directories;
+    let push = |path: &Path| {
+        let Some(full_path) = path.to_str() else {
+            return;
+        };
+        if let Some((_, relative_path)) = get_file_path(path, search_directories) {
+            injector.push(Arc::from(full_path), |_, cols| {
+                cols[0] = Utf32String::from(relative_path);
+            });
+        }
+    };
+
+    let indexes: Vec<Option<Arc<FileIndex>>> = search_directories
+        .iter()
+        .map(|root| {
+            let index_dir = inner.index_dir.as_ref()?;
+            FileIndex::open(index_dir, root, inner.respect_gitignore)
+                .map_err(|err| {
+                    tracing::warn!("file search index for {} failed: {err}", root.display());
+                })
+                .ok()
+        })
+        .collect();
+    let _ = inner.indexes.set(indexes.clone());
+    for (root, index) in search_directories.iter().zip(&indexes) {
+        let Some(index) = index else {
+            continue;
+        };
+        index.for_each_file(|relative_path| {
+            let path = root.join(relative_path);
+            if !override_matcher
+                .as_ref()
+                .is_some_and(|matcher| is_excluded(matcher, root, &path))
+            {
+                push(&path);
+            }
+        });
+    }
+
+    let unindexed: Vec<&PathBuf> = search_directories
+        .iter()
+        .zip(&indexes)
+        .filter(|(_, index)| index.is_none())
+        .map(|(root, _)| root)
+        .collect();
+    let Some(first_root) = unindexed.first() else {
+        let _ = inner.work_tx.send(WorkSignal::WalkComplete);
+        return;
+    };
+
+    let mut walk_builder = WalkBuilder::new(first_root);
+    for root in unindexed.iter().skip(1) {
+        walk_builder.add(root);
+    }
+    walk_builder.threads(inner.threads);
+    configure_walk_builder(&mut walk_builder, inner.respect_gitignore);
     if let Some(override_matcher) = override_matcher {
         walk_builder.overrides(override_matcher);
     }
@@ -466,6 +540,19 @@ fn walker_worker(
     let _ = inner.work_tx.send(WorkSignal::WalkComplete);
 }
 
+/// Whether `path`, or one of its directories below `root`, matches an
+/// `exclude` pattern. The walker prunes excluded directories as it goes, so
+/// indexed paths have to check their parents too.
+fn is_excluded(matcher: &ignore::overrides::Override, root: &Path, path: &Path) -> bool {
+    if matcher.matched(path, false).is_ignore() {
+        return true;
+    }
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .any(|dir| matcher.matched(dir, true).is_ignore())
+}
+
 fn matcher_worker(
     inner: Arc<SessionInner>,
     work_rx: Receiver<WorkSignal>,
@@ -525,9 +612,20 @@ fn matcher_worker(
                 let status = nucleo.tick(TICK_TIMEOUT_MS);
                 if status.changed {
                     let snapshot = nucleo.snapshot();
-                    let limit = inner.limit.min(snapshot.matched_item_count() as usize);
+                    // Indexed roots carry recency and git boosts, so consider a
+                    // wider window of nucleo's best matches and re-rank it.
+                    let indexes = inner
+                        .indexes
+                        .get()
+                        .filter(|indexes| indexes.iter().any(Option::is_some));
+                    let window = if indexes.is_some() {
+                        inner.limit.saturating_mul(RERANK_FACTOR)
+                    } else {
+                        inner.limit
+                    };
+                    let limit = window.min(snapshot.matched_item_count() as usize);
                     let pattern = snapshot.pattern().column_pattern(0);
-                    let matches: Vec<_> = snapshot
+                    let mut matches: Vec<_> = snapshot
                         .matches()
                         .iter()
                         .take(limit)
@@ -545,14 +643,25 @@ fn matcher_worker(
                             } else {
                                 None
                             };
+                            let score = match indexes.and_then(|indexes| indexes[root_idx].as_ref()) {
+                                Some(index) => index.boosted_score(relative_path, match_.score),
+                                None => match_.score,
+                            };
                             Some(FileMatch {
-                                score: match_.score,
+                                score,
                                 path: PathBuf::from(relative_path),
                                 root: inner.search_directories[root_idx].clone(),
                                 indices,
                             })
                         })
                         .collect();
+                    if indexes.is_some() {
+                        matches.sort_by(cmp_by_score_desc_then_path_asc::<FileMatch, _, _>(
+                            |file_match| file_match.score,
+                            |file_match| file_match.path.to_str().unwrap_or_default(),
+                        ));
+                        matches.truncate(inner.limit);
+                    }
 
                     let snapshot = FileSearchSnapshot {
                         query: last_query.clone(),
@@ -895,6 +1004,7 @@ mod tests {
             threads: NonZero::new(2).unwrap(),
             compute_indices: false,
             respect_gitignore: true,
+            index_dir: None,
         };
         let results =
             run("file-000", vec![dir.path().to_path_buf()], options, None).expect("run ok");
@@ -909,6 +1019,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_with_index_dir_serves_matches_from_index() {
+        let dir = create_temp_tree(40);
+        let index_dir = tempfile::tempdir().unwrap();
+        let options = || FileSearchOptions {
+            limit: NonZero::new(5).unwrap(),
+            exclude: vec!["file-0001.txt".to_string()],
+            index_dir: Some(index_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        let results =
+            run("file-000", vec![dir.path().to_path_buf()], options(), None).expect("run ok");
+        assert_eq!(results.matches.len(), 5);
+        assert!(
+            !results
+                .matches
+                .iter()
+                .any(|m| m.path == Path::new("file-0001.txt"))
+        );
+        assert_eq!(fs::read_dir(index_dir.path()).unwrap().count(), 1);
+
+        let results =
+            run("file-0039", vec![dir.path().to_path_buf()], options(), None).expect("run ok");
+        assert_eq!(
+            results
+                .matches
+                .iter()
+                .map(|m| m.path.clone())
+                .collect::<Vec<_>>(),
+            vec![PathBuf::from("file-0039.txt")]
+        );
+    }
+
     #[test]
     fn cancel_exits_run() {
         let dir = create_temp_tree(200);
@@ -1,107 +1,15 @@
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::bail;
-use serde::Deserialize;
-use serde::Serialize;
 use std::net::IpAddr;
 use std::net::SocketAddr;
 use tracing::warn;
 use url::Url;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct NetworkProxyConfig {
-    #[serde(default)]
-    pub network_proxy: NetworkProxySettings,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct NetworkProxySettings {
-    #[serde(default)]
-    pub enabled: bool,
-    #[serde(default = "default_proxy_url")]
-    pub proxy_url: String,
-    #[serde(default = "default_admin_url")]
-    pub admin_url: String,
-    #[serde(default)]
-    pub enable_socks5: bool,
-    #[serde(default = "default_socks_url")]
-    pub socks_url: String,
-    #[serde(default)]
-    pub enable_socks5_udp: bool,
-    #[serde(default)]
-    pub allow_upstream_proxy: bool,
-    #[serde(default)]
-    pub dangerously_allow_non_loopback_proxy: bool,
-    #[serde(default)]
-    pub dangerously_allow_non_loopback_admin: bool,
-    #[serde(default)]
-    pub mode: NetworkMode,
-    #[serde(default)]
-    pub policy: NetworkPolicy,
-}
-
-impl Default for NetworkProxySettings {
-    fn default() -> Self {
-        Self {
-            enabled: false,
-            proxy_url: default_proxy_url(),
-            admin_url: default_admin_url(),
-            enable_socks5: false,
-            socks_url: default_socks_url(),
-            enable_socks5_udp: false,
-            allow_upstream_proxy: false,
-            dangerously_allow_non_loopback_proxy: false,
-            dangerously_allow_non_loopback_admin: false,
-            mode: NetworkMode::default(),
-            policy: NetworkPolicy::default(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct NetworkPolicy {
-    #[serde(default)]
-    pub allowed_domains: Vec<String>,
-    #[serde(default)]
-    pub denied_domains: Vec<String>,
-    #[serde(default)]
-    pub allow_unix_sockets: Vec<String>,
-    #[serde(default)]
-    pub allow_local_binding: bool,
-}
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "lowercase")]
-pub enum NetworkMode {
-    /// Limited (read-only) access: only GET/HEAD/OPTIONS are allowed for HTTP. HTTPS CONNECT is
-    /// blocked unless MITM is enabled so the proxy can enforce method policy on inner requests.
-    Limited,
-    /// Full network access: all HTTP methods are allowed, and HTTPS CONNECTs are tunneled without
-    /// MITM interception.
-    #[default]
-    Full,
-}
-
-impl NetworkMode {
-    pub fn allows_method(self, method: &str) -> bool {
-        match self {
-            Self::Full => true,
-            Self::Limited => matches!(method, "GET" | "HEAD" | "OPTIONS"),
-        }
-    }
-}
-
-fn default_proxy_url() -> String {
-    "http://127.0.0.1:3128".to_string()
-}
-
-fn default_admin_url() -> String {
-    "http://127.0.0.1:8080".to_string()
-}
-
-fn default_socks_url() -> String {
-    "http://127.0.0.1:8081".to_string()
-}
+pub use codex_core::config::network_proxy::NetworkMode;
+pub use codex_core::config::network_proxy::NetworkPolicy;
+pub use codex_core::config::network_proxy::NetworkProxyConfig;
+pub use codex_core::config::network_proxy::NetworkProxySettings;
 
 /// Clamp non-loopback bind addresses to loopback unless explicitly allowed.
 fn clamp_non_loopback(addr: SocketAddr, allow_non_loopback: bool, name: &str) -> SocketAddr {
@@ -33,9 +33,11 @@ use std::collections::HashMap;
 use std::env;
 use std::error::Error;
 use std::sync::OnceLock;
+use tracing::Span;
 use tracing::debug;
 use tracing::level_filters::LevelFilter;
 use tracing::warn;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use tracing_subscriber::Layer;
 use tracing_subscriber::registry::LookupSpan;
 
@@ -176,6 +178,24 @@ pub(crate) fn traceparent_context_from_env() -> Option<Context> {
         .clone()
 }
 
+/// `TRACEPARENT` (and `TRACESTATE`, when set) for the current span, so a
+/// spawned process can continue the trace. Empty when tracing is disabled.
+pub fn current_span_trace_env() -> HashMap<String, String> {
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&Span::current().context(), &mut headers);
+    });
+    headers
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .filter_map(|(name, value)| match name.as_str() {
+            "traceparent" => Some((TRACEPARENT_ENV_VAR.to_string(), value)),
+            "tracestate" => Some((TRACESTATE_ENV_VAR.to_string(), value)),
+            _ => None,
+        })
+        .collect()
+}
+
 fn attach_traceparent_context() {
     TRACEPARENT_GUARD.with(|guard| {
         let mut guard = guard.borrow_mut();
@@ -398,4 +418,33 @@ mod tests {
     fn invalid_traceparent_returns_none() {
         assert!(extract_traceparent_context("not-a-traceparent".to_string(), None).is_none());
     }
+
+    #[test]
+    fn current_span_trace_env_continues_current_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+        use tracing_subscriber::util::SubscriberInitExt;
+
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = subscriber.set_default();
+
+        let span = tracing::trace_span!("exec");
+        let _entered = span.enter();
+        let span_context = span.context().span().span_context().clone();
+
+        let env = current_span_trace_env();
+        let context = extract_traceparent_context(env[TRACEPARENT_ENV_VAR].clone(), None)
+            .expect("trace context");
+        assert_eq!(
+            context.span().span_context().trace_id(),
+            span_context.trace_id()
+        );
+        assert_eq!(
+            context.span().span_context().span_id(),
+            span_context.span_id()
+        );
+        assert!(!env.contains_key(TRACESTATE_ENV_VAR));
+    }
 }
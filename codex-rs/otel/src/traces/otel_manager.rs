@@ -27,6 +27,8 @@ use codex_protocol::protocol::SessionSource;
 use codex_protocol::user_input::UserInput;
 use eventsource_stream::Event as StreamEvent;
 use eventsource_stream::EventStreamError as StreamError;
+use opentelemetry::Context;
+use opentelemetry::trace::TraceContextExt;
 use reqwest::Error;
 use reqwest::Response;
 use std::borrow::Cow;
@@ -86,6 +88,17 @@ impl OtelManager {
         }
     }
 
+    /// Starts a new trace at `span` (continuing `TRACEPARENT` when set), linked
+    /// to the span that is current when this is called.
+    pub fn start_trace(&self, span: &Span) {
+        let _ = span.set_parent(Context::new());
+        self.apply_traceparent_parent(span);
+        let caller = Span::current().context().span().span_context().clone();
+        if caller.is_valid() {
+            span.add_link(caller);
+        }
+    }
+
     pub fn record_responses(&self, handle_responses_span: &Span, event: &ResponseEvent) {
         handle_responses_span.record("otel.name", OtelManager::responses_type(event));
 
@@ -0,0 +1,71 @@
+use codex_otel::OtelManager;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::SessionSource;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::trace::SpanData;
+use pretty_assertions::assert_eq;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+fn find_span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("span {name} missing"))
+}
+
+// Each turn gets its own trace, linked to the span that started it.
+#[test]
+fn start_trace_roots_span_and_links_caller() {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let _guard = subscriber.set_default();
+    let manager = OtelManager::new(
+        ThreadId::new(),
+        "gpt-5.1",
+        "gpt-5.1",
+        None,
+        None,
+        None,
+        false,
+        "tty".to_string(),
+        SessionSource::Cli,
+    );
+
+    {
+        let session = tracing::info_span!("session_loop");
+        let _session = session.enter();
+        let turn = tracing::info_span!("turn");
+        manager.start_trace(&turn);
+        let _turn = turn.enter();
+        tracing::trace_span!("approval_wait").in_scope(|| {});
+    }
+
+    let spans = exporter.get_finished_spans().expect("finished spans");
+    let session = find_span(&spans, "session_loop");
+    let turn = find_span(&spans, "turn");
+    let approval = find_span(&spans, "approval_wait");
+
+    assert_ne!(
+        turn.span_context.trace_id(),
+        session.span_context.trace_id()
+    );
+    assert_eq!(
+        turn.links
+            .iter()
+            .map(|link| link.span_context.span_id())
+            .collect::<Vec<_>>(),
+        vec![session.span_context.span_id()]
+    );
+    assert_eq!(
+        approval.span_context.trace_id(),
+        turn.span_context.trace_id()
+    );
+    assert_eq!(approval.parent_span_id, turn.span_context.span_id());
+}
@@ -112,6 +112,23 @@ pub enum WebSearchMode {
     Live,
 }
 
+/// How conversation history is compacted once it nears the context window.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS, Default,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CompactionStrategy {
+    /// Replace the history with a model-written summary.
+    #[default]
+    Summary,
+    /// Replace the oldest tool outputs with short stubs until the history fits,
+    /// summarizing only when that is not enough.
+    DropToolOutputs,
+    /// Summarize everything except the most recent turns, which are kept verbatim.
+    Hybrid,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Display, JsonSchema, TS)]
 #[serde(rename_all = "lowercase")]
 #[strum(serialize_all = "lowercase")]
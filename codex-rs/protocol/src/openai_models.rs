@@ -212,6 +212,14 @@ const fn default_effective_context_window_percent() -> i64 {
     95
 }
 
+/// BPE encoding used to count tokens for a model.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, TS, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenizerEncoding {
+    O200kBase,
+    Cl100kBase,
+}
+
 /// Model metadata returned by the Codex backend `/models` endpoint.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, TS, JsonSchema)]
 pub struct ModelInfo {
@@ -249,6 +257,10 @@ pub struct ModelInfo {
     /// Input modalities accepted by the backend for this model.
     #[serde(default = "default_input_modalities")]
     pub input_modalities: Vec<InputModality>,
+    /// Encoding used for token estimates. When omitted, core falls back to a
+    /// bytes-per-token heuristic.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<TokenizerEncoding>,
 }
 
 impl ModelInfo {
@@ -505,6 +517,7 @@ mod tests {
             effective_context_window_percent: 95,
             experimental_supported_tools: vec![],
             input_modalities: default_input_modalities(),
+            tokenizer: None,
         }
     }
 
@@ -14,6 +14,7 @@ use std::time::Duration;
 use crate::ThreadId;
 use crate::approvals::ElicitationRequestEvent;
 use crate::config_types::CollaborationMode;
+use crate::config_types::CompactionStrategy;
 use crate::config_types::ModeKind;
 use crate::config_types::Personality;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
@@ -278,11 +279,26 @@ pub enum Op {
         is_preload: bool,
     },
 
+    /// Request the approvals remembered for the remainder of this session.
+    /// Reply is delivered via `EventMsg::ListSessionApprovalsResponse`.
+    ListSessionApprovals,
+
+    /// Forget a remembered session approval so matching requests prompt again.
+    /// Reply is an updated `EventMsg::ListSessionApprovalsResponse`.
+    RevokeSessionApproval {
+        /// Id of the entry, as reported by `ListSessionApprovalsResponse`.
+        id: String,
+    },
+
     /// Request the agent to summarize the current conversation context.
     /// The agent will use its existing context (either conversation history or previous response id)
     /// to generate a summary which will be returned as an AgentMessage event.
     Compact,
 
+    /// Report what `Op::Compact` would keep, summarize and drop without
+    /// changing the history. Reply is delivered via `EventMsg::CompactionPreview`.
+    PreviewCompact,
+
     /// Set a user-facing thread name in the persisted rollout metadata.
     /// This is a local-only operation handled by codex-core; it does not
     /// involve the model.
@@ -729,6 +745,10 @@ pub enum EventMsg {
     /// Optional means unknown — UIs should not display when `None`.
     TokenCount(TokenCountEvent),
 
+    /// Usage of the session's sub-agent tree, sent whenever a sub-agent
+    /// reports token usage.
+    AgentTreeUsage(AgentTreeUsageEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -817,6 +837,9 @@ pub enum EventMsg {
     /// Notification that a patch application has finished.
     PatchApplyEnd(PatchApplyEndEvent),
 
+    /// A lifecycle hook configured under `[hooks]` finished running.
+    HookCompleted(HookCompletedEvent),
+
     TurnDiff(TurnDiffEvent),
 
     /// Response to GetHistoryEntryRequest.
@@ -834,6 +857,12 @@ pub enum EventMsg {
     /// List of remote skills available to the agent.
     ListRemoteSkillsResponse(ListRemoteSkillsResponseEvent),
 
+    /// Approvals remembered for the remainder of the session.
+    ListSessionApprovalsResponse(ListSessionApprovalsResponseEvent),
+
+    /// What compaction would do to the current history.
+    CompactionPreview(CompactionPreviewEvent),
+
     /// Remote skill downloaded to local cache.
     RemoteSkillDownloaded(RemoteSkillDownloadedEvent),
 
@@ -1128,6 +1157,43 @@ pub struct WarningEvent {
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ContextCompactedEvent;
 
+/// What compaction would do with one history item.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum CompactionPreviewAction {
+    /// Kept verbatim.
+    Keep,
+    /// Folded into the summary.
+    Summarize,
+    /// Replaced with a short stub.
+    Drop,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct CompactionPreviewItem {
+    /// Short human-readable description, e.g. `user: fix the build`.
+    pub description: String,
+    /// Estimated size of the item in tokens.
+    pub tokens: i64,
+    pub action: CompactionPreviewAction,
+}
+
+/// Response payload for `Op::PreviewCompact`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct CompactionPreviewEvent {
+    /// Strategy that would run. `drop_tool_outputs` reports `summary` when
+    /// dropping outputs alone would not bring the history under the limit.
+    pub strategy: CompactionStrategy,
+    /// Model that would write the summary, when the strategy calls one.
+    pub summary_model: Option<String>,
+    /// History items, oldest first.
+    pub items: Vec<CompactionPreviewItem>,
+    /// Estimated size of the history before compacting.
+    pub tokens_before: i64,
+    /// Estimated size afterwards, when it can be known without calling the model.
+    pub estimated_tokens_after: Option<i64>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnCompleteEvent {
     pub last_agent_message: Option<String>,
@@ -1693,6 +1759,30 @@ pub enum RolloutItem {
     Compacted(CompactedItem),
     TurnContext(TurnContextItem),
     EventMsg(EventMsg),
+    Checkpoint(CheckpointItem),
+    ToolOutputsPruned(ToolOutputsPrunedItem),
+}
+
+/// Marks the history recorded before it as safe to replay when a `codex exec`
+/// run is continued with `codex exec resume --continue`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct CheckpointItem {
+    pub turn_id: String,
+    /// Tool call whose output was just recorded, or `None` once the turn finished.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+}
+
+/// Tool outputs in the history that were replaced with short stubs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct ToolOutputsPrunedItem {
+    pub outputs: Vec<PrunedToolOutput>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, TS)]
+pub struct PrunedToolOutput {
+    pub call_id: String,
+    pub stub: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, TS)]
@@ -1794,6 +1884,17 @@ pub enum ReviewTarget {
         title: Option<String>,
     },
 
+    /// Review the changes between two commits, as shown by `git diff from..to`.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    CommitRange { from: String, to: String },
+
+    /// Review the changes in a patch file, which need not be applied to the
+    /// working tree. Relative paths are resolved against the session cwd.
+    #[serde(rename_all = "camelCase")]
+    #[ts(rename_all = "camelCase")]
+    PatchFile { path: PathBuf },
+
     /// Arbitrary instructions provided by the user.
     #[serde(rename_all = "camelCase")]
     #[ts(rename_all = "camelCase")]
@@ -1928,6 +2029,42 @@ pub struct ExecCommandEndEvent {
     pub formatted_output: String,
 }
 
+/// Point in a session at which a lifecycle hook runs.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HookEventName {
+    SessionStart,
+    UserPromptSubmit,
+    PreToolUse,
+    PostToolUse,
+    TurnComplete,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct HookCompletedEvent {
+    pub hook: HookEventName,
+    /// The configured hook command.
+    pub command: Vec<String>,
+    /// Tool the hook ran for (`pre_tool_use` and `post_tool_use` only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tool_name: Option<String>,
+    /// Exit code, or `None` if the hook could not be spawned or timed out.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    #[ts(type = "string")]
+    pub duration: Duration,
+    pub timed_out: bool,
+    /// Whether the hook blocked the prompt or tool call.
+    pub blocked: bool,
+    /// Why the hook blocked, when it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ViewImageToolCallEvent {
     /// Identifier for the originating tool call.
@@ -2049,6 +2186,37 @@ pub struct PatchApplyEndEvent {
     /// The changes that were applied (mirrors PatchApplyBeginEvent::changes).
     #[serde(default)]
     pub changes: HashMap<PathBuf, FileChange>,
+    /// Results of the `[post_edit]` commands run against the edited files.
+    #[serde(default)]
+    pub post_edit: Vec<PostEditCommandResult>,
+    /// What the patch left on disk. Patches are applied all or nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub outcome: Option<PatchApplyOutcome>,
+}
+
+/// What an `apply_patch` call left on disk.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchApplyOutcome {
+    /// Every change was written.
+    Committed,
+    /// The patch failed before anything was written.
+    NotApplied,
+    /// Writing failed part way and every file was restored.
+    RolledBack,
+    /// Writing failed part way and some files could not be restored.
+    RollbackFailed,
+}
+
+/// Output of one `[post_edit]` formatter or linter run after a patch.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct PostEditCommandResult {
+    /// The command that was run, after `{files}` expansion.
+    pub command: Vec<String>,
+    pub exit_code: i32,
+    /// Combined stdout and stderr.
+    pub output: String,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
@@ -2155,6 +2323,37 @@ pub struct ListRemoteSkillsResponseEvent {
     pub skills: Vec<RemoteSkillSummary>,
 }
 
+/// What a remembered "approve for session" decision covers.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[ts(tag = "type")]
+pub enum SessionApprovalKind {
+    /// An exact command line run from `cwd`.
+    Command { command: Vec<String>, cwd: PathBuf },
+    /// Patches touching `path`.
+    PatchPath { path: PathBuf },
+    /// Commands starting with `prefix`, saved as an execpolicy rule.
+    CommandPrefix { prefix: Vec<String> },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct SessionApprovalEntry {
+    /// Stable id used to revoke the entry via `Op::RevokeSessionApproval`.
+    pub id: String,
+    pub kind: SessionApprovalKind,
+    /// Unix timestamp (in seconds) when the approval was granted.
+    pub approved_at: i64,
+    /// Unix timestamp (in seconds) after which the approval no longer applies,
+    /// when `session_approval_ttl_secs` is configured.
+    pub expires_at: Option<i64>,
+}
+
+/// Response payload for `Op::ListSessionApprovals` and `Op::RevokeSessionApproval`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ListSessionApprovalsResponseEvent {
+    pub approvals: Vec<SessionApprovalEntry>,
+}
+
 /// Response payload for `Op::DownloadRemoteSkill`.
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct RemoteSkillDownloadedEvent {
@@ -2348,6 +2547,17 @@ impl ReviewDecision {
 pub enum FileChange {
     Add {
         content: String,
+        /// Permission bits the file is created with, e.g. `0o755`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        mode: Option<u32>,
+    },
+    /// A binary file, whose contents are not included.
+    AddBinary {
+        size: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        mode: Option<u32>,
     },
     Delete {
         content: String,
@@ -2355,6 +2565,10 @@ pub enum FileChange {
     Update {
         unified_diff: String,
         move_path: Option<PathBuf>,
+        /// New permission bits, if the patch changes them.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[ts(optional)]
+        mode: Option<u32>,
     },
 }
 
@@ -2451,6 +2665,48 @@ pub struct CollabWaitingEndEvent {
     pub call_id: String,
     /// Last known status of the receiver agents reported to the sender agent.
     pub statuses: HashMap<ThreadId, AgentStatus>,
+    /// Resources used so far by every sub-agent in the sender's agent tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tree_usage: Option<AgentTreeUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct AgentTreeUsageEvent {
+    /// Resources used so far by every sub-agent in the session's agent tree.
+    pub usage: AgentTreeUsage,
+}
+
+/// Usage shared by all sub-agents spawned under one root session, measured
+/// against the `[agents]` budgets.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+pub struct AgentTreeUsage {
+    /// Tokens used by all sub-agents in the tree.
+    pub total_tokens: i64,
+    /// Seconds since the first sub-agent in the tree was spawned.
+    pub elapsed_secs: u64,
+    /// Configured token budget for the tree, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub token_budget: Option<i64>,
+    /// Configured wall-clock budget for the tree in seconds, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub wall_clock_budget_secs: Option<u64>,
+}
+
+impl fmt::Display for AgentTreeUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} tokens", self.total_tokens)?;
+        if let Some(budget) = self.token_budget {
+            write!(f, " of {budget}")?;
+        }
+        write!(f, ", {}s", self.elapsed_secs)?;
+        if let Some(budget) = self.wall_clock_budget_secs {
+            write!(f, " of {budget}s")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
@@ -11,6 +11,7 @@ use anyhow::anyhow;
 use futures::FutureExt;
 use futures::future::BoxFuture;
 use reqwest::header::HeaderMap;
+use rmcp::model::CallToolRequest;
 use rmcp::model::CallToolRequestParam;
 use rmcp::model::CallToolResult;
 use rmcp::model::ClientNotification;
@@ -33,6 +34,7 @@ use rmcp::model::ServerResult;
 use rmcp::model::Tool;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
+use rmcp::service::ServiceError;
 use rmcp::service::{self};
 use rmcp::transport::StreamableHttpClientTransport;
 use rmcp::transport::auth::AuthClient;
@@ -54,6 +56,8 @@ use crate::oauth::OAuthCredentialsStoreMode;
 use crate::oauth::OAuthPersistor;
 use crate::oauth::StoredOAuthTokens;
 use crate::program_resolver;
+use crate::trace_context::TraceContextHttpClient;
+use crate::trace_context::current_trace_meta;
 use crate::utils::apply_default_headers;
 use crate::utils::build_default_headers;
 use crate::utils::create_env_for_mcp_server;
@@ -62,10 +66,10 @@ use crate::utils::run_with_timeout;
 enum PendingTransport {
     ChildProcess(TokioChildProcess),
     StreamableHttp {
-        transport: StreamableHttpClientTransport<reqwest::Client>,
+        transport: StreamableHttpClientTransport<TraceContextHttpClient>,
     },
     StreamableHttpWithOAuth {
-        transport: StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
+        transport: StreamableHttpClientTransport<AuthClient<TraceContextHttpClient>>,
         oauth_persistor: OAuthPersistor,
     },
 }
@@ -206,7 +210,10 @@ impl RmcpClient {
             let http_client =
                 apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
 
-            let transport = StreamableHttpClientTransport::with_client(http_client, http_config);
+            let transport = StreamableHttpClientTransport::with_client(
+                TraceContextHttpClient::new(http_client),
+                http_config,
+            );
             PendingTransport::StreamableHttp { transport }
         };
         Ok(Self {
@@ -395,11 +402,23 @@ impl RmcpClient {
             }
             None => None,
         };
-        let rmcp_params = CallToolRequestParam {
+        let mut request = CallToolRequest::new(CallToolRequestParam {
             name: name.into(),
             arguments,
+        });
+        // Let the server join the caller's trace.
+        if let Some(meta) = current_trace_meta() {
+            request.extensions.insert(meta);
+        }
+        let fut = async {
+            match service
+                .send_request(ClientRequest::CallToolRequest(request))
+                .await?
+            {
+                ServerResult::CallToolResult(result) => Ok(result),
+                _ => Err(ServiceError::UnexpectedResponse),
+            }
         };
-        let fut = service.call_tool(rmcp_params);
         let result = run_with_timeout(fut, timeout, "tools/call").await?;
         self.persist_oauth_tokens().await;
         Ok(result)
@@ -480,7 +499,7 @@ async fn create_oauth_transport_and_runtime(
     credentials_store: OAuthCredentialsStoreMode,
     default_headers: HeaderMap,
 ) -> Result<(
-    StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
+    StreamableHttpClientTransport<AuthClient<TraceContextHttpClient>>,
     OAuthPersistor,
 )> {
     let http_client =
@@ -502,7 +521,7 @@ async fn create_oauth_transport_and_runtime(
         }
     };
 
-    let auth_client = AuthClient::new(http_client, manager);
+    let auth_client = AuthClient::new(TraceContextHttpClient::new(http_client), manager);
     let auth_manager = auth_client.auth_manager.clone();
 
     let transport = StreamableHttpClientTransport::with_client(
@@ -0,0 +1,354 @@
+//! W3C trace context propagation for MCP requests.
+//!
+//! Tool calls carry the caller's `traceparent`/`tracestate` in the request's
+//! `_meta`, so any MCP server can join the trace. Requests are sent from the
+//! transport's own task, so for streamable HTTP servers
+//! [`TraceContextHttpClient`] copies those fields into the matching HTTP
+//! headers.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use opentelemetry::global;
+use reqwest::header::ACCEPT;
+use reqwest::header::CONTENT_TYPE;
+use reqwest::header::WWW_AUTHENTICATE;
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::model::GetMeta;
+use rmcp::model::Meta;
+use rmcp::model::ServerJsonRpcMessage;
+use rmcp::transport::common::http_header::EVENT_STREAM_MIME_TYPE;
+use rmcp::transport::common::http_header::HEADER_SESSION_ID;
+use rmcp::transport::common::http_header::JSON_MIME_TYPE;
+use rmcp::transport::streamable_http_client::AuthRequiredError;
+use rmcp::transport::streamable_http_client::SseError;
+use rmcp::transport::streamable_http_client::StreamableHttpClient;
+use rmcp::transport::streamable_http_client::StreamableHttpError;
+use rmcp::transport::streamable_http_client::StreamableHttpPostResponse;
+use serde_json::Value;
+use sse_stream::Sse;
+use sse_stream::SseStream;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// `_meta` keys and HTTP header names, as defined by W3C Trace Context.
+const TRACE_CONTEXT_FIELDS: [&str; 2] = ["traceparent", "tracestate"];
+
+/// Trace context of the current span as request `_meta`, or `None` when
+/// tracing is not enabled.
+pub(crate) fn current_trace_meta() -> Option<Meta> {
+    let mut fields = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&Span::current().context(), &mut fields);
+    });
+    let mut meta = Meta::new();
+    for name in TRACE_CONTEXT_FIELDS {
+        if let Some(value) = fields.remove(name)
+            && !value.is_empty()
+        {
+            meta.0.insert(name.to_string(), Value::String(value));
+        }
+    }
+    (!meta.0.is_empty()).then_some(meta)
+}
+
+fn trace_context_headers(message: &ClientJsonRpcMessage) -> Vec<(&'static str, String)> {
+    let ClientJsonRpcMessage::Request(request) = message else {
+        return Vec::new();
+    };
+    let meta = request.request.get_meta();
+    TRACE_CONTEXT_FIELDS
+        .into_iter()
+        .filter_map(|name| Some((name, meta.0.get(name)?.as_str()?.to_string())))
+        .collect()
+}
+
+/// Streamable HTTP client that sends each request's `_meta` trace context as
+/// `traceparent`/`tracestate` headers.
+///
+/// rmcp has no hook for per-request headers, so `post_message` is a copy of
+/// `impl StreamableHttpClient for reqwest::Client` from rmcp 0.12.0
+/// (`transport/common/reqwest/streamable_http_client.rs`) with the trace
+/// headers added. `post_message_matches_rmcp` fails when an rmcp upgrade
+/// changes that implementation; copy the new version over when it does.
+#[derive(Clone, Debug)]
+pub(crate) struct TraceContextHttpClient {
+    inner: reqwest::Client,
+}
+
+impl TraceContextHttpClient {
+    pub(crate) fn new(inner: reqwest::Client) -> Self {
+        Self { inner }
+    }
+}
+
+impl StreamableHttpClient for TraceContextHttpClient {
+    type Error = reqwest::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        auth_token: Option<String>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        let mut request = self
+            .inner
+            .post(uri.as_ref())
+            .header(ACCEPT, [EVENT_STREAM_MIME_TYPE, JSON_MIME_TYPE].join(", "));
+        for (name, value) in trace_context_headers(&message) {
+            request = request.header(name, value);
+        }
+        if let Some(auth_header) = auth_token {
+            request = request.bearer_auth(auth_header);
+        }
+        if let Some(session_id) = session_id {
+            request = request.header(HEADER_SESSION_ID, session_id.as_ref());
+        }
+        let response = request.json(&message).send().await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED
+            && let Some(header) = response.headers().get(WWW_AUTHENTICATE)
+        {
+            let header = header.to_str().map_err(|_| {
+                StreamableHttpError::UnexpectedServerResponse(Cow::from(
+                    "invalid www-authenticate header value",
+                ))
+            })?;
+            return Err(StreamableHttpError::AuthRequired(AuthRequiredError {
+                www_authenticate_header: header.to_string(),
+            }));
+        }
+        let status = response.status();
+        let response = response.error_for_status()?;
+        if matches!(
+            status,
+            reqwest::StatusCode::ACCEPTED | reqwest::StatusCode::NO_CONTENT
+        ) {
+            return Ok(StreamableHttpPostResponse::Accepted);
+        }
+        let content_type = response.headers().get(CONTENT_TYPE).cloned();
+        let session_id = response
+            .headers()
+            .get(HEADER_SESSION_ID)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        match content_type {
+            Some(content_type)
+                if content_type
+                    .as_bytes()
+                    .starts_with(EVENT_STREAM_MIME_TYPE.as_bytes()) =>
+            {
+                let event_stream = SseStream::from_bytes_stream(response.bytes_stream()).boxed();
+                Ok(StreamableHttpPostResponse::Sse(event_stream, session_id))
+            }
+            Some(content_type)
+                if content_type
+                    .as_bytes()
+                    .starts_with(JSON_MIME_TYPE.as_bytes()) =>
+            {
+                let message: ServerJsonRpcMessage = response.json().await?;
+                Ok(StreamableHttpPostResponse::Json(message, session_id))
+            }
+            content_type => Err(StreamableHttpError::UnexpectedContentType(
+                content_type
+                    .map(|content_type| String::from_utf8_lossy(content_type.as_bytes()).into()),
+            )),
+        }
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        auth_token: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        self.inner.delete_session(uri, session_id, auth_token).await
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        auth_token: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        self.inner
+            .get_stream(uri, session_id, last_event_id, auth_token)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use pretty_assertions::assert_eq;
+    use rmcp::model::CallToolRequest;
+    use rmcp::model::CallToolRequestParam;
+    use rmcp::model::ClientRequest;
+    use rmcp::model::JsonRpcRequest;
+    use rmcp::model::JsonRpcVersion2_0;
+    use rmcp::model::NumberOrString;
+    use tracing::trace_span;
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    #[test]
+    fn trace_meta_is_sent_as_headers() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = subscriber.set_default();
+
+        let span = trace_span!("mcp_call");
+        let _entered = span.enter();
+        let span_context = span.context().span().span_context().clone();
+
+        let meta = current_trace_meta().expect("trace meta");
+        let traceparent = format!(
+            "00-{}-{}-01",
+            span_context.trace_id(),
+            span_context.span_id()
+        );
+        assert_eq!(
+            meta.0.get("traceparent"),
+            Some(&Value::String(traceparent.clone()))
+        );
+
+        let mut request = CallToolRequest::new(CallToolRequestParam {
+            name: "echo".into(),
+            arguments: None,
+        });
+        request.extensions.insert(meta);
+        let message = ClientJsonRpcMessage::Request(JsonRpcRequest {
+            jsonrpc: JsonRpcVersion2_0,
+            id: NumberOrString::Number(1),
+            request: ClientRequest::CallToolRequest(request),
+        });
+        assert_eq!(
+            trace_context_headers(&message),
+            vec![("traceparent", traceparent)]
+        );
+    }
+
+    #[test]
+    fn no_trace_meta_without_a_span() {
+        assert!(current_trace_meta().is_none());
+    }
+
+    fn summarize_post(
+        result: Result<StreamableHttpPostResponse, StreamableHttpError<reqwest::Error>>,
+    ) -> String {
+        match result {
+            Ok(StreamableHttpPostResponse::Accepted) => "accepted".to_string(),
+            Ok(StreamableHttpPostResponse::Json(message, session_id)) => format!(
+                "json {} {session_id:?}",
+                serde_json::to_string(&message).unwrap()
+            ),
+            Ok(StreamableHttpPostResponse::Sse(_, session_id)) => format!("sse {session_id:?}"),
+            Err(err) => format!("error {err}"),
+        }
+    }
+
+    fn summarize_request(request: &wiremock::Request) -> (String, Vec<(String, String)>, String) {
+        let mut headers: Vec<(String, String)> = request
+            .headers
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        headers.sort();
+        (
+            format!("{} {}", request.method, request.url),
+            headers,
+            String::from_utf8_lossy(&request.body).into_owned(),
+        )
+    }
+
+    /// Guards the copy of rmcp's `post_message`: without trace context, both
+    /// clients must send the same request and read every kind of response
+    /// the same way.
+    #[tokio::test]
+    async fn post_message_matches_rmcp() {
+        use wiremock::Mock;
+        use wiremock::MockServer;
+        use wiremock::ResponseTemplate;
+        use wiremock::matchers::method;
+
+        let json_reply = r#"{"jsonrpc":"2.0","id":1,"result":{}}"#;
+        let responses = [
+            ResponseTemplate::new(200)
+                .insert_header(CONTENT_TYPE.as_str(), JSON_MIME_TYPE)
+                .insert_header(HEADER_SESSION_ID, "session-1")
+                .set_body_string(json_reply),
+            ResponseTemplate::new(200)
+                .insert_header(CONTENT_TYPE.as_str(), EVENT_STREAM_MIME_TYPE)
+                .set_body_string("data: {}\n\n"),
+            ResponseTemplate::new(202),
+            ResponseTemplate::new(401)
+                .insert_header(WWW_AUTHENTICATE.as_str(), "Bearer resource_metadata=\"x\""),
+            ResponseTemplate::new(401),
+            ResponseTemplate::new(500),
+            ResponseTemplate::new(200)
+                .insert_header(CONTENT_TYPE.as_str(), "text/plain")
+                .set_body_string("hello"),
+        ];
+        let message = ClientJsonRpcMessage::Request(JsonRpcRequest {
+            jsonrpc: JsonRpcVersion2_0,
+            id: NumberOrString::Number(1),
+            request: ClientRequest::CallToolRequest(CallToolRequest::new(CallToolRequestParam {
+                name: "echo".into(),
+                arguments: None,
+            })),
+        });
+
+        for response in responses {
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+            let uri: Arc<str> = format!("{}/mcp", server.uri()).into();
+            let post = |client: reqwest::Client, ours: bool| {
+                let uri = uri.clone();
+                let message = message.clone();
+                async move {
+                    let session_id = Some(Arc::from("session-0"));
+                    let auth_token = Some("token".to_string());
+                    let result = if ours {
+                        TraceContextHttpClient::new(client)
+                            .post_message(uri, message, session_id, auth_token)
+                            .await
+                    } else {
+                        client
+                            .post_message(uri, message, session_id, auth_token)
+                            .await
+                    };
+                    summarize_post(result)
+                }
+            };
+
+            let expected = post(reqwest::Client::new(), false).await;
+            let actual = post(reqwest::Client::new(), true).await;
+            assert_eq!(actual, expected);
+
+            let requests = server.received_requests().await.unwrap();
+            assert_eq!(requests.len(), 2);
+            assert_eq!(
+                summarize_request(&requests[1]),
+                summarize_request(&requests[0])
+            );
+        }
+    }
+}
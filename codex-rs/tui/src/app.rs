@@ -29,11 +29,14 @@ use crate::pager_overlay::Overlay;
 use crate::render::highlight::highlight_bash_to_lines;
 use crate::render::renderable::Renderable;
 use crate::resume_picker::SessionSelection;
+use crate::theme;
+use crate::theme::Theme;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crate::update_action::UpdateAction;
 use codex_ansi_escape::ansi_escape_line;
 use codex_app_server_protocol::ConfigLayerSource;
+use codex_core::AgentWorktreeAction;
 use codex_core::AuthManager;
 use codex_core::CodexAuth;
 use codex_core::ThreadManager;
@@ -114,6 +117,8 @@ pub struct AppExitInfo {
     pub thread_id: Option<ThreadId>,
     pub thread_name: Option<String>,
     pub update_action: Option<UpdateAction>,
+    /// Sub-agent worktrees left on disk because they hold unmerged work.
+    pub kept_worktrees: Vec<PathBuf>,
     pub exit_reason: ExitReason,
 }
 
@@ -124,6 +129,7 @@ impl AppExitInfo {
             thread_id: None,
             thread_name: None,
             update_action: None,
+            kept_worktrees: Vec::new(),
             exit_reason: ExitReason::Fatal(message.into()),
         }
     }
@@ -158,6 +164,22 @@ fn session_summary(
     })
 }
 
+/// Transcript lines pointing at sub-agent worktrees that were kept because
+/// they hold unmerged work.
+fn kept_worktree_lines(paths: &[PathBuf]) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line<'static>> = vec![
+        "Kept agent worktrees with unmerged changes:"
+            .to_string()
+            .into(),
+    ];
+    lines.extend(
+        paths
+            .iter()
+            .map(|path| Line::from(format!("  {}", path.display()).dim())),
+    );
+    lines
+}
+
 fn errors_for_cwd(cwd: &Path, response: &ListSkillsResponseEvent) -> Vec<SkillErrorInfo> {
     response
         .skills
@@ -499,6 +521,7 @@ async fn handle_model_migration_prompt_if_needed(
                     thread_id: None,
                     thread_name: None,
                     update_action: None,
+                    kept_worktrees: Vec::new(),
                     exit_reason: ExitReason::UserRequested,
                 });
             }
@@ -767,18 +790,13 @@ impl App {
         Ok(())
     }
 
-    fn open_agent_picker(&mut self) {
-        if self.thread_event_channels.is_empty() {
-            self.chat_widget
-                .add_info_message("No agents available yet.".to_string(), None);
-            return;
-        }
-
+    async fn open_agent_picker(&mut self) {
+        let worktree_threads = self.server.agent_worktree_threads().await;
         let mut thread_ids: Vec<ThreadId> = self.thread_event_channels.keys().cloned().collect();
         thread_ids.sort_by_key(ToString::to_string);
 
         let mut initial_selected_idx = None;
-        let items: Vec<SelectionItem> = thread_ids
+        let mut items: Vec<SelectionItem> = thread_ids
             .iter()
             .enumerate()
             .map(|(idx, thread_id)| {
@@ -798,10 +816,37 @@ impl App {
                 }
             })
             .collect();
+        items.extend(
+            worktree_threads
+                .into_iter()
+                .map(|(thread_id, path)| SelectionItem {
+                    name: format!("Review changes from {thread_id}"),
+                    description: Some(path.display().to_string()),
+                    actions: vec![Box::new(move |tx| {
+                        tx.send(AppEvent::ReviewAgentWorktree(thread_id));
+                    })],
+                    dismiss_on_select: true,
+                    search_value: Some(thread_id.to_string()),
+                    ..Default::default()
+                }),
+        );
+        items.extend(self.config.agent_roles.iter().map(|(name, role)| {
+            let role_name = name.clone();
+            SelectionItem {
+                name: format!("New {name} agent"),
+                description: role.description.lines().next().map(str::to_string),
+                actions: vec![Box::new(move |tx| {
+                    tx.send(AppEvent::StartAgentWithRole(role_name.clone()));
+                })],
+                dismiss_on_select: true,
+                search_value: Some(name.clone()),
+                ..Default::default()
+            }
+        }));
 
         self.chat_widget.show_selection_view(SelectionViewParams {
             title: Some("Agents".to_string()),
-            subtitle: Some("Select a thread to focus".to_string()),
+            subtitle: Some("Select a thread to focus or start an agent with a role".to_string()),
             footer_hint: Some(standard_popup_hint_line()),
             items,
             initial_selected_idx,
@@ -809,6 +854,86 @@ impl App {
         });
     }
 
+    async fn start_agent_with_role(&mut self, tui: &mut tui::Tui, role: &str) -> Result<()> {
+        let mut config = self.config.clone();
+        if let Err(err) = codex_core::apply_agent_role(&mut config, role) {
+            self.chat_widget.add_error_message(err);
+            return Ok(());
+        }
+        let thread_id = match self.server.start_thread(config).await {
+            Ok(new_thread) => new_thread.thread_id,
+            Err(err) => {
+                self.chat_widget
+                    .add_error_message(format!("Failed to start {role} agent: {err}"));
+                return Ok(());
+            }
+        };
+        self.handle_thread_created(thread_id).await?;
+        self.select_agent_thread(tui, thread_id).await
+    }
+
+    async fn review_agent_worktree(&mut self, tui: &mut tui::Tui, thread_id: ThreadId) {
+        let diff = match self
+            .server
+            .resolve_agent_worktree(thread_id, AgentWorktreeAction::Diff)
+            .await
+        {
+            Ok(diff) => diff,
+            Err(err) => {
+                self.chat_widget.add_error_message(format!(
+                    "Failed to read worktree of agent {thread_id}: {err}"
+                ));
+                return;
+            }
+        };
+        let _ = tui.enter_alt_screen();
+        let pager_lines: Vec<ratatui::text::Line<'static>> = if diff.trim().is_empty() {
+            vec!["No changes detected.".italic().into()]
+        } else {
+            diff.lines().map(ansi_escape_line).collect()
+        };
+        self.overlay = Some(Overlay::new_static_with_lines(
+            pager_lines,
+            "D I F F".to_string(),
+        ));
+        tui.frame_requester().schedule_frame();
+
+        let items = [
+            (
+                "Merge",
+                "Merge the agent's branch into this checkout",
+                AgentWorktreeAction::Merge,
+            ),
+            (
+                "Cherry-pick",
+                "Apply the agent's commits without a merge commit",
+                AgentWorktreeAction::CherryPick,
+            ),
+            (
+                "Discard",
+                "Delete the worktree and its changes",
+                AgentWorktreeAction::Discard,
+            ),
+        ]
+        .into_iter()
+        .map(|(name, description, action)| SelectionItem {
+            name: name.to_string(),
+            description: Some(description.to_string()),
+            actions: vec![Box::new(move |tx| {
+                tx.send(AppEvent::ResolveAgentWorktree { thread_id, action });
+            })],
+            dismiss_on_select: true,
+            ..Default::default()
+        })
+        .collect();
+        self.chat_widget.show_selection_view(SelectionViewParams {
+            title: Some(format!("Changes from agent {thread_id}")),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
     async fn select_agent_thread(&mut self, tui: &mut tui::Tui, thread_id: ThreadId) -> Result<()> {
         if self.active_thread_id == Some(thread_id) {
             return Ok(());
@@ -1067,9 +1192,20 @@ impl App {
             }
         };
 
+        if let Some(name) = config.tui_theme.as_deref() {
+            match Theme::resolve(name, &config.tui_themes) {
+                Ok(resolved) => theme::set_active(name, resolved),
+                Err(err) => chat_widget.add_error_message(err),
+            }
+        }
+
         chat_widget.maybe_prompt_windows_sandbox_enable();
 
-        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
+        let file_search = FileSearchManager::new(
+            config.cwd.clone(),
+            config.file_search_index_dir(),
+            app_event_tx.clone(),
+        );
         #[cfg(not(debug_assertions))]
         let upgrade_version = crate::updates::get_upgrade_version(&config);
 
@@ -1149,6 +1285,7 @@ impl App {
                     thread_id: app.chat_widget.thread_id(),
                     thread_name: app.chat_widget.thread_name(),
                     update_action: app.pending_update_action,
+                    kept_worktrees: Vec::new(),
                     exit_reason,
                 });
             }
@@ -1211,6 +1348,7 @@ impl App {
             thread_id: app.chat_widget.thread_id(),
             thread_name: app.chat_widget.thread_name(),
             update_action: app.pending_update_action,
+            kept_worktrees: app.server.release_agent_worktrees().await,
             exit_reason,
         })
     }
@@ -1277,9 +1415,13 @@ impl App {
                     self.chat_widget.thread_name(),
                 );
                 self.shutdown_current_thread().await;
-                if let Err(err) = self.server.remove_and_close_all_threads().await {
-                    tracing::warn!(error = %err, "failed to close all threads");
-                }
+                let kept_worktrees = match self.server.remove_and_close_all_threads().await {
+                    Ok(kept) => kept,
+                    Err(err) => {
+                        tracing::warn!(error = %err, "failed to close all threads");
+                        Vec::new()
+                    }
+                };
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: self.config.clone(),
                     frame_requester: tui.frame_requester(),
@@ -1305,6 +1447,10 @@ impl App {
                     }
                     self.chat_widget.add_plain_history_lines(lines);
                 }
+                if !kept_worktrees.is_empty() {
+                    self.chat_widget
+                        .add_plain_history_lines(kept_worktree_lines(&kept_worktrees));
+                }
                 tui.frame_requester().schedule_frame();
             }
             AppEvent::OpenResumePicker => {
@@ -1572,6 +1718,9 @@ impl App {
             AppEvent::UpdatePersonality(personality) => {
                 self.on_update_personality(personality);
             }
+            AppEvent::UpdateTheme(name) => {
+                self.on_update_theme(name, tui);
+            }
             AppEvent::OpenReasoningPopup { model } => {
                 self.chat_widget.open_reasoning_popup(model);
             }
@@ -1901,6 +2050,27 @@ impl App {
                     }
                 }
             }
+            AppEvent::PersistThemeSelection { name } => {
+                if self.config.tui_theme.as_deref() != Some(name.as_str()) {
+                    // The selection failed to apply; UpdateTheme already reported why.
+                    return Ok(AppRunControl::Continue);
+                }
+                match ConfigEditsBuilder::new(&self.config.codex_home)
+                    .set_tui_theme(&name)
+                    .apply()
+                    .await
+                {
+                    Ok(()) => {
+                        self.chat_widget
+                            .add_info_message(format!("Theme set to {name}"), None);
+                    }
+                    Err(err) => {
+                        tracing::error!(error = %err, "failed to persist theme selection");
+                        self.chat_widget
+                            .add_error_message(format!("Failed to save theme: {err}"));
+                    }
+                }
+            }
             AppEvent::UpdateAskForApprovalPolicy(policy) => {
                 self.runtime_approval_policy_override = Some(policy);
                 if let Err(err) = self.config.approval_policy.set(policy) {
@@ -2111,11 +2281,25 @@ impl App {
                 self.chat_widget.open_approvals_popup();
             }
             AppEvent::OpenAgentPicker => {
-                self.open_agent_picker();
+                self.open_agent_picker().await;
             }
             AppEvent::SelectAgentThread(thread_id) => {
                 self.select_agent_thread(tui, thread_id).await?;
             }
+            AppEvent::StartAgentWithRole(role) => {
+                self.start_agent_with_role(tui, &role).await?;
+            }
+            AppEvent::ReviewAgentWorktree(thread_id) => {
+                self.review_agent_worktree(tui, thread_id).await;
+            }
+            AppEvent::ResolveAgentWorktree { thread_id, action } => {
+                match self.server.resolve_agent_worktree(thread_id, action).await {
+                    Ok(summary) => self.chat_widget.add_info_message(summary, None),
+                    Err(err) => self.chat_widget.add_error_message(format!(
+                        "Failed to update worktree of agent {thread_id}: {err}"
+                    )),
+                }
+            }
             AppEvent::OpenSkillsList => {
                 self.chat_widget.open_skills_list();
             }
@@ -2152,6 +2336,12 @@ impl App {
             AppEvent::OpenReviewCommitPicker(cwd) => {
                 self.chat_widget.show_review_commit_picker(&cwd).await;
             }
+            AppEvent::OpenReviewRangePrompt => {
+                self.chat_widget.show_review_range_prompt();
+            }
+            AppEvent::OpenReviewPatchPrompt => {
+                self.chat_widget.show_review_patch_prompt();
+            }
             AppEvent::OpenReviewCustomPrompt => {
                 self.chat_widget.show_review_custom_prompt();
             }
@@ -2324,6 +2514,17 @@ impl App {
         self.chat_widget.set_personality(personality);
     }
 
+    fn on_update_theme(&mut self, name: String, tui: &mut tui::Tui) {
+        match Theme::resolve(&name, &self.config.tui_themes) {
+            Ok(resolved) => {
+                theme::set_active(&name, resolved);
+                self.config.tui_theme = Some(name);
+                tui.frame_requester().schedule_frame();
+            }
+            Err(err) => self.chat_widget.add_error_message(err),
+        }
+    }
+
     fn personality_label(personality: Personality) -> &'static str {
         match personality {
             Personality::Friendly => "Friendly",
@@ -2603,7 +2804,11 @@ mod tests {
         ));
         let auth_manager =
             AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key"));
-        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
+        let file_search = FileSearchManager::new(
+            config.cwd.clone(),
+            config.file_search_index_dir(),
+            app_event_tx.clone(),
+        );
         let model = ModelsManager::get_model_offline(config.model.as_deref());
         let otel_manager = test_otel_manager(&config, model.as_str());
 
@@ -2655,7 +2860,11 @@ mod tests {
         ));
         let auth_manager =
             AuthManager::from_auth_for_testing(CodexAuth::from_api_key("Test API Key"));
-        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
+        let file_search = FileSearchManager::new(
+            config.cwd.clone(),
+            config.file_search_index_dir(),
+            app_event_tx.clone(),
+        );
         let model = ModelsManager::get_model_offline(config.model.as_deref());
         let otel_manager = test_otel_manager(&config, model.as_str());
 
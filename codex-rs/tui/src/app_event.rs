@@ -12,6 +12,7 @@ use std::path::PathBuf;
 
 use codex_chatgpt::connectors::AppInfo;
 use codex_common::approval_presets::ApprovalPreset;
+use codex_core::AgentWorktreeAction;
 use codex_core::protocol::Event;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_file_search::FileMatch;
@@ -54,6 +55,15 @@ pub(crate) enum AppEvent {
     OpenAgentPicker,
     /// Switch the active thread to the selected agent.
     SelectAgentThread(ThreadId),
+    /// Start a new agent thread configured with the named agent role.
+    StartAgentWithRole(String),
+    /// Show the diff of an agent running in its own worktree and offer to apply it.
+    ReviewAgentWorktree(ThreadId),
+    /// Merge, cherry-pick or discard an agent's worktree.
+    ResolveAgentWorktree {
+        thread_id: ThreadId,
+        action: AgentWorktreeAction,
+    },
 
     /// Start a new session.
     NewSession,
@@ -128,6 +138,9 @@ pub(crate) enum AppEvent {
     /// Update the current personality in the running app and widget.
     UpdatePersonality(Personality),
 
+    /// Switch the active color theme by name.
+    UpdateTheme(String),
+
     /// Persist the selected model and reasoning effort to the appropriate config.
     PersistModelSelection {
         model: String,
@@ -139,6 +152,11 @@ pub(crate) enum AppEvent {
         personality: Personality,
     },
 
+    /// Persist the selected color theme to the user config.
+    PersistThemeSelection {
+        name: String,
+    },
+
     /// Open the reasoning selection popup after picking a model.
     OpenReasoningPopup {
         model: ModelPreset,
@@ -267,6 +285,12 @@ pub(crate) enum AppEvent {
     /// Open the commit picker option from the review popup.
     OpenReviewCommitPicker(PathBuf),
 
+    /// Open the commit range prompt from the review popup.
+    OpenReviewRangePrompt,
+
+    /// Open the patch file prompt from the review popup.
+    OpenReviewPatchPrompt,
+
     /// Open the custom prompt option from the review popup.
     OpenReviewCustomPrompt,
 
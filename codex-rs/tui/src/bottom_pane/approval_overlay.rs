@@ -126,7 +126,7 @@ impl ApprovalOverlay {
         };
 
         let header = Box::new(ColumnRenderable::with([
-            Line::from(title.bold()).into(),
+            Line::from(title.bold().fg(crate::theme::current().approval_title)).into(),
             Line::from("").into(),
             header,
         ]));
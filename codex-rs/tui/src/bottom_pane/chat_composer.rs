@@ -125,6 +125,7 @@ use super::footer::render_footer_line;
 use super::footer::reset_mode_after_activity;
 use super::footer::single_line_footer_layout;
 use super::footer::toggle_shortcut_mode;
+use super::footer::with_vi_mode_indicator;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
 use super::skill_popup::MentionItem;
@@ -155,6 +156,7 @@ use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::LocalImageAttachment;
 use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
+use crate::bottom_pane::textarea::ViMode;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
 use crate::history_cell;
@@ -438,6 +440,25 @@ impl ChatComposer {
     pub fn set_personality_command_enabled(&mut self, enabled: bool) {
         self.personality_command_enabled = enabled;
     }
+
+    /// Switch the textarea between the default Emacs-style bindings and vi modal editing.
+    pub fn set_vi_mode_enabled(&mut self, enabled: bool) {
+        self.textarea.set_vi_mode_enabled(enabled);
+    }
+
+    /// True while vi editing is in insert mode, where `Esc` returns to normal mode instead of
+    /// triggering backtracking or interrupts.
+    pub(crate) fn vi_insert_mode_active(&self) -> bool {
+        self.textarea.vi_mode() == Some(ViMode::Insert)
+    }
+
+    /// True while vi normal or visual mode interprets plain characters as commands.
+    fn vi_command_mode_active(&self) -> bool {
+        matches!(
+            self.textarea.vi_mode(),
+            Some(ViMode::Normal | ViMode::Visual)
+        )
+    }
     /// Centralized feature gating keeps config checks out of call sites.
     fn popups_enabled(&self) -> bool {
         self.config.popups_enabled
@@ -2194,6 +2215,9 @@ impl ChatComposer {
             return (InputResult::None, true);
         }
         if key_event.code == KeyCode::Esc {
+            if self.vi_insert_mode_active() {
+                return self.handle_input_basic(key_event);
+            }
             if self.is_empty() {
                 let next_mode = esc_hint_mode(self.footer_mode, self.is_task_running);
                 if next_mode != self.footer_mode {
@@ -2342,7 +2366,8 @@ impl ChatComposer {
         } = input
         {
             let has_ctrl_or_alt = has_ctrl_or_alt(modifiers);
-            if !has_ctrl_or_alt && !self.disable_paste_burst {
+            // vi command keys must act immediately rather than being held as a possible paste.
+            if !has_ctrl_or_alt && !self.disable_paste_burst && !self.vi_command_mode_active() {
                 // Non-ASCII characters (e.g., from IMEs) can arrive in quick bursts, so avoid
                 // holding the first char while still allowing burst detection for paste input.
                 if !ch.is_ascii() {
@@ -3099,9 +3124,12 @@ impl ChatComposer {
                     | FooterMode::ShortcutOverlay
                     | FooterMode::EscHint => false,
                 };
-                let context_line = context_window_line(
-                    footer_props.context_window_percent,
-                    footer_props.context_window_used_tokens,
+                let context_line = with_vi_mode_indicator(
+                    self.textarea.vi_mode(),
+                    context_window_line(
+                        footer_props.context_window_percent,
+                        footer_props.context_window_used_tokens,
+                    ),
                 );
                 let context_width = context_line.width() as u16;
                 let custom_height = self.custom_footer_height();
@@ -32,6 +32,7 @@
 //! In short: `single_line_footer_layout` chooses *what* best fits, and the two
 //! render helpers choose whether to draw the chosen line or the default
 //! `FooterProps` mapping.
+use crate::bottom_pane::textarea::ViMode;
 use crate::key_hint;
 use crate::key_hint::KeyBinding;
 use crate::render::line_utils::prefix_lines;
@@ -100,9 +101,12 @@ impl CollaborationModeIndicator {
 
     fn styled_span(self, show_cycle_hint: bool) -> Span<'static> {
         let label = self.label(show_cycle_hint);
+        let theme = crate::theme::current();
         match self {
-            CollaborationModeIndicator::Plan => Span::from(label).magenta(),
-            CollaborationModeIndicator::PairProgramming => Span::from(label).cyan(),
+            CollaborationModeIndicator::Plan => Span::from(label).fg(theme.footer_mode),
+            CollaborationModeIndicator::PairProgramming => {
+                Span::from(label).fg(theme.status_accent)
+            }
             CollaborationModeIndicator::Execute => Span::from(label).dim(),
         }
     }
@@ -757,6 +761,25 @@ pub(crate) fn context_window_line(percent: Option<i64>, used_tokens: Option<i64>
     Line::from(vec![Span::from("100% context left").dim()])
 }
 
+/// Prefix the right-side context indicator with the vi editing mode when vi editing is enabled.
+pub(crate) fn with_vi_mode_indicator(
+    vi_mode: Option<ViMode>,
+    context_line: Line<'static>,
+) -> Line<'static> {
+    let Some(vi_mode) = vi_mode else {
+        return context_line;
+    };
+    let theme = crate::theme::current();
+    let label = match vi_mode {
+        ViMode::Normal => Span::from("NORMAL").fg(theme.status_accent).bold(),
+        ViMode::Insert => Span::from("INSERT").dim(),
+        ViMode::Visual => Span::from("VISUAL").fg(theme.footer_mode).bold(),
+    };
+    let mut spans = vec![label, " · ".dim()];
+    spans.extend(context_line.spans);
+    Line::from(spans)
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum ShortcutId {
     Commands,
@@ -1290,6 +1313,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn vi_mode_indicator_prefixes_context_line() {
+        let context_line = context_window_line(Some(72), None);
+        let text = |line: &Line<'static>| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        };
+
+        assert_eq!(
+            text(&with_vi_mode_indicator(None, context_line.clone())),
+            "72% context left"
+        );
+        assert_eq!(
+            text(&with_vi_mode_indicator(
+                Some(ViMode::Normal),
+                context_line.clone()
+            )),
+            "NORMAL · 72% context left"
+        );
+        assert_eq!(
+            text(&with_vi_mode_indicator(Some(ViMode::Visual), context_line)),
+            "VISUAL · 72% context left"
+        );
+    }
+
     #[test]
     fn paste_image_shortcut_prefers_ctrl_alt_v_under_wsl() {
         let descriptor = SHORTCUTS
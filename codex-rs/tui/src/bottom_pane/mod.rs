@@ -236,6 +236,11 @@ impl BottomPane {
         self.composer.set_steer_enabled(enabled);
     }
 
+    pub fn set_vi_mode_enabled(&mut self, enabled: bool) {
+        self.composer.set_vi_mode_enabled(enabled);
+        self.request_redraw();
+    }
+
     pub fn set_collaboration_modes_enabled(&mut self, enabled: bool) {
         self.composer.set_collaboration_modes_enabled(enabled);
         self.request_redraw();
@@ -335,9 +340,11 @@ impl BottomPane {
             // If a task is running and a status line is visible, allow Esc to
             // send an interrupt even while the composer has focus.
             // When a popup is active, prefer dismissing it over interrupting the task.
+            // In vi insert mode the first Esc only switches to normal mode.
             if key_event.code == KeyCode::Esc
                 && self.is_task_running
                 && !self.composer.popup_active()
+                && !self.composer.vi_insert_mode_active()
                 && let Some(status) = &self.status
             {
                 // Send Op::Interrupt
@@ -666,7 +673,10 @@ impl BottomPane {
     /// overlays or popups and not running a task. This is the safe context to
     /// use Esc-Esc for backtracking from the main view.
     pub(crate) fn is_normal_backtrack_mode(&self) -> bool {
-        !self.is_task_running && self.view_stack.is_empty() && !self.composer.popup_active()
+        !self.is_task_running
+            && self.view_stack.is_empty()
+            && !self.composer.popup_active()
+            && !self.composer.vi_insert_mode_active()
     }
 
     /// Return true when no popups or modal views are active, regardless of task state.
@@ -1315,6 +1325,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn esc_in_vi_insert_mode_enters_normal_mode_before_interrupting() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: tx,
+            frame_requester: FrameRequester::test_dummy(),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            placeholder_text: "Ask Codex to do anything".to_string(),
+            disable_paste_burst: false,
+            animations_enabled: true,
+            skills: Some(Vec::new()),
+        });
+        pane.set_vi_mode_enabled(true);
+        pane.set_task_running(true);
+
+        pane.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(
+            rx.try_recv().is_err(),
+            "expected the first Esc to only leave vi insert mode"
+        );
+
+        pane.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(
+            matches!(rx.try_recv(), Ok(AppEvent::CodexOp(Op::Interrupt))),
+            "expected Esc from vi normal mode to send Op::Interrupt"
+        );
+    }
+
     #[test]
     fn esc_routes_to_handle_key_event_when_requested() {
         #[derive(Default)]
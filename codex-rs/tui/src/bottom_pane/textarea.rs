@@ -7,6 +7,7 @@ use crossterm::event::KeyModifiers;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
+use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
@@ -17,6 +18,11 @@ use textwrap::Options;
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
+mod vi;
+
+pub(crate) use vi::ViMode;
+use vi::ViState;
+
 const WORD_SEPARATORS: &str = "`~!@#$%^&*()-=+[{]}\\|;:'\",.<>/?";
 
 fn is_word_separator(ch: char) -> bool {
@@ -36,6 +42,8 @@ pub(crate) struct TextArea {
     preferred_col: Option<usize>,
     elements: Vec<TextElement>,
     kill_buffer: String,
+    /// Modal vi editing state; `None` keeps the default Emacs-style bindings.
+    vi: Option<ViState>,
 }
 
 #[derive(Debug, Clone)]
@@ -59,9 +67,29 @@ impl TextArea {
             preferred_col: None,
             elements: Vec::new(),
             kill_buffer: String::new(),
+            vi: None,
+        }
+    }
+
+    /// Enable or disable vi-style modal editing. Enabling starts in insert mode.
+    pub fn set_vi_mode_enabled(&mut self, enabled: bool) {
+        match (enabled, self.vi.is_some()) {
+            (true, false) => self.vi = Some(ViState::default()),
+            (false, true) => self.vi = None,
+            _ => {}
         }
     }
 
+    /// The active vi mode, or `None` when vi editing is disabled.
+    pub(crate) fn vi_mode(&self) -> Option<ViMode> {
+        self.vi.as_ref().map(ViState::mode)
+    }
+
+    /// Byte range highlighted by vi visual mode, if it is active.
+    pub(crate) fn vi_selection(&self) -> Option<Range<usize>> {
+        self.vi.as_ref().and_then(|vi| vi.selection(self))
+    }
+
     /// Replace the textarea text and clear any existing text elements.
     pub fn set_text_clearing_elements(&mut self, text: &str) {
         self.set_text_inner(text, None);
@@ -96,6 +124,12 @@ impl TextArea {
         self.wrap_cache.replace(None);
         self.preferred_col = None;
         self.kill_buffer.clear();
+        // An emptied composer (e.g. after submitting) always starts over in insert mode.
+        if self.text.is_empty()
+            && let Some(vi) = self.vi.as_mut()
+        {
+            vi.reset_to_insert();
+        }
     }
 
     pub fn text(&self) -> &str {
@@ -242,6 +276,16 @@ impl TextArea {
     }
 
     pub fn input(&mut self, event: KeyEvent) {
+        if let Some(mut vi) = self.vi.take() {
+            vi.handle_key(self, event);
+            self.vi = Some(vi);
+            return;
+        }
+        self.input_emacs(event);
+    }
+
+    /// Default Emacs-style bindings; also used for vi insert mode.
+    fn input_emacs(&mut self, event: KeyEvent) {
         match event {
             // Some terminals (or configurations) send Control key chords as
             // C0 control characters without reporting the CONTROL modifier.
@@ -1229,6 +1273,20 @@ impl TextArea {
                 let style = Style::default().fg(Color::Cyan);
                 buf.set_string(area.x + x_off, y, styled, style);
             }
+
+            // Highlight the vi visual-mode selection on top of everything else.
+            if let Some(selection) = self.vi_selection() {
+                let overlap_start = selection.start.max(line_range.start);
+                let overlap_end = selection.end.min(line_range.end);
+                if overlap_start < overlap_end {
+                    let x_off = self.text[line_range.start..overlap_start].width() as u16;
+                    let width = self.text[overlap_start..overlap_end].width() as u16;
+                    buf.set_style(
+                        Rect::new(area.x + x_off, y, width, 1).intersection(area),
+                        Style::default().add_modifier(Modifier::REVERSED),
+                    );
+                }
+            }
         }
     }
 
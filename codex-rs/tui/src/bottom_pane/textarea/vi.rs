@@ -0,0 +1,1084 @@
+//! Optional vi-style modal editing for [`TextArea`].
+//!
+//! When enabled, the textarea starts in insert mode, where keys behave exactly like the default
+//! Emacs-style bindings. `Esc` switches to normal mode, which interprets plain characters as
+//! motions (`h j k l w b e 0 ^ $ gg G`), operators (`d c y`, with counts and doubled linewise
+//! forms), simple edits (`x X D C Y p P`) and mode switches (`i a I A o O v`). Visual mode
+//! extends a character-wise selection with the same motions and applies `d`/`c`/`y` to it.
+//!
+//! `.` replays the last change by feeding its recorded keys back through the state machine.
+//! Changes made from visual mode are not recorded, since replaying them would need the
+//! selection size rather than the keys that produced it.
+//!
+//! All edits go through the regular `TextArea` APIs so atomic text elements (paste and image
+//! placeholders) are never split.
+
+use super::TextArea;
+use super::is_word_separator;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use std::ops::Range;
+
+/// Counts are clamped to this value, like vi does, so a mistyped `99999999999j` or `.` returns
+/// immediately instead of looping until the UI freezes.
+const MAX_COUNT: usize = 9_999;
+
+/// Upper bound on the text a single `p`/`P` inserts; larger counts are reduced to fit.
+const MAX_PUT_BYTES: usize = 1024 * 1024;
+
+/// The vi editing state currently active in the textarea.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum ViMode {
+    Normal,
+    #[default]
+    Insert,
+    Visual,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Delete,
+    Change,
+    Yank,
+}
+
+impl Operator {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'd' => Some(Operator::Delete),
+            'c' => Some(Operator::Change),
+            'y' => Some(Operator::Yank),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Motion {
+    Left,
+    Right,
+    Down,
+    Up,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    FirstNonBlank,
+    LineEnd,
+    FirstLine,
+    LastLine,
+}
+
+impl Motion {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'h' => Some(Motion::Left),
+            'l' => Some(Motion::Right),
+            'j' => Some(Motion::Down),
+            'k' => Some(Motion::Up),
+            'w' => Some(Motion::WordForward),
+            'b' => Some(Motion::WordBackward),
+            'e' => Some(Motion::WordEnd),
+            '0' => Some(Motion::LineStart),
+            '^' => Some(Motion::FirstNonBlank),
+            '$' => Some(Motion::LineEnd),
+            'G' => Some(Motion::LastLine),
+            _ => None,
+        }
+    }
+
+    fn from_key_code(code: KeyCode) -> Option<Self> {
+        match code {
+            KeyCode::Left | KeyCode::Backspace => Some(Motion::Left),
+            KeyCode::Right => Some(Motion::Right),
+            KeyCode::Down => Some(Motion::Down),
+            KeyCode::Up => Some(Motion::Up),
+            KeyCode::Home => Some(Motion::LineStart),
+            KeyCode::End => Some(Motion::LineEnd),
+            _ => None,
+        }
+    }
+
+    /// Linewise motions make operators act on whole lines (`dj`, `yG`, ...).
+    fn is_linewise(self) -> bool {
+        matches!(
+            self,
+            Motion::Down | Motion::Up | Motion::FirstLine | Motion::LastLine
+        )
+    }
+
+    /// Inclusive motions make operators include the character under the target (`de`, `d$`).
+    fn is_inclusive(self) -> bool {
+        matches!(self, Motion::WordEnd | Motion::LineEnd)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ViState {
+    mode: ViMode,
+    /// Count typed before an operator or motion (the `3` in `3dw`).
+    count: Option<usize>,
+    /// Count typed after an operator (the `2` in `d2w`).
+    motion_count: Option<usize>,
+    operator: Option<Operator>,
+    /// A `g` was typed and is waiting for its second key.
+    pending_g: bool,
+    /// Fixed end of the selection while in visual mode.
+    visual_anchor: usize,
+    /// Unnamed register shared by `d`, `c`, `y`, `x` and read by `p`/`P`.
+    register: String,
+    register_linewise: bool,
+    /// Keys of the command currently being typed, kept so completed changes can be repeated.
+    pending_keys: Vec<KeyEvent>,
+    /// True while the keys typed in insert mode belong to a change started in normal mode.
+    recording_insert: bool,
+    last_change: Vec<KeyEvent>,
+    replaying: bool,
+}
+
+impl ViState {
+    pub(crate) fn mode(&self) -> ViMode {
+        self.mode
+    }
+
+    /// Selection covered by visual mode, inclusive of the character under the cursor.
+    pub(super) fn selection(&self, textarea: &TextArea) -> Option<Range<usize>> {
+        if self.mode != ViMode::Visual {
+            return None;
+        }
+        let start = self.visual_anchor.min(textarea.cursor_pos);
+        let end = self.visual_anchor.max(textarea.cursor_pos);
+        Some(start..textarea.next_atomic_boundary(end))
+    }
+
+    /// Leave whatever mode is active and start over in insert mode, e.g. after the composer
+    /// was cleared by a submission.
+    pub(super) fn reset_to_insert(&mut self) {
+        self.mode = ViMode::Insert;
+        self.clear_pending();
+        self.recording_insert = false;
+    }
+
+    pub(super) fn handle_key(&mut self, textarea: &mut TextArea, event: KeyEvent) {
+        match self.mode {
+            ViMode::Insert => self.handle_insert(textarea, event),
+            ViMode::Normal => self.handle_normal(textarea, event),
+            ViMode::Visual => self.handle_visual(textarea, event),
+        }
+    }
+
+    fn handle_insert(&mut self, textarea: &mut TextArea, event: KeyEvent) {
+        if self.recording_insert {
+            self.pending_keys.push(event);
+        }
+        if event.code == KeyCode::Esc {
+            self.enter_normal(textarea);
+            if self.recording_insert {
+                self.recording_insert = false;
+                self.finish_change();
+            }
+            return;
+        }
+        textarea.input_emacs(event);
+    }
+
+    fn handle_normal(&mut self, textarea: &mut TextArea, event: KeyEvent) {
+        if event.code == KeyCode::Esc {
+            self.clear_pending();
+            return;
+        }
+        self.pending_keys.push(event);
+        if let KeyCode::Char(c) = event.code
+            && matches!(event.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT)
+        {
+            self.normal_char(textarea, c);
+        } else if let Some(motion) = Motion::from_key_code(event.code) {
+            self.apply_motion(textarea, motion);
+        } else {
+            // Other keys (including Ctrl chords) never insert text in normal mode.
+            self.clear_pending();
+        }
+        self.clamp_normal_cursor(textarea);
+    }
+
+    fn normal_char(&mut self, textarea: &mut TextArea, c: char) {
+        if self.pending_g {
+            self.pending_g = false;
+            if c == 'g' {
+                self.apply_motion(textarea, Motion::FirstLine);
+            } else {
+                self.clear_pending();
+            }
+            return;
+        }
+        if let Some(digit) = c.to_digit(10)
+            && (digit != 0 || self.count.is_some() || self.motion_count.is_some())
+        {
+            let slot = if self.operator.is_some() {
+                &mut self.motion_count
+            } else {
+                &mut self.count
+            };
+            *slot = Some(append_count_digit(*slot, digit));
+            return;
+        }
+        if let Some(motion) = Motion::from_char(c) {
+            self.apply_motion(textarea, motion);
+            return;
+        }
+        if let Some(operator) = Operator::from_char(c) {
+            match self.operator {
+                None => self.operator = Some(operator),
+                Some(pending) if pending == operator => {
+                    self.operator = None;
+                    let count = self.take_count();
+                    self.apply_current_lines(textarea, operator, count);
+                }
+                Some(_) => self.clear_pending(),
+            }
+            return;
+        }
+        if self.operator.is_some() {
+            // An operator followed by something that is not a motion cancels the command.
+            self.clear_pending();
+            return;
+        }
+
+        match c {
+            'g' => self.pending_g = true,
+            'x' => {
+                self.operator = Some(Operator::Delete);
+                self.apply_motion(textarea, Motion::Right);
+            }
+            'X' => {
+                self.operator = Some(Operator::Delete);
+                self.apply_motion(textarea, Motion::Left);
+            }
+            'D' | 'C' => {
+                self.operator = Some(if c == 'D' {
+                    Operator::Delete
+                } else {
+                    Operator::Change
+                });
+                self.apply_motion(textarea, Motion::LineEnd);
+            }
+            'Y' => {
+                let count = self.take_count();
+                self.apply_current_lines(textarea, Operator::Yank, count);
+            }
+            'p' | 'P' => {
+                let count = self.take_count();
+                self.put(textarea, c == 'p', count);
+                self.finish_change();
+            }
+            'i' => self.start_insert(textarea, textarea.cursor_pos),
+            'a' => {
+                let pos = if textarea.cursor_pos < textarea.end_of_current_line() {
+                    textarea.next_atomic_boundary(textarea.cursor_pos)
+                } else {
+                    textarea.cursor_pos
+                };
+                self.start_insert(textarea, pos);
+            }
+            'I' => {
+                let pos = first_non_blank(&textarea.text, textarea.beginning_of_current_line());
+                self.start_insert(textarea, pos);
+            }
+            'A' => self.start_insert(textarea, textarea.end_of_current_line()),
+            'o' => {
+                let eol = textarea.end_of_current_line();
+                textarea.insert_str_at(eol, "\n");
+                self.start_insert(textarea, eol + 1);
+            }
+            'O' => {
+                let bol = textarea.beginning_of_current_line();
+                textarea.insert_str_at(bol, "\n");
+                self.start_insert(textarea, bol);
+            }
+            'v' => {
+                self.clear_pending();
+                self.visual_anchor = textarea.cursor_pos;
+                self.mode = ViMode::Visual;
+            }
+            '.' => {
+                let count = self.take_count();
+                self.pending_keys.clear();
+                self.repeat_last_change(textarea, count);
+            }
+            _ => self.clear_pending(),
+        }
+    }
+
+    fn handle_visual(&mut self, textarea: &mut TextArea, event: KeyEvent) {
+        let plain_char = match event.code {
+            KeyCode::Char(c)
+                if matches!(event.modifiers, KeyModifiers::NONE | KeyModifiers::SHIFT) =>
+            {
+                Some(c)
+            }
+            _ => None,
+        };
+        if event.code == KeyCode::Esc || plain_char == Some('v') {
+            self.enter_normal(textarea);
+            return;
+        }
+        let Some(c) = plain_char else {
+            if let Some(motion) = Motion::from_key_code(event.code) {
+                self.apply_motion(textarea, motion);
+            }
+            return;
+        };
+        if self.pending_g {
+            self.pending_g = false;
+            if c == 'g' {
+                self.apply_motion(textarea, Motion::FirstLine);
+            }
+            return;
+        }
+        if let Some(digit) = c.to_digit(10)
+            && (digit != 0 || self.count.is_some())
+        {
+            self.count = Some(append_count_digit(self.count, digit));
+            return;
+        }
+        if let Some(motion) = Motion::from_char(c) {
+            self.apply_motion(textarea, motion);
+            return;
+        }
+        let operator = match c {
+            'd' | 'x' => Operator::Delete,
+            'c' | 's' => Operator::Change,
+            'y' => Operator::Yank,
+            'g' => {
+                self.pending_g = true;
+                return;
+            }
+            _ => return,
+        };
+        let Some(range) = self.selection(textarea) else {
+            return;
+        };
+        self.mode = ViMode::Normal;
+        self.apply_operator(textarea, operator, range, false);
+        // Visual changes are intentionally not repeatable with `.`.
+        self.pending_keys.clear();
+        self.recording_insert = false;
+        self.clamp_normal_cursor(textarea);
+    }
+
+    fn apply_motion(&mut self, textarea: &mut TextArea, motion: Motion) {
+        let explicit_count = self.count.is_some() || self.motion_count.is_some();
+        let count = self.take_count();
+        let Some(operator) = self.operator.take() else {
+            self.move_cursor(textarea, motion, count, explicit_count);
+            if self.mode == ViMode::Normal {
+                self.pending_keys.clear();
+            }
+            return;
+        };
+
+        let cursor = textarea.cursor_pos;
+        if motion.is_linewise() {
+            let target = motion_target(textarea, motion, count, explicit_count);
+            let first = textarea.beginning_of_line(cursor.min(target));
+            let last = textarea.end_of_line(cursor.max(target));
+            self.apply_operator(textarea, operator, first..last, true);
+            return;
+        }
+
+        // `cw` behaves like `ce` when the cursor is on a word, as in vi.
+        let motion = if operator == Operator::Change
+            && motion == Motion::WordForward
+            && class_at(textarea, cursor).is_some_and(|class| class != CharClass::Whitespace)
+        {
+            Motion::WordEnd
+        } else {
+            motion
+        };
+        let mut target = motion_target(textarea, motion, count, explicit_count);
+        if motion == Motion::WordForward {
+            // `dw` on the last word of a line stops at the line end instead of joining lines.
+            let eol = textarea.end_of_line(cursor);
+            if cursor < eol && target > eol {
+                target = eol;
+            }
+        }
+        let range = if motion.is_inclusive() && target >= cursor {
+            let end = if motion == Motion::LineEnd {
+                textarea.end_of_line(target)
+            } else {
+                textarea.next_atomic_boundary(target)
+            };
+            cursor..end
+        } else {
+            cursor.min(target)..cursor.max(target)
+        };
+        self.apply_operator(textarea, operator, range, false);
+    }
+
+    fn move_cursor(
+        &mut self,
+        textarea: &mut TextArea,
+        motion: Motion,
+        count: usize,
+        explicit_count: bool,
+    ) {
+        match motion {
+            // Reuse the textarea's vertical movement so wrapped lines and the preferred column
+            // behave the same way as with the arrow keys.
+            Motion::Down | Motion::Up => {
+                for _ in 0..count {
+                    let before = textarea.cursor_pos;
+                    if motion == Motion::Down {
+                        textarea.move_cursor_down();
+                    } else {
+                        textarea.move_cursor_up();
+                    }
+                    if textarea.cursor_pos == before {
+                        break;
+                    }
+                }
+            }
+            _ => {
+                let target = motion_target(textarea, motion, count, explicit_count);
+                textarea.set_cursor(target);
+            }
+        }
+    }
+
+    /// Apply `dd`, `cc`, `yy` (and `Y`) to `count` lines starting at the cursor line.
+    fn apply_current_lines(&mut self, textarea: &mut TextArea, operator: Operator, count: usize) {
+        let first = textarea.beginning_of_current_line();
+        let mut last = textarea.end_of_current_line();
+        for _ in 1..count {
+            if last >= textarea.text.len() {
+                break;
+            }
+            last = textarea.end_of_line(last + 1);
+        }
+        self.apply_operator(textarea, operator, first..last, true);
+    }
+
+    /// Apply `operator` to `range`. For linewise operations `range` spans whole lines without
+    /// the trailing newline; the newline is handled here so lines are removed cleanly.
+    fn apply_operator(
+        &mut self,
+        textarea: &mut TextArea,
+        operator: Operator,
+        range: Range<usize>,
+        linewise: bool,
+    ) {
+        let range = textarea.expand_range_to_element_boundaries(range);
+        let mut yanked = textarea.text[range.clone()].to_string();
+        if linewise {
+            yanked.push('\n');
+        }
+        self.register = yanked;
+        self.register_linewise = linewise;
+
+        match operator {
+            Operator::Yank => {
+                textarea.set_cursor(range.start);
+                self.pending_keys.clear();
+            }
+            Operator::Delete => {
+                let delete_range = if linewise {
+                    linewise_delete_range(&textarea.text, range)
+                } else {
+                    range
+                };
+                let start = delete_range.start;
+                textarea.replace_range(delete_range, "");
+                if linewise {
+                    let bol = textarea.beginning_of_line(start.min(textarea.text.len()));
+                    textarea.set_cursor(first_non_blank(&textarea.text, bol));
+                } else {
+                    textarea.set_cursor(start);
+                }
+                self.finish_change();
+            }
+            Operator::Change => {
+                let start = range.start;
+                textarea.replace_range(range, "");
+                self.start_insert(textarea, start);
+            }
+        }
+    }
+
+    fn put(&mut self, textarea: &mut TextArea, after: bool, count: usize) {
+        if self.register.is_empty() {
+            return;
+        }
+        let count = count.clamp(1, (MAX_PUT_BYTES / self.register.len()).max(1));
+        let text = self.register.repeat(count);
+        if self.register_linewise {
+            if after {
+                let eol = textarea.end_of_current_line();
+                let mut inserted = String::from("\n");
+                inserted.push_str(text.strip_suffix('\n').unwrap_or(&text));
+                textarea.insert_str_at(eol, &inserted);
+                textarea.set_cursor(eol + 1);
+            } else {
+                let bol = textarea.beginning_of_current_line();
+                textarea.insert_str_at(bol, &text);
+                textarea.set_cursor(bol);
+            }
+            return;
+        }
+        let at = if after && textarea.cursor_pos < textarea.end_of_current_line() {
+            textarea.next_atomic_boundary(textarea.cursor_pos)
+        } else {
+            textarea.cursor_pos
+        };
+        textarea.insert_str_at(at, &text);
+        // Leave the cursor on the last inserted character.
+        let end = at + text.len();
+        textarea.set_cursor(textarea.prev_atomic_boundary(end).max(at));
+    }
+
+    fn start_insert(&mut self, textarea: &mut TextArea, pos: usize) {
+        textarea.set_cursor(pos);
+        self.clear_pending_state();
+        self.mode = ViMode::Insert;
+        self.recording_insert = !self.replaying;
+        if self.replaying {
+            self.pending_keys.clear();
+        }
+    }
+
+    fn enter_normal(&mut self, textarea: &mut TextArea) {
+        self.clear_pending_state();
+        let was_insert = self.mode == ViMode::Insert;
+        self.mode = ViMode::Normal;
+        if was_insert && textarea.cursor_pos > textarea.beginning_of_current_line() {
+            textarea.move_cursor_left();
+        }
+        self.clamp_normal_cursor(textarea);
+    }
+
+    fn repeat_last_change(&mut self, textarea: &mut TextArea, count: usize) {
+        if self.last_change.is_empty() {
+            return;
+        }
+        let keys = self.last_change.clone();
+        self.replaying = true;
+        for _ in 0..count {
+            let before = (textarea.text.clone(), textarea.cursor_pos);
+            for key in &keys {
+                self.handle_key(textarea, *key);
+            }
+            // Once a repetition changes nothing (e.g. `x` on an empty line), the rest won't either.
+            if textarea.text == before.0 && textarea.cursor_pos == before.1 {
+                break;
+            }
+        }
+        self.replaying = false;
+        // A replayed change that ends in insert mode (e.g. an interrupted recording) must not
+        // leave the editor stuck there.
+        if self.mode == ViMode::Insert {
+            self.enter_normal(textarea);
+        }
+        self.pending_keys.clear();
+    }
+
+    fn finish_change(&mut self) {
+        if !self.replaying {
+            self.last_change = std::mem::take(&mut self.pending_keys);
+        } else {
+            self.pending_keys.clear();
+        }
+    }
+
+    /// In normal mode the cursor sits on a character, never past the end of a non-empty line.
+    fn clamp_normal_cursor(&self, textarea: &mut TextArea) {
+        if self.mode != ViMode::Normal {
+            return;
+        }
+        let pos = textarea.cursor_pos;
+        if pos > textarea.beginning_of_current_line() && pos == textarea.end_of_current_line() {
+            let prev = textarea.prev_atomic_boundary(pos);
+            textarea.set_cursor(prev);
+        }
+    }
+
+    fn take_count(&mut self) -> usize {
+        let count = self.count.take().unwrap_or(1);
+        let motion_count = self.motion_count.take().unwrap_or(1);
+        count.saturating_mul(motion_count).clamp(1, MAX_COUNT)
+    }
+
+    fn clear_pending_state(&mut self) {
+        self.count = None;
+        self.motion_count = None;
+        self.operator = None;
+        self.pending_g = false;
+    }
+
+    fn clear_pending(&mut self) {
+        self.clear_pending_state();
+        self.pending_keys.clear();
+    }
+}
+
+/// Where `motion` repeated `count` times lands, starting from the cursor.
+fn motion_target(textarea: &TextArea, motion: Motion, count: usize, explicit_count: bool) -> usize {
+    let text = textarea.text.as_str();
+    let cursor = textarea.cursor_pos;
+    match motion {
+        Motion::Left => {
+            let bol = textarea.beginning_of_line(cursor);
+            let mut pos = cursor;
+            for _ in 0..count {
+                if pos <= bol {
+                    break;
+                }
+                pos = textarea.prev_atomic_boundary(pos);
+            }
+            pos
+        }
+        Motion::Right => {
+            let eol = textarea.end_of_line(cursor);
+            let mut pos = cursor;
+            for _ in 0..count {
+                if pos >= eol {
+                    break;
+                }
+                pos = textarea.next_atomic_boundary(pos);
+            }
+            pos
+        }
+        Motion::Down => {
+            let mut pos = cursor;
+            for _ in 0..count {
+                let eol = textarea.end_of_line(pos);
+                if eol >= text.len() {
+                    break;
+                }
+                pos = eol + 1;
+            }
+            pos
+        }
+        Motion::Up => {
+            let mut pos = cursor;
+            for _ in 0..count {
+                let bol = textarea.beginning_of_line(pos);
+                if bol == 0 {
+                    break;
+                }
+                pos = bol - 1;
+            }
+            pos
+        }
+        Motion::WordForward => repeat_motion(cursor, count, |pos| next_word_start(textarea, pos)),
+        Motion::WordBackward => repeat_motion(cursor, count, |pos| prev_word_start(textarea, pos)),
+        Motion::WordEnd => repeat_motion(cursor, count, |pos| word_end(textarea, pos)),
+        Motion::LineStart => textarea.beginning_of_line(cursor),
+        Motion::FirstNonBlank => first_non_blank(text, textarea.beginning_of_line(cursor)),
+        Motion::LineEnd => {
+            let mut pos = cursor;
+            for _ in 1..count {
+                let eol = textarea.end_of_line(pos);
+                if eol >= text.len() {
+                    break;
+                }
+                pos = eol + 1;
+            }
+            textarea.end_of_line(pos)
+        }
+        Motion::FirstLine | Motion::LastLine => {
+            let line = if explicit_count {
+                Some(count)
+            } else if motion == Motion::FirstLine {
+                Some(1)
+            } else {
+                None
+            };
+            let bol = match line {
+                Some(line) => line_start_by_number(text, line),
+                None => textarea.beginning_of_line(text.len()),
+            };
+            first_non_blank(text, bol)
+        }
+    }
+}
+
+/// Apply `step` up to `count` times, stopping early once it no longer moves.
+fn repeat_motion(start: usize, count: usize, step: impl Fn(usize) -> usize) -> usize {
+    let mut pos = start;
+    for _ in 0..count {
+        let next = step(pos);
+        if next == pos {
+            break;
+        }
+        pos = next;
+    }
+    pos
+}
+
+/// Extend a linewise range to also remove one adjacent newline so the lines disappear
+/// entirely rather than leaving an empty line behind.
+fn linewise_delete_range(text: &str, range: Range<usize>) -> Range<usize> {
+    if range.end < text.len() {
+        range.start..range.end + 1
+    } else if range.start > 0 {
+        range.start - 1..range.end
+    } else {
+        range
+    }
+}
+
+/// Add a typed digit to a pending count, clamping the result to [`MAX_COUNT`].
+fn append_count_digit(count: Option<usize>, digit: u32) -> usize {
+    count
+        .unwrap_or(0)
+        .saturating_mul(10)
+        .saturating_add(digit as usize)
+        .min(MAX_COUNT)
+}
+
+/// Byte offset of the start of the 1-based `line`, clamped to the last line.
+fn line_start_by_number(text: &str, line: usize) -> usize {
+    let mut start = 0;
+    for _ in 1..line {
+        match text[start..].find('\n') {
+            Some(offset) => start += offset + 1,
+            None => break,
+        }
+    }
+    start
+}
+
+fn first_non_blank(text: &str, bol: usize) -> usize {
+    text[bol..]
+        .char_indices()
+        .find(|&(_, ch)| ch == '\n' || !ch.is_whitespace())
+        .map(|(idx, _)| bol + idx)
+        .unwrap_or(text.len())
+}
+
+/// vi distinguishes runs of whitespace, punctuation and word characters. Atomic text elements
+/// (placeholders) count as words of their own.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Whitespace,
+    Punctuation,
+    Word,
+    Element,
+}
+
+fn class_at(textarea: &TextArea, pos: usize) -> Option<CharClass> {
+    if textarea.find_element_containing(pos).is_some()
+        || textarea.elements.iter().any(|e| e.range.start == pos)
+    {
+        return Some(CharClass::Element);
+    }
+    let ch = textarea.text.get(pos..)?.chars().next()?;
+    Some(if ch.is_whitespace() {
+        CharClass::Whitespace
+    } else if is_word_separator(ch) {
+        CharClass::Punctuation
+    } else {
+        CharClass::Word
+    })
+}
+
+/// `w`: start of the next word, skipping the rest of the current one and any whitespace.
+fn next_word_start(textarea: &TextArea, pos: usize) -> usize {
+    let len = textarea.text.len();
+    let mut pos = pos;
+    if let Some(class) = class_at(textarea, pos)
+        && class != CharClass::Whitespace
+    {
+        pos = textarea.next_atomic_boundary(pos);
+        if class != CharClass::Element {
+            while pos < len && class_at(textarea, pos) == Some(class) {
+                pos = textarea.next_atomic_boundary(pos);
+            }
+        }
+    }
+    while pos < len && class_at(textarea, pos) == Some(CharClass::Whitespace) {
+        pos = textarea.next_atomic_boundary(pos);
+    }
+    pos
+}
+
+/// `b`: start of the current word, or of the previous one when already at a word start.
+fn prev_word_start(textarea: &TextArea, pos: usize) -> usize {
+    let mut pos = pos;
+    while pos > 0 {
+        let prev = textarea.prev_atomic_boundary(pos);
+        if class_at(textarea, prev) != Some(CharClass::Whitespace) {
+            break;
+        }
+        pos = prev;
+    }
+    if pos == 0 {
+        return 0;
+    }
+    let prev = textarea.prev_atomic_boundary(pos);
+    let class = class_at(textarea, prev);
+    pos = prev;
+    if class == Some(CharClass::Element) {
+        return pos;
+    }
+    while pos > 0 {
+        let prev = textarea.prev_atomic_boundary(pos);
+        if class_at(textarea, prev) != class {
+            break;
+        }
+        pos = prev;
+    }
+    pos
+}
+
+/// `e`: last character of the current word, or of the next one when already at a word end.
+fn word_end(textarea: &TextArea, pos: usize) -> usize {
+    let len = textarea.text.len();
+    let mut pos = textarea.next_atomic_boundary(pos);
+    while pos < len && class_at(textarea, pos) == Some(CharClass::Whitespace) {
+        pos = textarea.next_atomic_boundary(pos);
+    }
+    let Some(class) = class_at(textarea, pos) else {
+        return textarea.prev_atomic_boundary(len);
+    };
+    if class == CharClass::Element {
+        return pos;
+    }
+    loop {
+        let next = textarea.next_atomic_boundary(pos);
+        if next >= len || class_at(textarea, next) != Some(class) {
+            return pos;
+        }
+        pos = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vi_textarea(text: &str) -> TextArea {
+        let mut t = TextArea::new();
+        t.set_vi_mode_enabled(true);
+        t.insert_str(text);
+        t
+    }
+
+    fn press(t: &mut TextArea, keys: &str) {
+        for ch in keys.chars() {
+            let code = match ch {
+                '\u{1b}' => KeyCode::Esc,
+                ch => KeyCode::Char(ch),
+            };
+            t.input(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn esc_enters_normal_mode_and_i_returns_to_insert() {
+        let mut t = vi_textarea("hello");
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+        press(&mut t, "\u{1b}");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+        // The cursor steps back onto the last character, as in vi.
+        assert_eq!(t.cursor(), 4);
+        press(&mut t, "ix");
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+        assert_eq!(t.text(), "hellxo");
+    }
+
+    #[test]
+    fn word_and_line_motions() {
+        let mut t = vi_textarea("foo bar.baz qux");
+        press(&mut t, "\u{1b}0");
+        assert_eq!(t.cursor(), 0);
+        press(&mut t, "w");
+        assert_eq!(t.cursor(), 4);
+        press(&mut t, "w");
+        assert_eq!(t.cursor(), 7);
+        press(&mut t, "e");
+        assert_eq!(t.cursor(), 10);
+        press(&mut t, "b");
+        assert_eq!(t.cursor(), 8);
+        press(&mut t, "$");
+        assert_eq!(t.cursor(), 14);
+        press(&mut t, "2b");
+        assert_eq!(t.cursor(), 8);
+    }
+
+    #[test]
+    fn gg_and_g_move_between_lines() {
+        let mut t = vi_textarea("one\n  two\nthree");
+        press(&mut t, "\u{1b}gg");
+        assert_eq!(t.cursor(), 0);
+        press(&mut t, "G");
+        assert_eq!(t.cursor(), "one\n  two\n".len());
+        press(&mut t, "2G");
+        assert_eq!(t.cursor(), "one\n  ".len());
+    }
+
+    #[test]
+    fn delete_with_motions_and_counts() {
+        let mut t = vi_textarea("one two three four");
+        press(&mut t, "\u{1b}0dw");
+        assert_eq!(t.text(), "two three four");
+        press(&mut t, "2dw");
+        assert_eq!(t.text(), "four");
+
+        let mut t = vi_textarea("one two three four");
+        press(&mut t, "\u{1b}0d2e");
+        assert_eq!(t.text(), " three four");
+
+        let mut t = vi_textarea("one two");
+        press(&mut t, "\u{1b}0wD");
+        assert_eq!(t.text(), "one ");
+    }
+
+    #[test]
+    fn dw_on_last_word_does_not_join_lines() {
+        let mut t = vi_textarea("alpha beta\ngamma");
+        press(&mut t, "\u{1b}ggwdw");
+        assert_eq!(t.text(), "alpha \ngamma");
+    }
+
+    #[test]
+    fn linewise_delete_yank_and_put() {
+        let mut t = vi_textarea("one\ntwo\nthree");
+        press(&mut t, "\u{1b}ggdd");
+        assert_eq!(t.text(), "two\nthree");
+        press(&mut t, "p");
+        assert_eq!(t.text(), "two\none\nthree");
+        press(&mut t, "ggyyGp");
+        assert_eq!(t.text(), "two\none\nthree\ntwo");
+        press(&mut t, "gg2dd");
+        assert_eq!(t.text(), "three\ntwo");
+        press(&mut t, "Gdd");
+        assert_eq!(t.text(), "three");
+    }
+
+    #[test]
+    fn change_word_enters_insert_mode() {
+        let mut t = vi_textarea("hello world");
+        press(&mut t, "\u{1b}0cwbye\u{1b}");
+        assert_eq!(t.text(), "bye world");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+
+        press(&mut t, "ccnew line\u{1b}");
+        assert_eq!(t.text(), "new line");
+    }
+
+    #[test]
+    fn x_and_put_characterwise() {
+        let mut t = vi_textarea("abc");
+        press(&mut t, "\u{1b}0x");
+        assert_eq!(t.text(), "bc");
+        press(&mut t, "p");
+        assert_eq!(t.text(), "bac");
+        press(&mut t, "0P");
+        assert_eq!(t.text(), "abac");
+    }
+
+    #[test]
+    fn dot_repeats_last_change() {
+        let mut t = vi_textarea("a b c d e");
+        press(&mut t, "\u{1b}0dw.");
+        assert_eq!(t.text(), "c d e");
+        press(&mut t, "2.");
+        assert_eq!(t.text(), "e");
+
+        let mut t = vi_textarea("one two three");
+        press(&mut t, "\u{1b}0cwX\u{1b}w.");
+        assert_eq!(t.text(), "X X three");
+
+        let mut t = vi_textarea("ab");
+        press(&mut t, "\u{1b}A!\u{1b}.");
+        assert_eq!(t.text(), "ab!!");
+    }
+
+    #[test]
+    fn motions_do_not_replace_last_change() {
+        let mut t = vi_textarea("a b c d");
+        press(&mut t, "\u{1b}0xwyw.");
+        assert_eq!(t.text(), "  c d");
+    }
+
+    #[test]
+    fn open_line_above_and_below() {
+        let mut t = vi_textarea("middle");
+        press(&mut t, "\u{1b}obelow\u{1b}ggOabove\u{1b}");
+        assert_eq!(t.text(), "above\nmiddle\nbelow");
+    }
+
+    #[test]
+    fn visual_mode_selects_and_operates() {
+        let mut t = vi_textarea("hello world");
+        press(&mut t, "\u{1b}0vly");
+        assert_eq!(t.vi_mode(), Some(ViMode::Normal));
+        assert_eq!(t.text(), "hello world");
+        press(&mut t, "$p");
+        assert_eq!(t.text(), "hello worldhe");
+
+        let mut t = vi_textarea("hello world");
+        press(&mut t, "\u{1b}0ve");
+        assert_eq!(t.vi_selection(), Some(0..5));
+        press(&mut t, "d");
+        assert_eq!(t.text(), " world");
+
+        let mut t = vi_textarea("hello world");
+        press(&mut t, "\u{1b}0wvecthere\u{1b}");
+        assert_eq!(t.text(), "hello there");
+    }
+
+    #[test]
+    fn normal_mode_never_inserts_plain_characters() {
+        let mut t = vi_textarea("abc");
+        press(&mut t, "\u{1b}zqZ");
+        assert_eq!(t.text(), "abc");
+    }
+
+    #[test]
+    fn operators_respect_atomic_elements() {
+        let mut t = TextArea::new();
+        t.set_vi_mode_enabled(true);
+        t.insert_str("a ");
+        t.insert_element("[Image #1]");
+        t.insert_str(" b");
+        press(&mut t, "\u{1b}0wdw");
+        assert_eq!(t.text(), "a b");
+        assert!(t.element_payloads().is_empty());
+    }
+
+    #[test]
+    fn huge_counts_are_clamped_and_stop_at_the_buffer_edge() {
+        let mut t = vi_textarea("one\ntwo\nthree");
+        press(&mut t, "\u{1b}gg99999999999999999999j");
+        assert!(t.cursor() >= "one\ntwo\n".len());
+        press(&mut t, "099999999999999999999w");
+        assert_eq!(t.cursor(), "one\ntwo\nthre".len());
+
+        let mut t = vi_textarea("ab");
+        press(&mut t, "\u{1b}0x99999999999999999999.");
+        assert_eq!(t.text(), "");
+    }
+
+    #[test]
+    fn put_with_a_huge_count_is_bounded() {
+        let mut t = vi_textarea("ab");
+        press(&mut t, "\u{1b}0yl99999999999999999999p");
+        assert_eq!(t.text().len(), 2 + MAX_COUNT);
+
+        let mut t = vi_textarea(&"x".repeat(MAX_PUT_BYTES / 4));
+        press(&mut t, "\u{1b}yy9999P");
+        assert!(t.text().len() <= MAX_PUT_BYTES + MAX_PUT_BYTES / 4 + 1);
+    }
+
+    #[test]
+    fn clearing_text_returns_to_insert_mode() {
+        let mut t = vi_textarea("draft");
+        press(&mut t, "\u{1b}");
+        t.set_text_clearing_elements("");
+        assert_eq!(t.vi_mode(), Some(ViMode::Insert));
+    }
+}
@@ -34,6 +34,7 @@ use codex_backend_client::Client as BackendClient;
 use codex_chatgpt::connectors;
 use codex_core::config::Config;
 use codex_core::config::ConstraintResult;
+use codex_core::config::types::EditingMode;
 use codex_core::config::types::Notifications;
 use codex_core::features::FEATURES;
 use codex_core::features::Feature;
@@ -47,6 +48,7 @@ use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
+use codex_core::protocol::AgentTreeUsage;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
 use codex_core::protocol::CodexErrorInfo;
@@ -61,6 +63,7 @@ use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::ExecCommandSource;
 use codex_core::protocol::ExitedReviewModeEvent;
+use codex_core::protocol::HookCompletedEvent;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
 use codex_core::protocol::ListSkillsResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
@@ -163,6 +166,7 @@ use crate::diff_render::display_path_for;
 use crate::exec_cell::CommandOutput;
 use crate::exec_cell::ExecCell;
 use crate::exec_cell::new_active_exec_command;
+use crate::exec_command::escape_command;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::get_git_diff::get_git_diff;
 use crate::history_cell;
@@ -183,6 +187,7 @@ use crate::render::renderable::RenderableItem;
 use crate::slash_command::SlashCommand;
 use crate::status::RateLimitSnapshotDisplay;
 use crate::text_formatting::truncate_text;
+use crate::theme;
 use crate::tui::FrameRequester;
 mod interrupts;
 use self::interrupts::InterruptManager;
@@ -190,6 +195,7 @@ mod agent;
 use self::agent::spawn_agent;
 use self::agent::spawn_agent_from_existing;
 pub(crate) use self::agent::spawn_op_forwarder;
+mod session_approvals;
 mod session_header;
 use self::session_header::SessionHeader;
 mod skills;
@@ -484,6 +490,9 @@ pub(crate) struct ChatWidget {
     initial_user_message: Option<UserMessage>,
     token_info: Option<TokenUsageInfo>,
     rate_limit_snapshot: Option<RateLimitSnapshotDisplay>,
+    /// Latest usage of this session's sub-agent tree, from `AgentTreeUsage` and
+    /// `CollabWaitingEnd` events.
+    agent_tree_usage: Option<AgentTreeUsage>,
     plan_type: Option<PlanType>,
     rate_limit_warnings: RateLimitWarningState,
     rate_limit_switch_prompt: RateLimitSwitchPromptState,
@@ -1807,6 +1816,49 @@ impl ChatWidget {
         self.request_redraw();
     }
 
+    /// Successful hook runs stay quiet; blocks and failures are surfaced so the user knows
+    /// why a prompt or tool call did not go through.
+    fn on_hook_completed(&mut self, event: HookCompletedEvent) {
+        let HookCompletedEvent {
+            hook,
+            command,
+            tool_name,
+            exit_code,
+            stderr,
+            timed_out,
+            blocked,
+            reason,
+            ..
+        } = event;
+        let outcome = if blocked {
+            "blocked"
+        } else if timed_out {
+            "timed out"
+        } else if exit_code == Some(0) {
+            return;
+        } else {
+            "failed"
+        };
+        let target = tool_name
+            .map(|name| format!(" for {name}"))
+            .unwrap_or_default();
+        let mut message = format!(
+            "{hook} hook `{}` {outcome}{target}",
+            escape_command(&command)
+        );
+        let detail = reason.or_else(|| {
+            stderr
+                .lines()
+                .find(|line| !line.trim().is_empty())
+                .map(|line| line.trim().to_string())
+        });
+        if let Some(detail) = detail {
+            message.push_str(": ");
+            message.push_str(&detail);
+        }
+        self.on_warning(message);
+    }
+
     fn on_background_event(&mut self, message: String) {
         debug!("BackgroundEvent: {message}");
         self.bottom_pane.ensure_status_indicator();
@@ -2030,10 +2082,16 @@ impl ChatWidget {
         event: codex_core::protocol::PatchApplyEndEvent,
     ) {
         // If the patch was successful, just let the "Edited" block stand.
-        // Otherwise, add a failure block.
+        // Otherwise, add a failure block. Post-edit commands only show up
+        // when they fail.
         if !event.success {
             self.add_to_history(history_cell::new_patch_apply_failure(event.stderr));
         }
+        for result in event.post_edit {
+            if result.exit_code != 0 {
+                self.add_to_history(history_cell::new_post_edit_failure(result));
+            }
+        }
         // Mark that actual work was done (patch applied)
         self.had_work_activity = true;
     }
@@ -2285,6 +2343,7 @@ impl ChatWidget {
             initial_user_message,
             token_info: None,
             rate_limit_snapshot: None,
+            agent_tree_usage: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
             rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
@@ -2335,6 +2394,9 @@ impl ChatWidget {
         widget
             .bottom_pane
             .set_steer_enabled(widget.config.features.enabled(Feature::Steer));
+        widget
+            .bottom_pane
+            .set_vi_mode_enabled(widget.config.tui_editing_mode == EditingMode::Vi);
         widget.bottom_pane.set_collaboration_modes_enabled(
             widget.config.features.enabled(Feature::CollaborationModes),
         );
@@ -2431,6 +2493,7 @@ impl ChatWidget {
             initial_user_message,
             token_info: None,
             rate_limit_snapshot: None,
+            agent_tree_usage: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
             rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
@@ -2481,6 +2544,9 @@ impl ChatWidget {
         widget
             .bottom_pane
             .set_steer_enabled(widget.config.features.enabled(Feature::Steer));
+        widget
+            .bottom_pane
+            .set_vi_mode_enabled(widget.config.tui_editing_mode == EditingMode::Vi);
         widget.bottom_pane.set_collaboration_modes_enabled(
             widget.config.features.enabled(Feature::CollaborationModes),
         );
@@ -2566,6 +2632,7 @@ impl ChatWidget {
             initial_user_message,
             token_info: None,
             rate_limit_snapshot: None,
+            agent_tree_usage: None,
             plan_type: None,
             rate_limit_warnings: RateLimitWarningState::default(),
             rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
@@ -2616,6 +2683,9 @@ impl ChatWidget {
         widget
             .bottom_pane
             .set_steer_enabled(widget.config.features.enabled(Feature::Steer));
+        widget
+            .bottom_pane
+            .set_vi_mode_enabled(widget.config.tui_editing_mode == EditingMode::Vi);
         widget.bottom_pane.set_collaboration_modes_enabled(
             widget.config.features.enabled(Feature::CollaborationModes),
         );
@@ -2883,6 +2953,9 @@ impl ChatWidget {
             SlashCommand::Personality => {
                 self.open_personality_popup();
             }
+            SlashCommand::Theme => {
+                self.open_theme_popup();
+            }
             SlashCommand::Plan => {
                 if !self.collaboration_modes_enabled() {
                     self.add_info_message(
@@ -3047,6 +3120,7 @@ impl ChatWidget {
                                 PathBuf::from("/tmp/test.txt"),
                                 FileChange::Add {
                                     content: "test".to_string(),
+                                    mode: None,
                                 },
                             ),
                             (
@@ -3054,6 +3128,7 @@ impl ChatWidget {
                                 FileChange::Update {
                                     unified_diff: "+test\n-test2".to_string(),
                                     move_path: None,
+                                    mode: None,
                                 },
                             ),
                         ]),
@@ -3131,6 +3206,28 @@ impl ChatWidget {
                     self.queue_user_message(user_message);
                 }
             }
+            SlashCommand::Compact if trimmed == "--dry-run" => {
+                if self
+                    .bottom_pane
+                    .prepare_inline_args_submission(false)
+                    .is_none()
+                {
+                    return;
+                }
+                self.submit_op(Op::PreviewCompact);
+                self.bottom_pane.drain_pending_submission_state();
+            }
+            SlashCommand::Approvals if trimmed == "list" => {
+                if self
+                    .bottom_pane
+                    .prepare_inline_args_submission(false)
+                    .is_none()
+                {
+                    return;
+                }
+                self.submit_op(Op::ListSessionApprovals);
+                self.bottom_pane.drain_pending_submission_state();
+            }
             SlashCommand::Review if !trimmed.is_empty() => {
                 let Some((prepared_args, _prepared_elements)) =
                     self.bottom_pane.prepare_inline_args_submission(false)
@@ -3487,6 +3584,7 @@ impl ChatWidget {
                 self.set_token_info(ev.info);
                 self.on_rate_limit_snapshot(ev.rate_limits);
             }
+            EventMsg::AgentTreeUsage(ev) => self.agent_tree_usage = Some(ev.usage),
             EventMsg::Warning(WarningEvent { message }) => self.on_warning(message),
             EventMsg::Error(ErrorEvent {
                 message,
@@ -3542,6 +3640,7 @@ impl ChatWidget {
             EventMsg::PatchApplyEnd(ev) => self.on_patch_apply_end(ev),
             EventMsg::ExecCommandEnd(ev) => self.on_exec_command_end(ev),
             EventMsg::ViewImageToolCall(ev) => self.on_view_image_tool_call(ev),
+            EventMsg::HookCompleted(ev) => self.on_hook_completed(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
             EventMsg::McpToolCallEnd(ev) => self.on_mcp_tool_call_end(ev),
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
@@ -3551,6 +3650,10 @@ impl ChatWidget {
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
             EventMsg::ListSkillsResponse(ev) => self.on_list_skills(ev),
             EventMsg::ListRemoteSkillsResponse(_) | EventMsg::RemoteSkillDownloaded(_) => {}
+            EventMsg::ListSessionApprovalsResponse(ev) => self.on_list_session_approvals(ev),
+            EventMsg::CompactionPreview(ev) => {
+                self.add_to_history(history_cell::new_compaction_preview(ev));
+            }
             EventMsg::SkillsUpdateAvailable => {
                 self.submit_op(Op::ListSkills {
                     cwds: Vec::new(),
@@ -3587,7 +3690,12 @@ impl ChatWidget {
                 self.on_collab_event(collab::interaction_end(ev))
             }
             EventMsg::CollabWaitingBegin(ev) => self.on_collab_event(collab::waiting_begin(ev)),
-            EventMsg::CollabWaitingEnd(ev) => self.on_collab_event(collab::waiting_end(ev)),
+            EventMsg::CollabWaitingEnd(ev) => {
+                if let Some(tree_usage) = ev.tree_usage {
+                    self.agent_tree_usage = Some(tree_usage);
+                }
+                self.on_collab_event(collab::waiting_end(ev))
+            }
             EventMsg::CollabCloseBegin(_) => {}
             EventMsg::CollabCloseEnd(ev) => self.on_collab_event(collab::close_end(ev)),
             EventMsg::ThreadRolledBack(_) => {}
@@ -3777,6 +3885,7 @@ impl ChatWidget {
             self.model_display_name(),
             collaboration_mode,
             reasoning_effort_override,
+            self.agent_tree_usage.as_ref(),
         ));
     }
 
@@ -4053,6 +4162,54 @@ impl ChatWidget {
         });
     }
 
+    pub(crate) fn open_theme_popup(&mut self) {
+        let current_theme = theme::current_name();
+        let items: Vec<SelectionItem> = theme::available_theme_names(&self.config.tui_themes)
+            .into_iter()
+            .map(|name| {
+                let description = Self::theme_description(&name).map(str::to_string);
+                let is_current = name == current_theme;
+                let selected = name.clone();
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::UpdateTheme(selected.clone()));
+                    tx.send(AppEvent::PersistThemeSelection {
+                        name: selected.clone(),
+                    });
+                })];
+                SelectionItem {
+                    name,
+                    description,
+                    is_current,
+                    actions,
+                    dismiss_on_select: true,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let mut header = ColumnRenderable::new();
+        header.push(Line::from("Select Theme".bold()));
+        header.push(Line::from(
+            "Choose colors for diffs, markdown and highlights.".dim(),
+        ));
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            header: Box::new(header),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    fn theme_description(name: &str) -> Option<&'static str> {
+        match name {
+            "dark" => Some("Default palette for dark terminal backgrounds."),
+            "light" => Some("Blue accents that stay readable on light backgrounds."),
+            "high-contrast" => Some("Bright colors for maximum legibility."),
+            _ => None,
+        }
+    }
+
     fn model_menu_header(&self, title: &str, subtitle: &str) -> Box<dyn Renderable> {
         let title = title.to_string();
         let subtitle = subtitle.to_string();
@@ -6015,6 +6172,24 @@ impl ChatWidget {
             ..Default::default()
         });
 
+        items.push(SelectionItem {
+            name: "Review a commit range".to_string(),
+            actions: vec![Box::new(move |tx| {
+                tx.send(AppEvent::OpenReviewRangePrompt);
+            })],
+            dismiss_on_select: false,
+            ..Default::default()
+        });
+
+        items.push(SelectionItem {
+            name: "Review a patch file".to_string(),
+            actions: vec![Box::new(move |tx| {
+                tx.send(AppEvent::OpenReviewPatchPrompt);
+            })],
+            dismiss_on_select: false,
+            ..Default::default()
+        });
+
         items.push(SelectionItem {
             name: "Custom review instructions".to_string(),
             actions: vec![Box::new(move |tx| {
@@ -6107,6 +6282,56 @@ impl ChatWidget {
         });
     }
 
+    pub(crate) fn show_review_range_prompt(&mut self) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Review a commit range".to_string(),
+            "Type a range like main..HEAD and press Enter".to_string(),
+            None,
+            Box::new(move |range: String| {
+                if range.trim().is_empty() {
+                    return;
+                }
+                match codex_core::review_prompts::parse_commit_range(&range) {
+                    Ok(target) => tx.send(AppEvent::CodexOp(Op::Review {
+                        review_request: ReviewRequest {
+                            target,
+                            user_facing_hint: None,
+                        },
+                    })),
+                    Err(err) => tx.send(AppEvent::InsertHistoryCell(Box::new(
+                        history_cell::new_error_event(err.to_string()),
+                    ))),
+                }
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    pub(crate) fn show_review_patch_prompt(&mut self) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Review a patch file".to_string(),
+            "Type the path to a patch file and press Enter".to_string(),
+            None,
+            Box::new(move |path: String| {
+                let trimmed = path.trim();
+                if trimmed.is_empty() {
+                    return;
+                }
+                tx.send(AppEvent::CodexOp(Op::Review {
+                    review_request: ReviewRequest {
+                        target: ReviewTarget::PatchFile {
+                            path: PathBuf::from(trimmed),
+                        },
+                        user_facing_hint: None,
+                    },
+                }));
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
     pub(crate) fn show_review_custom_prompt(&mut self) {
         let tx = self.app_event_tx.clone();
         let view = CustomPromptView::new(
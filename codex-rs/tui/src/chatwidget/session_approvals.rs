@@ -0,0 +1,139 @@
+use chrono::DateTime;
+use chrono::Utc;
+
+use super::ChatWidget;
+use crate::app_event::AppEvent;
+use crate::bottom_pane::SelectionAction;
+use crate::bottom_pane::SelectionItem;
+use crate::bottom_pane::SelectionViewParams;
+use crate::bottom_pane::popup_consts::standard_popup_hint_line;
+use crate::exec_command::escape_command;
+use crate::status::format_directory_display;
+use codex_core::protocol::ListSessionApprovalsResponseEvent;
+use codex_core::protocol::Op;
+use codex_core::protocol::SessionApprovalEntry;
+use codex_core::protocol::SessionApprovalKind;
+
+impl ChatWidget {
+    pub(crate) fn on_list_session_approvals(&mut self, ev: ListSessionApprovalsResponseEvent) {
+        if ev.approvals.is_empty() {
+            self.add_info_message(
+                "No approvals are remembered for this session.".to_string(),
+                Some(
+                    "Approvals you ask not to be prompted for again will appear here.".to_string(),
+                ),
+            );
+            return;
+        }
+
+        let now = Utc::now();
+        let items: Vec<SelectionItem> = ev
+            .approvals
+            .into_iter()
+            .map(|entry| {
+                let name = session_approval_label(&entry.kind);
+                let description = Some(session_approval_description(&entry, now));
+                let id = entry.id;
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                    tx.send(AppEvent::CodexOp(Op::RevokeSessionApproval {
+                        id: id.clone(),
+                    }));
+                })];
+                SelectionItem {
+                    name,
+                    description,
+                    actions,
+                    dismiss_on_select: true,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: Some("Session approvals".to_string()),
+            subtitle: Some("Press enter to revoke an approval.".to_string()),
+            footer_hint: Some(standard_popup_hint_line()),
+            items,
+            ..Default::default()
+        });
+    }
+}
+
+fn session_approval_label(kind: &SessionApprovalKind) -> String {
+    match kind {
+        SessionApprovalKind::Command { command, .. } => format!("$ {}", escape_command(command)),
+        SessionApprovalKind::PatchPath { path } => {
+            format!("edit {}", format_directory_display(path, None))
+        }
+        SessionApprovalKind::CommandPrefix { prefix } => {
+            format!("$ {} …", escape_command(prefix))
+        }
+    }
+}
+
+fn session_approval_description(entry: &SessionApprovalEntry, now: DateTime<Utc>) -> String {
+    let mut parts = Vec::new();
+    match &entry.kind {
+        SessionApprovalKind::Command { cwd, .. } => {
+            parts.push(format!("in {}", format_directory_display(cwd, None)));
+        }
+        SessionApprovalKind::CommandPrefix { .. } => parts.push("saved to rules".to_string()),
+        SessionApprovalKind::PatchPath { .. } => {}
+    }
+    parts.push(format!(
+        "approved {} ago",
+        format_age(now.timestamp() - entry.approved_at)
+    ));
+    if let Some(expires_at) = entry.expires_at {
+        parts.push(format!(
+            "expires in {}",
+            format_age(expires_at - now.timestamp())
+        ));
+    }
+    parts.join(" · ")
+}
+
+fn format_age(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn formats_ages_in_the_largest_whole_unit() {
+        assert_eq!(format_age(-5), "0s");
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(61), "1m");
+        assert_eq!(format_age(7200), "2h");
+        assert_eq!(format_age(3 * 86400), "3d");
+    }
+
+    #[test]
+    fn describes_command_approvals_with_expiry() {
+        let entry = SessionApprovalEntry {
+            id: "1".to_string(),
+            kind: SessionApprovalKind::Command {
+                command: vec!["cargo".to_string(), "test".to_string()],
+                cwd: "/repo".into(),
+            },
+            approved_at: 1_000,
+            expires_at: Some(1_000 + 1_800),
+        };
+        let now = DateTime::from_timestamp(1_000 + 120, 0).expect("timestamp");
+
+        assert_eq!(session_approval_label(&entry.kind), "$ cargo test");
+        assert_eq!(
+            session_approval_description(&entry, now),
+            "in /repo · approved 2m ago · expires in 28m"
+        );
+    }
+}
@@ -28,6 +28,8 @@ use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::AgentTreeUsage;
+use codex_core::protocol::AgentTreeUsageEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
 use codex_core::protocol::CreditsSnapshot;
@@ -676,6 +678,25 @@ async fn review_restores_context_window_indicator() {
     assert!(!chat.is_review_mode);
 }
 
+/// Agent tree usage updates are kept for `/status` without waiting for a `wait` call.
+#[tokio::test]
+async fn agent_tree_usage_event_updates_status_usage() {
+    let (mut chat, _rx, _ops) = make_chatwidget_manual(None).await;
+    let usage = AgentTreeUsage {
+        total_tokens: 1_200,
+        elapsed_secs: 30,
+        token_budget: Some(10_000),
+        wall_clock_budget_secs: None,
+    };
+
+    chat.handle_codex_event(Event {
+        id: String::new(),
+        msg: EventMsg::AgentTreeUsage(AgentTreeUsageEvent { usage }),
+    });
+
+    assert_eq!(chat.agent_tree_usage, Some(usage));
+}
+
 /// Receiving a TokenCount event without usage clears the context indicator.
 #[tokio::test]
 async fn token_count_none_resets_context_indicator() {
@@ -848,6 +869,7 @@ async fn make_chatwidget_manual(
         initial_user_message: None,
         token_info: None,
         rate_limit_snapshot: None,
+        agent_tree_usage: None,
         plan_type: None,
         rate_limit_warnings: RateLimitWarningState::default(),
         rate_limit_switch_prompt: RateLimitSwitchPromptState::default(),
@@ -2289,10 +2311,10 @@ async fn review_popup_custom_prompt_action_sends_event() {
     // Open the preset selection popup
     chat.open_review_popup();
 
-    // Move selection down to the fourth item: "Custom review instructions"
-    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
-    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
-    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    // Move selection down to the sixth item: "Custom review instructions"
+    for _ in 0..5 {
+        chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    }
     // Activate
     chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
 
@@ -2307,6 +2329,31 @@ async fn review_popup_custom_prompt_action_sends_event() {
     assert!(found, "expected OpenReviewCustomPrompt event to be sent");
 }
 
+/// Entering a commit range in the review prompt submits a CommitRange review.
+#[tokio::test]
+async fn review_range_prompt_submits_commit_range() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual(None).await;
+
+    chat.show_review_range_prompt();
+    chat.handle_paste("main..feature".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+    let mut target = None;
+    while let Ok(ev) = rx.try_recv() {
+        if let AppEvent::CodexOp(Op::Review { review_request }) = ev {
+            target = Some(review_request.target);
+            break;
+        }
+    }
+    assert_eq!(
+        target,
+        Some(ReviewTarget::CommitRange {
+            from: "main".to_string(),
+            to: "feature".to_string(),
+        })
+    );
+}
+
 #[tokio::test]
 async fn slash_init_skips_when_project_doc_exists() {
     let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual(None).await;
@@ -3890,6 +3937,7 @@ async fn approval_modal_patch_snapshot() -> anyhow::Result<()> {
         PathBuf::from("README.md"),
         FileChange::Add {
             content: "hello\nworld\n".into(),
+            mode: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -4275,6 +4323,7 @@ async fn apply_patch_events_emit_history_cells() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -4316,6 +4365,7 @@ async fn apply_patch_events_emit_history_cells() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     let begin = PatchApplyBeginEvent {
@@ -4342,6 +4392,7 @@ async fn apply_patch_events_emit_history_cells() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     let end = PatchApplyEndEvent {
@@ -4351,6 +4402,8 @@ async fn apply_patch_events_emit_history_cells() {
         stderr: String::new(),
         success: true,
         changes: end_changes,
+        post_edit: Vec::new(),
+        outcome: None,
     };
     chat.handle_codex_event(Event {
         id: "s1".into(),
@@ -4372,6 +4425,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -4391,6 +4445,7 @@ async fn apply_patch_manual_approval_adjusts_header() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -4421,6 +4476,7 @@ async fn apply_patch_manual_flow_snapshot() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -4444,6 +4500,7 @@ async fn apply_patch_manual_flow_snapshot() {
         PathBuf::from("foo.txt"),
         FileChange::Add {
             content: "hello\n".to_string(),
+            mode: None,
         },
     );
     chat.handle_codex_event(Event {
@@ -4474,6 +4531,7 @@ async fn apply_patch_approval_sends_op_with_submission_id() {
         PathBuf::from("file.rs"),
         FileChange::Add {
             content: "fn main(){}\n".into(),
+            mode: None,
         },
     );
     let ev = ApplyPatchApprovalRequestEvent {
@@ -4512,7 +4570,10 @@ async fn apply_patch_full_flow_integration_like() {
     let mut changes = HashMap::new();
     changes.insert(
         PathBuf::from("pkg.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            mode: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-xyz".into(),
@@ -4553,7 +4614,10 @@ async fn apply_patch_full_flow_integration_like() {
     let mut changes2 = HashMap::new();
     changes2.insert(
         PathBuf::from("pkg.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            mode: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-xyz".into(),
@@ -4567,7 +4631,10 @@ async fn apply_patch_full_flow_integration_like() {
     let mut end_changes = HashMap::new();
     end_changes.insert(
         PathBuf::from("pkg.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            mode: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-xyz".into(),
@@ -4578,6 +4645,8 @@ async fn apply_patch_full_flow_integration_like() {
             stderr: String::new(),
             success: true,
             changes: end_changes,
+            post_edit: Vec::new(),
+            outcome: None,
         }),
     });
 }
@@ -4592,7 +4661,10 @@ async fn apply_patch_untrusted_shows_approval_modal() -> anyhow::Result<()> {
     let mut changes = HashMap::new();
     changes.insert(
         PathBuf::from("a.rs"),
-        FileChange::Add { content: "".into() },
+        FileChange::Add {
+            content: "".into(),
+            mode: None,
+        },
     );
     chat.handle_codex_event(Event {
         id: "sub-1".into(),
@@ -4643,6 +4715,7 @@ async fn apply_patch_request_shows_diff_summary() -> anyhow::Result<()> {
         FileChange::Add {
             // Two lines (no trailing empty line counted)
             content: "line one\nline two\n".into(),
+            mode: None,
         },
     );
     chat.handle_codex_event(Event {
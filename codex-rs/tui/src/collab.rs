@@ -76,9 +76,13 @@ pub(crate) fn waiting_end(ev: CollabWaitingEndEvent) -> PlainHistoryCell {
         call_id,
         sender_thread_id: _,
         statuses,
+        tree_usage,
     } = ev;
     let mut details = vec![detail_line("call", call_id)];
     details.extend(wait_complete_lines(&statuses));
+    if let Some(tree_usage) = tree_usage {
+        details.push(detail_line("agent tree", tree_usage.to_string()));
+    }
     collab_event("Wait complete", details)
 }
 
@@ -1,7 +1,6 @@
 use diffy::Hunk;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::Color;
 use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::style::Stylize;
@@ -18,6 +17,7 @@ use crate::render::line_utils::prefix_lines;
 use crate::render::renderable::ColumnRenderable;
 use crate::render::renderable::InsetRenderable;
 use crate::render::renderable::Renderable;
+use crate::theme;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::FileChange;
 
@@ -100,7 +100,8 @@ fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
     let mut rows: Vec<Row> = Vec::new();
     for (path, change) in changes.iter() {
         let (added, removed) = match change {
-            FileChange::Add { content } => (content.lines().count(), 0),
+            FileChange::Add { content, .. } => (content.lines().count(), 0),
+            FileChange::AddBinary { .. } => (0, 0),
             FileChange::Delete { content } => (0, content.lines().count()),
             FileChange::Update { unified_diff, .. } => calculate_add_remove_from_diff(unified_diff),
         };
@@ -126,9 +127,9 @@ fn collect_rows(changes: &HashMap<PathBuf, FileChange>) -> Vec<Row> {
 fn render_line_count_summary(added: usize, removed: usize) -> Vec<RtSpan<'static>> {
     let mut spans = Vec::new();
     spans.push("(".into());
-    spans.push(format!("+{added}").green());
+    spans.push(format!("+{added}").fg(theme::current().diff_add));
     spans.push(" ".into());
-    spans.push(format!("-{removed}").red());
+    spans.push(format!("-{removed}").fg(theme::current().diff_delete));
     spans.push(")".into());
     spans
 }
@@ -153,7 +154,7 @@ fn render_changes_block(rows: Vec<Row>, wrap_cols: usize, cwd: &Path) -> Vec<RtL
     let mut header_spans: Vec<RtSpan<'static>> = vec!["• ".dim()];
     if let [row] = &rows[..] {
         let verb = match &row.change {
-            FileChange::Add { .. } => "Added",
+            FileChange::Add { .. } | FileChange::AddBinary { .. } => "Added",
             FileChange::Delete { .. } => "Deleted",
             _ => "Edited",
         };
@@ -194,8 +195,21 @@ fn render_changes_block(rows: Vec<Row>, wrap_cols: usize, cwd: &Path) -> Vec<RtL
 }
 
 fn render_change(change: &FileChange, out: &mut Vec<RtLine<'static>>, width: usize) {
+    let mode = match change {
+        FileChange::Add { mode, .. }
+        | FileChange::AddBinary { mode, .. }
+        | FileChange::Update { mode, .. } => *mode,
+        FileChange::Delete { .. } => None,
+    };
+    if let Some(mode) = mode {
+        out.push(RtLine::from(format!("mode {mode:o}").dim()));
+    }
     match change {
-        FileChange::Add { content } => {
+        FileChange::AddBinary { size, .. } => {
+            let noun = if *size == 1 { "byte" } else { "bytes" };
+            out.push(RtLine::from(format!("binary file, {size} {noun}").dim()));
+        }
+        FileChange::Add { content, .. } => {
             let line_number_width = line_number_width(content.lines().count());
             for (i, raw) in content.lines().enumerate() {
                 out.extend(push_wrapped_diff_line(
@@ -420,11 +434,11 @@ fn style_context() -> Style {
 }
 
 fn style_add() -> Style {
-    Style::default().fg(Color::Green)
+    Style::default().fg(theme::current().diff_add)
 }
 
 fn style_del() -> Style {
-    Style::default().fg(Color::Red)
+    Style::default().fg(theme::current().diff_delete)
 }
 
 #[cfg(test)]
@@ -516,6 +530,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                mode: None,
             },
         );
 
@@ -536,6 +551,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: Some(PathBuf::from("new_name.rs")),
+                mode: None,
             },
         );
 
@@ -556,6 +572,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch_a,
                 move_path: None,
+                mode: None,
             },
         );
 
@@ -564,6 +581,7 @@ mod tests {
             PathBuf::from("b.txt"),
             FileChange::Add {
                 content: "new\n".to_string(),
+                mode: None,
             },
         );
 
@@ -579,6 +597,7 @@ mod tests {
             PathBuf::from("new_file.txt"),
             FileChange::Add {
                 content: "alpha\nbeta\n".to_string(),
+                mode: None,
             },
         );
 
@@ -587,6 +606,30 @@ mod tests {
         snapshot_lines("apply_add_block", lines, 80, 10);
     }
 
+    #[test]
+    fn ui_snapshot_apply_binary_and_mode_block() {
+        let mut changes: HashMap<PathBuf, FileChange> = HashMap::new();
+        changes.insert(
+            PathBuf::from("fixture.png"),
+            FileChange::AddBinary {
+                size: 1024,
+                mode: None,
+            },
+        );
+        changes.insert(
+            PathBuf::from("run.sh"),
+            FileChange::Update {
+                unified_diff: String::new(),
+                move_path: None,
+                mode: Some(0o755),
+            },
+        );
+
+        let lines = diff_summary_for_tests(&changes);
+
+        snapshot_lines("apply_binary_and_mode_block", lines, 80, 10);
+    }
+
     #[test]
     fn ui_snapshot_apply_delete_block() {
         let mut changes: HashMap<PathBuf, FileChange> = HashMap::new();
@@ -614,6 +657,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                mode: None,
             },
         );
 
@@ -637,6 +681,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                mode: None,
             },
         );
 
@@ -664,6 +709,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: None,
+                mode: None,
             },
         );
 
@@ -687,6 +733,7 @@ mod tests {
             FileChange::Update {
                 unified_diff: patch,
                 move_path: Some(abs_new),
+                mode: None,
             },
         );
 
@@ -17,6 +17,7 @@ use crate::exec_cell::OutputLinesParams;
 use crate::exec_cell::TOOL_CALL_MAX_LINES;
 use crate::exec_cell::output_lines;
 use crate::exec_cell::spinner;
+use crate::exec_command::escape_command;
 use crate::exec_command::relativize_to_home;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::live_wrap::take_prefix_by_width;
@@ -25,6 +26,7 @@ use crate::render::line_utils::line_to_static;
 use crate::render::line_utils::prefix_lines;
 use crate::render::line_utils::push_owned_lines;
 use crate::render::renderable::Renderable;
+use crate::status::format_tokens_compact;
 use crate::style::proposed_plan_style;
 use crate::style::user_message_style;
 use crate::text_formatting::format_and_truncate_tool_result;
@@ -40,9 +42,12 @@ use base64::Engine;
 use codex_common::format_env_display::format_env_display;
 use codex_core::config::Config;
 use codex_core::config::types::McpServerTransportConfig;
+use codex_core::protocol::CompactionPreviewAction;
+use codex_core::protocol::CompactionPreviewEvent;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpAuthStatus;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::PostEditCommandResult;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::web_search::web_search_detail;
 use codex_otel::RuntimeMetricsSummary;
@@ -714,11 +719,12 @@ pub fn new_approval_decision_cell(
 ) -> Box<dyn HistoryCell> {
     use codex_core::protocol::ReviewDecision::*;
 
+    let theme = crate::theme::current();
     let (symbol, summary): (Span<'static>, Vec<Span<'static>>) = match decision {
         Approved => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
-                "✔ ".green(),
+                "✔ ".fg(theme.decision_approved),
                 vec![
                     "You ".into(),
                     "approved".bold(),
@@ -733,7 +739,7 @@ pub fn new_approval_decision_cell(
         } => {
             let snippet = Span::from(exec_snippet(&proposed_execpolicy_amendment.command)).dim();
             (
-                "✔ ".green(),
+                "✔ ".fg(theme.decision_approved),
                 vec![
                     "You ".into(),
                     "approved".bold(),
@@ -745,7 +751,7 @@ pub fn new_approval_decision_cell(
         ApprovedForSession => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
-                "✔ ".green(),
+                "✔ ".fg(theme.decision_approved),
                 vec![
                     "You ".into(),
                     "approved".bold(),
@@ -758,7 +764,7 @@ pub fn new_approval_decision_cell(
         Denied => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
-                "✗ ".red(),
+                "✗ ".fg(theme.decision_denied),
                 vec![
                     "You ".into(),
                     "did not approve".bold(),
@@ -770,7 +776,7 @@ pub fn new_approval_decision_cell(
         Abort => {
             let snippet = Span::from(exec_snippet(&command)).dim();
             (
-                "✗ ".red(),
+                "✗ ".fg(theme.decision_denied),
                 vec![
                     "You ".into(),
                     "canceled".bold(),
@@ -2083,6 +2089,99 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+/// Result of `/compact --dry-run`. Runs of more than
+/// [`COMPACTION_PREVIEW_MAX_RUN`] items with the same action are collapsed.
+pub(crate) fn new_compaction_preview(preview: CompactionPreviewEvent) -> PlainHistoryCell {
+    let mut header: Vec<Span<'static>> = vec![
+        "• ".dim(),
+        "Compaction preview".bold(),
+        format!(" ({})", preview.strategy).dim(),
+    ];
+    if let Some(model) = preview.summary_model {
+        header.push(format!(" · summary by {model}").dim());
+    }
+    let mut lines: Vec<Line<'static>> = vec![header.into()];
+
+    let mut size = format!(
+        "  {} tokens now",
+        format_tokens_compact(preview.tokens_before)
+    );
+    if let Some(after) = preview.estimated_tokens_after {
+        size.push_str(&format!(", about {} after", format_tokens_compact(after)));
+    }
+    lines.push(size.dim().into());
+
+    if preview.items.is_empty() {
+        lines.push("  (history is empty)".dim().into());
+        return PlainHistoryCell { lines };
+    }
+
+    for run in preview.items.chunk_by(|a, b| a.action == b.action) {
+        if run.len() > COMPACTION_PREVIEW_MAX_RUN {
+            let tokens = run.iter().map(|item| item.tokens).sum();
+            let description = format!("{} items", run.len());
+            lines.push(compaction_preview_line(run[0].action, description, tokens));
+        } else {
+            lines.extend(run.iter().map(|item| {
+                compaction_preview_line(item.action, item.description.clone(), item.tokens)
+            }));
+        }
+    }
+
+    PlainHistoryCell { lines }
+}
+
+const COMPACTION_PREVIEW_MAX_RUN: usize = 3;
+
+fn compaction_preview_line(
+    action: CompactionPreviewAction,
+    description: String,
+    tokens: i64,
+) -> Line<'static> {
+    let action = match action {
+        CompactionPreviewAction::Keep => "keep     ".green(),
+        CompactionPreviewAction::Summarize => "summarize".cyan(),
+        CompactionPreviewAction::Drop => "drop     ".red(),
+    };
+    Line::from(vec![
+        "  ".into(),
+        action,
+        " ".into(),
+        description.into(),
+        format!(" ({} tokens)", format_tokens_compact(tokens)).dim(),
+    ])
+}
+
+/// A `[post_edit]` formatter or linter that exited non-zero after a patch.
+pub(crate) fn new_post_edit_failure(result: PostEditCommandResult) -> PlainHistoryCell {
+    let command = escape_command(&result.command);
+    let mut lines: Vec<Line<'static>> = vec![Line::from(vec![
+        "✘ ".red(),
+        "Post-edit ".bold(),
+        command.into(),
+        format!(" exited {}", result.exit_code).dim(),
+    ])];
+
+    if !result.output.trim().is_empty() {
+        let output = output_lines(
+            Some(&CommandOutput {
+                exit_code: result.exit_code,
+                formatted_output: String::new(),
+                aggregated_output: result.output,
+            }),
+            OutputLinesParams {
+                line_limit: TOOL_CALL_MAX_LINES,
+                only_err: true,
+                include_angle_pipe: true,
+                include_prefix: true,
+            },
+        );
+        lines.extend(output.lines);
+    }
+
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_view_image_tool_call(path: PathBuf, cwd: &Path) -> PlainHistoryCell {
     let display_path = display_path_for(&path, cwd);
 
@@ -2272,9 +2371,11 @@ mod tests {
     use codex_core::config::ConfigBuilder;
     use codex_core::config::types::McpServerConfig;
     use codex_core::config::types::McpServerTransportConfig;
+    use codex_core::protocol::CompactionPreviewItem;
     use codex_core::protocol::McpAuthStatus;
     use codex_otel::RuntimeMetricTotals;
     use codex_otel::RuntimeMetricsSummary;
+    use codex_protocol::config_types::CompactionStrategy;
     use codex_protocol::models::WebSearchAction;
     use codex_protocol::parse_command::ParsedCommand;
     use dirs::home_dir;
@@ -2477,6 +2578,32 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn compaction_preview_snapshot() {
+        let item = |description: &str, tokens, action| CompactionPreviewItem {
+            description: description.to_string(),
+            tokens,
+            action,
+        };
+        let mut items = vec![item(
+            "user: fix the build",
+            12,
+            CompactionPreviewAction::Keep,
+        )];
+        items.extend((0..5).map(|_| item("output of shell", 4_200, CompactionPreviewAction::Drop)));
+        items.push(item("call shell", 30, CompactionPreviewAction::Keep));
+        items.push(item("output of shell", 900, CompactionPreviewAction::Keep));
+        let cell = new_compaction_preview(CompactionPreviewEvent {
+            strategy: CompactionStrategy::DropToolOutputs,
+            summary_model: None,
+            items,
+            tokens_before: 24_000,
+            estimated_tokens_after: Some(3_100),
+        });
+        let rendered = render_lines(&cell.display_lines(80)).join("\n");
+        insta::assert_snapshot!(rendered);
+    }
+
     #[tokio::test]
     async fn mcp_tools_output_masks_sensitive_values() {
         let mut config = test_config().await;
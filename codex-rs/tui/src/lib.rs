@@ -100,6 +100,7 @@ mod streaming;
 mod style;
 mod terminal_palette;
 mod text_formatting;
+mod theme;
 mod tooltips;
 mod tui;
 mod ui_consts;
@@ -448,6 +449,7 @@ async fn run_ratatui_app(
                         thread_id: None,
                         thread_name: None,
                         update_action: Some(action),
+                        kept_worktrees: Vec::new(),
                         exit_reason: ExitReason::UserRequested,
                     });
                 }
@@ -489,6 +491,7 @@ async fn run_ratatui_app(
                 thread_id: None,
                 thread_name: None,
                 update_action: None,
+                kept_worktrees: Vec::new(),
                 exit_reason: ExitReason::UserRequested,
             });
         }
@@ -517,6 +520,7 @@ async fn run_ratatui_app(
             thread_id: None,
             thread_name: None,
             update_action: None,
+            kept_worktrees: Vec::new(),
             exit_reason: ExitReason::Fatal(format!(
                 "No saved session found with ID {id_str}. Run `codex {action}` without an ID to choose from existing sessions."
             )),
@@ -573,6 +577,7 @@ async fn run_ratatui_app(
                         thread_id: None,
                         thread_name: None,
                         update_action: None,
+                        kept_worktrees: Vec::new(),
                         exit_reason: ExitReason::UserRequested,
                     });
                 }
@@ -631,6 +636,7 @@ async fn run_ratatui_app(
                     thread_id: None,
                     thread_name: None,
                     update_action: None,
+                    kept_worktrees: Vec::new(),
                     exit_reason: ExitReason::UserRequested,
                 });
             }
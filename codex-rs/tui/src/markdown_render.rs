@@ -35,6 +35,7 @@ impl Default for MarkdownStyles {
     fn default() -> Self {
         use ratatui::style::Stylize;
 
+        let theme = crate::theme::current();
         Self {
             h1: Style::new().bold().underlined(),
             h2: Style::new().bold(),
@@ -42,14 +43,14 @@ impl Default for MarkdownStyles {
             h4: Style::new().italic(),
             h5: Style::new().italic(),
             h6: Style::new().italic(),
-            code: Style::new().cyan(),
+            code: Style::new().fg(theme.markdown_code),
             emphasis: Style::new().italic(),
             strong: Style::new().bold(),
             strikethrough: Style::new().crossed_out(),
-            ordered_list_marker: Style::new().light_blue(),
+            ordered_list_marker: Style::new().fg(theme.markdown_list_marker),
             unordered_list_marker: Style::new(),
-            link: Style::new().cyan().underlined(),
-            blockquote: Style::new().green(),
+            link: Style::new().fg(theme.markdown_link).underlined(),
+            blockquote: Style::new().fg(theme.markdown_blockquote),
         }
     }
 }
@@ -42,6 +42,7 @@ pub enum SlashCommand {
     Rollout,
     Ps,
     Personality,
+    Theme,
     TestApproval,
 }
 
@@ -66,6 +67,7 @@ impl SlashCommand {
             SlashCommand::Ps => "list background terminals",
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Personality => "choose a communication style for Codex",
+            SlashCommand::Theme => "choose a color theme",
             SlashCommand::Plan => "switch to Plan mode",
             SlashCommand::Collab => "change collaboration mode (experimental)",
             SlashCommand::Agent => "switch the active agent thread",
@@ -91,7 +93,11 @@ impl SlashCommand {
     pub fn supports_inline_args(self) -> bool {
         matches!(
             self,
-            SlashCommand::Review | SlashCommand::Rename | SlashCommand::Plan
+            SlashCommand::Review
+                | SlashCommand::Rename
+                | SlashCommand::Plan
+                | SlashCommand::Approvals
+                | SlashCommand::Compact
         )
     }
 
@@ -119,6 +125,7 @@ impl SlashCommand {
             | SlashCommand::Skills
             | SlashCommand::Status
             | SlashCommand::Ps
+            | SlashCommand::Theme
             | SlashCommand::Mcp
             | SlashCommand::Apps
             | SlashCommand::Feedback
@@ -8,6 +8,7 @@ use chrono::Local;
 use codex_common::summarize_sandbox_policy;
 use codex_core::WireApi;
 use codex_core::config::Config;
+use codex_core::protocol::AgentTreeUsage;
 use codex_core::protocol::NetworkAccess;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TokenUsage;
@@ -38,6 +39,7 @@ use super::rate_limits::StatusRateLimitValue;
 use super::rate_limits::compose_rate_limit_data;
 use super::rate_limits::format_status_limit_summary;
 use super::rate_limits::render_status_limit_progress_bar;
+use crate::status_indicator_widget::fmt_elapsed_compact;
 use crate::wrapping::RtOptions;
 use crate::wrapping::word_wrap_lines;
 use codex_core::AuthManager;
@@ -72,6 +74,7 @@ struct StatusHistoryCell {
     session_id: Option<String>,
     forked_from: Option<String>,
     token_usage: StatusTokenUsageData,
+    agent_tree: Option<String>,
     rate_limits: StatusRateLimitData,
 }
 
@@ -90,6 +93,7 @@ pub(crate) fn new_status_output(
     model_name: &str,
     collaboration_mode: Option<&str>,
     reasoning_effort_override: Option<Option<ReasoningEffort>>,
+    agent_tree_usage: Option<&AgentTreeUsage>,
 ) -> CompositeHistoryCell {
     let command = PlainHistoryCell::new(vec!["/status".magenta().into()]);
     let card = StatusHistoryCell::new(
@@ -106,6 +110,7 @@ pub(crate) fn new_status_output(
         model_name,
         collaboration_mode,
         reasoning_effort_override,
+        agent_tree_usage,
     );
 
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(card)])
@@ -127,6 +132,7 @@ impl StatusHistoryCell {
         model_name: &str,
         collaboration_mode: Option<&str>,
         reasoning_effort_override: Option<Option<ReasoningEffort>>,
+        agent_tree_usage: Option<&AgentTreeUsage>,
     ) -> Self {
         let mut config_entries = vec![
             ("workdir", config.cwd.display().to_string()),
@@ -190,6 +196,7 @@ impl StatusHistoryCell {
             context_window,
         };
         let rate_limits = compose_rate_limit_data(rate_limits, now);
+        let agent_tree = agent_tree_usage.map(compose_agent_tree_summary);
 
         Self {
             model_name,
@@ -205,6 +212,7 @@ impl StatusHistoryCell {
             session_id,
             forked_from,
             token_usage,
+            agent_tree,
             rate_limits,
         }
     }
@@ -403,6 +411,9 @@ impl HistoryCell for StatusHistoryCell {
             push_label(&mut labels, &mut seen, "Collaboration mode");
         }
         push_label(&mut labels, &mut seen, "Token usage");
+        if self.agent_tree.is_some() {
+            push_label(&mut labels, &mut seen, "Sub-agents");
+        }
         if self.token_usage.context_window.is_some() {
             push_label(&mut labels, &mut seen, "Context window");
         }
@@ -412,15 +423,16 @@ impl HistoryCell for StatusHistoryCell {
         let formatter = FieldFormatter::from_labels(labels.iter().map(String::as_str));
         let value_width = formatter.value_width(available_inner_width);
 
+        let accent = crate::theme::current().status_accent;
         let note_first_line = Line::from(vec![
-            Span::from("Visit ").cyan(),
+            Span::from("Visit ").fg(accent),
             "https://chatgpt.com/codex/settings/usage"
-                .cyan()
+                .fg(accent)
                 .underlined(),
-            Span::from(" for up-to-date").cyan(),
+            Span::from(" for up-to-date").fg(accent),
         ]);
         let note_second_line = Line::from(vec![
-            Span::from("information on rate limits and credits").cyan(),
+            Span::from("information on rate limits and credits").fg(accent),
         ]);
         let note_lines = word_wrap_lines(
             [note_first_line, note_second_line],
@@ -476,6 +488,10 @@ impl HistoryCell for StatusHistoryCell {
             lines.push(formatter.line("Context window", spans));
         }
 
+        if let Some(agent_tree) = self.agent_tree.as_ref() {
+            lines.push(formatter.line("Sub-agents", vec![Span::from(agent_tree.clone())]));
+        }
+
         lines.extend(self.rate_limit_lines(available_inner_width, &formatter));
 
         let content_width = lines.iter().map(line_display_width).max().unwrap_or(0);
@@ -489,6 +505,18 @@ impl HistoryCell for StatusHistoryCell {
     }
 }
 
+fn compose_agent_tree_summary(usage: &AgentTreeUsage) -> String {
+    let mut summary = format!("{} tokens", format_tokens_compact(usage.total_tokens));
+    if let Some(budget) = usage.token_budget {
+        summary.push_str(&format!(" of {}", format_tokens_compact(budget)));
+    }
+    summary.push_str(&format!(", {}", fmt_elapsed_compact(usage.elapsed_secs)));
+    if let Some(budget) = usage.wall_clock_budget_secs {
+        summary.push_str(&format!(" of {}", fmt_elapsed_compact(budget)));
+    }
+    summary
+}
+
 fn format_model_provider(config: &Config) -> Option<String> {
     let provider = &config.model_provider;
     let name = provider.name.trim();
@@ -0,0 +1,352 @@
+//! Named color themes for the TUI.
+//!
+//! A [`Theme`] maps the handful of semantic roles the TUI colors (diff lines,
+//! markdown accents, footer mode labels, selection highlights, status accents,
+//! approval prompts and decisions) to concrete terminal colors. Three themes are built in; users can declare
+//! more under `[tui.themes.<name>]`, each starting from a built-in `base` and
+//! overriding individual roles. User colors are limited to the ANSI names so
+//! themes follow the same rules as `styles.md`; 256-color indexes and
+//! `#rrggbb` values are rejected.
+//!
+//! The active theme is process-global so renderers can read it without having
+//! the config threaded through every call site. It is set once at startup and
+//! again whenever the user picks a theme with `/theme`.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use codex_core::config::types::ThemeToml;
+use ratatui::style::Color;
+
+pub(crate) const DEFAULT_THEME_NAME: &str = "dark";
+
+/// Built-in theme names, in the order they are presented in `/theme`.
+pub(crate) const BUILTIN_THEME_NAMES: [&str; 3] = ["dark", "light", "high-contrast"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Theme {
+    pub diff_add: Color,
+    pub diff_delete: Color,
+    pub markdown_code: Color,
+    pub markdown_link: Color,
+    pub markdown_blockquote: Color,
+    pub markdown_list_marker: Color,
+    pub status_accent: Color,
+    pub footer_mode: Color,
+    pub selection: Color,
+    pub approval_title: Color,
+    pub decision_approved: Color,
+    pub decision_denied: Color,
+}
+
+impl Theme {
+    /// The original TUI palette; tuned for dark terminal backgrounds.
+    pub(crate) const DARK: Theme = Theme {
+        diff_add: Color::Green,
+        diff_delete: Color::Red,
+        markdown_code: Color::Cyan,
+        markdown_link: Color::Cyan,
+        markdown_blockquote: Color::Green,
+        markdown_list_marker: Color::LightBlue,
+        status_accent: Color::Cyan,
+        footer_mode: Color::Magenta,
+        selection: Color::Cyan,
+        approval_title: Color::Reset,
+        decision_approved: Color::Green,
+        decision_denied: Color::Red,
+    };
+
+    /// Avoids light variants, which wash out on light backgrounds, and leans on
+    /// magenta where the dark theme uses cyan for running text.
+    pub(crate) const LIGHT: Theme = Theme {
+        diff_add: Color::Green,
+        diff_delete: Color::Red,
+        markdown_code: Color::Magenta,
+        markdown_link: Color::Cyan,
+        markdown_blockquote: Color::Green,
+        markdown_list_marker: Color::Reset,
+        status_accent: Color::Magenta,
+        footer_mode: Color::Magenta,
+        selection: Color::Cyan,
+        approval_title: Color::Reset,
+        decision_approved: Color::Green,
+        decision_denied: Color::Red,
+    };
+
+    /// Bright variants of every role for maximum separation from the default foreground.
+    pub(crate) const HIGH_CONTRAST: Theme = Theme {
+        diff_add: Color::LightGreen,
+        diff_delete: Color::LightRed,
+        markdown_code: Color::LightMagenta,
+        markdown_link: Color::LightCyan,
+        markdown_blockquote: Color::LightGreen,
+        markdown_list_marker: Color::LightCyan,
+        status_accent: Color::LightCyan,
+        footer_mode: Color::LightMagenta,
+        selection: Color::LightCyan,
+        approval_title: Color::LightCyan,
+        decision_approved: Color::LightGreen,
+        decision_denied: Color::LightRed,
+    };
+
+    pub(crate) fn builtin(name: &str) -> Option<Theme> {
+        match name {
+            "dark" => Some(Self::DARK),
+            "light" => Some(Self::LIGHT),
+            "high-contrast" => Some(Self::HIGH_CONTRAST),
+            _ => None,
+        }
+    }
+
+    /// Resolve `name` against the built-in themes and the user-defined `themes`.
+    ///
+    /// User themes take precedence over built-ins with the same name. Errors are
+    /// human-readable and suitable for showing directly in the transcript.
+    pub(crate) fn resolve(
+        name: &str,
+        themes: &BTreeMap<String, ThemeToml>,
+    ) -> Result<Theme, String> {
+        let Some(user) = themes.get(name) else {
+            return Self::builtin(name).ok_or_else(|| format!("Unknown theme `{name}`."));
+        };
+
+        let base_name = user.base.as_deref().unwrap_or(DEFAULT_THEME_NAME);
+        let mut theme = Self::builtin(base_name).ok_or_else(|| {
+            format!("Theme `{name}` has unknown base `{base_name}`; expected one of dark, light, high-contrast.")
+        })?;
+
+        let overrides = [
+            ("diff_add", &user.diff_add, &mut theme.diff_add),
+            ("diff_delete", &user.diff_delete, &mut theme.diff_delete),
+            (
+                "markdown_code",
+                &user.markdown_code,
+                &mut theme.markdown_code,
+            ),
+            (
+                "markdown_link",
+                &user.markdown_link,
+                &mut theme.markdown_link,
+            ),
+            (
+                "markdown_blockquote",
+                &user.markdown_blockquote,
+                &mut theme.markdown_blockquote,
+            ),
+            (
+                "markdown_list_marker",
+                &user.markdown_list_marker,
+                &mut theme.markdown_list_marker,
+            ),
+            (
+                "status_accent",
+                &user.status_accent,
+                &mut theme.status_accent,
+            ),
+            ("footer_mode", &user.footer_mode, &mut theme.footer_mode),
+            ("selection", &user.selection, &mut theme.selection),
+            (
+                "approval_title",
+                &user.approval_title,
+                &mut theme.approval_title,
+            ),
+            (
+                "decision_approved",
+                &user.decision_approved,
+                &mut theme.decision_approved,
+            ),
+            (
+                "decision_denied",
+                &user.decision_denied,
+                &mut theme.decision_denied,
+            ),
+        ];
+        for (field, value, slot) in overrides {
+            if let Some(value) = value {
+                *slot = parse_color(value)
+                    .map_err(|err| format!("Theme `{name}` has an invalid `{field}`: {err}"))?;
+            }
+        }
+        Ok(theme)
+    }
+}
+
+/// Names offered by `/theme`: built-ins first, then user themes that do not
+/// shadow a built-in.
+pub(crate) fn available_theme_names(themes: &BTreeMap<String, ThemeToml>) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_THEME_NAMES
+        .iter()
+        .map(|s| (*s).to_string())
+        .collect();
+    names.extend(
+        themes
+            .keys()
+            .filter(|name| !BUILTIN_THEME_NAMES.contains(&name.as_str()))
+            .cloned(),
+    );
+    names
+}
+
+/// Parse an ANSI color name. Indexed and RGB colors are refused for the same
+/// reason `styles.md` avoids custom colors: they can't adapt to the terminal's
+/// own palette.
+fn parse_color(value: &str) -> Result<Color, String> {
+    match Color::from_str(value.trim()) {
+        Ok(Color::Indexed(_) | Color::Rgb(..)) => Err(format!(
+            "`{value}` is a custom color; use an ANSI name such as `cyan` or `light-magenta`"
+        )),
+        Ok(color) => Ok(color),
+        Err(_) => Err(format!(
+            "`{value}` is not a color; use an ANSI name such as `cyan` or `light-magenta`"
+        )),
+    }
+}
+
+struct ActiveTheme {
+    name: Option<String>,
+    theme: Theme,
+}
+
+static ACTIVE_THEME: RwLock<ActiveTheme> = RwLock::new(ActiveTheme {
+    name: None,
+    theme: Theme::DARK,
+});
+
+/// The theme renderers should use right now.
+pub(crate) fn current() -> Theme {
+    ACTIVE_THEME
+        .read()
+        .map(|active| active.theme)
+        .unwrap_or(Theme::DARK)
+}
+
+/// Name of the active theme, defaulting to `dark` when none has been set.
+pub(crate) fn current_name() -> String {
+    ACTIVE_THEME
+        .read()
+        .ok()
+        .and_then(|active| active.name.clone())
+        .unwrap_or_else(|| DEFAULT_THEME_NAME.to_string())
+}
+
+pub(crate) fn set_active(name: &str, theme: Theme) {
+    if let Ok(mut active) = ACTIVE_THEME.write() {
+        active.name = Some(name.to_string());
+        active.theme = theme;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resolves_builtin_themes() {
+        let themes = BTreeMap::new();
+        assert_eq!(Theme::resolve("dark", &themes), Ok(Theme::DARK));
+        assert_eq!(Theme::resolve("light", &themes), Ok(Theme::LIGHT));
+        assert_eq!(
+            Theme::resolve("high-contrast", &themes),
+            Ok(Theme::HIGH_CONTRAST)
+        );
+        assert_eq!(
+            Theme::resolve("solarized", &themes),
+            Err("Unknown theme `solarized`.".to_string())
+        );
+    }
+
+    #[test]
+    fn user_theme_overrides_base_colors() {
+        let themes = BTreeMap::from([(
+            "mine".to_string(),
+            ThemeToml {
+                base: Some("light".to_string()),
+                diff_add: Some("light-green".to_string()),
+                selection: Some("magenta".to_string()),
+                decision_denied: Some("light-red".to_string()),
+                ..Default::default()
+            },
+        )]);
+
+        let theme = Theme::resolve("mine", &themes).expect("resolve");
+        assert_eq!(theme.diff_add, Color::LightGreen);
+        assert_eq!(theme.selection, Color::Magenta);
+        assert_eq!(theme.decision_denied, Color::LightRed);
+        assert_eq!(theme.markdown_code, Theme::LIGHT.markdown_code);
+    }
+
+    #[test]
+    fn user_theme_errors_name_the_bad_field() {
+        let themes = BTreeMap::from([
+            (
+                "bad-color".to_string(),
+                ThemeToml {
+                    diff_delete: Some("not-a-color".to_string()),
+                    ..Default::default()
+                },
+            ),
+            (
+                "bad-base".to_string(),
+                ThemeToml {
+                    base: Some("sepia".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let err = Theme::resolve("bad-color", &themes).expect_err("invalid color");
+        assert!(err.contains("`diff_delete`"), "{err}");
+        let err = Theme::resolve("bad-base", &themes).expect_err("invalid base");
+        assert!(err.contains("unknown base `sepia`"), "{err}");
+    }
+
+    #[test]
+    fn user_themes_reject_indexed_and_rgb_colors() {
+        for value in ["33", "#ff8800"] {
+            let themes = BTreeMap::from([(
+                "custom".to_string(),
+                ThemeToml {
+                    selection: Some(value.to_string()),
+                    ..Default::default()
+                },
+            )]);
+            let err = Theme::resolve("custom", &themes).expect_err("custom color");
+            assert!(err.contains("is a custom color"), "{err}");
+        }
+    }
+
+    #[test]
+    fn builtin_themes_avoid_blue_and_yellow() {
+        for theme in [Theme::LIGHT, Theme::HIGH_CONTRAST] {
+            let colors = [
+                theme.markdown_code,
+                theme.markdown_link,
+                theme.markdown_list_marker,
+                theme.status_accent,
+                theme.selection,
+                theme.approval_title,
+            ];
+            assert!(
+                colors.iter().all(|color| !matches!(
+                    color,
+                    Color::Blue | Color::LightBlue | Color::Yellow | Color::LightYellow
+                )),
+                "{theme:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn available_names_list_builtins_then_user_themes() {
+        let themes = BTreeMap::from([
+            ("zebra".to_string(), ThemeToml::default()),
+            ("dark".to_string(), ThemeToml::default()),
+        ]);
+        assert_eq!(
+            available_theme_names(&themes),
+            vec!["dark", "light", "high-contrast", "zebra"]
+        );
+    }
+}
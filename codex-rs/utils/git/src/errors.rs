@@ -20,8 +20,12 @@ pub enum GitToolingError {
         #[source]
         source: FromUtf8Error,
     },
+    #[error("git command `{command}` printed unexpected output {output:?}")]
+    UnexpectedGitOutput { command: String, output: String },
     #[error("{path:?} is not a git repository")]
     NotAGitRepository { path: PathBuf },
+    #[error("{path:?} has no commits yet")]
+    NoHeadCommit { path: PathBuf },
     #[error("path {path:?} must be relative to the repository root")]
     NonRelativePath { path: PathBuf },
     #[error("path {path:?} escapes the repository root")]
@@ -7,6 +7,7 @@ mod errors;
 mod ghost_commits;
 mod operations;
 mod platform;
+mod worktree;
 
 pub use apply::ApplyGitRequest;
 pub use apply::ApplyGitResult;
@@ -33,6 +34,15 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use ts_rs::TS;
+pub use worktree::AgentWorktree;
+pub use worktree::WorktreeMerge;
+pub use worktree::WorktreeMergeMode;
+pub use worktree::agent_worktree_diff;
+pub use worktree::create_agent_worktree;
+pub use worktree::merge_agent_worktree;
+pub use worktree::prepare_agent_worktree_merge;
+pub use worktree::remove_agent_worktree;
+pub use worktree::remove_agent_worktree_if_merged;
 
 type CommitID = String;
 
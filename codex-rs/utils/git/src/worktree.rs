@@ -0,0 +1,490 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::repo_subdir;
+use crate::operations::resolve_head;
+use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_status;
+use crate::operations::run_git_for_stdout;
+use crate::operations::run_git_for_stdout_all;
+
+/// A throwaway `git worktree` checked out on its own branch, used to isolate a
+/// sub-agent's edits from the repository it was spawned from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentWorktree {
+    repo_root: PathBuf,
+    path: PathBuf,
+    subdir: Option<PathBuf>,
+    branch: String,
+    base_commit: String,
+}
+
+/// How [`merge_agent_worktree`] brings a worktree's commits into the
+/// repository it was created from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeMergeMode {
+    /// `git merge --no-ff` of the worktree branch.
+    Merge,
+    /// `git cherry-pick` of every commit made on the worktree branch.
+    CherryPick,
+}
+
+impl AgentWorktree {
+    /// Root of the repository the worktree was created from.
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Root of the worktree checkout.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Directory inside the worktree matching the directory it was created
+    /// from, so a sub-agent starts in the same place as its parent.
+    pub fn cwd(&self) -> PathBuf {
+        match &self.subdir {
+            Some(subdir) => self.path.join(subdir),
+            None => self.path.clone(),
+        }
+    }
+
+    /// Branch checked out in the worktree.
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Commit the worktree branch started from.
+    pub fn base_commit(&self) -> &str {
+        &self.base_commit
+    }
+}
+
+/// Creates a worktree at `worktree_path` on a new `branch` starting at the
+/// current `HEAD` of the repository containing `repo_path`. Uncommitted
+/// changes in the repository are not carried over.
+pub fn create_agent_worktree(
+    repo_path: &Path,
+    worktree_path: &Path,
+    branch: &str,
+) -> Result<AgentWorktree, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    let repo_root = resolve_repository_root(repo_path)?;
+    let Some(base_commit) = resolve_head(repo_root.as_path())? else {
+        return Err(GitToolingError::NoHeadCommit { path: repo_root });
+    };
+    if let Some(parent) = worktree_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("worktree"),
+            OsString::from("add"),
+            OsString::from("-b"),
+            OsString::from(branch),
+            worktree_path.as_os_str().to_os_string(),
+            OsString::from(&base_commit),
+        ],
+        None,
+    )?;
+    Ok(AgentWorktree {
+        subdir: repo_subdir(repo_root.as_path(), repo_path),
+        repo_root,
+        path: worktree_path.to_path_buf(),
+        branch: branch.to_string(),
+        base_commit,
+    })
+}
+
+/// Returns the unified diff of everything changed in the worktree since it was
+/// created, including uncommitted and untracked files.
+pub fn agent_worktree_diff(worktree: &AgentWorktree) -> Result<String, GitToolingError> {
+    stage_all(worktree)?;
+    run_git_for_stdout_all(
+        worktree.path(),
+        vec![
+            OsString::from("diff"),
+            OsString::from("--cached"),
+            OsString::from("--no-color"),
+            OsString::from(worktree.base_commit()),
+        ],
+        None,
+    )
+}
+
+/// A `git` invocation that brings a worktree's commits into the repository's
+/// checked-out branch, for callers that run it themselves (for example under a
+/// sandbox).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeMerge {
+    /// Directory to run the command in: the repository root.
+    pub cwd: PathBuf,
+    /// Arguments to `git` that apply the commits.
+    pub args: Vec<String>,
+    /// Arguments to `git` that undo a merge that stopped on a conflict.
+    pub abort_args: Vec<String>,
+    /// Committer identity to set when the repository has none configured.
+    pub env: Vec<(String, String)>,
+    /// Number of commits the command applies.
+    pub commits: usize,
+}
+
+/// Commits any pending worktree changes with `message` and returns the
+/// command that brings the worktree branch into the repository, or `None` when
+/// the branch has nothing to apply. The repository's checkout is left alone,
+/// but the commit is written to the object store and branch it shares with
+/// the worktree.
+pub fn prepare_agent_worktree_merge(
+    worktree: &AgentWorktree,
+    mode: WorktreeMergeMode,
+    message: &str,
+) -> Result<Option<WorktreeMerge>, GitToolingError> {
+    commit_pending_changes(worktree, message)?;
+    let range = format!("{}..{}", worktree.base_commit(), worktree.branch());
+    let count_args = vec!["rev-list".to_string(), "--count".to_string(), range.clone()];
+    let output = run_git_for_stdout(worktree.repo_root(), &count_args, None)?;
+    let commits = output
+        .parse::<usize>()
+        .map_err(|_| GitToolingError::UnexpectedGitOutput {
+            command: format!("git {}", count_args.join(" ")),
+            output: output.clone(),
+        })?;
+    if commits == 0 {
+        return Ok(None);
+    }
+
+    let (args, abort) = match mode {
+        WorktreeMergeMode::Merge => (
+            vec![
+                "merge".to_string(),
+                "--no-ff".to_string(),
+                "-m".to_string(),
+                message.to_string(),
+                worktree.branch().to_string(),
+            ],
+            "merge",
+        ),
+        WorktreeMergeMode::CherryPick => (vec!["cherry-pick".to_string(), range], "cherry-pick"),
+    };
+    let env = commit_identity(worktree.repo_root())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| {
+            (
+                key.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    Ok(Some(WorktreeMerge {
+        cwd: worktree.repo_root().to_path_buf(),
+        args,
+        abort_args: vec![abort.to_string(), "--abort".to_string()],
+        env,
+        commits,
+    }))
+}
+
+/// Commits any pending worktree changes with `message`, then brings the
+/// worktree branch into the repository's checked-out branch. Returns the
+/// number of commits that were applied; conflicts abort the operation and
+/// leave the repository untouched.
+pub fn merge_agent_worktree(
+    worktree: &AgentWorktree,
+    mode: WorktreeMergeMode,
+    message: &str,
+) -> Result<usize, GitToolingError> {
+    let Some(merge) = prepare_agent_worktree_merge(worktree, mode, message)? else {
+        return Ok(0);
+    };
+    let env: Vec<(OsString, OsString)> = merge
+        .env
+        .iter()
+        .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+        .collect();
+    let env = (!env.is_empty()).then_some(env.as_slice());
+    if let Err(err) = run_git_for_status(&merge.cwd, &merge.args, env) {
+        // Best effort: a failed merge may not have started, leaving nothing to abort.
+        let _ = run_git_for_status(&merge.cwd, &merge.abort_args, None);
+        return Err(err);
+    }
+    Ok(merge.commits)
+}
+
+/// Deletes the worktree checkout and its branch, discarding any changes.
+pub fn remove_agent_worktree(worktree: &AgentWorktree) -> Result<(), GitToolingError> {
+    run_git_for_status(
+        worktree.repo_root(),
+        vec![
+            OsString::from("worktree"),
+            OsString::from("remove"),
+            OsString::from("--force"),
+            worktree.path().as_os_str().to_os_string(),
+        ],
+        None,
+    )?;
+    run_git_for_status(
+        worktree.repo_root(),
+        vec![
+            OsString::from("branch"),
+            OsString::from("-D"),
+            OsString::from(worktree.branch()),
+        ],
+        None,
+    )
+}
+
+/// Removes the worktree and its branch only when nothing would be lost: the
+/// checkout has no uncommitted or untracked changes and the repository's
+/// `HEAD` already contains every commit on the branch. Returns whether the
+/// worktree was removed.
+pub fn remove_agent_worktree_if_merged(worktree: &AgentWorktree) -> Result<bool, GitToolingError> {
+    let status = run_git_for_stdout(
+        worktree.path(),
+        [OsString::from("status"), OsString::from("--porcelain")],
+        None,
+    )?;
+    if !status.is_empty() {
+        return Ok(false);
+    }
+    let unmerged = run_git_for_stdout(
+        worktree.repo_root(),
+        [
+            OsString::from("rev-list"),
+            OsString::from("--count"),
+            OsString::from(format!("HEAD..{}", worktree.branch())),
+        ],
+        None,
+    )?;
+    if unmerged != "0" {
+        return Ok(false);
+    }
+    remove_agent_worktree(worktree)?;
+    Ok(true)
+}
+
+fn stage_all(worktree: &AgentWorktree) -> Result<(), GitToolingError> {
+    run_git_for_status(
+        worktree.path(),
+        [OsString::from("add"), OsString::from("-A")],
+        None,
+    )
+}
+
+fn commit_pending_changes(worktree: &AgentWorktree, message: &str) -> Result<(), GitToolingError> {
+    stage_all(worktree)?;
+    let status = run_git_for_stdout(
+        worktree.path(),
+        [OsString::from("status"), OsString::from("--porcelain")],
+        None,
+    )?;
+    if status.is_empty() {
+        return Ok(());
+    }
+    let identity = commit_identity(worktree.path());
+    run_git_for_status(
+        worktree.path(),
+        [
+            OsString::from("commit"),
+            OsString::from("--no-verify"),
+            OsString::from("-m"),
+            OsString::from(message),
+        ],
+        identity.as_deref(),
+    )
+}
+
+/// Falls back to a Codex identity when the repository has no committer
+/// configured, so merges still succeed on fresh machines.
+fn commit_identity(repo: &Path) -> Option<Vec<(OsString, OsString)>> {
+    let configured = run_git_for_stdout(
+        repo,
+        [OsString::from("config"), OsString::from("user.email")],
+        None,
+    )
+    .is_ok_and(|email| !email.is_empty());
+    if configured {
+        return None;
+    }
+    Some(
+        [
+            ("GIT_AUTHOR_NAME", "Codex Agent"),
+            ("GIT_AUTHOR_EMAIL", "agent@codex.local"),
+            ("GIT_COMMITTER_NAME", "Codex Agent"),
+            ("GIT_COMMITTER_EMAIL", "agent@codex.local"),
+        ]
+        .into_iter()
+        .map(|(key, value)| (OsString::from(key), OsString::from(value)))
+        .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    fn init_repo_with_commit(repo: &Path) {
+        run_git_in(repo, &["init", "--initial-branch=main"]);
+        run_git_in(repo, &["config", "core.autocrlf", "false"]);
+        run_git_in(repo, &["config", "user.name", "Tester"]);
+        run_git_in(repo, &["config", "user.email", "test@example.com"]);
+        std::fs::create_dir(repo.join("src")).expect("mkdir");
+        std::fs::write(repo.join("src/lib.rs"), "fn a() {}\n").expect("write");
+        run_git_in(repo, &["add", "."]);
+        run_git_in(repo, &["commit", "-m", "init"]);
+    }
+
+    #[test]
+    fn worktree_changes_merge_back_into_repo() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        init_repo_with_commit(&repo);
+
+        let worktree = create_agent_worktree(
+            &repo.join("src"),
+            &temp.path().join("worktrees/agent-1"),
+            "codex/agent-1",
+        )?;
+        assert_eq!(worktree.cwd(), temp.path().join("worktrees/agent-1/src"));
+        std::fs::write(worktree.cwd().join("lib.rs"), "fn b() {}\n")?;
+        std::fs::write(worktree.cwd().join("new.rs"), "fn c() {}\n")?;
+
+        let diff = agent_worktree_diff(&worktree)?;
+        assert!(diff.contains("+fn b() {}"), "{diff}");
+        assert!(diff.contains("+fn c() {}"), "{diff}");
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/lib.rs"))?,
+            "fn a() {}\n"
+        );
+
+        let applied =
+            merge_agent_worktree(&worktree, WorktreeMergeMode::CherryPick, "agent changes")?;
+        remove_agent_worktree(&worktree)?;
+
+        assert_eq!(applied, 1);
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/lib.rs"))?,
+            "fn b() {}\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/new.rs"))?,
+            "fn c() {}\n"
+        );
+        assert!(!worktree.path().exists());
+        Ok(())
+    }
+
+    #[test]
+    fn prepared_merge_leaves_the_checkout_alone() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktree = create_agent_worktree(&repo, &temp.path().join("agent-3"), "codex/agent-3")?;
+        assert_eq!(
+            prepare_agent_worktree_merge(&worktree, WorktreeMergeMode::Merge, "agent changes")?,
+            None
+        );
+
+        std::fs::write(worktree.path().join("src/lib.rs"), "fn worker() {}\n")?;
+        let merge =
+            prepare_agent_worktree_merge(&worktree, WorktreeMergeMode::Merge, "agent changes")?
+                .expect("worktree has a commit to merge");
+
+        assert_eq!(
+            merge,
+            WorktreeMerge {
+                cwd: worktree.repo_root().to_path_buf(),
+                args: vec![
+                    "merge".to_string(),
+                    "--no-ff".to_string(),
+                    "-m".to_string(),
+                    "agent changes".to_string(),
+                    "codex/agent-3".to_string(),
+                ],
+                abort_args: vec!["merge".to_string(), "--abort".to_string()],
+                env: Vec::new(),
+                commits: 1,
+            }
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/lib.rs"))?,
+            "fn a() {}\n"
+        );
+        run_git_in(
+            &repo,
+            &["merge", "--no-ff", "-m", "agent changes", "codex/agent-3"],
+        );
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/lib.rs"))?,
+            "fn worker() {}\n"
+        );
+        remove_agent_worktree(&worktree)?;
+        Ok(())
+    }
+
+    #[test]
+    fn only_merged_and_clean_worktrees_are_removed() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        init_repo_with_commit(&repo);
+
+        let clean = create_agent_worktree(&repo, &temp.path().join("agent-4"), "codex/agent-4")?;
+        assert!(remove_agent_worktree_if_merged(&clean)?);
+        assert!(!clean.path().exists());
+
+        let dirty = create_agent_worktree(&repo, &temp.path().join("agent-5"), "codex/agent-5")?;
+        std::fs::write(dirty.path().join("notes.txt"), "draft\n")?;
+        assert!(!remove_agent_worktree_if_merged(&dirty)?);
+        assert!(dirty.path().join("notes.txt").exists());
+
+        run_git_in(dirty.path(), &["add", "."]);
+        run_git_in(dirty.path(), &["commit", "-m", "notes"]);
+        assert!(!remove_agent_worktree_if_merged(&dirty)?);
+
+        run_git_in(&repo, &["merge", "--ff-only", "codex/agent-5"]);
+        assert!(remove_agent_worktree_if_merged(&dirty)?);
+        assert!(!dirty.path().exists());
+        Ok(())
+    }
+
+    #[test]
+    fn conflicting_merge_is_aborted() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir(&repo)?;
+        init_repo_with_commit(&repo);
+
+        let worktree = create_agent_worktree(&repo, &temp.path().join("agent-2"), "codex/agent-2")?;
+        std::fs::write(worktree.path().join("src/lib.rs"), "fn worker() {}\n")?;
+        std::fs::write(repo.join("src/lib.rs"), "fn parent() {}\n")?;
+        run_git_in(&repo, &["commit", "-am", "parent edit"]);
+
+        let result = merge_agent_worktree(&worktree, WorktreeMergeMode::Merge, "agent changes");
+
+        assert!(matches!(result, Err(GitToolingError::GitCommand { .. })));
+        assert_eq!(
+            std::fs::read_to_string(repo.join("src/lib.rs"))?,
+            "fn parent() {}\n"
+        );
+        remove_agent_worktree(&worktree)?;
+        Ok(())
+    }
+}
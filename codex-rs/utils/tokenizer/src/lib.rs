@@ -0,0 +1,307 @@
+//! Byte-pair encoding tokenizer compatible with tiktoken rank tables.
+//!
+//! Rank tables use the `.tiktoken` text format: one base64-encoded token and
+//! its rank per line. Pre-tokenization follows the published split patterns
+//! for each encoding; the `\s+(?!\S)` lookahead is emulated because the
+//! `regex` crate has no lookaround.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use regex::Regex;
+
+const O200K_PATTERN: &str = concat!(
+    r"[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]*[\p{Ll}\p{Lm}\p{Lo}\p{M}]+(?i:'s|'t|'re|'ve|'m|'ll|'d)?",
+    r"|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]+[\p{Ll}\p{Lm}\p{Lo}\p{M}]*(?i:'s|'t|'re|'ve|'m|'ll|'d)?",
+    r"|\p{N}{1,3}",
+    r"| ?[^\s\p{L}\p{N}]+[\r\n/]*",
+    r"|\s*[\r\n]+",
+    r"|\s+",
+);
+
+const CL100K_PATTERN: &str = concat!(
+    r"'(?i:[sdmt]|ll|ve|re)",
+    r"|[^\r\n\p{L}\p{N}]?\p{L}+",
+    r"|\p{N}{1,3}",
+    r"| ?[^\s\p{L}\p{N}]+[\r\n]*",
+    r"|\s*[\r\n]",
+    r"|\s+",
+);
+
+/// Encodings with a known pre-tokenization pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    O200kBase,
+    Cl100kBase,
+}
+
+impl Encoding {
+    /// Name used by tiktoken, e.g. `o200k_base`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::O200kBase => "o200k_base",
+            Encoding::Cl100kBase => "cl100k_base",
+        }
+    }
+
+    /// File name of the rank table for this encoding.
+    pub fn file_name(self) -> String {
+        format!("{}.tiktoken", self.name())
+    }
+
+    fn pattern(self) -> &'static str {
+        match self {
+            Encoding::O200kBase => O200K_PATTERN,
+            Encoding::Cl100kBase => CL100K_PATTERN,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenizerError {
+    #[error("invalid rank table line {line}: {reason}")]
+    InvalidLine { line: usize, reason: String },
+    #[error("rank table has no token for byte {0:#04x}")]
+    MissingByte(u8),
+    #[error("invalid split pattern: {0}")]
+    Pattern(#[from] regex::Error),
+}
+
+/// A loaded BPE encoding.
+#[derive(Debug)]
+pub struct Bpe {
+    encoding: Encoding,
+    ranks: HashMap<Vec<u8>, u32>,
+    pattern: Regex,
+}
+
+impl Bpe {
+    /// Parses a `.tiktoken` rank table for `encoding`.
+    pub fn from_tiktoken(encoding: Encoding, data: &str) -> Result<Self, TokenizerError> {
+        let mut ranks = HashMap::new();
+        for (index, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = |reason: &str| TokenizerError::InvalidLine {
+                line: index + 1,
+                reason: reason.to_string(),
+            };
+            let (token, rank) = line
+                .split_once(' ')
+                .ok_or_else(|| invalid("expected `<base64 token> <rank>`"))?;
+            let token = BASE64
+                .decode(token)
+                .map_err(|err| invalid(&err.to_string()))?;
+            let rank = rank
+                .trim()
+                .parse::<u32>()
+                .map_err(|err| invalid(&err.to_string()))?;
+            ranks.insert(token, rank);
+        }
+        Self::from_ranks(encoding, ranks)
+    }
+
+    /// Builds an encoding from an in-memory rank table. Every single byte must
+    /// have a rank so that any input can be encoded.
+    pub fn from_ranks(
+        encoding: Encoding,
+        ranks: HashMap<Vec<u8>, u32>,
+    ) -> Result<Self, TokenizerError> {
+        if let Some(byte) = (0..=u8::MAX).find(|byte| !ranks.contains_key([*byte].as_slice())) {
+            return Err(TokenizerError::MissingByte(byte));
+        }
+        Ok(Self {
+            encoding,
+            ranks,
+            pattern: Regex::new(encoding.pattern())?,
+        })
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Encodes `text` without special tokens.
+    pub fn encode(&self, text: &str) -> Vec<u32> {
+        let mut tokens = Vec::new();
+        for piece in self.pieces(text) {
+            let piece = piece.as_bytes();
+            if let Some(rank) = self.ranks.get(piece) {
+                tokens.push(*rank);
+                continue;
+            }
+            let parts = self.byte_pair_merge(piece);
+            tokens.extend(
+                parts
+                    .windows(2)
+                    .map(|window| self.rank(&piece[window[0]..window[1]])),
+            );
+        }
+        tokens
+    }
+
+    /// Number of tokens `text` encodes to.
+    pub fn count(&self, text: &str) -> usize {
+        self.pieces(text)
+            .map(|piece| {
+                let piece = piece.as_bytes();
+                if self.ranks.contains_key(piece) {
+                    1
+                } else {
+                    self.byte_pair_merge(piece).len() - 1
+                }
+            })
+            .sum()
+    }
+
+    /// Splits `text` into the pieces that are encoded independently.
+    pub fn pieces<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let mut start = 0;
+        std::iter::from_fn(move || {
+            let found = self.pattern.find_at(text, start)?;
+            let mut end = found.end();
+            let piece = found.as_str();
+            // `\s+(?!\S)`: a run of spaces followed by a word leaves its last
+            // space to be merged with that word.
+            if end < text.len()
+                && !piece.contains(['\r', '\n'])
+                && piece.chars().all(char::is_whitespace)
+                && let Some(last) = piece.chars().last()
+                && piece.len() > last.len_utf8()
+            {
+                end -= last.len_utf8();
+            }
+            let piece = &text[found.start()..end];
+            start = end;
+            Some(piece)
+        })
+    }
+
+    fn rank(&self, bytes: &[u8]) -> u32 {
+        self.ranks.get(bytes).copied().unwrap_or(u32::MAX)
+    }
+
+    /// Returns the start offsets of the merged parts of `piece`, followed by
+    /// `piece.len()`.
+    ///
+    /// Parts form a linked list over byte offsets and candidate merges sit in
+    /// a min-heap keyed by `(rank, start)`, so the lowest-ranked, leftmost pair
+    /// merges first as in tiktoken. Entries made stale by an earlier merge are
+    /// skipped when popped.
+    fn byte_pair_merge(&self, piece: &[u8]) -> Vec<usize> {
+        let len = piece.len();
+        // `next[i]` is the start of the part after the one starting at `i`.
+        let mut next: Vec<usize> = (1..=len).collect();
+        let mut prev: Vec<Option<usize>> = (0..len).map(|i| i.checked_sub(1)).collect();
+        let mut alive = vec![true; len];
+        let pair_rank = |next: &[usize], start: usize| {
+            let end = next[start];
+            if end < len {
+                self.rank(&piece[start..next[end]])
+            } else {
+                u32::MAX
+            }
+        };
+
+        let mut heap: BinaryHeap<Reverse<(u32, usize)>> = (0..len)
+            .map(|start| Reverse((pair_rank(&next, start), start)))
+            .filter(|Reverse((rank, _))| *rank != u32::MAX)
+            .collect();
+        while let Some(Reverse((rank, start))) = heap.pop() {
+            if !alive[start] || pair_rank(&next, start) != rank {
+                continue;
+            }
+            let removed = next[start];
+            alive[removed] = false;
+            next[start] = next[removed];
+            if next[start] < len {
+                prev[next[start]] = Some(start);
+            }
+            for start in std::iter::once(start).chain(prev[start]) {
+                let rank = pair_rank(&next, start);
+                if rank != u32::MAX {
+                    heap.push(Reverse((rank, start)));
+                }
+            }
+        }
+
+        let mut parts: Vec<usize> = (0..len).filter(|start| alive[*start]).collect();
+        parts.push(len);
+        parts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn test_ranks(merges: &[&str]) -> HashMap<Vec<u8>, u32> {
+        let mut ranks: HashMap<Vec<u8>, u32> = (0..=u8::MAX)
+            .map(|byte| (vec![byte], u32::from(byte)))
+            .collect();
+        for (offset, merge) in merges.iter().enumerate() {
+            ranks.insert(merge.as_bytes().to_vec(), 256 + offset as u32);
+        }
+        ranks
+    }
+
+    #[test]
+    fn o200k_pieces_follow_split_pattern() {
+        let bpe = Bpe::from_ranks(Encoding::O200kBase, test_ranks(&[])).expect("valid ranks");
+        let pieces: Vec<&str> = bpe.pieces("Hello world  foo\n\nbar's 12345 (x)").collect();
+        assert_eq!(
+            pieces,
+            vec![
+                "Hello", " world", " ", " foo", "\n\n", "bar's", " ", "123", "45", " (", "x", ")",
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_by_rank() {
+        let bpe = Bpe::from_ranks(Encoding::Cl100kBase, test_ranks(&["ab", "abab", "cd"]))
+            .expect("valid ranks");
+        assert_eq!(bpe.encode("ababcd"), vec![257, 258]);
+        assert_eq!(bpe.encode("abc"), vec![256, u32::from(b'c')]);
+        assert_eq!(bpe.count("ababcd abc"), 5);
+        assert_eq!(bpe.count(""), 0);
+    }
+
+    #[test]
+    fn long_pieces_merge_to_the_same_tokens() {
+        let bpe = Bpe::from_ranks(
+            Encoding::Cl100kBase,
+            test_ranks(&["aa", "aaaa", "ab", "aaaaaaaa"]),
+        )
+        .expect("valid ranks");
+        assert_eq!(bpe.encode("aaaaab"), vec![257, 258]);
+        // Runs far longer than any token used to take quadratic time.
+        let text = "a".repeat(100_000);
+        assert_eq!(bpe.count(&text), 100_000 / 8);
+    }
+
+    #[test]
+    fn parses_tiktoken_tables() {
+        let mut data = String::new();
+        for byte in 0..=u8::MAX {
+            data.push_str(&format!("{} {byte}\n", BASE64.encode([byte])));
+        }
+        data.push_str(&format!("{} 256\n", BASE64.encode("hi")));
+        let bpe = Bpe::from_tiktoken(Encoding::O200kBase, &data).expect("valid table");
+        assert_eq!(bpe.encode("hi"), vec![256]);
+
+        let err = Bpe::from_tiktoken(Encoding::O200kBase, "aGk= 1\n").expect_err("incomplete");
+        assert_eq!(err.to_string(), "rank table has no token for byte 0x00");
+        let err = Bpe::from_tiktoken(Encoding::O200kBase, "aGk=\n").expect_err("malformed");
+        assert_eq!(
+            err.to_string(),
+            "invalid rank table line 1: expected `<base64 token> <rank>`"
+        );
+    }
+}